@@ -0,0 +1,66 @@
+//! Compares building large tables and arrays one entry at a time with building them
+//! with pre-allocated capacity, run with `cargo bench --bench construction`.
+
+use std::time::{Duration, Instant};
+use toml_edit::{value, Array, Table};
+
+const KEYS: usize = 10_000;
+const ROUNDS: u32 = 20;
+
+fn measure<F: FnMut() -> usize>(name: &str, mut build: F) -> Duration {
+    let start = Instant::now();
+    let mut len = 0;
+    for _ in 0..ROUNDS {
+        len += build();
+    }
+    let elapsed = start.elapsed() / ROUNDS;
+    println!(
+        "{:<32} {:>10.2?} ({} entries)",
+        name,
+        elapsed,
+        len / ROUNDS as usize
+    );
+    elapsed
+}
+
+fn main() {
+    let keys: Vec<String> = (0..KEYS).map(|i| format!("package-{}", i)).collect();
+
+    let grown = measure("Table::new + insert", || {
+        let mut table = Table::new();
+        for (i, key) in keys.iter().enumerate() {
+            table.insert(key, value(i as i64));
+        }
+        table.len()
+    });
+    let reserved = measure("Table::with_capacity + extend", || {
+        let mut table = Table::with_capacity(keys.len());
+        table.extend(
+            keys.iter()
+                .enumerate()
+                .map(|(i, key)| (key, value(i as i64))),
+        );
+        table.len()
+    });
+    println!(
+        "speedup: {:.2}x\n",
+        grown.as_secs_f64() / reserved.as_secs_f64()
+    );
+
+    let grown = measure("Array::default + push", || {
+        let mut array = Array::default();
+        for i in 0..KEYS as i64 {
+            array.push(i).unwrap();
+        }
+        array.len()
+    });
+    let reserved = measure("Array::with_capacity + extend", || {
+        let mut array = Array::with_capacity(keys.len());
+        array.extend(0..KEYS as i64);
+        array.len()
+    });
+    println!(
+        "speedup: {:.2}x",
+        grown.as_secs_f64() / reserved.as_secs_f64()
+    );
+}
@@ -0,0 +1,45 @@
+//! Compares displaying a large generated document into a string with writing it
+//! to a buffered writer, run with `cargo bench --bench display`.
+
+use std::io::{self, BufWriter, Write};
+use std::time::{Duration, Instant};
+use toml_edit::{value, Document, Table};
+
+const TABLES: usize = 1_000;
+const ROUNDS: u32 = 20;
+
+fn measure<F: FnMut()>(name: &str, mut write: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        write();
+    }
+    let elapsed = start.elapsed() / ROUNDS;
+    println!("{:<24} {:>10.2?}", name, elapsed);
+    elapsed
+}
+
+fn main() {
+    let mut doc = Document::new();
+    for i in 0..TABLES {
+        let mut table = Table::with_capacity(4);
+        table["id"] = value(i as i64 * 7919);
+        table["weight"] = value(i as f64 / 3.0);
+        table["name"] = value(format!("package-{}", i));
+        table["tags"] = value(vec![1i64, -2, 3]);
+        doc[format!("t{}", i).as_str()] = toml_edit::Item::Table(table);
+    }
+
+    let string = measure("Document::to_string", || {
+        let s = doc.to_string();
+        io::sink().write_all(s.as_bytes()).unwrap();
+    });
+    let written = measure("Document::write_to", || {
+        let mut out = BufWriter::new(io::sink());
+        doc.write_to(&mut out).unwrap();
+        out.flush().unwrap();
+    });
+    println!(
+        "speedup: {:.2}x",
+        string.as_secs_f64() / written.as_secs_f64()
+    );
+}
@@ -1,14 +1,22 @@
+use crate::metadata::NodeIdCell;
 use crate::table::{Item, Table};
+use crate::value::to_range;
+use std::ops::RangeBounds;
 
 /// Type representing a TOML array of tables
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArrayOfTables {
     // always Vec<Item::Table>
     pub(crate) values: Vec<Item>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) id: NodeIdCell,
 }
 
 /// An iterator type over `ArrayOfTables`'s values.
 type ArrayOfTablesIter<'a> = Box<dyn Iterator<Item = &'a Table> + 'a>;
+/// A mutable iterator type over `ArrayOfTables`'s values.
+type ArrayOfTablesIterMut<'a> = Box<dyn Iterator<Item = &'a mut Table> + 'a>;
 
 impl ArrayOfTables {
     /// Creates an empty array of tables.
@@ -21,6 +29,11 @@ impl ArrayOfTables {
         Box::new(self.values.iter().filter_map(Item::as_table))
     }
 
+    /// Returns a mutable iterator over tables.
+    pub fn iter_mut(&mut self) -> ArrayOfTablesIterMut<'_> {
+        Box::new(self.values.iter_mut().filter_map(Item::as_table_mut))
+    }
+
     /// Returns an optional reference to the table.
     pub fn get(&self, index: usize) -> Option<&Table> {
         self.values.get(index).and_then(Item::as_table)
@@ -31,6 +44,26 @@ impl ArrayOfTables {
         self.values.get_mut(index).and_then(Item::as_table_mut)
     }
 
+    /// Returns an optional reference to the first table.
+    pub fn first(&self) -> Option<&Table> {
+        self.values.first().and_then(Item::as_table)
+    }
+
+    /// Returns an optional mutable reference to the first table.
+    pub fn first_mut(&mut self) -> Option<&mut Table> {
+        self.values.first_mut().and_then(Item::as_table_mut)
+    }
+
+    /// Returns an optional reference to the last table.
+    pub fn last(&self) -> Option<&Table> {
+        self.values.last().and_then(Item::as_table)
+    }
+
+    /// Returns an optional mutable reference to the last table.
+    pub fn last_mut(&mut self) -> Option<&mut Table> {
+        self.values.last_mut().and_then(Item::as_table_mut)
+    }
+
     /// Appends a table to the array.
     pub fn append(&mut self, table: Table) -> &mut Table {
         self.values.push(Item::Table(table));
@@ -38,11 +71,107 @@ impl ArrayOfTables {
         self.get_mut(i).unwrap()
     }
 
+    /// Appends a table to the array like `append`, preceding its header with a comment.
+    ///
+    /// The lines of `comment` are prefixed with `# ` unless they are comments already,
+    /// and are separated from the previous table by a blank line, e.g.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{value, Document, Table};
+    /// let mut doc = "[[package]]\nname = 'a'\n".parse::<Document>().unwrap();
+    /// let mut package = Table::new();
+    /// package["name"] = value("b");
+    /// doc["package"]
+    ///     .as_array_of_tables_mut()
+    ///     .unwrap()
+    ///     .append_with_comment(package, "added by cargo-release\n# do not edit");
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "[[package]]\nname = 'a'\n\n# added by cargo-release\n# do not edit\n[[package]]\nname = \"b\"\n"
+    /// );
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn append_with_comment(&mut self, mut table: Table, comment: &str) -> &mut Table {
+        let mut prefix = String::from("\n");
+        for line in comment.trim_end_matches(['\r', '\n']).lines() {
+            let line = line.trim_end();
+            if line.trim_start().starts_with('#') {
+                prefix.push_str(line.trim_start());
+            } else if line.is_empty() {
+                prefix.push('#');
+            } else {
+                prefix.push_str("# ");
+                prefix.push_str(line);
+            }
+            prefix.push('\n');
+        }
+        table.decor.set_prefix(prefix);
+        self.append(table)
+    }
+
     /// Removes a table with the given index.
     pub fn remove(&mut self, index: usize) {
         self.values.remove(index);
     }
 
+    /// Replaces the tables in the range with the given ones like `Vec::splice`,
+    /// returning the removed tables.
+    ///
+    /// The new tables take the decor and the position of the removed ones in order,
+    /// like `Table::replace`, additional tables keep their own.
+    ///
+    /// Panics if the range is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{value, Document, Table};
+    /// let mut doc = "[[test]]\nname = 'a'\n\n# b\n[[test]]\nname = 'b'\n".parse::<Document>().unwrap();
+    /// let tests = doc["test"].as_array_of_tables_mut().unwrap();
+    /// let new = ["c", "d"].iter().map(|name| {
+    ///     let mut t = Table::new();
+    ///     t["name"] = value(*name);
+    ///     t
+    /// });
+    /// let removed = tests.splice(1.., new);
+    /// assert_eq!(removed[0]["name"].as_str(), Some("b"));
+    /// assert_eq!(doc.to_string(), "[[test]]\nname = 'a'\n\n# b\n[[test]]\nname = \"c\"\n\n[[test]]\nname = \"d\"\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn splice<R, I>(&mut self, range: R, tables: I) -> Vec<Table>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = Table>,
+    {
+        let range = to_range(range, self.len());
+        let formats: Vec<_> = self.values[range.clone()]
+            .iter()
+            .filter_map(Item::as_table)
+            .map(|t| (t.decor.clone(), t.position))
+            .collect();
+        let inserted = tables.into_iter().enumerate().map(|(i, mut t)| {
+            if let Some((decor, position)) = formats.get(i) {
+                t.decor = decor.clone();
+                t.position = *position;
+            }
+            Item::Table(t)
+        });
+        self.values
+            .splice(range, inserted)
+            .filter_map(|item| match item {
+                Item::Table(t) => Some(t),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Removes all the tables.
     pub fn clear(&mut self) {
         self.values.clear()
@@ -59,3 +188,21 @@ impl ArrayOfTables {
         self.len() == 0
     }
 }
+
+impl<'a> IntoIterator for &'a ArrayOfTables {
+    type Item = &'a Table;
+    type IntoIter = ArrayOfTablesIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut ArrayOfTables {
+    type Item = &'a mut Table;
+    type IntoIter = ArrayOfTablesIterMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
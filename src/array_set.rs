@@ -0,0 +1,252 @@
+use crate::formatted;
+use crate::merge::same_value;
+use crate::table::Item;
+use crate::value::{Array, Value};
+use std::cmp::Ordering;
+
+impl Array {
+    /// Returns true iff the array contains a value equal to `value`, regardless of
+    /// their representations and decor, e.g. `'a'` and `"a"` are equal.
+    pub fn contains(&self, value: &Value) -> bool {
+        self.iter().any(|v| same_value(v, value))
+    }
+
+    /// Removes the values equal to a previous value, keeping the first occurrences
+    /// with their decor.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let mut doc = "features = ['std', \"derive\", \"std\"]  # defaults".parse::<Document>().unwrap();
+    /// doc["features"].as_array_mut().unwrap().dedup();
+    /// assert_eq!(doc.to_string(), "features = ['std', \"derive\"]  # defaults\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn dedup(&mut self) {
+        let mut seen: Vec<Value> = Vec::new();
+        self.retain_values(|v| {
+            if seen.iter().any(|s| same_value(s, v)) {
+                false
+            } else {
+                seen.push(v.clone());
+                true
+            }
+        });
+    }
+
+    /// Sorts the strings, integers, floats, booleans and date-times of the array,
+    /// the other values keep their relative order. The sort is stable.
+    ///
+    /// The values are moved with their decor, e.g. the comments above them, except
+    /// that the first value keeps the prefix of the first position and the last value
+    /// the suffix of the last position, so that the layout of the array is kept.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let mut doc = "features = [\"std\", 'alloc', \"derive\"]".parse::<Document>().unwrap();
+    /// doc["features"].as_array_mut().unwrap().sort();
+    /// assert_eq!(doc.to_string(), "features = ['alloc', \"derive\", \"std\"]\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn sort(&mut self) {
+        let (first, last) = match (self.values.first(), self.values.last()) {
+            (Some(first), Some(last)) => (
+                first.as_value().map(|v| v.decor().prefix().to_owned()),
+                last.as_value().map(|v| v.decor().suffix().to_owned()),
+            ),
+            _ => return,
+        };
+        self.values
+            .sort_by(|a, b| match (a.as_value(), b.as_value()) {
+                (Some(a), Some(b)) => compare(a, b),
+                _ => Ordering::Equal,
+            });
+        // the old first value swaps its prefix with the new first one,
+        // and the old last value its suffix with the new last one
+        if let (Some(prefix), Some((value, others))) = (first, self.values.split_first_mut()) {
+            swap_prefix(others, value.as_value_mut(), prefix);
+        }
+        if let (Some(suffix), Some((value, others))) = (last, self.values.split_last_mut()) {
+            swap_suffix(others, value.as_value_mut(), suffix);
+        }
+    }
+
+    /// Appends the values of `other` which are not in the array yet, once each,
+    /// decorated like the last value of the array.
+    ///
+    /// Returns an error with the first value of a different type than the values in
+    /// the array, in which case the array is left unchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{Document, Value};
+    /// let mut doc = "features = [\n    'std',\n]".parse::<Document>().unwrap();
+    /// let other = "['derive', 'std', \"derive\"]".parse::<Value>().unwrap();
+    /// let features = doc["features"].as_array_mut().unwrap();
+    /// features.union(other.as_array().unwrap()).unwrap();
+    /// assert_eq!(doc.to_string(), "features = [\n    'std',\n    'derive',\n]\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn union(&mut self, other: &Array) -> Result<(), Value> {
+        let mut added: Vec<Value> = Vec::new();
+        for value in other.iter() {
+            if !self.contains(value) && !added.iter().any(|v| same_value(v, value)) {
+                added.push(value.clone());
+            }
+        }
+        if let Some(value) = self
+            .iter()
+            .next()
+            .and_then(|first| added.iter().find(|v| v.get_type() != first.get_type()))
+        {
+            return Err(value.clone());
+        }
+        let prefix = self.iter().last().map(|v| indentation(v.decor().prefix()));
+        for mut value in added {
+            match prefix {
+                Some(ref prefix) => formatted::decorate(&mut value, prefix, ""),
+                None => formatted::decorate(&mut value, if self.is_empty() { "" } else { " " }, ""),
+            }
+            self.values.push(Item::Value(value));
+        }
+        Ok(())
+    }
+
+    /// Removes the values which are in `other`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{Document, Value};
+    /// let mut doc = "features = ['std', 'derive', 'alloc']".parse::<Document>().unwrap();
+    /// let other = "['std', 'serde']".parse::<Value>().unwrap();
+    /// let features = doc["features"].as_array_mut().unwrap();
+    /// features.difference(other.as_array().unwrap());
+    /// assert_eq!(doc.to_string(), "features = ['derive', 'alloc']\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn difference(&mut self, other: &Array) {
+        self.retain_values(|v| !other.contains(v));
+    }
+
+    // Removes the values for which `keep` returns false. A removed first value leaves
+    // its prefix to the next one, and a removed last value its suffix to the previous one.
+    fn retain_values<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&Value) -> bool,
+    {
+        let values = std::mem::take(&mut self.values);
+        let n = values.len();
+        let mut prefix: Option<String> = None;
+        let mut suffix: Option<String> = None;
+        for (i, mut item) in values.into_iter().enumerate() {
+            if item.as_value().is_none_or(&mut keep) {
+                if let (Some(prefix), Some(value)) = (prefix.take(), item.as_value_mut()) {
+                    let suffix = value.decor().suffix().to_owned();
+                    formatted::decorate(value, &prefix, &suffix);
+                }
+                self.values.push(item);
+                continue;
+            }
+            let decor = item.as_value().map(Value::decor);
+            if self.values.is_empty() && prefix.is_none() {
+                prefix = decor.map(|d| d.prefix().to_owned());
+            }
+            if i + 1 == n {
+                suffix = decor.map(|d| d.suffix().to_owned());
+            }
+        }
+        if let (Some(suffix), Some(value)) = (suffix, self.values.last_mut()) {
+            if let Some(value) = value.as_value_mut() {
+                let prefix = value.decor().prefix().to_owned();
+                formatted::decorate(value, &prefix, &suffix);
+            }
+        }
+        if self.is_empty() {
+            self.set_trailing_comma(false);
+        }
+    }
+}
+
+// Orders the values of the same type, the values of other types are equal.
+fn compare(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => a.value().cmp(b.value()),
+        (Value::Integer(a), Value::Integer(b)) => a.value().cmp(b.value()),
+        (Value::Float(a), Value::Float(b)) => a.value().total_cmp(b.value()),
+        (Value::Boolean(a), Value::Boolean(b)) => a.value().cmp(b.value()),
+        (Value::DateTime(a), Value::DateTime(b)) => {
+            a.value().to_string().cmp(&b.value().to_string())
+        }
+        _ => Ordering::Equal,
+    }
+}
+
+// Gives `prefix` to `value`, and the prefix of `value` to the one of `others`
+// which has `prefix`.
+fn swap_prefix(others: &mut [Item], value: Option<&mut Value>, prefix: String) {
+    let value = match value {
+        Some(value) if value.decor().prefix() != prefix => value,
+        _ => return,
+    };
+    let (old, suffix) = (
+        value.decor().prefix().to_owned(),
+        value.decor().suffix().to_owned(),
+    );
+    formatted::decorate(value, &prefix, &suffix);
+    if let Some(other) = others
+        .iter_mut()
+        .filter_map(Item::as_value_mut)
+        .find(|v| v.decor().prefix() == prefix)
+    {
+        let suffix = other.decor().suffix().to_owned();
+        formatted::decorate(other, &old, &suffix);
+    }
+}
+
+// Gives `suffix` to `value`, and the suffix of `value` to the one of `others`
+// which has `suffix`.
+fn swap_suffix(others: &mut [Item], value: Option<&mut Value>, suffix: String) {
+    let value = match value {
+        Some(value) if value.decor().suffix() != suffix => value,
+        _ => return,
+    };
+    let (prefix, old) = (
+        value.decor().prefix().to_owned(),
+        value.decor().suffix().to_owned(),
+    );
+    formatted::decorate(value, &prefix, &suffix);
+    if let Some(other) = others
+        .iter_mut()
+        .filter_map(Item::as_value_mut)
+        .rev()
+        .find(|v| v.decor().suffix() == suffix)
+    {
+        let prefix = other.decor().prefix().to_owned();
+        formatted::decorate(other, &prefix, &old);
+    }
+}
+
+// The whitespaces before a value on its line, without the comments above it.
+fn indentation(prefix: &str) -> String {
+    match prefix.rfind('\n') {
+        Some(i) => format!("\n{}", &prefix[i + 1..]),
+        None => " ".to_owned(),
+    }
+}
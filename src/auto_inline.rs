@@ -0,0 +1,124 @@
+use crate::reflow::is_commented_element;
+use crate::table::{value, Item, Table};
+use crate::value::{InlineTable, Value};
+
+/// A formatting pass which chooses between standard and inline tables
+/// based on their size.
+///
+/// Tables with at most `max_keys` values that fit into a `key = { .. }` line
+/// of at most `max_width` characters are written inline,
+/// inline tables exceeding any of the thresholds are written as standard tables.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "parse")]
+/// # fn main() {
+/// use toml_edit::{AutoInline, Document};
+///
+/// let mut doc = r#"
+/// [dependencies]
+/// serde = { version = "1.0", features = ["derive", "rc", "std", "alloc", "unstable"] }
+///
+/// [dependencies.chrono]
+/// version = "0.4"
+/// "#.parse::<Document>().unwrap();
+///
+/// let deps = doc["dependencies"].as_table_mut().unwrap();
+/// AutoInline::new().max_width(60).max_keys(2).apply(deps);
+///
+/// assert_eq!(doc.to_string(), r#"
+/// [dependencies]
+/// chrono = { version = "0.4" }
+///
+/// [dependencies.serde]
+/// version = "1.0"
+/// features = ["derive", "rc", "std", "alloc", "unstable"]
+/// "#);
+/// # }
+/// # #[cfg(not(feature = "parse"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AutoInline {
+    max_width: usize,
+    max_keys: usize,
+}
+
+impl Default for AutoInline {
+    fn default() -> Self {
+        Self {
+            max_width: 80,
+            max_keys: 3,
+        }
+    }
+}
+
+impl AutoInline {
+    /// Creates the default thresholds: 80 characters and 3 keys.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the maximum width of an inline table line, including its key.
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Sets the maximum number of keys in an inline table.
+    pub fn max_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = max_keys;
+        self
+    }
+
+    /// Applies the heuristic to the direct children of `table`.
+    ///
+    /// Only tables containing nothing but values are made inline, and tables with comments
+    /// after their header or within their body are left as they are. The comments preceding
+    /// a header are kept on the lines preceding the key of the inline table.
+    pub fn apply(&self, table: &mut Table) {
+        for (_, kv) in table.items.iter_mut() {
+            let key = &kv.key.raw_value;
+            let inline = match kv.value {
+                Item::Table(ref t)
+                    if t.values_len() == t.len() && !t.is_empty() && !is_commented(t) =>
+                {
+                    let inline = t.clone().into_inline_table();
+                    if !self.fits(key, &inline) {
+                        continue;
+                    }
+                    // keep comments preceding the header
+                    if !t.decor.prefix.trim().is_empty() {
+                        kv.key.decor.prefix = t.decor.prefix.trim_start_matches('\n').into();
+                    }
+                    true
+                }
+                Item::Value(Value::InlineTable(ref t)) if !self.fits(key, t) => false,
+                _ => continue,
+            };
+            kv.value = match std::mem::take(&mut kv.value) {
+                Item::Table(t) if inline => value(t.into_inline_table()),
+                Item::Value(Value::InlineTable(t)) if !inline => Item::Table(t.into_table()),
+                _ => unreachable!(),
+            };
+        }
+    }
+
+    fn fits(&self, key: &str, table: &InlineTable) -> bool {
+        let mut formatted = table.clone();
+        formatted.fmt();
+        let width = key.chars().count() + " = ".len() + formatted.to_string().chars().count();
+        formatted.len() <= self.max_keys && width <= self.max_width
+    }
+}
+
+// Whether the comments of the table would be lost in an inline table.
+fn is_commented(table: &Table) -> bool {
+    table.decor.suffix.contains('#')
+        || table.items.values().any(|kv| {
+            kv.key.decor.prefix.contains('#')
+                || kv.key.decor.suffix.contains('#')
+                || kv.value.as_value().is_some_and(is_commented_element)
+        })
+}
@@ -0,0 +1,106 @@
+//! Format-preserving edits of TOML files from the command line (`cli` feature).
+//!
+//! ```text
+//! toml-edit get <file> <path>
+//! toml-edit set <file> <path> <value>
+//! toml-edit rm <file> <path>
+//! toml-edit append <file> <path> <value>
+//! toml-edit fmt <file> [<width>]
+//! toml-edit sort <file> [<path>]
+//! ```
+//!
+//! Paths are dotted keys, e.g. `dependencies."serde.json".version`, values are written
+//! as TOML, e.g. `'"1.0"'` or `'{ version = "1.0" }'`. `get` prints strings without
+//! quotes and other items as TOML values. The other commands rewrite the file in place,
+//! keeping the comments and the formatting of everything they don't touch.
+//! A file named `-` is read from the standard input and written to the standard output.
+
+use std::io::{self, Read, Write};
+use std::process;
+use toml_edit::wasm::Editor;
+use toml_edit::{KeyPath, Reflow};
+
+const USAGE: &str = "usage:
+    toml-edit get <file> <path>
+    toml-edit set <file> <path> <value>
+    toml-edit rm <file> <path>
+    toml-edit append <file> <path> <value>
+    toml-edit fmt <file> [<width>]
+    toml-edit sort <file> [<path>]";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    if let Err(message) = run(&args) {
+        eprintln!("toml-edit: {}", message);
+        process::exit(1);
+    }
+}
+
+fn run(args: &[&str]) -> Result<(), String> {
+    let (command, file, rest) = match *args {
+        ["-h"] | ["--help"] => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        [command, file, ref rest @ ..] => (command, file, rest),
+        _ => return Err(USAGE.to_owned()),
+    };
+    let mut editor = Editor::parse(&read(file)?).map_err(|e| format!("{}: {}", file, e))?;
+    match (command, rest) {
+        ("get", [path]) => {
+            let parsed = path.parse::<KeyPath>().map_err(|e| e.to_string())?;
+            let item = editor
+                .document()
+                .get_path(&parsed)
+                .filter(|item| !item.is_none())
+                .ok_or_else(|| format!("no item at `{}`", path))?;
+            match item.as_str() {
+                Some(s) => println!("{}", s),
+                None => println!("{}", editor.get(path)?.unwrap_or_default()),
+            }
+            return Ok(());
+        }
+        ("set", [path, value]) => editor.set(path, value)?,
+        ("rm", [path]) => {
+            if !editor.remove(path)? {
+                return Err(format!("no item at `{}`", path));
+            }
+        }
+        ("append", [path, value]) => editor.append(path, value)?,
+        ("fmt", []) => Reflow::new().apply(editor.document_mut().as_table_mut()),
+        ("fmt", [width]) => {
+            let width = width
+                .parse()
+                .map_err(|_| format!("invalid width `{}`", width))?;
+            Reflow::new()
+                .max_width(width)
+                .apply(editor.document_mut().as_table_mut())
+        }
+        ("sort", []) => editor.sort("")?,
+        ("sort", [path]) => editor.sort(path)?,
+        _ => return Err(USAGE.to_owned()),
+    }
+    write(file, &editor.try_to_string()?)
+}
+
+fn read(file: &str) -> Result<String, String> {
+    let mut input = String::new();
+    let result = if file == "-" {
+        io::stdin().read_to_string(&mut input).map(|_| ())
+    } else {
+        std::fs::read_to_string(file).map(|s| input = s)
+    };
+    result
+        .map(|_| input)
+        .map_err(|e| format!("{}: {}", file, e))
+}
+
+fn write(file: &str, output: &str) -> Result<(), String> {
+    let result = if file == "-" {
+        io::stdout().write_all(output.as_bytes())
+    } else {
+        std::fs::write(file, output)
+    };
+    result.map_err(|e| format!("{}: {}", file, e))
+}
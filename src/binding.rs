@@ -0,0 +1,344 @@
+//! Two-way binding of structs to tables, reading fields from a document and
+//! writing changed fields back in place.
+//!
+//! Unlike serializing a struct anew, writing back with `TomlEdit::update_table` only
+//! replaces the values which differ from those in the table, keeping the decor of
+//! the replaced values, so that the formatting and comments of the document are preserved.
+//!
+//! `TomlEdit` is usually derived with `#[derive(TomlEdit)]` (`derive` feature),
+//! fields are bound to the keys of the same names unless renamed with
+//! `#[toml_edit(rename = "key")]`, and their types have to implement `TomlField`.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[cfg(all(feature = "derive", feature = "parse"))]
+//! # fn main() {
+//! use toml_edit::binding::TomlEdit;
+//! use toml_edit::Document;
+//!
+//! #[derive(TomlEdit)]
+//! struct Package {
+//!     name: String,
+//!     version: String,
+//!     #[toml_edit(rename = "rust-version")]
+//!     rust_version: Option<String>,
+//! }
+//!
+//! let mut doc = r#"
+//! name = 'hello'     # the name
+//! version = '0.1.0'  # the version
+//! "#.parse::<Document>().unwrap();
+//!
+//! let mut package = Package::from_document(&doc).unwrap();
+//! package.version = "0.2.0".to_owned();
+//! package.rust_version = Some("1.50".to_owned());
+//! package.update_document(&mut doc);
+//! assert_eq!(doc.to_string(), r#"
+//! name = 'hello'     # the name
+//! version = "0.2.0"  # the version
+//! rust-version = "1.50"
+//! "#);
+//! # }
+//! # #[cfg(not(all(feature = "derive", feature = "parse")))]
+//! # fn main() {}
+//! ```
+
+use crate::document::Document;
+use crate::formatted;
+use crate::key::Key;
+use crate::table::{Item, Table, TableLike};
+use crate::value::{Array, Value};
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "derive")]
+pub use toml_edit_derive::TomlEdit;
+
+/// Errors of reading a struct from a table.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Error {
+    message: String,
+    // dotted key path of the value, array indices are written as `[i]`
+    path: String,
+}
+
+impl Error {
+    /// Creates an error with the given message.
+    pub fn custom<T: Display>(msg: T) -> Self {
+        Self {
+            message: msg.to_string(),
+            path: String::new(),
+        }
+    }
+
+    /// Returns the key path of the value which failed to be read,
+    /// or `None` if the error is not related to a nested value.
+    pub fn path(&self) -> Option<&str> {
+        if self.path.is_empty() {
+            None
+        } else {
+            Some(&self.path)
+        }
+    }
+
+    fn expected(what: &str, item: &Item) -> Self {
+        if item.is_none() {
+            Self::custom("missing value")
+        } else {
+            Self::custom(format!("expected {}", what))
+        }
+    }
+
+    fn at_key(mut self, key: &str) -> Self {
+        if !self.path.is_empty() && !self.path.starts_with('[') {
+            self.path.insert(0, '.');
+        }
+        self.path.insert_str(0, Key::from_unquoted(key).raw());
+        self
+    }
+
+    fn at_index(mut self, index: usize) -> Self {
+        if !self.path.is_empty() && !self.path.starts_with('[') {
+            self.path.insert(0, '.');
+        }
+        self.path.insert_str(0, &format!("[{}]", index));
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.path() {
+            Some(path) => write!(f, "{} for key `{}`", self.message, path),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A struct bound to the keys of a table.
+pub trait TomlEdit: Sized {
+    /// Reads the struct from a table.
+    fn from_table(table: &dyn TableLike) -> Result<Self, Error>;
+
+    /// Writes the fields which differ from the table into it.
+    fn update_table(&self, table: &mut dyn TableLike);
+
+    /// Reads the struct from the root table of a document.
+    fn from_document(doc: &Document) -> Result<Self, Error> {
+        Self::from_table(doc.as_table())
+    }
+
+    /// Writes the fields which differ from the root table of a document into it.
+    fn update_document(&self, doc: &mut Document) {
+        self.update_table(doc.as_table_mut())
+    }
+}
+
+/// A type of struct fields bound by `TomlEdit`.
+pub trait TomlField: Sized {
+    /// Reads the field from an item, which is `Item::None` for missing keys.
+    fn from_item(item: &Item) -> Result<Self, Error>;
+
+    /// Writes the field into an item unless they are equal,
+    /// setting it to `Item::None` removes the key.
+    fn update_item(&self, item: &mut Item);
+
+    /// Reads the field from the given key of a table.
+    fn read_key(table: &dyn TableLike, key: &str) -> Result<Self, Error> {
+        Self::from_item(table.get(key).unwrap_or(&Item::None)).map_err(|e| e.at_key(key))
+    }
+
+    /// Writes the field into the given key of a table, inserting the key if it is missing.
+    fn update_key(&self, table: &mut dyn TableLike, key: &str) {
+        match table.get_mut(key) {
+            Some(item) => {
+                self.update_item(item);
+                if item.is_none() {
+                    table.remove(key);
+                }
+            }
+            None => {
+                let mut item = Item::None;
+                self.update_item(&mut item);
+                if !item.is_none() {
+                    table.insert(key, item);
+                }
+            }
+        }
+    }
+}
+
+// replaces the item with the value, keeping the decor of a replaced value
+fn update_value<V: Into<Value>>(item: &mut Item, value: V) {
+    let mut value = value.into();
+    match item.as_value() {
+        Some(old) => formatted::decorate(&mut value, old.decor().prefix(), old.decor().suffix()),
+        None => formatted::decorate(&mut value, " ", ""),
+    }
+    *item = Item::Value(value);
+}
+
+impl<T: TomlEdit> TomlField for T {
+    fn from_item(item: &Item) -> Result<Self, Error> {
+        match item.as_table_like() {
+            Some(table) => T::from_table(table),
+            None => Err(Error::expected("a table", item)),
+        }
+    }
+
+    fn update_item(&self, item: &mut Item) {
+        if item.as_table_like().is_none() {
+            *item = Item::Table(Table::new());
+        }
+        if let Some(table) = item.as_table_like_mut() {
+            self.update_table(table);
+        }
+    }
+}
+
+impl TomlField for String {
+    fn from_item(item: &Item) -> Result<Self, Error> {
+        item.as_str()
+            .map(str::to_owned)
+            .ok_or_else(|| Error::expected("a string", item))
+    }
+
+    fn update_item(&self, item: &mut Item) {
+        if item.as_str() != Some(self) {
+            update_value(item, formatted::basic_string_value(self));
+        }
+    }
+}
+
+impl TomlField for bool {
+    fn from_item(item: &Item) -> Result<Self, Error> {
+        item.as_bool()
+            .ok_or_else(|| Error::expected("a boolean", item))
+    }
+
+    fn update_item(&self, item: &mut Item) {
+        if item.as_bool() != Some(*self) {
+            update_value(item, *self);
+        }
+    }
+}
+
+macro_rules! impl_integer_field {
+    ($($t:ty),*) => {
+        $(
+            impl TomlField for $t {
+                fn from_item(item: &Item) -> Result<Self, Error> {
+                    let i = item
+                        .as_integer()
+                        .ok_or_else(|| Error::expected("an integer", item))?;
+                    <$t>::try_from(i)
+                        .map_err(|_| Error::custom(format!("integer {} out of range", i)))
+                }
+
+                fn update_item(&self, item: &mut Item) {
+                    if item.as_integer() != Some(i64::from(*self)) {
+                        update_value(item, i64::from(*self));
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_field!(i8, i16, i32, i64, u8, u16, u32);
+
+macro_rules! impl_float_field {
+    ($($t:ty),*) => {
+        $(
+            impl TomlField for $t {
+                fn from_item(item: &Item) -> Result<Self, Error> {
+                    item.as_float()
+                        .map(|f| f as $t)
+                        .ok_or_else(|| Error::expected("a float", item))
+                }
+
+                fn update_item(&self, item: &mut Item) {
+                    // compared as `$t`, so that reading and writing back a field is a no-op
+                    if item.as_float().map(|f| f as $t) != Some(*self) {
+                        update_value(item, formatted::float_value(f64::from(*self)));
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_float_field!(f32, f64);
+
+impl<T: TomlField> TomlField for Option<T> {
+    fn from_item(item: &Item) -> Result<Self, Error> {
+        if item.is_none() {
+            Ok(None)
+        } else {
+            T::from_item(item).map(Some)
+        }
+    }
+
+    fn update_item(&self, item: &mut Item) {
+        match *self {
+            Some(ref v) => v.update_item(item),
+            None => *item = Item::None,
+        }
+    }
+}
+
+#[cfg(feature = "units")]
+impl<U: crate::units::Unit> TomlField for crate::units::Quantity<U> {
+    fn from_item(item: &Item) -> Result<Self, Error> {
+        let s = item
+            .as_str()
+            .ok_or_else(|| Error::expected("a string", item))?;
+        Self::parse(s).ok_or_else(|| Error::custom(format!("invalid quantity `{}`", s)))
+    }
+
+    fn update_item(&self, item: &mut Item) {
+        if item.as_str() != Some(self.raw()) {
+            update_value(item, formatted::basic_string_value(self.raw()));
+        }
+    }
+}
+
+impl<T: TomlField> TomlField for Vec<T> {
+    fn from_item(item: &Item) -> Result<Self, Error> {
+        let array = item
+            .as_array()
+            .ok_or_else(|| Error::expected("an array", item))?;
+        array
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| T::from_item(v).map_err(|e| e.at_index(i)))
+            .collect()
+    }
+
+    /// Updates the elements in place if the lengths are equal,
+    /// otherwise replaces the array.
+    fn update_item(&self, item: &mut Item) {
+        if let Some(array) = item.as_array_mut() {
+            if array.len() == self.len() {
+                for (v, item) in self.iter().zip(array.values.iter_mut()) {
+                    v.update_item(item);
+                }
+                return;
+            }
+        }
+        let mut array = Array::default();
+        for v in self {
+            let mut item = Item::None;
+            v.update_item(&mut item);
+            if let Ok(value) = item.into_value() {
+                array.values.push(Item::Value(value));
+            }
+        }
+        formatted::decorate_array(&mut array);
+        update_value(item, array);
+    }
+}
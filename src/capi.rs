@@ -0,0 +1,196 @@
+//! A C interface for editing documents from other languages (`capi` feature).
+//!
+//! The functions wrap a `wasm::Editor`: paths are dotted keys and values are written as TOML,
+//! all strings are NUL-terminated UTF-8. Documents are freed with `toml_edit_free`,
+//! strings returned by the functions with `toml_edit_string_free`.
+//! Errors are reported by returning null or false and, if `error` is not null,
+//! storing a message in `*error` which has to be freed as well. Panics don't unwind
+//! into the caller, they are reported as errors.
+//!
+//! The functions are exported unmangled, a shared library can be built with e.g.
+//! `cargo rustc --release --features capi --crate-type cdylib`.
+//!
+//! ```c
+//! char *error = NULL;
+//! toml_edit_document *doc = toml_edit_parse("[package]\nname = 'hello'\n", &error);
+//! toml_edit_set(doc, "package.version", "\"0.1.0\"", &error);
+//! char *s = toml_edit_to_string(doc, &error);
+//! toml_edit_string_free(s);
+//! toml_edit_free(doc);
+//! ```
+
+use crate::wasm::Editor;
+use std::any::Any;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+// Converts a string to a C string, replacing NUL characters which can't be represented.
+fn to_c_string(s: String) -> *mut c_char {
+    let s = CString::new(s).unwrap_or_else(|e| {
+        let s = String::from_utf8_lossy(&e.into_vec()).replace('\0', "\u{FFFD}");
+        CString::new(s).expect("no NUL characters")
+    });
+    s.into_raw()
+}
+
+unsafe fn report(error: *mut *mut c_char, message: String) {
+    if !error.is_null() {
+        *error = to_c_string(message);
+    }
+}
+
+// Runs the body of an exported function, returning `failed` and reporting the error
+// if it fails or panics, as unwinding out of an `extern "C"` function aborts.
+unsafe fn guard<T>(
+    error: *mut *mut c_char,
+    failed: T,
+    body: impl FnOnce() -> Result<T, String>,
+) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(t)) => t,
+        Ok(Err(e)) => {
+            report(error, e);
+            failed
+        }
+        Err(payload) => {
+            report(error, format!("panicked: {}", panic_message(&*payload)));
+            failed
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "unknown error"
+    }
+}
+
+unsafe fn to_str<'a>(s: *const c_char, name: &str) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err(format!("`{}` is null", name));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| format!("`{}` is not valid UTF-8", name))
+}
+
+/// Parses a document, returning null on errors.
+///
+/// # Safety
+///
+/// `input` has to be a NUL-terminated string and `error` null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn toml_edit_parse(
+    input: *const c_char,
+    error: *mut *mut c_char,
+) -> *mut Editor {
+    guard(error, ptr::null_mut(), || {
+        let editor = Editor::parse(to_str(input, "input")?)?;
+        Ok(Box::into_raw(Box::new(editor)))
+    })
+}
+
+/// Returns the item at the path written as TOML, see `Editor::get`,
+/// or null if there is no such item or on errors.
+///
+/// # Safety
+///
+/// `doc` has to be a document returned by `toml_edit_parse`, `path` a NUL-terminated string
+/// and `error` null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn toml_edit_get(
+    doc: *const Editor,
+    path: *const c_char,
+    error: *mut *mut c_char,
+) -> *mut c_char {
+    guard(error, ptr::null_mut(), || {
+        let item = (*doc).get(to_str(path, "path")?)?;
+        Ok(item.map_or(ptr::null_mut(), to_c_string))
+    })
+}
+
+/// Sets the value at the path, see `Editor::set`, returning false on errors.
+///
+/// # Safety
+///
+/// `doc` has to be a document returned by `toml_edit_parse`, `path` and `value`
+/// NUL-terminated strings and `error` null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn toml_edit_set(
+    doc: *mut Editor,
+    path: *const c_char,
+    value: *const c_char,
+    error: *mut *mut c_char,
+) -> bool {
+    guard(error, false, || {
+        (*doc).set(to_str(path, "path")?, to_str(value, "value")?)?;
+        Ok(true)
+    })
+}
+
+/// Removes the item at the path, see `Editor::remove`,
+/// returning false if there is no such item or on errors.
+///
+/// # Safety
+///
+/// `doc` has to be a document returned by `toml_edit_parse`, `path` a NUL-terminated string
+/// and `error` null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn toml_edit_remove(
+    doc: *mut Editor,
+    path: *const c_char,
+    error: *mut *mut c_char,
+) -> bool {
+    guard(error, false, || (*doc).remove(to_str(path, "path")?))
+}
+
+/// Writes the document as TOML, see `Editor::try_to_string`, returning null on errors.
+///
+/// # Safety
+///
+/// `doc` has to be a document returned by `toml_edit_parse` and `error` null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn toml_edit_to_string(
+    doc: *const Editor,
+    error: *mut *mut c_char,
+) -> *mut c_char {
+    guard(error, ptr::null_mut(), || {
+        (*doc).try_to_string().map(to_c_string)
+    })
+}
+
+/// Frees a document, null is ignored.
+///
+/// # Safety
+///
+/// `doc` has to be null or a document returned by `toml_edit_parse` which is not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn toml_edit_free(doc: *mut Editor) {
+    if !doc.is_null() {
+        guard(ptr::null_mut(), (), || {
+            drop(Box::from_raw(doc));
+            Ok(())
+        });
+    }
+}
+
+/// Frees a string returned by the functions of this module, null is ignored.
+///
+/// # Safety
+///
+/// `s` has to be null or a string returned by the functions of this module which is not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn toml_edit_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        guard(ptr::null_mut(), (), || {
+            drop(CString::from_raw(s));
+            Ok(())
+        });
+    }
+}
@@ -0,0 +1,74 @@
+//! Parsers of TOML values and keys for embedding them in other grammars (`combinators` feature),
+//! e.g. a DSL accepting TOML values in its expressions.
+//!
+//! Each parser reads a construct at the start of its input and returns it together with
+//! the remainder of the input, which starts right after the construct. Whitespace is not
+//! skipped before nor after it, and the remainder is returned as is, so that the caller
+//! decides what may follow, e.g. a comma or the end of a line. The positions of the errors
+//! are relative to the given input.
+//!
+//! The parsers follow the grammar of the document parser, including the `ParseOptions`,
+//! so that e.g. `1,000` is read as `1` followed by `,000`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use toml_edit::combinators::{dotted_key, value};
+//! use toml_edit::ParseOptions;
+//!
+//! // set <dotted key> to <value>; ...
+//! let input = "set server.port to 8080; set name to 'app'";
+//! let rest = input.strip_prefix("set ").unwrap();
+//! let (path, rest) = dotted_key(rest).unwrap();
+//! let rest = rest.strip_prefix(" to ").unwrap();
+//! let (port, rest) = value(rest, &ParseOptions::new()).unwrap();
+//! assert_eq!(path.to_string(), "server.port");
+//! assert_eq!(port.as_integer(), Some(8080));
+//! assert_eq!(rest, "; set name to 'app'");
+//! ```
+
+use crate::key::Key;
+use crate::key_path::KeyPath;
+use crate::parser::{self, ParseOptions, TomlError};
+use crate::value::{InlineTable, Value};
+use combine::stream::position::Stream;
+use combine::EasyParser;
+
+/// Parses a value, e.g. `"a"`, `[1, 2]` or `{ x = 1 }`, at the start of `input`.
+pub fn value<'a>(input: &'a str, options: &ParseOptions) -> Result<(Value, &'a str), TomlError> {
+    parser::value_parser(*options)
+        .easy_parse(Stream::new(input))
+        .map(|(value, rest)| (value, rest.input))
+        .map_err(|e| TomlError::new(e, input))
+}
+
+/// Parses a simple key, bare or quoted, e.g. `name` or `"a.b"`, at the start of `input`.
+pub fn key(input: &str) -> Result<(Key, &str), TomlError> {
+    parser::key_parser()
+        .easy_parse(Stream::new(input))
+        .map(|((raw, key), rest)| (Key::new(raw, key), rest.input))
+        .map_err(|e| TomlError::new(e, input))
+}
+
+/// Parses a dotted key, e.g. `server."host name".port`, or a simple key,
+/// at the start of `input`. Whitespace is allowed around the dots only.
+pub fn dotted_key(input: &str) -> Result<(KeyPath, &str), TomlError> {
+    parser::dotted_key_parser()
+        .easy_parse(Stream::new(input))
+        .map(|(keys, rest)| {
+            let path = keys.into_iter().map(|(raw, key)| Key::new(raw, key));
+            (path.collect(), rest.input)
+        })
+        .map_err(|e| TomlError::new(e, input))
+}
+
+/// Parses an inline table, e.g. `{ x = 1, y = 2 }`, at the start of `input`.
+pub fn inline_table<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<(InlineTable, &'a str), TomlError> {
+    parser::inline_table_parser(*options, 0)
+        .easy_parse(Stream::new(input))
+        .map(|(table, rest)| (table, rest.input))
+        .map_err(|e| TomlError::new(e, input))
+}
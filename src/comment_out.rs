@@ -0,0 +1,689 @@
+use crate::decor::{Decor, InternalString};
+use crate::display::is_body_empty;
+use crate::document::Document;
+use crate::key::Key;
+use crate::key_path::KeyPath;
+#[cfg(feature = "parse")]
+use crate::merge::clear_positions;
+use crate::table::{Item, KeyValuePairs, Table, TableKeyValue};
+use crate::value::Value;
+#[cfg(feature = "parse")]
+use std::ops::Range;
+
+impl Table {
+    /// Comments out the key/value pair with the given key: the pair is removed and
+    /// its displayed lines are kept as comments, in the decor of the next line displayed
+    /// in the table, or of the previous one if it is the last.
+    ///
+    /// Returns false if there is no such pair, or if the table displays no other line
+    /// to keep the comments, e.g. for the only pair of the root table,
+    /// see `Document::comment_out`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let mut doc = "[package]\nname = 'a'\nlicense = 'MIT' # the license\nversion = '1.0'\n"
+    ///     .parse::<Document>()
+    ///     .unwrap();
+    /// let package = doc["package"].as_table_mut().unwrap();
+    /// assert!(package.comment_out("license"));
+    /// assert!(!package.contains_key("license"));
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "[package]\nname = 'a'\n# license = 'MIT' # the license\nversion = '1.0'\n"
+    /// );
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn comment_out(&mut self, key: &str) -> bool {
+        let block = match self.items.get(key) {
+            Some(kv) if kv.value.is_value() => value_block(kv),
+            _ => return false,
+        };
+        let target = LineId::key_value(&[], key);
+        let index = match lines(&mut self.items).iter().position(|l| l.is(&target)) {
+            Some(index) => index,
+            None => return false,
+        };
+        let mut others = lines_without(&mut self.items, key);
+        if let Some(next) = others.get_mut(index) {
+            prepend(next.prefix, &block);
+        } else if let Some(suffix) = index
+            .checked_sub(1)
+            .and_then(|i| others.get_mut(i))
+            .and_then(|l| l.suffix.as_mut())
+        {
+            suffix.push('\n');
+            suffix.push_str(block.trim_end_matches('\n'));
+        } else {
+            return false;
+        }
+        self.items.remove(key);
+        true
+    }
+
+    /// Restores the key/value pair with the given key commented out by `comment_out`,
+    /// if its comments are still in the decor of a line of the table and their text
+    /// is still a valid key/value pair. Returns true iff the pair was restored.
+    ///
+    /// The pair is restored before the line whose decor has the comments,
+    /// or at the end of the table.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use toml_edit::Document;
+    /// let s = "[package]\nname = 'a'\nlicense = 'MIT' # the license\nversion = '1.0'\n";
+    /// let mut doc = s.parse::<Document>().unwrap();
+    /// let package = doc["package"].as_table_mut().unwrap();
+    /// package.comment_out("license");
+    /// assert!(package.uncomment("license"));
+    /// assert!(!package.uncomment("edition"));
+    /// assert_eq!(doc.to_string(), s);
+    /// ```
+    #[cfg(feature = "parse")]
+    pub fn uncomment(&mut self, key: &str) -> bool {
+        if self.contains_key(key) {
+            return false;
+        }
+        let accept = |doc: &Document| is_only_value(doc.as_table(), key);
+        let found = lines(&mut self.items)
+            .into_iter()
+            .find_map(|line| line.uncomment(&[], &accept));
+        match found {
+            Some((anchor, doc)) => {
+                restore(&mut self.items, key, anchor, doc);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Document {
+    /// Comments out the key/value pair or the table at the given path, including
+    /// its subtables: the item is removed and its displayed lines are kept as comments,
+    /// in the decor of the next line of the document or at the end of the document.
+    ///
+    /// Returns false if there is no such item, or if it's in an inline table
+    /// or a dotted key/value pair.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{Document, KeyPath};
+    /// let mut doc = "[dependencies]\nserde = '1.0'\n\n[dependencies.toml]\nversion = '0.5'\n"
+    ///     .parse::<Document>()
+    ///     .unwrap();
+    /// let path = "dependencies.toml".parse::<KeyPath>().unwrap();
+    /// assert!(doc.comment_out(&path));
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "[dependencies]\nserde = '1.0'\n\n# [dependencies.toml]\n# version = '0.5'\n"
+    /// );
+    /// assert!(doc.uncomment(&path));
+    /// assert_eq!(doc["dependencies"]["toml"]["version"].as_str(), Some("0.5"));
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn comment_out(&mut self, path: &KeyPath) -> bool {
+        let (last, parents) = match path.keys().split_last() {
+            Some(split) => split,
+            None => return false,
+        };
+        let parent = match parent_mut(self.as_table_mut(), parents) {
+            Some(parent) => parent,
+            None => return false,
+        };
+        let parents_path = segments(parents);
+        let (target, block) = match parent.items.get(last.get()) {
+            Some(kv) => match kv.value {
+                Item::Value(..) => (
+                    LineId::key_value(&parents_path, last.get()),
+                    value_block(kv),
+                ),
+                Item::Table(ref t) if !t.dotted => (
+                    LineId::header(&parents_path, last.get()),
+                    table_block(path, kv),
+                ),
+                Item::ArrayOfTables(ref a) if !a.is_empty() => (
+                    LineId::header(&parents_path, last.get()),
+                    table_block(path, kv),
+                ),
+                _ => return false,
+            },
+            None => return false,
+        };
+        let index = match lines(&mut self.as_table_mut().items)
+            .iter()
+            .position(|l| l.is(&target))
+        {
+            Some(index) => index,
+            None => return false,
+        };
+        if let Some(parent) = parent_mut(self.as_table_mut(), parents) {
+            parent.items.remove(last.get());
+        }
+        match lines(&mut self.as_table_mut().items).get_mut(index) {
+            Some(next) => prepend(next.prefix, &block),
+            None => prepend(&mut self.trailing, &block),
+        }
+        true
+    }
+
+    /// Restores the key/value pair or the table at the given path commented out by
+    /// `comment_out`, if its comments are still in the document and their text is still
+    /// valid TOML, and its parent table still exists. Returns true iff the item was restored.
+    ///
+    /// The item is restored before the line whose decor has the comments if it's
+    /// a sibling, otherwise at the end of its parent table.
+    #[cfg(feature = "parse")]
+    pub fn uncomment(&mut self, path: &KeyPath) -> bool {
+        let (last, parents) = match path.keys().split_last() {
+            Some(split) => split,
+            None => return false,
+        };
+        match parent_mut(self.as_table_mut(), parents) {
+            Some(parent) if !parent.contains_key(last.get()) => {}
+            _ => return false,
+        }
+        let accept_table = |doc: &Document| is_only_table(doc.as_table(), path.keys());
+        let accept =
+            |doc: &Document| is_only_value(doc.as_table(), last.get()) || accept_table(doc);
+        let Document {
+            ref mut root,
+            ref mut trailing,
+            ..
+        } = *self;
+        let root = root.as_table_mut().expect("root should always be a table");
+        let parent = segments(parents);
+        if parent_mut(root, parents).is_none() {
+            return false;
+        }
+        let mut lines = lines(&mut root.items);
+        lines.push(Line {
+            prefix: trailing,
+            suffix: None,
+            id: None,
+            position: None,
+        });
+        // the comments of a key/value pair are in the lines of the parent table or in a line
+        // following them, those of a table with its full header may be anywhere,
+        // as the lines of the subtables are apart when sorted by position
+        let (inside, header): (Vec<_>, Vec<_>) = lines
+            .iter()
+            .map(|l| match l.id {
+                Some(ref id) if id.path.starts_with(&parent) => {
+                    (id.path.len() > parent.len(), id.path.len() == parent.len())
+                }
+                _ => (false, false),
+            })
+            .unzip();
+        let anywhere = !inside.iter().chain(&header).any(|&b| b);
+        let found = lines.into_iter().enumerate().find_map(|(i, line)| {
+            let position = line.position;
+            let near = anywhere || inside[i] || (i > 0 && (inside[i - 1] || header[i - 1]));
+            let accept: &dyn Fn(&Document) -> bool = if near { &accept } else { &accept_table };
+            line.uncomment(&parent, accept)
+                .map(|(anchor, doc)| (anchor, doc, position))
+        });
+        let (anchor, mut doc, position) = match found {
+            Some(found) => found,
+            None => return false,
+        };
+        if let Some(found) = parent_mut(doc.as_table_mut(), parents) {
+            let items = std::mem::take(&mut found.items);
+            doc.as_table_mut().items = items;
+        }
+        match parent_mut(self.as_table_mut(), parents) {
+            Some(parent) => {
+                restore(&mut parent.items, last.get(), anchor, doc);
+                // the table is displayed in original order where its comments were
+                let table = parent
+                    .items
+                    .get_mut(last.get())
+                    .and_then(|kv| match kv.value {
+                        Item::Table(ref mut t) => Some(t),
+                        Item::ArrayOfTables(ref mut a) => {
+                            a.values.iter_mut().find_map(Item::as_table_mut)
+                        }
+                        _ => None,
+                    });
+                if let Some(t) = table {
+                    t.position = position;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+// A key of a path to a displayed line, with the index of the table in its array of tables,
+// 0 for the other items.
+type Segment = (String, usize);
+
+fn segments(keys: &[Key]) -> Vec<Segment> {
+    keys.iter().map(|key| (key.get().to_owned(), 0)).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    // the line of a key/value pair, whose decor is the one of its key and value
+    KeyValue,
+    // the header of a table, whose decor is the one of the table
+    Header,
+}
+
+// Identifies a displayed line by the path to its item,
+// from the table whose lines are listed, and its kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LineId {
+    path: Vec<Segment>,
+    kind: LineKind,
+}
+
+impl LineId {
+    fn key_value(parent: &[Segment], key: &str) -> Self {
+        LineId {
+            path: child(parent, key, 0),
+            kind: LineKind::KeyValue,
+        }
+    }
+
+    // the header of a table, or of the first table of an array
+    fn header(parent: &[Segment], key: &str) -> Self {
+        LineId {
+            path: child(parent, key, 0),
+            kind: LineKind::Header,
+        }
+    }
+
+    // the key of the item of the line if it's in the table at `parent`,
+    // `None` for the tables of arrays but the first one, next to which arrays are restored
+    #[cfg(feature = "parse")]
+    fn sibling_key(self, parent: &[Segment]) -> Option<String> {
+        let mut path = self.path;
+        match path.pop() {
+            Some((key, 0)) if path == parent => Some(key),
+            _ => None,
+        }
+    }
+}
+
+fn child(parent: &[Segment], key: &str, index: usize) -> Vec<Segment> {
+    let mut path = parent.to_vec();
+    path.push((key.to_owned(), index));
+    path
+}
+
+// A displayed line of a key/value pair or a table header, with its decor.
+struct Line<'t> {
+    prefix: &'t mut InternalString,
+    // `None` for the end of the document
+    suffix: Option<&'t mut InternalString>,
+    // `None` for the end of the document
+    id: Option<LineId>,
+    // the position the lines of its table are sorted by, `None` for the end of the document
+    #[cfg_attr(not(feature = "parse"), allow(dead_code))]
+    position: Option<usize>,
+}
+
+#[cfg(feature = "parse")]
+// Where to restore an item, relative to a sibling.
+enum Anchor {
+    Before(String),
+    After(String),
+}
+
+impl<'t> Line<'t> {
+    fn is(&self, id: &LineId) -> bool {
+        self.id.as_ref() == Some(id)
+    }
+
+    // finds the first commented out item accepted by `accept` in the decor of the line,
+    // and removes it, returning its parsed text and where to restore it,
+    // next to the item of the line
+    // if it's a line of a sibling in the table at `parent`
+    #[cfg(feature = "parse")]
+    fn uncomment(
+        self,
+        parent: &[Segment],
+        accept: &dyn Fn(&Document) -> bool,
+    ) -> Option<(Option<Anchor>, Document)> {
+        let Line {
+            prefix, suffix, id, ..
+        } = self;
+        let key = id.and_then(|id| id.sibling_key(parent));
+        if let Some((range, indent, mut doc)) = find_block(prefix, accept) {
+            // the lines preceding the comments precede the restored item
+            let before = format!("{}{}", &prefix[..range.start], indent);
+            set_first_prefix(&mut doc, before);
+            *prefix = InternalString::from(&prefix[range.end..]);
+            return Some((key.map(Anchor::Before), doc));
+        }
+        let suffix = suffix?;
+        let (range, indent, mut doc) = find_block(suffix, accept)?;
+        set_first_prefix(&mut doc, indent);
+        // the comments are on the lines following the suffix
+        let start = suffix[..range.start]
+            .strip_suffix('\n')
+            .map_or(range.start, str::len);
+        let end = range.end.min(suffix.len());
+        *suffix = InternalString::from(format!("{}{}", &suffix[..start], &suffix[end..]));
+        Some((key.map(Anchor::After), doc))
+    }
+}
+
+// the displayed lines of the items and of their subtables, in the order they are displayed:
+// the tables are sorted by their positions like `Document::to_string_in_original_order` does
+fn lines(items: &mut KeyValuePairs) -> Vec<Line<'_>> {
+    let mut tables = Vec::new();
+    let mut last_position = 0;
+    table_lines(items, &[], None, &mut last_position, &mut tables);
+    tables.sort_by_key(|&(position, _)| position);
+    tables.into_iter().flat_map(|(_, lines)| lines).collect()
+}
+
+// the displayed lines of the items without the key/value pair with the key
+fn lines_without<'t>(items: &'t mut KeyValuePairs, key: &str) -> Vec<Line<'t>> {
+    let target = LineId::key_value(&[], key);
+    let mut lines = lines(items);
+    lines.retain(|l| !l.is(&target));
+    lines
+}
+
+// lists the lines of the table with the items at `path` and its header, if displayed,
+// then those of its subtables, with the position of the last table having one
+fn table_lines<'t>(
+    items: &'t mut KeyValuePairs,
+    path: &[Segment],
+    header: Option<&'t mut Decor>,
+    last_position: &mut usize,
+    tables: &mut Vec<(usize, Vec<Line<'t>>)>,
+) {
+    let position = Some(*last_position);
+    let mut lines = Vec::new();
+    if let Some(decor) = header {
+        let Decor {
+            ref mut prefix,
+            ref mut suffix,
+        } = *decor;
+        lines.push(Line {
+            prefix,
+            suffix: Some(suffix),
+            id: Some(LineId {
+                path: path.to_vec(),
+                kind: LineKind::Header,
+            }),
+            position,
+        });
+    }
+    let mut subtables = Vec::new();
+    body_lines(items, path, position, &mut lines, &mut subtables);
+    tables.push((*last_position, lines));
+    for (table, displayed, path) in subtables {
+        let Table {
+            ref mut decor,
+            ref mut items,
+            position,
+            ..
+        } = *table;
+        if let Some(position) = position {
+            *last_position = position;
+        }
+        let header = if displayed { Some(decor) } else { None };
+        table_lines(items, &path, header, last_position, tables);
+    }
+}
+
+// the lines of the key/value pairs at `path`, and the subtables with whether their headers
+// are displayed and their paths
+fn body_lines<'t>(
+    items: &'t mut KeyValuePairs,
+    path: &[Segment],
+    position: Option<usize>,
+    lines: &mut Vec<Line<'t>>,
+    subtables: &mut Vec<(&'t mut Table, bool, Vec<Segment>)>,
+) {
+    for (key, kv) in items.iter_mut() {
+        let TableKeyValue {
+            key: ref mut repr,
+            ref mut value,
+        } = *kv;
+        match *value {
+            Item::Value(ref mut v) => lines.push(Line {
+                prefix: &mut repr.decor.prefix,
+                suffix: Some(&mut value_decor(v).suffix),
+                id: Some(LineId::key_value(path, key)),
+                position,
+            }),
+            Item::Table(ref mut t) => {
+                if t.dotted {
+                    let path = child(path, key, 0);
+                    body_lines(&mut t.items, &path, position, lines, subtables);
+                } else {
+                    let displayed = !(t.implicit && is_body_empty(t));
+                    subtables.push((t, displayed, child(path, key, 0)));
+                }
+            }
+            Item::ArrayOfTables(ref mut a) => {
+                for (i, t) in a
+                    .values
+                    .iter_mut()
+                    .filter_map(Item::as_table_mut)
+                    .enumerate()
+                {
+                    subtables.push((t, true, child(path, key, i)));
+                }
+            }
+            Item::None => {}
+        }
+    }
+}
+
+fn value_decor(value: &mut Value) -> &mut Decor {
+    match *value {
+        Value::Integer(ref mut f) => &mut f.repr.decor,
+        Value::String(ref mut f) => &mut f.repr.decor,
+        Value::Float(ref mut f) => &mut f.repr.decor,
+        Value::DateTime(ref mut f) => &mut f.repr.decor,
+        Value::Boolean(ref mut f) => &mut f.repr.decor,
+        Value::Array(ref mut a) => &mut a.decor,
+        Value::InlineTable(ref mut t) => &mut t.decor,
+    }
+}
+
+// the table at the path, not dotted
+fn parent_mut<'t>(table: &'t mut Table, path: &[Key]) -> Option<&'t mut Table> {
+    let mut table = table;
+    for key in path {
+        table = match table.items.get_mut(key.get())?.value {
+            Item::Table(ref mut t) => t,
+            _ => return None,
+        };
+    }
+    Some(table).filter(|t| !t.dotted)
+}
+
+// Splits a decor prefix into the lines preceding an item and its indentation.
+fn split_prefix(prefix: &str) -> (&str, &str) {
+    let i = prefix.rfind('\n').map_or(0, |i| i + 1);
+    (&prefix[..i], &prefix[i..])
+}
+
+fn prepend(decor: &mut InternalString, block: &str) {
+    *decor = InternalString::from(format!("{}{}", block, decor));
+}
+
+// the comments of a key/value pair, preceded by the lines preceding it
+fn value_block(kv: &TableKeyValue) -> String {
+    let (before, indent) = split_prefix(&kv.key.decor.prefix);
+    let text = match kv.value {
+        Item::Value(ref v) => format!("{}{}={}", kv.key.raw_value, kv.key.decor.suffix, v),
+        _ => String::new(),
+    };
+    format!("{}{}", before, comment_lines(&text, indent))
+}
+
+// the comments of a table or an array of tables with its subtables,
+// with its full headers, preceded by the lines preceding it
+fn table_block(path: &KeyPath, kv: &TableKeyValue) -> String {
+    let mut item = kv.value.clone();
+    let prefix = match item {
+        Item::Table(ref mut t) => std::mem::take(&mut t.decor.prefix),
+        Item::ArrayOfTables(ref mut a) => a
+            .values
+            .iter_mut()
+            .find_map(Item::as_table_mut)
+            .map(|t| std::mem::take(&mut t.decor.prefix))
+            .unwrap_or_default(),
+        _ => InternalString::new(),
+    };
+    let mut doc = Document::new();
+    let (last, parents) = path
+        .keys()
+        .split_last()
+        .expect("the path is not empty; qed");
+    let mut table = doc.as_table_mut();
+    for key in parents {
+        let mut parent = Table::new();
+        parent.set_implicit(true);
+        table.insert_formatted(key, Item::Table(parent));
+        table = table[key.get()].as_table_mut().expect("just inserted; qed");
+    }
+    table.insert_formatted(last, item);
+    let (before, indent) = split_prefix(&prefix);
+    format!("{}{}", before, comment_lines(&doc.to_string(), indent))
+}
+
+// Prefixes the lines of `text` with `indent` and `# `.
+fn comment_lines(text: &str, indent: &str) -> String {
+    let mut block = String::new();
+    for line in text.trim_end_matches('\n').split('\n') {
+        block.push_str(indent);
+        block.push('#');
+        if !line.is_empty() {
+            block.push(' ');
+            block.push_str(line);
+        }
+        block.push('\n');
+    }
+    block
+}
+
+// Returns the text of a line written by `comment_lines`.
+#[cfg(feature = "parse")]
+fn uncomment_line(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    line.strip_prefix("# ")
+        .or_else(|| Some("").filter(|_| line == "#"))
+}
+
+// Finds the first run of commented lines of `decor` whose text is accepted,
+// the longest one from the first line, returning the byte range of the lines,
+// the indentation of the first line and the parsed text.
+#[cfg(feature = "parse")]
+fn find_block(
+    decor: &str,
+    accept: &dyn Fn(&Document) -> bool,
+) -> Option<(Range<usize>, String, Document)> {
+    let mut starts = vec![0];
+    starts.extend(decor.match_indices('\n').map(|(i, _)| i + 1));
+    let line = |i: usize| {
+        let end = starts.get(i + 1).map_or(decor.len(), |&e| e - 1);
+        &decor[starts[i]..end]
+    };
+    let n = starts.len();
+    for first in 0..n {
+        match uncomment_line(line(first)) {
+            Some(text) if !text.is_empty() && !text.starts_with('#') => {}
+            _ => continue,
+        }
+        let run = (first..n)
+            .take_while(|&i| uncomment_line(line(i)).is_some())
+            .count();
+        for last in (first..first + run).rev() {
+            let text: Vec<_> = (first..=last)
+                .filter_map(|i| uncomment_line(line(i)))
+                .collect();
+            if let Ok(doc) = text.join("\n").parse::<Document>() {
+                if accept(&doc) {
+                    let start = starts[first];
+                    let end = starts.get(last + 1).copied().unwrap_or(decor.len());
+                    let indent = &line(first)[..line(first).len() - line(first).trim_start().len()];
+                    return Some((start..end, indent.to_owned(), doc));
+                }
+            }
+        }
+    }
+    None
+}
+
+// whether the table has a single item, the value with the key
+#[cfg(feature = "parse")]
+fn is_only_value(table: &Table, key: &str) -> bool {
+    table.iter().filter(|(_, item)| !item.is_none()).count() == 1
+        && table.get(key).is_some_and(Item::is_value)
+}
+
+// whether the table has only the table at the path, within implicit tables
+#[cfg(feature = "parse")]
+fn is_only_table(table: &Table, path: &[Key]) -> bool {
+    let (first, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return true,
+    };
+    let mut items = table.iter().filter(|(_, item)| !item.is_none());
+    match (items.next(), items.next()) {
+        (Some((key, item)), None) if key == first.get() => match *item {
+            Item::Table(ref t) if rest.is_empty() => !t.dotted,
+            Item::ArrayOfTables(..) => rest.is_empty(),
+            Item::Table(ref t) => t.implicit && is_body_empty(t) && is_only_table(t, rest),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+// sets the decor prefix of the first line of a parsed text
+#[cfg(feature = "parse")]
+fn set_first_prefix(doc: &mut Document, prefix: String) {
+    if let Some(line) = lines(&mut doc.as_table_mut().items).into_iter().next() {
+        *line.prefix = InternalString::from(prefix);
+    }
+}
+
+// inserts the item with the key of the parsed text into the items, next to the anchor
+#[cfg(feature = "parse")]
+fn restore(items: &mut KeyValuePairs, key: &str, anchor: Option<Anchor>, mut doc: Document) {
+    let mut kv = match doc.as_table_mut().items.remove(key) {
+        Some(kv) => kv,
+        None => return,
+    };
+    clear_positions(&mut kv.value);
+    items.insert(key.into(), kv);
+    let (anchor, after) = match anchor {
+        Some(Anchor::Before(anchor)) => (anchor, false),
+        Some(Anchor::After(anchor)) => (anchor, true),
+        None => return,
+    };
+    if !items.contains_key(anchor.as_str()) {
+        return;
+    }
+    // move the items following the restored one to the back
+    let moved: Vec<_> = items
+        .keys()
+        .skip_while(|k| **k != anchor)
+        .skip(if after { 1 } else { 0 })
+        .take_while(|k| *k != key)
+        .cloned()
+        .collect();
+    for k in moved {
+        items.get_refresh(&k);
+    }
+}
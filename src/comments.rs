@@ -0,0 +1,711 @@
+use crate::decor::{Decor, Repr};
+use crate::display::is_body_empty;
+use crate::document::Document;
+use crate::formatted;
+use crate::key::Key;
+use crate::key_path::KeyPath;
+use crate::table::{Item, Table};
+use crate::value::Value;
+use linked_hash_map::LinkedHashMap;
+use std::ops::Range;
+
+/// The comments of a document keyed by the paths of the commented items,
+/// see `Document::comments` and `Document::apply_comments`.
+///
+/// Paths are dotted keys, quoted when needed, where elements of arrays of tables
+/// are written as `[i]`, e.g. `bin[1].name`.
+///
+/// The comments preceding and following table headers and key/value pairs are kept,
+/// those inside arrays are not.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct CommentMap {
+    comments: LinkedHashMap<String, Decor>,
+    trailing: String,
+}
+
+impl CommentMap {
+    /// Returns the number of commented items.
+    pub fn len(&self) -> usize {
+        self.comments.len()
+    }
+
+    /// Returns true iff there are no comments, including the trailing ones.
+    pub fn is_empty(&self) -> bool {
+        self.comments.is_empty() && self.trailing.is_empty()
+    }
+
+    /// Returns the comments of the item with the given path.
+    ///
+    /// The prefix holds the lines preceding the item, the suffix the comment
+    /// following it on the same line, either is empty if there is no comment.
+    pub fn get(&self, path: &str) -> Option<&Decor> {
+        self.comments.get(path)
+    }
+
+    /// Returns an iterator over the paths of the commented items and their comments.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (&str, &Decor)> + '_> {
+        Box::new(self.comments.iter().map(|(path, decor)| (&path[..], decor)))
+    }
+
+    /// Returns the comments at the end of the document.
+    pub fn trailing(&self) -> &str {
+        &self.trailing
+    }
+}
+
+impl Document {
+    /// Extracts the comments of the document.
+    ///
+    /// Together with `apply_comments`, this allows restoring the comments of a document
+    /// after it has been deserialized, modified as a typed struct and serialized again.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::Document;
+    ///
+    /// let doc = r#"
+    /// ## the package
+    /// [package]
+    /// name = "hello" # not final
+    /// "#.parse::<Document>().unwrap();
+    /// let comments = doc.comments();
+    ///
+    /// let mut doc = "[package]\nname = \"world\"\n".parse::<Document>().unwrap();
+    /// doc.apply_comments(&comments);
+    /// assert_eq!(doc.to_string(), r#"
+    /// ## the package
+    /// [package]
+    /// name = "world" # not final
+    /// "#);
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn comments(&self) -> CommentMap {
+        let mut comments = LinkedHashMap::new();
+        extract_table(self.as_table(), "", &mut comments);
+        CommentMap {
+            comments,
+            trailing: comment(&self.trailing).to_owned(),
+        }
+    }
+
+    /// Applies the comments onto the items with the same paths,
+    /// replacing their decor where there is a comment.
+    ///
+    /// The comments of items missing from the document are ignored.
+    pub fn apply_comments(&mut self, comments: &CommentMap) {
+        apply_table(self.as_table_mut(), "", comments);
+        if !comments.trailing.is_empty() {
+            self.trailing = comments.trailing.as_str().into();
+        }
+    }
+}
+
+/// A comment of a document, see `Document::comment_spans`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Comment {
+    path: KeyPath,
+    text: String,
+    span: Range<usize>,
+}
+
+impl Comment {
+    /// Returns the path of the table header or the key/value pair the comment belongs to,
+    /// comments within arrays belong to the array, comments within inline tables
+    /// to their key/value pairs. The path is empty for the comments at the end of the document.
+    pub fn path(&self) -> &KeyPath {
+        &self.path
+    }
+
+    /// Returns the text of the comment, starting with `#` and without the line ending.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the byte range of the comment in the displayed document.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl Document {
+    /// Returns the comments of the document in the order they are displayed,
+    /// with their byte ranges in `to_string()` and the paths of the items they belong to.
+    ///
+    /// Unlike `comments`, this includes the comments within arrays and inline tables.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::Document;
+    ///
+    /// let doc = r#"# TODO: license
+    /// [package]
+    /// name = "hello" # the name
+    /// authors = [
+    ///     "a", # TODO: email
+    /// ]
+    /// "#.parse::<Document>().unwrap();
+    /// let todos: Vec<_> = doc
+    ///     .comment_spans()
+    ///     .into_iter()
+    ///     .filter(|c| c.text().starts_with("# TODO"))
+    ///     .map(|c| (c.path().to_string(), c.span()))
+    ///     .collect();
+    /// assert_eq!(todos, vec![("package".to_owned(), 0..15), ("package.authors".to_owned(), 73..86)]);
+    /// assert_eq!(&doc.to_string()[73..86], "# TODO: email");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn comment_spans(&self) -> Vec<Comment> {
+        let mut scanner = Scanner::default();
+        scanner.tables(self.as_table(), &mut KeyPath::new(), false);
+        scanner.decor(&self.trailing, &KeyPath::new());
+        scanner.comments
+    }
+}
+
+// Walks the document in the order of `Display`, keeping track of the offset.
+#[derive(Default)]
+pub(crate) struct Scanner {
+    offset: usize,
+    comments: Vec<Comment>,
+    // the paths of the keys of headers and key/value pairs with their ranges, if recorded
+    keys: Option<Vec<(KeyPath, Range<usize>)>>,
+    // the offsets from which the text belongs to the paths, and the ranges of the
+    // escape sequences, if recorded, see `text_spans`
+    owners: Option<Vec<(usize, KeyPath)>>,
+    escapes: Vec<Range<usize>>,
+}
+
+// The paths owning the displayed text of a document, see `Scanner::text_spans`.
+pub(crate) struct TextSpans {
+    pub(crate) owners: Vec<(usize, KeyPath)>,
+    pub(crate) escapes: Vec<Range<usize>>,
+}
+
+impl Scanner {
+    // returns the keys of the table as displayed by `Display`, see `keys`
+    pub(crate) fn key_spans(table: &Table) -> Vec<(KeyPath, Range<usize>)> {
+        let mut scanner = Scanner {
+            keys: Some(Vec::new()),
+            ..Default::default()
+        };
+        scanner.tables(table, &mut KeyPath::new(), false);
+        scanner.keys.unwrap_or_default()
+    }
+
+    // returns the offsets from which the displayed text belongs to the items of the
+    // document, i.e. the items whose decor, key or value starts there, and the ranges
+    // of the escape sequences of the basic strings
+    pub(crate) fn text_spans(doc: &Document) -> TextSpans {
+        let mut scanner = Scanner {
+            owners: Some(Vec::new()),
+            ..Default::default()
+        };
+        scanner.tables(doc.as_table(), &mut KeyPath::new(), false);
+        scanner.decor(&doc.trailing, &KeyPath::new());
+        TextSpans {
+            owners: scanner.owners.unwrap_or_default(),
+            escapes: scanner.escapes,
+        }
+    }
+
+    // records the key of `path` at the given index, at the offset
+    fn key(&mut self, path: &KeyPath, index: usize, offset: usize) {
+        if let Some(ref mut keys) = self.keys {
+            let key = &path.keys()[index];
+            let prefix = path.keys()[..=index].iter().cloned().collect();
+            keys.push((prefix, offset..offset + key.raw().len()));
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        self.offset += text.len();
+    }
+
+    // a key or a scalar, which may contain escape sequences
+    fn raw(&mut self, raw: &str) {
+        if self.owners.is_some() && raw.starts_with('"') {
+            let offset = self.offset;
+            self.escapes
+                .extend(escapes(raw).map(|r| offset + r.start..offset + r.end));
+        }
+        self.text(raw);
+    }
+
+    fn decor(&mut self, decor: &str, path: &KeyPath) {
+        if let Some(ref mut owners) = self.owners {
+            if owners.last().is_none_or(|(_, p)| p != path) {
+                owners.push((self.offset, path.clone()));
+            }
+        }
+        let mut start = 0;
+        while let Some(i) = decor[start..].find('#') {
+            let begin = start + i;
+            let end = decor[begin..]
+                .find(['\r', '\n'])
+                .map_or(decor.len(), |i| begin + i);
+            self.comments.push(Comment {
+                path: path.clone(),
+                text: decor[begin..end].to_owned(),
+                span: self.offset + begin..self.offset + end,
+            });
+            start = end;
+        }
+        self.text(decor);
+    }
+
+    // the table and its subtables, see `Table::visit_nested_tables`
+    fn tables(&mut self, table: &Table, path: &mut KeyPath, is_array_of_tables: bool) {
+        if !table.dotted {
+            self.header(table, path, is_array_of_tables);
+            self.body(table, path, &mut Vec::new());
+        }
+        for (key, kv) in table.items.iter() {
+            let key = Key::with_repr(key.clone(), kv.key.clone());
+            match kv.value {
+                Item::Table(ref t) => {
+                    path.push(key);
+                    self.tables(t, path, false);
+                    path.pop();
+                }
+                Item::ArrayOfTables(ref a) => {
+                    path.push(key);
+                    for t in a.iter() {
+                        self.tables(t, path, true);
+                    }
+                    path.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn header(&mut self, table: &Table, path: &KeyPath, is_array_of_tables: bool) {
+        if path.is_empty() || !is_array_of_tables && table.implicit && is_body_empty(table) {
+            return;
+        }
+        let brackets = if is_array_of_tables { 2 } else { 1 };
+        let keys: Vec<_> = path.iter().map(|k| k.raw()).collect();
+        self.decor(table.decor.prefix(), path);
+        let mut offset = self.offset + brackets;
+        for (i, key) in keys.iter().enumerate() {
+            self.key(path, i, offset);
+            if self.owners.is_some() && key.starts_with('"') {
+                self.escapes
+                    .extend(escapes(key).map(|r| offset + r.start..offset + r.end));
+            }
+            offset += key.len() + 1;
+        }
+        self.offset += 2 * brackets + keys.join(".").len();
+        self.decor(table.decor.suffix(), path);
+        self.text("\n");
+    }
+
+    // `dotted` holds the raw keys of the enclosing dotted tables, see `visit_body`
+    fn body<'t>(&mut self, table: &'t Table, path: &mut KeyPath, dotted: &mut Vec<&'t str>) {
+        for (key, kv) in table.items.iter() {
+            path.push(Key::with_repr(key.clone(), kv.key.clone()));
+            match kv.value {
+                Item::Value(ref value) => {
+                    self.decor(kv.key.decor.prefix(), path);
+                    let first = path.len() - dotted.len() - 1;
+                    for (i, key) in dotted.iter().enumerate() {
+                        self.key(path, first + i, self.offset);
+                        self.raw(key);
+                        self.text(".");
+                    }
+                    self.key(path, path.len() - 1, self.offset);
+                    self.raw(&kv.key.raw_value);
+                    self.decor(kv.key.decor.suffix(), path);
+                    self.text("=");
+                    self.value(value, path);
+                    self.text("\n");
+                }
+                Item::Table(ref t) if t.dotted => {
+                    dotted.push(&kv.key.raw_value);
+                    self.body(t, path, dotted);
+                    dotted.pop();
+                }
+                _ => {}
+            }
+            path.pop();
+        }
+    }
+
+    fn repr(&mut self, repr: &Repr, path: &KeyPath) {
+        self.decor(repr.decor.prefix(), path);
+        self.raw(&repr.raw_value);
+        self.decor(repr.decor.suffix(), path);
+    }
+
+    fn value(&mut self, value: &Value, path: &mut KeyPath) {
+        match *value {
+            Value::Array(ref a) => {
+                self.decor(a.decor.prefix(), path);
+                self.text("[");
+                for (i, v) in a.iter().enumerate() {
+                    if i > 0 {
+                        self.text(",");
+                    }
+                    self.value(v, path);
+                }
+                if a.trailing_comma && !a.is_empty() {
+                    self.text(",");
+                }
+                self.decor(&a.trailing, path);
+                self.text("]");
+                self.decor(a.decor.suffix(), path);
+            }
+            Value::InlineTable(ref t) => {
+                self.decor(t.decor.prefix(), path);
+                self.text("{");
+                self.decor(&t.preamble, path);
+                let values = t.items.iter().filter(|(_, kv)| kv.value.is_value());
+                for (i, (key, kv)) in values.enumerate() {
+                    if i > 0 {
+                        self.text(",");
+                    }
+                    path.push(Key::with_repr(key.clone(), kv.key.clone()));
+                    self.repr(&kv.key, path);
+                    self.text("=");
+                    if let Some(v) = kv.value.as_value() {
+                        self.value(v, path);
+                    }
+                    path.pop();
+                }
+                self.text("}");
+                self.decor(t.decor.suffix(), path);
+            }
+            Value::String(ref f) => self.repr(f.repr(), path),
+            Value::Integer(ref f) => self.repr(f.repr(), path),
+            Value::Float(ref f) => self.repr(f.repr(), path),
+            Value::Boolean(ref f) => self.repr(f.repr(), path),
+            Value::DateTime(ref f) => self.repr(f.repr(), path),
+        }
+    }
+}
+
+// Returns the ranges of the escape sequences of a quoted basic string, including
+// the whitespaces trimmed by a backslash at the end of a line.
+fn escapes(raw: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        let begin = start + raw[start..].find('\\')?;
+        let rest = &raw[begin + 1..];
+        let len = match rest.chars().next() {
+            Some('u') => 5,
+            Some('U') => 9,
+            Some(c) if c.is_whitespace() => {
+                rest.len() - rest.trim_start_matches(char::is_whitespace).len()
+            }
+            Some(c) => c.len_utf8(),
+            None => 0,
+        };
+        let end = (begin + 1 + len).min(raw.len());
+        start = end;
+        Some(begin..end)
+    })
+}
+
+/// The comments removed by `Document::strip_comments`.
+#[derive(Clone, Copy)]
+pub enum CommentFilter<'a> {
+    /// All the comments.
+    All,
+    /// The comment lines preceding table headers.
+    TableBlocks,
+    /// The comments following headers, values or array elements on the same line.
+    Trailing,
+    /// The comments for which the predicate returns true, given the text of a comment
+    /// starting with `#`.
+    Matching(&'a dyn Fn(&str) -> bool),
+}
+
+impl CommentFilter<'_> {
+    fn removes(&self, comment: &str, own_line: bool, above_table: bool) -> bool {
+        match *self {
+            CommentFilter::All => true,
+            CommentFilter::TableBlocks => own_line && above_table,
+            CommentFilter::Trailing => !own_line,
+            CommentFilter::Matching(predicate) => predicate(comment),
+        }
+    }
+}
+
+impl Document {
+    /// Removes the comments selected by the filter, keeping the blank lines.
+    ///
+    /// Lines consisting of a removed comment are removed, the whitespaces preceding
+    /// a removed trailing comment are removed as well.
+    /// Returns the number of removed comments.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::{CommentFilter, Document};
+    ///
+    /// let s = r#"# the package
+    /// [package] # required
+    /// ## TODO: rename
+    /// name = "hello" # the name
+    ///
+    /// ### dependencies
+    /// [dependencies]
+    /// "#;
+    /// let mut doc = s.parse::<Document>().unwrap();
+    /// assert_eq!(doc.strip_comments(CommentFilter::Trailing), 2);
+    /// let todo = |c: &str| c.contains("TODO");
+    /// assert_eq!(doc.strip_comments(CommentFilter::Matching(&todo)), 1);
+    /// assert_eq!(doc.to_string(), r#"# the package
+    /// [package]
+    /// name = "hello"
+    ///
+    /// ### dependencies
+    /// [dependencies]
+    /// "#);
+    /// assert_eq!(doc.strip_comments(CommentFilter::TableBlocks), 2);
+    /// assert_eq!(doc.to_string(), "[package]\nname = \"hello\"\n\n[dependencies]\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn strip_comments(&mut self, filter: CommentFilter<'_>) -> usize {
+        let mut stripper = Stripper { filter, count: 0 };
+        stripper.table(self.as_table_mut());
+        if let Some(trailing) = stripper.strip(&self.trailing, true, false) {
+            self.trailing = trailing.into();
+        }
+        stripper.count
+    }
+}
+
+struct Stripper<'a> {
+    filter: CommentFilter<'a>,
+    count: usize,
+}
+
+impl Stripper<'_> {
+    // Returns the decor without the removed comments, or `None` if nothing is removed.
+    // `line_start` tells whether the decor starts at the beginning of a line.
+    fn strip(&mut self, decor: &str, line_start: bool, above_table: bool) -> Option<String> {
+        let mut result = String::with_capacity(decor.len());
+        let mut removed = false;
+        let lines = decor.split('\n').collect::<Vec<_>>();
+        for (i, line) in lines.iter().enumerate() {
+            let last = i + 1 == lines.len();
+            let (code, cr) = match line.strip_suffix('\r') {
+                Some(code) => (code, "\r"),
+                None => (*line, ""),
+            };
+            let kept = match code.find('#') {
+                Some(start) => {
+                    let before = &code[..start];
+                    let own_line = before.trim().is_empty() && (i > 0 || line_start);
+                    if self.filter.removes(&code[start..], own_line, above_table) {
+                        removed = true;
+                        self.count += 1;
+                        if own_line {
+                            // the whole line is removed
+                            if !last {
+                                continue;
+                            }
+                            Some(before)
+                        } else {
+                            Some(before.trim_end())
+                        }
+                    } else {
+                        None
+                    }
+                }
+                None => None,
+            };
+            match kept {
+                Some(code) => {
+                    result.push_str(code);
+                    result.push_str(cr);
+                }
+                None => result.push_str(line),
+            }
+            if !last {
+                result.push('\n');
+            }
+        }
+        if removed {
+            Some(result)
+        } else {
+            None
+        }
+    }
+
+    fn table(&mut self, table: &mut Table) {
+        for (_, kv) in table.items.iter_mut() {
+            match kv.value {
+                Item::None => {}
+                Item::Value(ref mut v) => {
+                    if let Some(prefix) = self.strip(kv.key.decor.prefix(), true, false) {
+                        kv.key.decor.set_prefix(prefix);
+                    }
+                    self.value(v, false);
+                }
+                Item::Table(ref mut t) => self.subtable(t),
+                Item::ArrayOfTables(ref mut a) => {
+                    for t in a.iter_mut() {
+                        self.subtable(t);
+                    }
+                }
+            }
+        }
+    }
+
+    fn subtable(&mut self, table: &mut Table) {
+        // dotted tables have no header, their decor is not displayed
+        if !table.dotted {
+            if let Some(prefix) = self.strip(table.decor.prefix(), true, true) {
+                table.decor.set_prefix(prefix);
+            }
+            if let Some(suffix) = self.strip(table.decor.suffix(), false, false) {
+                table.decor.set_suffix(suffix);
+            }
+        }
+        self.table(table);
+    }
+
+    // `prefix` tells whether the prefix of the value may contain comments, i.e. it's an element
+    fn value(&mut self, value: &mut Value, prefix: bool) {
+        let stripped_prefix = if prefix {
+            self.strip(value.decor().prefix(), false, false)
+        } else {
+            None
+        };
+        let stripped_suffix = self.strip(value.decor().suffix(), false, false);
+        if stripped_prefix.is_some() || stripped_suffix.is_some() {
+            let prefix = stripped_prefix.unwrap_or_else(|| value.decor().prefix().to_owned());
+            let suffix = stripped_suffix.unwrap_or_else(|| value.decor().suffix().to_owned());
+            formatted::decorate(value, &prefix, &suffix);
+        }
+        if let Value::Array(ref mut a) = *value {
+            for v in a.values.iter_mut().filter_map(Item::as_value_mut) {
+                self.value(v, true);
+            }
+            if let Some(trailing) = self.strip(a.trailing(), false, false) {
+                a.set_trailing(&trailing);
+            }
+        }
+    }
+}
+
+// returns `decor` if it contains a comment
+fn comment(decor: &str) -> &str {
+    if decor.contains('#') {
+        decor
+    } else {
+        ""
+    }
+}
+
+pub(crate) fn join(path: &str, key: &str) -> String {
+    let key = Key::from_unquoted(key);
+    if path.is_empty() {
+        key.raw().to_owned()
+    } else {
+        format!("{}.{}", path, key.raw())
+    }
+}
+
+fn extract_table(table: &Table, path: &str, comments: &mut LinkedHashMap<String, Decor>) {
+    for (key, kv) in table.items.iter() {
+        let path = join(path, key);
+        match kv.value {
+            Item::None => {}
+            Item::Value(ref v) => {
+                extract_decor(&path, kv.key.decor.prefix(), v.decor().suffix(), comments)
+            }
+            Item::Table(ref t) => {
+                if !t.dotted {
+                    extract_decor(&path, t.decor.prefix(), t.decor.suffix(), comments);
+                }
+                extract_table(t, &path, comments);
+            }
+            Item::ArrayOfTables(ref a) => {
+                for (i, t) in a.iter().enumerate() {
+                    let path = format!("{}[{}]", path, i);
+                    extract_decor(&path, t.decor.prefix(), t.decor.suffix(), comments);
+                    extract_table(t, &path, comments);
+                }
+            }
+        }
+    }
+}
+
+fn extract_decor(
+    path: &str,
+    prefix: &str,
+    suffix: &str,
+    comments: &mut LinkedHashMap<String, Decor>,
+) {
+    let decor = Decor::new(comment(prefix), comment(suffix));
+    if decor != Decor::default() {
+        comments.insert(path.to_owned(), decor);
+    }
+}
+
+fn apply_table(table: &mut Table, path: &str, comments: &CommentMap) {
+    for (key, kv) in table.items.iter_mut() {
+        let path = join(path, key);
+        let decor = comments.get(&path);
+        match kv.value {
+            Item::None => {}
+            Item::Value(ref mut v) => {
+                if let Some(decor) = decor {
+                    if !decor.prefix().is_empty() {
+                        kv.key.decor.set_prefix(decor.prefix());
+                    }
+                    if !decor.suffix().is_empty() {
+                        let prefix = v.decor().prefix().to_owned();
+                        formatted::decorate(v, &prefix, decor.suffix());
+                    }
+                }
+            }
+            Item::Table(ref mut t) => {
+                if let (Some(decor), false) = (decor, t.dotted) {
+                    apply_decor(&mut t.decor, decor);
+                }
+                apply_table(t, &path, comments);
+            }
+            Item::ArrayOfTables(ref mut a) => {
+                for (i, t) in a
+                    .values
+                    .iter_mut()
+                    .filter_map(Item::as_table_mut)
+                    .enumerate()
+                {
+                    let path = format!("{}[{}]", path, i);
+                    if let Some(decor) = comments.get(&path) {
+                        apply_decor(&mut t.decor, decor);
+                    }
+                    apply_table(t, &path, comments);
+                }
+            }
+        }
+    }
+}
+
+fn apply_decor(decor: &mut Decor, comments: &Decor) {
+    if !comments.prefix().is_empty() {
+        decor.set_prefix(comments.prefix());
+    }
+    if !comments.suffix().is_empty() {
+        decor.set_suffix(comments.suffix());
+    }
+}
@@ -0,0 +1,157 @@
+use crate::value::DateTime;
+#[cfg(feature = "datetime")]
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+
+/// The kind of a TOML Date-Time.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash)]
+#[allow(clippy::enum_variant_names)]
+pub enum DatetimeKind {
+    /// A date-time with offset, e.g. `1979-05-27T07:32:00Z`.
+    OffsetDateTime,
+    /// A date-time without offset, e.g. `1979-05-27T07:32:00`.
+    LocalDateTime,
+    /// A date, e.g. `1979-05-27`.
+    LocalDate,
+    /// A time, e.g. `07:32:00`.
+    LocalTime,
+}
+
+#[cfg(feature = "datetime")]
+impl DatetimeKind {
+    fn has_date(self) -> bool {
+        self != DatetimeKind::LocalTime
+    }
+
+    fn has_time(self) -> bool {
+        self != DatetimeKind::LocalDate
+    }
+}
+
+/// The policy of converting a Date-Time between kinds, see `DateTime::convert`.
+///
+/// By default, only conversions which neither lose nor invent information are allowed,
+/// i.e. none but the identity.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "parse")]
+/// # fn main() {
+/// use chrono::NaiveTime;
+/// use toml_edit::{DatetimeConversion, DatetimeKind, Value};
+///
+/// let v = "1979-05-27".parse::<Value>().unwrap();
+/// let d = v.as_date_time().unwrap();
+/// assert_eq!(d.kind(), DatetimeKind::LocalDate);
+///
+/// let conversion = DatetimeConversion::new().time(NaiveTime::from_hms_opt(7, 32, 0).unwrap());
+/// let converted = d.convert(DatetimeKind::LocalDateTime, &conversion).unwrap();
+/// assert_eq!(converted.to_string(), "1979-05-27 07:32:00");
+/// assert!(d.convert(DatetimeKind::LocalTime, &conversion).is_none());
+/// # }
+/// # #[cfg(not(feature = "parse"))]
+/// # fn main() {}
+/// ```
+#[cfg(feature = "datetime")]
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash, Default)]
+pub struct DatetimeConversion {
+    lossy: bool,
+    utc: bool,
+    offset: Option<FixedOffset>,
+    date: Option<NaiveDate>,
+    time: Option<NaiveTime>,
+}
+
+#[cfg(feature = "datetime")]
+impl DatetimeConversion {
+    /// Creates the default policy.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Allows dropping the offset, the date or the time.
+    pub fn lossy(mut self, lossy: bool) -> Self {
+        self.lossy = lossy;
+        self
+    }
+
+    /// When dropping the offset, converts the date-time to UTC
+    /// instead of keeping its local date and time.
+    pub fn utc(mut self, utc: bool) -> Self {
+        self.utc = utc;
+        self
+    }
+
+    /// Sets the offset for converting local date-times to offset ones.
+    pub fn offset(mut self, offset: FixedOffset) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Sets the date for converting local times to date-times.
+    pub fn date(mut self, date: NaiveDate) -> Self {
+        self.date = Some(date);
+        self
+    }
+
+    /// Sets the time for converting local dates to date-times.
+    pub fn time(mut self, time: NaiveTime) -> Self {
+        self.time = Some(time);
+        self
+    }
+}
+
+impl DateTime {
+    /// Returns the kind of `self`.
+    pub fn kind(&self) -> DatetimeKind {
+        match *self {
+            DateTime::OffsetDateTime(..) => DatetimeKind::OffsetDateTime,
+            DateTime::LocalDateTime(..) => DatetimeKind::LocalDateTime,
+            DateTime::LocalDate(..) => DatetimeKind::LocalDate,
+            DateTime::LocalTime(..) => DatetimeKind::LocalTime,
+        }
+    }
+
+    /// Converts `self` to the given kind, returns `None` if the conversion
+    /// is not allowed by the policy.
+    #[cfg(feature = "datetime")]
+    pub fn convert(&self, kind: DatetimeKind, conversion: &DatetimeConversion) -> Option<DateTime> {
+        if self.kind() == kind {
+            return Some(self.clone());
+        }
+        let (date, time) = match *self {
+            DateTime::OffsetDateTime(ref dt) => {
+                if !conversion.lossy {
+                    return None;
+                }
+                let dt = if conversion.utc {
+                    dt.naive_utc()
+                } else {
+                    dt.naive_local()
+                };
+                (Some(dt.date()), Some(dt.time()))
+            }
+            DateTime::LocalDateTime(ref dt) => (Some(dt.date()), Some(dt.time())),
+            DateTime::LocalDate(ref d) => (Some(*d), None),
+            DateTime::LocalTime(ref t) => (None, Some(*t)),
+        };
+        let dropped = (date.is_some() && !kind.has_date()) || (time.is_some() && !kind.has_time());
+        if dropped && !conversion.lossy {
+            return None;
+        }
+        let date = date.or(conversion.date);
+        let time = time.or(conversion.time);
+        let converted = match kind {
+            DatetimeKind::OffsetDateTime => {
+                let dt = NaiveDateTime::new(date?, time?);
+                DateTime::OffsetDateTime(conversion.offset?.from_local_datetime(&dt).single()?)
+            }
+            DatetimeKind::LocalDateTime => {
+                DateTime::LocalDateTime(NaiveDateTime::new(date?, time?))
+            }
+            DatetimeKind::LocalDate => DateTime::LocalDate(date?),
+            DatetimeKind::LocalTime => DateTime::LocalTime(time?),
+        };
+        Some(converted)
+    }
+}
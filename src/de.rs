@@ -0,0 +1,873 @@
+//! Deserializing TOML documents into Rust structures using [serde](https://serde.rs).
+//!
+//! Deserialization borrows from the document, so strings can be deserialized
+//! as `&str` without copying, and as bytes (`serde_bytes`), as can arrays of integers.
+//!
+//! # Example
+//!
+//! ```rust
+//! use serde::Deserialize;
+//! use toml_edit::Document;
+//!
+//! #[derive(Deserialize)]
+//! struct Package<'a> {
+//!     name: &'a str,
+//!     version: &'a str,
+//! }
+//!
+//! let doc = "name = 'toml_edit'\nversion = '0.2.1'".parse::<Document>().unwrap();
+//! let package: Package = toml_edit::de::from_document(&doc).unwrap();
+//! assert_eq!(package.name, "toml_edit");
+//! ```
+//!
+//! Errors of nested values refer to their key path, e.g. ``invalid type: string "yes",
+//! expected a boolean for key `package.publish` ``.
+//! Legacy documents with mistyped values can be deserialized with opt-in coercions,
+//! see `Settings`.
+//!
+//! Floats and out-of-range integers are not truncated into integer fields: the error
+//! refers to the literal and the target type, see `Error::found` and `Error::target`,
+//! unless `Settings::lossy_numbers` is enabled, in which case the conversions can be
+//! recorded with `from_document_with_conversions`.
+
+use crate::document::Document;
+use crate::key::Key;
+use crate::parser::TomlError;
+use crate::ser::Nulls;
+use crate::table::Item;
+use crate::value::Value;
+use serde::de::value::{BorrowedStrDeserializer, MapAccessDeserializer};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+
+/// Type representing a deserialization error.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Error {
+    message: String,
+    // dotted key path of the value, array indices are written as `[i]`
+    path: String,
+    // the literal of a number which doesn't fit its integer type, and the type
+    number: Option<(String, &'static str)>,
+}
+
+impl Error {
+    /// Returns the key path of the value which failed to deserialize,
+    /// or `None` if the error is not related to a nested value.
+    pub fn path(&self) -> Option<&str> {
+        if self.path.is_empty() {
+            None
+        } else {
+            Some(&self.path)
+        }
+    }
+
+    /// Returns the literal of the number which could not be deserialized as an integer
+    /// without truncation, e.g. `3.5` or `300`.
+    pub fn found(&self) -> Option<&str> {
+        self.number.as_ref().map(|(found, _)| &found[..])
+    }
+
+    /// Returns the integer type the number could not be deserialized as, e.g. `u8`.
+    pub fn target(&self) -> Option<&str> {
+        self.number.as_ref().map(|(_, target)| *target)
+    }
+
+    fn truncation(number: Number, found: String, target: &'static str) -> Self {
+        let message = match number {
+            Number::Integer(_) => format!("integer `{}` is out of range for `{}`", found, target),
+            Number::Float(_) => format!(
+                "expected an integer of type `{}`, found float `{}`",
+                target, found
+            ),
+        };
+        Self {
+            message,
+            path: String::new(),
+            number: Some((found, target)),
+        }
+    }
+
+    fn at_key(mut self, key: &str) -> Self {
+        prefix_key(&mut self.path, key);
+        self
+    }
+
+    fn at_index(mut self, index: usize) -> Self {
+        prefix_index(&mut self.path, index);
+        self
+    }
+}
+
+/// A number truncated to fit an integer type, see `Settings::lossy_numbers`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Conversion {
+    path: String,
+    found: String,
+    target: &'static str,
+    converted: String,
+}
+
+impl Conversion {
+    /// Returns the key path of the number, or `None` for a top-level value.
+    pub fn path(&self) -> Option<&str> {
+        if self.path.is_empty() {
+            None
+        } else {
+            Some(&self.path)
+        }
+    }
+
+    /// Returns the literal of the number, e.g. `3.5`.
+    pub fn found(&self) -> &str {
+        &self.found
+    }
+
+    /// Returns the integer type of the field, e.g. `i32`.
+    pub fn target(&self) -> &str {
+        self.target
+    }
+
+    /// Returns the number the field was set to, e.g. `3`.
+    pub fn converted(&self) -> &str {
+        &self.converted
+    }
+}
+
+impl Display for Conversion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` was converted to `{}` of type `{}`",
+            self.found, self.converted, self.target
+        )?;
+        match self.path() {
+            Some(path) => write!(f, " for key `{}`", path),
+            None => Ok(()),
+        }
+    }
+}
+
+// Prefixes a dotted key path with a key.
+fn prefix_key(path: &mut String, key: &str) {
+    let key = key
+        .parse::<Key>()
+        .map_or_else(|_| format!("{:?}", key), |k| k.raw().to_owned());
+    if !path.is_empty() && !path.starts_with('[') {
+        path.insert(0, '.');
+    }
+    path.insert_str(0, &key);
+}
+
+// Prefixes a dotted key path with an array index.
+fn prefix_index(path: &mut String, index: usize) {
+    if !path.is_empty() && !path.starts_with('[') {
+        path.insert(0, '.');
+    }
+    path.insert_str(0, &format!("[{}]", index));
+}
+
+// The conversions recorded by `from_document_with_conversions`, if any.
+type Conversions<'de> = Option<&'de RefCell<Vec<Conversion>>>;
+
+// Prefixes the paths of the conversions recorded since `start`.
+fn prefix_conversions(conversions: Conversions<'_>, start: usize, prefix: impl Fn(&mut String)) {
+    if let Some(conversions) = conversions {
+        for conversion in conversions.borrow_mut().iter_mut().skip(start) {
+            prefix(&mut conversion.path);
+        }
+    }
+}
+
+fn recorded(conversions: Conversions<'_>) -> usize {
+    conversions.map_or(0, |c| c.borrow().len())
+}
+
+#[derive(Clone, Copy)]
+enum Number {
+    Integer(i64),
+    Float(f64),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.path() {
+            Some(path) => write!(f, "{} for key `{}`", self.message, path),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self {
+            message: msg.to_string(),
+            path: String::new(),
+            number: None,
+        }
+    }
+}
+
+impl From<TomlError> for Error {
+    fn from(e: TomlError) -> Self {
+        de::Error::custom(e)
+    }
+}
+
+/// Settings controlling the coercion of mistyped values, all disabled by default.
+///
+/// Integers are always accepted where floats are expected.
+///
+/// # Example
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use toml_edit::de::{self, Settings};
+///
+/// #[derive(Deserialize)]
+/// struct Config {
+///     verbose: bool,
+///     port: u16,
+/// }
+///
+/// let input = "verbose = 'true'\nport = '8080'";
+/// assert!(de::from_str::<Config>(input).is_err());
+///
+/// let settings = Settings::new().bool_from_string(true).number_from_string(true);
+/// let config: Config = de::from_str_with(input, &settings).unwrap();
+/// assert!(config.verbose);
+/// assert_eq!(config.port, 8080);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Settings {
+    bool_from_string: bool,
+    number_from_string: bool,
+    lossy_numbers: bool,
+    nulls: Nulls,
+}
+
+impl Settings {
+    /// Creates the default settings, which don't coerce values.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets whether the strings `"true"` and `"false"` are deserialized as booleans.
+    pub fn bool_from_string(mut self, enabled: bool) -> Self {
+        self.bool_from_string = enabled;
+        self
+    }
+
+    /// Sets whether strings containing numbers, e.g. `"42"`, are deserialized as numbers.
+    pub fn number_from_string(mut self, enabled: bool) -> Self {
+        self.number_from_string = enabled;
+        self
+    }
+
+    /// Sets whether floats are truncated towards zero into integer fields, and integers
+    /// out of the range of their fields are clamped to it, instead of failing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    /// use toml_edit::de::{self, Settings};
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Limits {
+    ///     retries: u8,
+    ///     timeout: u32,
+    /// }
+    ///
+    /// let doc = "retries = 300\ntimeout = 2.5".parse::<toml_edit::Document>().unwrap();
+    /// let err = de::from_document::<Limits>(&doc).unwrap_err();
+    /// assert_eq!(err.to_string(), "integer `300` is out of range for `u8` for key `retries`");
+    /// assert_eq!((err.found(), err.target()), (Some("300"), Some("u8")));
+    ///
+    /// let settings = Settings::new().lossy_numbers(true);
+    /// let (limits, conversions) =
+    ///     de::from_document_with_conversions::<Limits>(&doc, &settings).unwrap();
+    /// assert_eq!((limits.retries, limits.timeout), (255, 2));
+    /// assert_eq!(
+    ///     conversions[1].to_string(),
+    ///     "`2.5` was converted to `2` of type `u32` for key `timeout`"
+    /// );
+    /// ```
+    pub fn lossy_numbers(mut self, enabled: bool) -> Self {
+        self.lossy_numbers = enabled;
+        self
+    }
+
+    /// Sets the handling of missing values written with the given policy, the inverse
+    /// of `ser::Settings::nulls`: with `Nulls::EmptyString` or `Nulls::Sentinel`,
+    /// strings equal to the sentinel are deserialized as `None` and `()`, and as
+    /// strings elsewhere. Missing keys are always deserialized as `None`.
+    ///
+    /// `Nulls::Skip` by default, which leaves strings as they are.
+    pub fn nulls(mut self, policy: Nulls) -> Self {
+        self.nulls = policy;
+        self
+    }
+}
+
+/// Deserializes an instance of `T` from a document, borrowing from it.
+pub fn from_document<'de, T: de::Deserialize<'de>>(doc: &'de Document) -> Result<T, Error> {
+    from_document_with(doc, &Settings::default())
+}
+
+/// Deserializes an instance of `T` from a document with the given settings, borrowing from it.
+pub fn from_document_with<'de, T: de::Deserialize<'de>>(
+    doc: &'de Document,
+    settings: &Settings,
+) -> Result<T, Error> {
+    from_item_with(&doc.root, settings)
+}
+
+/// Deserializes an instance of `T` from a document with the given settings, returning
+/// the numbers truncated to fit their fields, see `Settings::lossy_numbers`.
+pub fn from_document_with_conversions<T: DeserializeOwned>(
+    doc: &Document,
+    settings: &Settings,
+) -> Result<(T, Vec<Conversion>), Error> {
+    let conversions = RefCell::new(Vec::new());
+    let value = T::deserialize(ItemDeserializer {
+        item: &doc.root,
+        settings: *settings,
+        conversions: Some(&conversions),
+    })?;
+    Ok((value, conversions.into_inner()))
+}
+
+/// Deserializes an instance of `T` from an item, borrowing from it.
+pub fn from_item<'de, T: de::Deserialize<'de>>(item: &'de Item) -> Result<T, Error> {
+    from_item_with(item, &Settings::default())
+}
+
+/// Deserializes an instance of `T` from an item with the given settings, borrowing from it.
+pub fn from_item_with<'de, T: de::Deserialize<'de>>(
+    item: &'de Item,
+    settings: &Settings,
+) -> Result<T, Error> {
+    T::deserialize(ItemDeserializer::with_settings(item, *settings))
+}
+
+/// Deserializes an instance of `T` from a value, borrowing from it.
+pub fn from_value<'de, T: de::Deserialize<'de>>(value: &'de Value) -> Result<T, Error> {
+    from_value_with(value, &Settings::default())
+}
+
+/// Deserializes an instance of `T` from a value with the given settings, borrowing from it.
+pub fn from_value_with<'de, T: de::Deserialize<'de>>(
+    value: &'de Value,
+    settings: &Settings,
+) -> Result<T, Error> {
+    T::deserialize(ValueDeserializer::with_settings(value, *settings))
+}
+
+/// Parses a document from a &str and deserializes an instance of `T` from it.
+pub fn from_str<T: DeserializeOwned>(s: &str) -> Result<T, Error> {
+    from_str_with(s, &Settings::default())
+}
+
+/// Parses a document from a &str and deserializes an instance of `T` from it
+/// with the given settings.
+pub fn from_str_with<T: DeserializeOwned>(s: &str, settings: &Settings) -> Result<T, Error> {
+    let doc = s.parse::<Document>()?;
+    from_document_with(&doc, settings)
+}
+
+/// A deserializer over a borrowed `Item`.
+#[derive(Debug, Clone, Copy)]
+pub struct ItemDeserializer<'de> {
+    item: &'de Item,
+    settings: Settings,
+    conversions: Conversions<'de>,
+}
+
+/// A deserializer over a borrowed `Value`.
+#[derive(Debug, Clone, Copy)]
+pub struct ValueDeserializer<'de> {
+    value: &'de Value,
+    settings: Settings,
+    conversions: Conversions<'de>,
+}
+
+impl<'de> ItemDeserializer<'de> {
+    /// Creates a deserializer over `item` with the given settings.
+    pub fn with_settings(item: &'de Item, settings: Settings) -> Self {
+        Self {
+            item,
+            settings,
+            conversions: None,
+        }
+    }
+}
+
+impl<'de> ValueDeserializer<'de> {
+    /// Creates a deserializer over `value` with the given settings.
+    pub fn with_settings(value: &'de Value, settings: Settings) -> Self {
+        Self {
+            value,
+            settings,
+            conversions: None,
+        }
+    }
+
+    fn child(&self, value: &'de Value) -> Self {
+        Self { value, ..*self }
+    }
+
+    fn coerced_bool(&self) -> Option<bool> {
+        match *self.value {
+            Value::String(ref f) if self.settings.bool_from_string => match f.value().trim() {
+                "true" => Some(true),
+                "false" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn coerced_integer(&self) -> Option<i64> {
+        match *self.value {
+            Value::String(ref f) if self.settings.number_from_string => {
+                f.value().trim().parse().ok()
+            }
+            _ => None,
+        }
+    }
+
+    fn coerced_float(&self) -> Option<f64> {
+        match *self.value {
+            Value::String(ref f) if self.settings.number_from_string => {
+                f.value().trim().parse().ok()
+            }
+            _ => None,
+        }
+    }
+
+    // Whether the value is the string missing values are written as, see `Settings::nulls`.
+    fn is_null(&self) -> bool {
+        match (self.value, self.settings.nulls.sentinel()) {
+            (Value::String(ref f), Some(sentinel)) => f.value() == sentinel,
+            _ => false,
+        }
+    }
+
+    // The number deserialized into an integer field, with its literal.
+    fn number(&self) -> Option<(Number, String)> {
+        match *self.value {
+            Value::Integer(ref f) => Some((Number::Integer(*f.value()), f.raw().to_owned())),
+            Value::Float(ref f) => Some((Number::Float(*f.value()), f.raw().to_owned())),
+            _ => self.coerced_integer().map(|i| {
+                (
+                    Number::Integer(i),
+                    self.value.as_raw().unwrap_or_default().to_owned(),
+                )
+            }),
+        }
+    }
+
+    fn record(&self, found: String, target: &'static str, converted: String) {
+        if let Some(conversions) = self.conversions {
+            conversions.borrow_mut().push(Conversion {
+                path: String::new(),
+                found,
+                target,
+                converted,
+            });
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for &'de Item {
+    type Deserializer = ItemDeserializer<'de>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        ItemDeserializer::with_settings(self, Settings::default())
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for &'de Value {
+    type Deserializer = ValueDeserializer<'de>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        ValueDeserializer::with_settings(self, Settings::default())
+    }
+}
+
+type KeyDeserializer<'de> = BorrowedStrDeserializer<'de, Error>;
+
+// Prefixes the errors of the values with their keys.
+struct MapDeserializer<'de, I> {
+    iter: I,
+    value: Option<(&'de str, &'de Item)>,
+    settings: Settings,
+    conversions: Conversions<'de>,
+}
+
+// `Item::None` entries are placeholders and are skipped
+fn map_deserializer<'de, I>(
+    iter: I,
+    settings: Settings,
+    conversions: Conversions<'de>,
+) -> impl de::MapAccess<'de, Error = Error>
+where
+    I: Iterator<Item = (&'de str, &'de Item)>,
+{
+    MapDeserializer {
+        iter: iter.filter(|(_, item)| !item.is_none()),
+        value: None,
+        settings,
+        conversions,
+    }
+}
+
+impl<'de, I> de::MapAccess<'de> for MapDeserializer<'de, I>
+where
+    I: Iterator<Item = (&'de str, &'de Item)>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.iter.next() {
+            Some((key, item)) => {
+                self.value = Some((key, item));
+                seed.deserialize(KeyDeserializer::new(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (key, item) = self
+            .value
+            .take()
+            .expect("next_value_seed is called after next_key_seed; qed");
+        let start = recorded(self.conversions);
+        let deserializer = ItemDeserializer {
+            item,
+            settings: self.settings,
+            conversions: self.conversions,
+        };
+        let value = seed.deserialize(deserializer).map_err(|e| e.at_key(key))?;
+        prefix_conversions(self.conversions, start, |path| prefix_key(path, key));
+        Ok(value)
+    }
+}
+
+// Prefixes the errors of the elements with their indices.
+struct SeqDeserializer<'de, I> {
+    iter: std::iter::Enumerate<I>,
+    conversions: Conversions<'de>,
+}
+
+fn seq_deserializer<I: Iterator>(iter: I, conversions: Conversions<'_>) -> SeqDeserializer<'_, I> {
+    SeqDeserializer {
+        iter: iter.enumerate(),
+        conversions,
+    }
+}
+
+impl<'de, I, D> de::SeqAccess<'de> for SeqDeserializer<'de, I>
+where
+    I: Iterator<Item = D>,
+    D: de::Deserializer<'de, Error = Error>,
+{
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.iter.next() {
+            Some((i, d)) => {
+                let start = recorded(self.conversions);
+                let value = seed.deserialize(d).map_err(|e| e.at_index(i))?;
+                prefix_conversions(self.conversions, start, |path| prefix_index(path, i));
+                Ok(Some(value))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+// Values may be coerced according to the type hints.
+macro_rules! forward_to_value {
+    ($($name:ident)*) => {
+        $(
+            fn $name<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match *self.item {
+                    Item::Value(ref v) => self.value(v).$name(visitor),
+                    _ => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! coerce {
+    ($coerced:ident, $visit:ident => $($name:ident)*) => {
+        $(
+            fn $name<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.$coerced() {
+                    Some(v) => visitor.$visit(v),
+                    None => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+// Floats and out-of-range integers fail, or are truncated with `Settings::lossy_numbers`.
+macro_rules! integer {
+    ($($name:ident => $ty:ident, $visit:ident;)*) => {
+        $(
+            fn $name<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                let (number, found) = match self.number() {
+                    Some((Number::Integer(i), _)) if $ty::try_from(i).is_ok() => {
+                        return visitor.visit_i64(i)
+                    }
+                    Some(number) => number,
+                    None => return self.deserialize_any(visitor),
+                };
+                if !self.settings.lossy_numbers {
+                    return Err(Error::truncation(number, found, stringify!($ty)));
+                }
+                let converted = match number {
+                    Number::Integer(i) if i < 0 => $ty::MIN,
+                    Number::Integer(_) => $ty::MAX,
+                    Number::Float(f) => f as $ty,
+                };
+                self.record(found, stringify!($ty), converted.to_string());
+                visitor.$visit(converted)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ItemDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match *self.item {
+            Item::None => visitor.visit_unit(),
+            Item::Value(ref v) => self.value(v).deserialize_any(visitor),
+            Item::Table(ref t) => {
+                visitor.visit_map(map_deserializer(t.iter(), self.settings, self.conversions))
+            }
+            Item::ArrayOfTables(ref a) => visitor.visit_seq(seq_deserializer(
+                a.values
+                    .iter()
+                    .map(|item| ItemDeserializer { item, ..self }),
+                self.conversions,
+            )),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match *self.item {
+            Item::None => visitor.visit_none(),
+            Item::Value(ref v) => self.value(v).deserialize_option(visitor),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match *self.item {
+            Item::Value(ref v) => self.value(v).deserialize_unit(visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match *self.item {
+            Item::Value(ref v) => self.value(v).deserialize_bytes(visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match *self.item {
+            Item::Value(ref v) => self.value(v).deserialize_enum(name, variants, visitor),
+            Item::Table(ref t) => visitor.visit_enum(MapAccessDeserializer::new(map_deserializer(
+                t.iter(),
+                self.settings,
+                self.conversions,
+            ))),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_value! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_u128 deserialize_f32 deserialize_f64
+    }
+
+    forward_to_deserialize_any! {
+        char str string
+        seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> ItemDeserializer<'de> {
+    fn value(&self, value: &'de Value) -> ValueDeserializer<'de> {
+        ValueDeserializer {
+            value,
+            settings: self.settings,
+            conversions: self.conversions,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match *self.value {
+            Value::Integer(ref f) => visitor.visit_i64(*f.value()),
+            Value::Float(ref f) => visitor.visit_f64(*f.value()),
+            Value::Boolean(ref f) => visitor.visit_bool(*f.value()),
+            Value::String(ref f) => visitor.visit_borrowed_str(f.value()),
+            Value::DateTime(ref f) => visitor.visit_string(f.value().to_string()),
+            Value::Array(ref a) => visitor.visit_seq(seq_deserializer(
+                a.iter().map(|v| self.child(v)),
+                self.conversions,
+            )),
+            Value::InlineTable(ref t) => visitor.visit_map(map_deserializer(
+                t.items.iter().map(|(k, kv)| (&k[..], &kv.value)),
+                self.settings,
+                self.conversions,
+            )),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_null() {
+            visitor.visit_unit()
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Strings are deserialized as their UTF-8 bytes,
+    /// arrays of integers in the `0..=255` range as the corresponding bytes.
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match *self.value {
+            Value::String(ref f) => visitor.visit_borrowed_bytes(f.value().as_bytes()),
+            Value::Array(ref a) => {
+                let bytes = a
+                    .iter()
+                    .map(|v| v.as_integer().and_then(|i| u8::try_from(i).ok()))
+                    .collect::<Option<Vec<u8>>>();
+                match bytes {
+                    Some(bytes) => visitor.visit_byte_buf(bytes),
+                    None => self.deserialize_any(visitor),
+                }
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match *self.value {
+            Value::String(ref f) => visitor.visit_enum(KeyDeserializer::new(f.value())),
+            Value::InlineTable(ref t) => {
+                visitor.visit_enum(MapAccessDeserializer::new(map_deserializer(
+                    t.items.iter().map(|(k, kv)| (&k[..], &kv.value)),
+                    self.settings,
+                    self.conversions,
+                )))
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    coerce!(coerced_bool, visit_bool => deserialize_bool);
+
+    integer! {
+        deserialize_i8 => i8, visit_i8;
+        deserialize_i16 => i16, visit_i16;
+        deserialize_i32 => i32, visit_i32;
+        deserialize_i64 => i64, visit_i64;
+        deserialize_i128 => i128, visit_i128;
+        deserialize_u8 => u8, visit_u8;
+        deserialize_u16 => u16, visit_u16;
+        deserialize_u32 => u32, visit_u32;
+        deserialize_u64 => u64, visit_u64;
+        deserialize_u128 => u128, visit_u128;
+    }
+
+    coerce!(coerced_float, visit_f64 => deserialize_f32 deserialize_f64);
+
+    forward_to_deserialize_any! {
+        char str string
+        seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
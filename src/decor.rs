@@ -1,35 +1,232 @@
+use crate::metadata::NodeIdCell;
+use crate::parser::{lexer, TomlError};
+use crate::value::{DateTime, Value};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
 /// A value together with its `to_string` representation,
 /// including surrounding it whitespaces and comments.
+///
+/// The payload of the scalar variants of `Value`, see `FormattedValue`.
+/// The representation is validated when a `Formatted` is created by the user.
+///
+/// # Example
+/// ```rust
+/// # #[cfg(feature = "parse")]
+/// # fn main() {
+/// use toml_edit::{Formatted, Repr, Value};
+///
+/// let answer = Formatted::<i64>::from_repr(Repr::new(" ", "0x2a", " # hex")).unwrap();
+/// assert_eq!(*answer.value(), 42);
+/// assert_eq!(Value::Integer(answer).to_string(), " 0x2a # hex");
+///
+/// assert!(Formatted::<i64>::from_repr(Repr::new(" ", "'42'", "")).is_err());
+/// # }
+/// # #[cfg(not(feature = "parse"))]
+/// # fn main() {}
+/// ```
 #[derive(Eq, PartialEq, Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Formatted<T> {
     value: T,
     pub(crate) repr: Repr,
+    // the tag of a string captured from an extension literal
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) tag: Option<&'static str>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) id: NodeIdCell,
 }
 
-// String representation of a key or a value
-// together with a decoration.
+/// String representation of a key or a value
+/// together with a decoration.
 #[derive(Eq, PartialEq, Clone, Debug, Hash)]
-pub(crate) struct Repr {
-    pub decor: Decor,
-    pub raw_value: InternalString,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Repr {
+    pub(crate) decor: Decor,
+    pub(crate) raw_value: InternalString,
 }
 
 /// A prefix and suffix,
 /// including comments, whitespaces and newlines.
-#[derive(Eq, PartialEq, Clone, Default, Debug, Hash)]
+#[derive(Eq, PartialEq, PartialOrd, Ord, Clone, Default, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Decor {
     pub(crate) prefix: InternalString,
     pub(crate) suffix: InternalString,
 }
 
-pub(crate) type InternalString = String;
+// The text of keys, representations and decor, shared by the nodes with the same text
+// after `Document::shrink_to_fit`, and copied when one of them is edited.
+// The empty string doesn't allocate.
+#[derive(Clone, Default)]
+pub(crate) struct InternalString(Option<Arc<String>>);
+
+impl InternalString {
+    pub(crate) fn new() -> Self {
+        Self(None)
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        self
+    }
+
+    // The capacity released by dropping the string, zero if other nodes share it.
+    pub(crate) fn owned_capacity(&self) -> usize {
+        match self.0 {
+            Some(ref s) if Arc::strong_count(s) == 1 => s.capacity(),
+            _ => 0,
+        }
+    }
+
+    // Trims the string to its length unless other nodes share it,
+    // returning the released capacity.
+    pub(crate) fn shrink_to_fit(&mut self) -> usize {
+        match self.0.as_mut().and_then(Arc::get_mut) {
+            Some(s) => {
+                let capacity = s.capacity();
+                s.shrink_to_fit();
+                capacity - s.capacity()
+            }
+            None => 0,
+        }
+    }
+}
+
+impl Deref for InternalString {
+    type Target = String;
+
+    fn deref(&self) -> &String {
+        static EMPTY: String = String::new();
+        self.0.as_deref().unwrap_or(&EMPTY)
+    }
+}
+
+impl DerefMut for InternalString {
+    fn deref_mut(&mut self) -> &mut String {
+        Arc::make_mut(self.0.get_or_insert_with(Default::default))
+    }
+}
+
+impl From<String> for InternalString {
+    fn from(s: String) -> Self {
+        Self(Some(s).filter(|s| !s.is_empty()).map(Arc::new))
+    }
+}
+
+impl From<&String> for InternalString {
+    fn from(s: &String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl From<&str> for InternalString {
+    fn from(s: &str) -> Self {
+        Self::from(s.to_owned())
+    }
+}
+
+impl From<InternalString> for String {
+    fn from(s: InternalString) -> Self {
+        match s.0 {
+            Some(s) => Arc::try_unwrap(s).unwrap_or_else(|s| (*s).clone()),
+            None => String::new(),
+        }
+    }
+}
+
+impl Borrow<str> for InternalString {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl AsRef<str> for InternalString {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl PartialEq for InternalString {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for InternalString {}
+
+impl PartialEq<str> for InternalString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for InternalString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for InternalString {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialOrd for InternalString {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for InternalString {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+// hashed like `str`, so that maps keyed by it can be looked up by `&str`
+impl Hash for InternalString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl Debug for InternalString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for InternalString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for InternalString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for InternalString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
 
 impl Decor {
     /// Creates a new decor from the given prefix and suffix.
-    pub fn new<S: Into<InternalString>>(prefix: S, suffix: S) -> Self {
+    pub fn new<S: Into<String>>(prefix: S, suffix: S) -> Self {
         Self {
-            prefix: prefix.into(),
-            suffix: suffix.into(),
+            prefix: prefix.into().into(),
+            suffix: suffix.into().into(),
         }
     }
 
@@ -42,35 +239,216 @@ impl Decor {
     pub fn suffix(&self) -> &str {
         &self.suffix
     }
+
+    /// Set the prefix.
+    pub fn set_prefix<S: Into<String>>(&mut self, prefix: S) {
+        self.prefix = prefix.into().into();
+    }
+
+    /// Set the suffix.
+    pub fn set_suffix<S: Into<String>>(&mut self, suffix: S) {
+        self.suffix = suffix.into().into();
+    }
+
+    /// Creates a new decor from the given prefix and suffix,
+    /// which may consist of whitespaces, newlines and comments only.
+    ///
+    /// Note that a comment extends to the end of its line, a decor
+    /// with a comment and without a trailing newline has to end a line.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use toml_edit::Decor;
+    /// assert!(Decor::try_new("\n# comment\n", " ").is_ok());
+    /// assert!(Decor::try_new("x", "").is_err());
+    /// ```
+    pub fn try_new<S: Into<String>>(prefix: S, suffix: S) -> Result<Self, TomlError> {
+        let decor = Self::new(prefix, suffix);
+        validate_decor(&decor.prefix)?;
+        validate_decor(&decor.suffix)?;
+        Ok(decor)
+    }
+}
+
+fn validate_decor(s: &str) -> Result<(), TomlError> {
+    lexer::decor(s)
 }
 
 impl Repr {
-    pub fn new<S: Into<InternalString>>(prefix: S, value: S, suffix: S) -> Self {
+    /// Creates a new representation from the raw value and its prefix and suffix.
+    ///
+    /// The representation is not validated, see `Formatted::from_repr`.
+    pub fn new<S: Into<String>>(prefix: S, value: S, suffix: S) -> Self {
         Repr {
             decor: Decor::new(prefix, suffix),
-            raw_value: value.into(),
+            raw_value: value.into().into(),
         }
     }
+
+    /// Returns the raw value.
+    pub fn raw(&self) -> &str {
+        &self.raw_value
+    }
+
+    /// Returns the decor.
+    pub fn decor(&self) -> &Decor {
+        &self.decor
+    }
+
+    /// Returns the mutable decor.
+    pub fn decor_mut(&mut self) -> &mut Decor {
+        &mut self.decor
+    }
 }
 
 impl<T> Formatted<T> {
+    /// Returns the raw value, i.e. its `to_string` representation without the decor.
     pub fn raw(&self) -> &str {
         &self.repr.raw_value
     }
 
+    /// Returns the prefix of the decor.
     pub fn prefix(&self) -> &str {
         &self.repr.decor.prefix
     }
 
+    /// Returns the suffix of the decor.
     pub fn suffix(&self) -> &str {
         &self.repr.decor.suffix
     }
 
+    /// Returns the value.
     pub fn value(&self) -> &T {
         &self.value
     }
 
-    pub(crate) fn new(v: T, repr: Repr) -> Self {
-        Self { value: v, repr }
+    /// Returns the representation.
+    pub fn repr(&self) -> &Repr {
+        &self.repr
+    }
+
+    /// Returns the decor.
+    pub fn decor(&self) -> &Decor {
+        &self.repr.decor
+    }
+
+    /// Returns the mutable decor.
+    pub fn decor_mut(&mut self) -> &mut Decor {
+        &mut self.repr.decor
+    }
+
+    /// Returns the value, dropping its representation.
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    pub(crate) fn with_repr(v: T, repr: Repr) -> Self {
+        Self {
+            value: v,
+            repr,
+            tag: None,
+            id: NodeIdCell::default(),
+        }
+    }
+}
+
+impl Formatted<String> {
+    // Shrinks the string value, see `Document::shrink_to_fit`.
+    pub(crate) fn shrink_value(&mut self) -> usize {
+        let capacity = self.value.capacity();
+        self.value.shrink_to_fit();
+        capacity - self.value.capacity()
+    }
+}
+
+impl<T: FormattedValue> Formatted<T> {
+    /// Creates the default representation of the value, without decor.
+    pub fn new(value: T) -> Self {
+        match T::from_value(value.into()) {
+            Ok(f) => f,
+            Err(..) => unreachable!("the value should convert to its own variant"),
+        }
+    }
+
+    /// Creates a value from its representation,
+    /// failing if the raw value is not a valid TOML value of type `T`
+    /// or if the decor is not valid, see `Decor::try_new`.
+    #[cfg(feature = "parse")]
+    pub fn from_repr(repr: Repr) -> Result<Self, TomlError> {
+        validate_decor(&repr.decor.prefix)?;
+        validate_decor(&repr.decor.suffix)?;
+        let value = repr.raw_value.parse::<Value>()?;
+        match T::from_value(value) {
+            Ok(f) => Ok(Self::with_repr(f.value, repr)),
+            Err(value) => Err(TomlError::unexpected_type(
+                format!("{:?}", value.get_type()),
+                T::TYPE_NAME,
+                &repr.raw_value,
+            )),
+        }
+    }
+
+    /// Replaces the raw value, and the value with the parsed raw value, keeping the decor.
+    /// Fails if the raw value is not a valid TOML value of type `T`.
+    #[cfg(feature = "parse")]
+    pub fn set_raw<S: Into<String>>(&mut self, raw: S) -> Result<(), TomlError> {
+        let repr = Repr {
+            decor: Decor::default(),
+            raw_value: raw.into().into(),
+        };
+        let f = Self::from_repr(repr)?;
+        self.value = f.value;
+        self.repr.raw_value = f.repr.raw_value;
+        self.tag = None;
+        Ok(())
+    }
+}
+
+impl Formatted<String> {
+    // Replaces the raw value of a string, and the value with the lexed raw value,
+    // like `set_raw` but without the parser.
+    pub(crate) fn set_raw_string(&mut self, raw: String) -> Result<(), TomlError> {
+        self.value = lexer::string(&raw)?;
+        self.repr.raw_value = raw.into();
+        self.tag = None;
+        Ok(())
     }
 }
+
+/// The types of the values of `Formatted`: `String`, `i64`, `f64`, `bool` and `DateTime`.
+///
+/// This trait is sealed, it is not meant to be implemented outside of this crate.
+pub trait FormattedValue: Into<Value> + private::Sealed {
+    #[doc(hidden)]
+    const TYPE_NAME: &'static str;
+
+    /// Returns the payload of the value if it is of this type, and the value otherwise.
+    fn from_value(value: Value) -> Result<Formatted<Self>, Value>;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_formatted_value {
+    ($ty:ty, $variant:ident) => {
+        impl private::Sealed for $ty {}
+
+        impl FormattedValue for $ty {
+            const TYPE_NAME: &'static str = stringify!($variant);
+
+            fn from_value(value: Value) -> Result<Formatted<Self>, Value> {
+                match value {
+                    Value::$variant(f) => Ok(f),
+                    value => Err(value),
+                }
+            }
+        }
+    };
+}
+
+impl_formatted_value!(String, String);
+impl_formatted_value!(i64, Integer);
+impl_formatted_value!(f64, Float);
+impl_formatted_value!(bool, Boolean);
+impl_formatted_value!(DateTime, DateTime);
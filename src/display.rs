@@ -1,79 +1,108 @@
 use crate::decor::{Formatted, Repr};
 use crate::document::Document;
+use crate::style::Newline;
 use crate::table::{Item, Table};
 use crate::value::{Array, DateTime, InlineTable, Value};
-use std::fmt::{Display, Formatter, Result, Write};
+use std::fmt::{Display, Error, Formatter, Result, Write};
+use std::io;
 
+// The scalars are written piece by piece, without formatting arguments.
 impl Display for Repr {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(
-            f,
-            "{}{}{}",
-            self.decor.prefix, self.raw_value, self.decor.suffix
-        )
+        f.write_str(&self.decor.prefix)?;
+        f.write_str(&self.raw_value)?;
+        f.write_str(&self.decor.suffix)
     }
 }
 
 impl<T> Display for Formatted<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "{}", self.repr)
+        self.repr.fmt(f)
     }
 }
 
 impl Display for DateTime {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match *self {
-            DateTime::OffsetDateTime(d) => write!(f, "{}", d),
-            DateTime::LocalDateTime(d) => write!(f, "{}", d),
-            DateTime::LocalDate(d) => write!(f, "{}", d),
-            DateTime::LocalTime(d) => write!(f, "{}", d),
+            DateTime::OffsetDateTime(ref d) => write!(f, "{}", d),
+            DateTime::LocalDateTime(ref d) => write!(f, "{}", d),
+            DateTime::LocalDate(ref d) => write!(f, "{}", d),
+            DateTime::LocalTime(ref d) => write!(f, "{}", d),
         }
     }
 }
 
-impl Display for Value {
+// Values and tables nested deeper than this are reported as an error
+// instead of overflowing the stack, the parser rejects such documents.
+pub(crate) const MAX_DEPTH: usize = 128;
+
+// A value nested at the given depth.
+struct Nested<'a>(&'a Value, usize);
+
+impl<'a> Display for Nested<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        match *self {
-            Value::Integer(ref repr) => write!(f, "{}", repr),
-            Value::String(ref repr) => write!(f, "{}", repr),
-            Value::Float(ref repr) => write!(f, "{}", repr),
-            Value::Boolean(ref repr) => write!(f, "{}", repr),
-            Value::DateTime(ref repr) => write!(f, "{}", repr),
-            Value::Array(ref array) => write!(f, "{}", array),
-            Value::InlineTable(ref table) => write!(f, "{}", table),
+        let Nested(value, depth) = *self;
+        match *value {
+            Value::Integer(ref repr) => repr.fmt(f),
+            Value::String(ref repr) => repr.fmt(f),
+            Value::Float(ref repr) => repr.fmt(f),
+            Value::Boolean(ref repr) => repr.fmt(f),
+            Value::DateTime(ref repr) => repr.fmt(f),
+            Value::Array(ref array) => fmt_array(f, array, depth),
+            Value::InlineTable(ref table) => fmt_inline_table(f, table, depth),
         }
     }
 }
 
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", Nested(self, 0))
+    }
+}
+
+fn fmt_array(f: &mut Formatter<'_>, array: &Array, depth: usize) -> Result {
+    if depth >= MAX_DEPTH {
+        return Err(Error);
+    }
+    write!(f, "{}[", array.decor.prefix)?;
+    join(f, array.iter().map(|v| Nested(v, depth + 1)), ",")?;
+    if array.trailing_comma && !array.is_empty() {
+        write!(f, ",")?;
+    }
+    write!(f, "{}", array.trailing)?;
+    write!(f, "]{}", array.decor.suffix)
+}
+
 impl Display for Array {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "{}[", self.decor.prefix)?;
-        join(f, self.iter(), ",")?;
-        if self.trailing_comma {
+        fmt_array(f, self, 0)
+    }
+}
+
+fn fmt_inline_table(f: &mut Formatter<'_>, table: &InlineTable, depth: usize) -> Result {
+    if depth >= MAX_DEPTH {
+        return Err(Error);
+    }
+    write!(f, "{}{{", table.decor.prefix)?;
+    write!(f, "{}", table.preamble)?;
+    for (i, (key, value)) in table
+        .items
+        .iter()
+        .filter(|&(_, kv)| kv.value.is_value())
+        .map(|(_, kv)| (&kv.key, kv.value.as_value().unwrap()))
+        .enumerate()
+    {
+        if i > 0 {
             write!(f, ",")?;
         }
-        write!(f, "{}", self.trailing)?;
-        write!(f, "]{}", self.decor.suffix)
+        write!(f, "{}={}", key, Nested(value, depth + 1))?;
     }
+    write!(f, "}}{}", table.decor.suffix)
 }
 
 impl Display for InlineTable {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "{}{{", self.decor.prefix)?;
-        write!(f, "{}", self.preamble)?;
-        for (i, (key, value)) in self
-            .items
-            .iter()
-            .filter(|&(_, kv)| kv.value.is_value())
-            .map(|(_, kv)| (&kv.key, kv.value.as_value().unwrap()))
-            .enumerate()
-        {
-            if i > 0 {
-                write!(f, ",")?;
-            }
-            write!(f, "{}={}", key, value)?;
-        }
-        write!(f, "}}{}", self.decor.suffix)
+        fmt_inline_table(f, self, 0)
     }
 }
 
@@ -87,7 +116,13 @@ impl Table {
     where
         F: FnMut(&Table, &Vec<&'t str>, bool) -> Result,
     {
-        callback(self, path, is_array_of_tables)?;
+        if path.len() > MAX_DEPTH {
+            return Err(Error);
+        }
+        // dotted tables are displayed in their parent's body
+        if !self.dotted {
+            callback(self, path, is_array_of_tables)?;
+        }
 
         for kv in self.items.values() {
             match kv.value {
@@ -120,17 +155,59 @@ fn visit_table(
         // don't print header for the root node
     } else if is_array_of_tables {
         write!(f, "{}[[", table.decor.prefix)?;
-        write!(f, "{}", path.join("."))?;
+        write_path(f, path)?;
         writeln!(f, "]]{}", table.decor.suffix)?;
-    } else if !(table.implicit && table.values_len() == 0) {
+    } else if !(table.implicit && is_body_empty(table)) {
         write!(f, "{}[", table.decor.prefix)?;
-        write!(f, "{}", path.join("."))?;
+        write_path(f, path)?;
         writeln!(f, "]{}", table.decor.suffix)?;
     }
     // print table body
+    visit_body(f, table, &mut Vec::new())
+}
+
+// writes the dotted header path without joining it into a new string
+fn write_path(f: &mut dyn Write, path: &[&str]) -> Result {
+    for (i, key) in path.iter().enumerate() {
+        if i > 0 {
+            f.write_char('.')?;
+        }
+        f.write_str(key)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn is_body_empty(table: &Table) -> bool {
+    table.items.values().all(|kv| match kv.value {
+        Item::Value(..) => false,
+        Item::Table(ref t) if t.dotted => is_body_empty(t),
+        _ => true,
+    })
+}
+
+// prints key/value pairs, including those of dotted subtables prefixed with `path`
+fn visit_body<'t>(f: &mut dyn Write, table: &'t Table, path: &mut Vec<&'t str>) -> Result {
+    if path.len() > MAX_DEPTH {
+        return Err(Error);
+    }
     for kv in table.items.values() {
-        if let Item::Value(ref value) = kv.value {
-            writeln!(f, "{}={}", kv.key, value)?;
+        match kv.value {
+            Item::Value(ref value) => {
+                f.write_str(&kv.key.decor.prefix)?;
+                for key in path.iter() {
+                    f.write_str(key)?;
+                    f.write_char('.')?;
+                }
+                f.write_str(&kv.key.raw_value)?;
+                f.write_str(&kv.key.decor.suffix)?;
+                writeln!(f, "={}", value)?;
+            }
+            Item::Table(ref t) if t.dotted => {
+                path.push(&kv.key.raw_value);
+                visit_body(f, t, path)?;
+                path.pop();
+            }
+            _ => {}
         }
     }
     Ok(())
@@ -150,8 +227,26 @@ impl Display for Table {
 impl Document {
     /// Returns a string representation of the TOML document, attempting to keep
     /// the table headers in their original order.
+    ///
+    /// Like `to_string`, panics if the document was edited to be nested too deeply
+    /// to be displayed, parsed documents never are.
     pub fn to_string_in_original_order(&self) -> String {
         let mut string = String::new();
+        let tables = self
+            .tables_in_original_order()
+            .expect("the document is nested too deeply to be displayed");
+        for (_, table) in tables {
+            string.push_str(&table);
+        }
+        string.push_str(&self.trailing);
+        string
+    }
+
+    // returns the displayed tables in their original order, with their positions,
+    // or an error if the document is nested too deeply to be displayed
+    pub(crate) fn tables_in_original_order(
+        &self,
+    ) -> std::result::Result<Vec<(Option<usize>, String)>, Error> {
         let mut path = Vec::new();
         let mut last_position = 0;
         let mut tables = Vec::new();
@@ -162,24 +257,123 @@ impl Document {
                 }
                 let mut s = String::new();
                 visit_table(&mut s, t, p, is_array)?;
-                tables.push((last_position, s));
+                tables.push((last_position, t.position, s));
                 Ok(())
+            })?;
+
+        tables.sort_by_key(|&(id, _, _)| id);
+        Ok(tables
+            .into_iter()
+            .map(|(_, position, table)| (position, table))
+            .collect())
+    }
+
+    /// Returns a string representation of the TOML document,
+    /// or an error if it's nested too deeply to be displayed.
+    ///
+    /// Displaying such a document with `to_string` panics.
+    /// Parsed documents are never nested too deeply, only edited ones.
+    pub fn try_to_string(&self) -> std::result::Result<String, Error> {
+        let mut string = String::new();
+        write!(string, "{}", self)?;
+        Ok(string)
+    }
+
+    /// Writes the TOML document to `writer` as it is displayed, without building the
+    /// whole string first, e.g. to write large generated documents to files.
+    ///
+    /// The document is written in many small pieces, so `writer` should be buffered.
+    /// Returns an `InvalidData` error if the document is nested too deeply to be displayed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let doc = "[package]\nname = 'a'  # the name\n".parse::<Document>().unwrap();
+    /// let mut out = Vec::new();
+    /// doc.write_to(&mut out).unwrap();
+    /// assert_eq!(out, doc.to_string().into_bytes());
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut adapter = IoWriter {
+            inner: writer,
+            error: None,
+        };
+        write!(adapter, "{}", self).map_err(|_| {
+            adapter.error.unwrap_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the document is nested too deeply to be displayed",
+                )
             })
-            .unwrap();
+        })
+    }
+}
 
-        tables.sort_by_key(|&(id, _)| id);
-        for (_, table) in tables {
-            string.push_str(&table);
-        }
-        string.push_str(&self.trailing);
-        string
+// Forwards the displayed pieces to an `io::Write`, keeping its error.
+struct IoWriter<'w, W> {
+    inner: &'w mut W,
+    error: Option<io::Error>,
+}
+
+impl<'w, W: io::Write> Write for IoWriter<'w, W> {
+    fn write_str(&mut self, s: &str) -> Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            Error
+        })
     }
 }
 
 impl Display for Document {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "{}", self.as_table())?;
-        write!(f, "{}", self.trailing)
+        if self
+            .style
+            .as_ref()
+            .is_some_and(|s| s.newline() == Newline::CrLf)
+        {
+            let mut crlf = CrLfWriter {
+                inner: f,
+                cr: false,
+            };
+            write!(crlf, "{}", self.as_table())?;
+            return crlf.write_str(&self.trailing);
+        }
+        self.as_table().fmt(f)?;
+        f.write_str(&self.trailing)
+    }
+}
+
+// Writes the lines ending with `\n` with `\r\n` instead.
+struct CrLfWriter<'a, 'b> {
+    inner: &'a mut Formatter<'b>,
+    // whether the last character written was `\r`
+    cr: bool,
+}
+
+impl<'a, 'b> Write for CrLfWriter<'a, 'b> {
+    fn write_str(&mut self, s: &str) -> Result {
+        let mut start = 0;
+        for (i, _) in s.match_indices('\n') {
+            let cr = if i == 0 {
+                self.cr
+            } else {
+                s.as_bytes()[i - 1] == b'\r'
+            };
+            if !cr {
+                self.inner.write_str(&s[start..i])?;
+                self.inner.write_str("\r")?;
+                start = i;
+            }
+        }
+        if let Some(&last) = s.as_bytes().last() {
+            self.cr = last == b'\r';
+        }
+        self.inner.write_str(&s[start..])
     }
 }
 
@@ -190,9 +384,9 @@ where
 {
     for (i, v) in iter.enumerate() {
         if i > 0 {
-            write!(f, "{}", sep)?;
+            f.write_str(sep)?;
         }
-        write!(f, "{}", v)?;
+        v.fmt(f)?;
     }
     Ok(())
 }
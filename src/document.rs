@@ -1,15 +1,51 @@
 use crate::decor::InternalString;
-use crate::parser;
-use crate::table::{Item, Iter, Table};
+use crate::key_path::KeyPath;
+#[cfg(feature = "parse")]
+use crate::parser::{self, ParseOptions};
+use crate::table::{IntoFlattenedIter, Item, Iter, Table};
+#[cfg(feature = "parse")]
+use std::ops::Range;
+#[cfg(feature = "parse")]
 use std::str::FromStr;
 
 /// Type representing a TOML document
+///
+/// With the `serde` feature, the document implements `Serialize` and `Deserialize`
+/// preserving its formatting, e.g. to cache parsed documents in a binary format.
+/// Use the `de` and `ser` modules to convert between TOML and Rust structures instead.
+///
+/// Deserializing a document checks that its root is a table and that it would be written
+/// as valid TOML and read back as the same document, see `Document::assert_valid`.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Document {
     /// Root should always be `Item::Table`.
     pub root: Item,
     // Trailing comments and whitespaces
     pub(crate) trailing: InternalString,
+    // the source of a parsed document, see `to_string_verbatim`
+    #[cfg(feature = "verbatim")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) source: Option<Box<crate::verbatim::Source>>,
+    // see `enable_history`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) history: Option<Box<crate::history::History>>,
+    // whether most lines of the parsed source end with `\r\n`, see `detect_style`
+    pub(crate) crlf: bool,
+    // see `set_default_style`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) style: Option<Box<crate::style::StyleReport>>,
+}
+
+/// Where `Document::append_table` puts a table relative to the comments
+/// at the end of the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TablePlacement {
+    /// The table is written before the comments, which stay at the end of the document.
+    #[default]
+    BeforeTrailing,
+    /// The table is written after the comments.
+    AfterTrailing,
 }
 
 impl Default for Document {
@@ -17,6 +53,46 @@ impl Default for Document {
         Self {
             root: Item::Table(Table::with_pos(Some(0))),
             trailing: Default::default(),
+            #[cfg(feature = "verbatim")]
+            source: None,
+            history: None,
+            crlf: false,
+            style: None,
+        }
+    }
+}
+
+// The serialized fields of a document, checked before they make a document.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct DocumentData {
+    root: Item,
+    trailing: InternalString,
+    #[serde(default)]
+    crlf: bool,
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Document {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        let data = DocumentData::deserialize(deserializer)?;
+        if !data.root.is_table() {
+            return Err(D::Error::custom("the root of a document should be a table"));
+        }
+        let doc = Document {
+            root: data.root,
+            trailing: data.trailing,
+            crlf: data.crlf,
+            ..Default::default()
+        };
+        match doc.assert_valid() {
+            Ok(()) => Ok(doc),
+            Err(errors) => Err(D::Error::custom(&errors[0])),
         }
     }
 }
@@ -46,13 +122,227 @@ impl Document {
             .expect("root should always be a table")
             .iter()
     }
+
+    /// Returns a mutable iterator over the tables of the array of tables with the given key,
+    /// e.g. the `[[bin]]` entries, which is empty if there is no such array.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{value, Document};
+    /// let mut doc = "[[bin]]\nname = 'a'\n[[bin]]\nname = 'b'\n".parse::<Document>().unwrap();
+    /// for (i, bin) in doc.array_of_tables_iter_mut("bin").enumerate() {
+    ///     bin["path"] = value(format!("src/bin/{}.rs", i));
+    /// }
+    /// assert_eq!(doc["bin"][1]["path"].as_str(), Some("src/bin/1.rs"));
+    /// assert_eq!(doc.array_of_tables_iter_mut("lib").count(), 0);
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn array_of_tables_iter_mut<'a>(
+        &'a mut self,
+        key: &str,
+    ) -> Box<dyn Iterator<Item = &'a mut Table> + 'a> {
+        match self
+            .as_table_mut()
+            .get_mut(key)
+            .and_then(Item::as_array_of_tables_mut)
+        {
+            Some(array) => array.iter_mut(),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Returns the whitespaces and comments at the end of the document, after the last table,
+    /// e.g. an editor modeline.
+    pub fn trailing(&self) -> &str {
+        &self.trailing
+    }
+
+    /// Sets the whitespaces and comments at the end of the document.
+    pub fn set_trailing(&mut self, trailing: &str) {
+        self.trailing = InternalString::from(trailing);
+    }
+
+    /// Inserts a table into the root table at the end of the document,
+    /// returning the previous item if the key was present.
+    ///
+    /// The comments at the end of the document stay there with `TablePlacement::BeforeTrailing`,
+    /// with `TablePlacement::AfterTrailing` they become the comments preceding the header
+    /// of the new table.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{value, Document, Table, TablePlacement};
+    /// let mut doc = "[a]\nb = 1\n\n# vim: ft=toml\n".parse::<Document>().unwrap();
+    /// let mut c = Table::new();
+    /// c["d"] = value(2);
+    /// doc.append_table("c", c.clone(), TablePlacement::BeforeTrailing);
+    /// assert_eq!(doc.to_string(), "[a]\nb = 1\n\n[c]\nd = 2\n\n# vim: ft=toml\n");
+    ///
+    /// doc.append_table("e", c, TablePlacement::AfterTrailing);
+    /// assert_eq!(doc.trailing(), "");
+    /// assert!(doc.to_string().ends_with("\n# vim: ft=toml\n\n[e]\nd = 2\n"));
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn append_table(
+        &mut self,
+        key: &str,
+        mut table: Table,
+        placement: TablePlacement,
+    ) -> Option<Item> {
+        if placement == TablePlacement::AfterTrailing {
+            let mut prefix = std::mem::take(&mut self.trailing);
+            prefix.push_str(table.decor.prefix());
+            table.decor.set_prefix(prefix);
+        }
+        let old = self.as_table_mut().remove(key);
+        self.as_table_mut().insert(key, Item::Table(table));
+        old
+    }
+
+    /// Removes all the items of the root table,
+    /// keeping the whitespaces and comments at the end of the document.
+    pub fn clear(&mut self) {
+        self.as_table_mut().clear()
+    }
+
+    /// Normalizes the keys of the document, see `Table::bareify_keys`.
+    pub fn bareify_keys(&mut self) -> usize {
+        self.as_table_mut().bareify_keys()
+    }
+
+    /// Removes the entries set to `Item::None` and the implicit tables which are left empty,
+    /// returning their paths, see `Table::compact`.
+    pub fn compact(&mut self) -> Vec<KeyPath> {
+        self.as_table_mut().compact()
+    }
+
+    /// Parses a document from a &str with the given options.
+    #[cfg(feature = "parse")]
+    pub fn parse_with(s: &str, options: &ParseOptions) -> Result<Self, parser::TomlError> {
+        parser::TomlParser::parse(s, options)
+    }
+
+    /// Parses a document from a &str with the given options, returning the inline tables
+    /// merged into table headers with `TableConflicts::Merge`, which are not valid TOML.
+    ///
+    /// # Example
+    /// ```rust
+    /// use toml_edit::{Document, ParseOptions, TableConflicts};
+    ///
+    /// let toml = "a = { x = 1 }\n\n[a.b]\ny = 2\n";
+    /// let err = toml.parse::<Document>().unwrap_err();
+    /// assert_eq!(err.line_col(), (3, 1));
+    ///
+    /// let options = ParseOptions::new().table_conflicts(TableConflicts::Merge);
+    /// let (doc, conflicts) = Document::parse_with_conflicts(toml, &options).unwrap();
+    /// assert_eq!(doc.to_string(), "[a]\nx = 1\n\n[a.b]\ny = 2\n");
+    /// assert_eq!(conflicts[0].path().to_string(), "a");
+    /// assert_eq!(&toml[conflicts[0].span()], "[a.b]");
+    /// assert_eq!(conflicts[0].line_col(), (3, 1));
+    /// ```
+    #[cfg(feature = "parse")]
+    pub fn parse_with_conflicts(
+        s: &str,
+        options: &ParseOptions,
+    ) -> Result<(Self, Vec<parser::TableConflict>), parser::TomlError> {
+        parser::TomlParser::parse_with_conflicts(s, options)
+    }
+
+    /// Returns an iterator parsing the documents separated by lines consisting
+    /// of the delimiter, e.g. `+++`, and surrounding whitespace.
+    ///
+    /// Each document is returned together with its byte range in the input,
+    /// excluding the delimiter lines. Input preceding the first delimiter
+    /// and following the last one are documents too.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use toml_edit::Document;
+    /// let input = "+++\ntitle = 'first'\n+++\ntitle = 'second'\n";
+    /// let docs = Document::parse_multi(input, "+++")
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(docs.len(), 3);
+    /// assert!(docs[0].0.as_table().is_empty());
+    /// assert_eq!(docs[1].0["title"].as_str(), Some("first"));
+    /// assert_eq!(&input[docs[2].1.clone()], "title = 'second'\n");
+    /// ```
+    #[cfg(feature = "parse")]
+    pub fn parse_multi<'a>(input: &'a str, delimiter: &'a str) -> MultiDocumentIter<'a> {
+        MultiDocumentIter {
+            input,
+            delimiter,
+            offset: Some(0),
+        }
+    }
 }
 
+/// An iterator over the documents of a multi-document input.
+///
+/// Created by `Document::parse_multi`.
+#[cfg(feature = "parse")]
+#[derive(Debug, Clone)]
+pub struct MultiDocumentIter<'a> {
+    input: &'a str,
+    delimiter: &'a str,
+    // `None` once the input is exhausted
+    offset: Option<usize>,
+}
+
+#[cfg(feature = "parse")]
+impl<'a> Iterator for MultiDocumentIter<'a> {
+    type Item = Result<(Document, Range<usize>), parser::TomlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.offset?;
+        let mut line_start = start;
+        let range = loop {
+            let line_end = self.input[line_start..]
+                .find('\n')
+                .map_or(self.input.len(), |i| line_start + i + 1);
+            if self.input[line_start..line_end].trim() == self.delimiter {
+                self.offset = Some(line_end);
+                break start..line_start;
+            }
+            if line_end == self.input.len() {
+                self.offset = None;
+                break start..line_end;
+            }
+            line_start = line_end;
+        };
+        let doc = self.input[range.clone()].parse::<Document>();
+        Some(doc.map(|doc| (doc, range)))
+    }
+}
+
+#[cfg(feature = "parse")]
 impl FromStr for Document {
     type Err = parser::TomlError;
 
     /// Parses a document from a &str
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        parser::TomlParser::parse(s)
+        Self::parse_with(s, &ParseOptions::default())
+    }
+}
+
+impl IntoIterator for Document {
+    type Item = (KeyPath, Item);
+    type IntoIter = IntoFlattenedIter;
+
+    /// Converts the document into an iterator over its leaf items and their paths,
+    /// see `Table::into_flattened_iter`.
+    fn into_iter(self) -> Self::IntoIter {
+        match self.root {
+            Item::Table(t) => t.into_flattened_iter(),
+            _ => unreachable!("root should always be a table"),
+        }
     }
 }
@@ -0,0 +1,145 @@
+use crate::decor::{Decor, Repr};
+use crate::document::Document;
+use crate::table::{Item, Table};
+
+impl Document {
+    /// Converts the tables defined by dotted keys into standard tables.
+    ///
+    /// Comments preceding the dotted keys are preserved.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::Document;
+    ///
+    /// let mut doc = "name = 'toml_edit'\n# the edition\npackage.edition = '2018'\n"
+    ///     .parse::<Document>()
+    ///     .unwrap();
+    /// doc.expand_dotted_keys();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "name = 'toml_edit'\n\n[package]\n# the edition\nedition = '2018'\n"
+    /// );
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn expand_dotted_keys(&mut self) {
+        expand(self.as_table_mut());
+    }
+
+    /// Converts standard tables into dotted keys in the body of their parent tables,
+    /// as long as the resulting keys consist of at most `max_depth` parts.
+    ///
+    /// Tables containing arrays of tables, as well as empty tables, are kept.
+    /// Comments preceding the table headers are moved before the first dotted key.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::Document;
+    ///
+    /// let mut doc = "[a]\nb = 1\n[c.d.e]\nf = 2\n".parse::<Document>().unwrap();
+    /// doc.collapse_to_dotted(3);
+    /// assert_eq!(doc.to_string(), "a.b = 1\n\n[c]\nd.e.f = 2\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn collapse_to_dotted(&mut self, max_depth: usize) {
+        collapse(self.as_table_mut(), max_depth);
+    }
+}
+
+fn expand(table: &mut Table) {
+    for (_, kv) in table.items.iter_mut() {
+        match kv.value {
+            Item::Table(ref mut t) => {
+                if t.dotted {
+                    t.dotted = false;
+                    // hide the headers of tables without key/value pairs
+                    t.implicit = true;
+                    t.decor = Decor::new("\n", "");
+                }
+                expand(t);
+            }
+            Item::ArrayOfTables(ref mut a) => {
+                for t in a.values.iter_mut().filter_map(Item::as_table_mut) {
+                    expand(t);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collapse(table: &mut Table, max_depth: usize) {
+    for (_, kv) in table.items.iter_mut() {
+        match kv.value {
+            Item::Table(ref mut t) if t.dotted => {}
+            Item::Table(ref mut t) => match depth(t) {
+                // a dotted key has a part per table and one for the value
+                Some(depth) if depth < max_depth => make_dotted(t),
+                _ => collapse(t, max_depth),
+            },
+            Item::ArrayOfTables(ref mut a) => {
+                for t in a.values.iter_mut().filter_map(Item::as_table_mut) {
+                    collapse(t, max_depth);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Returns the number of nested tables on the longest path to a value,
+// or `None` if the table can't be made dotted.
+fn depth(table: &Table) -> Option<usize> {
+    let mut max = None;
+    for (_, kv) in table.items.iter() {
+        let d = match kv.value {
+            Item::None => continue,
+            Item::Value(..) => 1,
+            Item::Table(ref t) => depth(t)? + 1,
+            Item::ArrayOfTables(..) => return None,
+        };
+        max = max.max(Some(d));
+    }
+    max
+}
+
+fn make_dotted(table: &mut Table) {
+    table.dotted = true;
+    for (_, kv) in table.items.iter_mut() {
+        if let Item::Table(ref mut t) = kv.value {
+            make_dotted(t);
+        }
+    }
+    let comment = table.decor.prefix.trim_start_matches('\n');
+    if !comment.trim().is_empty() {
+        let comment = comment.to_owned();
+        if let Some(key) = first_key(table) {
+            key.decor.prefix = (comment + &key.decor.prefix).into();
+        }
+    }
+}
+
+// Returns the key of the first value displayed in the body of the table.
+fn first_key(table: &mut Table) -> Option<&mut Repr> {
+    for (_, kv) in table.items.iter_mut() {
+        match kv.value {
+            Item::Value(..) => return Some(&mut kv.key),
+            Item::Table(ref mut t) if t.dotted => {
+                if let Some(key) = first_key(t) {
+                    return Some(key);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
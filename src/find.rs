@@ -0,0 +1,90 @@
+use crate::comments::{Scanner, TextSpans};
+use crate::document::Document;
+use crate::key_path::KeyPath;
+use std::ops::Range;
+
+impl Document {
+    /// Returns the occurrences of `pattern` in `to_string()` with their byte ranges
+    /// and the paths of the items they belong to, in the order they are displayed.
+    ///
+    /// An occurrence belongs to the table header or the key/value pair on which it starts,
+    /// including its decor, e.g. the comments above it. Occurrences within arrays
+    /// belong to the array, occurrences within inline tables to their key/value pairs.
+    /// The path is empty for the text at the end of the document.
+    /// The ranges are those of lines ending with `\n`, whatever the line endings
+    /// of `Document::set_default_style`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let doc = "[package]\nname = 'toml' # toml parser\n[dependencies]\ntoml = '0.5'\n"
+    ///     .parse::<Document>()
+    ///     .unwrap();
+    /// let found: Vec<_> = doc
+    ///     .find_text("toml")
+    ///     .into_iter()
+    ///     .map(|(path, span)| (path.to_string(), span))
+    ///     .collect();
+    /// assert_eq!(found, vec![
+    ///     ("package.name".to_owned(), 18..22),
+    ///     ("package.name".to_owned(), 26..30),
+    ///     ("dependencies.toml".to_owned(), 53..57),
+    /// ]);
+    /// assert_eq!(&doc.to_string()[53..57], "toml");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn find_text(&self, pattern: &str) -> Vec<(KeyPath, Range<usize>)> {
+        self.find(pattern, false)
+    }
+
+    /// Returns the occurrences of `pattern` like `find_text`, except those which cut
+    /// an escape sequence of a basic string, e.g. the `n` of `"\n"` or the `\u00` of `"é"`.
+    /// Occurrences containing whole escape sequences are kept.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let doc = r#"message = "one\ntwo\\n""#.parse::<Document>().unwrap();
+    /// assert_eq!(doc.find_text("n").len(), 3);
+    /// let found: Vec<_> = doc.find_text_outside_escapes("n").into_iter().map(|(_, s)| s).collect();
+    /// assert_eq!(found, vec![12..13, 21..22]);
+    /// assert_eq!(doc.find_text_outside_escapes("\\n").len(), 1);
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn find_text_outside_escapes(&self, pattern: &str) -> Vec<(KeyPath, Range<usize>)> {
+        self.find(pattern, true)
+    }
+
+    fn find(&self, pattern: &str, skip_escapes: bool) -> Vec<(KeyPath, Range<usize>)> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+        let TextSpans { owners, escapes } = Scanner::text_spans(self);
+        let cuts = |span: &Range<usize>| {
+            escapes.iter().any(|e| {
+                span.start > e.start && span.start < e.end || span.end > e.start && span.end < e.end
+            })
+        };
+        let mut text = self.as_table().to_string();
+        text.push_str(&self.trailing);
+        text.match_indices(pattern)
+            .map(|(i, m)| i..i + m.len())
+            .filter(|span| !skip_escapes || !cuts(span))
+            .map(|span| {
+                let owner = owners.partition_point(|&(offset, _)| offset <= span.start);
+                let path = owner
+                    .checked_sub(1)
+                    .map_or_else(KeyPath::new, |i| owners[i].1.clone());
+                (path, span)
+            })
+            .collect()
+    }
+}
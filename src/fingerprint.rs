@@ -0,0 +1,304 @@
+use crate::document::Document;
+use crate::table::{Item, TableLike};
+use crate::value::{DateTime, Value};
+#[cfg(feature = "datetime")]
+use chrono::{Datelike, Timelike};
+
+impl Document {
+    /// Returns a hash of the content of the document regardless of its formatting.
+    ///
+    /// Documents which differ in comments, whitespaces, the representations of keys
+    /// and values, the order of keys or in whether tables are written as standard tables,
+    /// inline tables or dotted keys, and arrays of tables as arrays of inline tables,
+    /// have the same fingerprint. Unlike `std` hashers, the fingerprint is stable across runs
+    /// and platforms, so that it may be stored e.g. as a cache key or to detect edits which
+    /// didn't change the meaning of a document.
+    ///
+    /// Date-times are compared by what they denote, offset date-times as instants,
+    /// e.g. `1979-05-27T07:32:00-08:00` and `1979-05-27T15:32:00Z` are the same,
+    /// and the fingerprint is the same with or without the `datetime` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::{Document, value};
+    ///
+    /// let a = "[package]\nname = 'a' # the name\nversion = 0x10\n".parse::<Document>().unwrap();
+    /// let mut b = "package = { version = 16, name = \"a\" }".parse::<Document>().unwrap();
+    /// assert_eq!(a.fingerprint(), b.fingerprint());
+    ///
+    /// b["package"]["version"] = value(17);
+    /// assert_ne!(a.fingerprint(), b.fingerprint());
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv::new();
+        hasher.table(self.as_table());
+        hasher.0
+    }
+}
+
+// Returns the FNV-1a hash of a text, stable across runs and platforms.
+pub(crate) fn hash_text(text: &str) -> u64 {
+    let mut hasher = Fnv::new();
+    hasher.bytes(text.as_bytes());
+    hasher.0
+}
+
+// The components of a date-time: the year, month and day, the hour, minute, second
+// and nanosecond, where a leap second is the second 59 with more than 10^9 nanoseconds,
+// and the offset in minutes.
+type Date = (i64, u32, u32);
+type Time = (u32, u32, u32, u32);
+
+#[derive(Default)]
+struct Parts {
+    date: Option<Date>,
+    time: Option<Time>,
+    offset: Option<i64>,
+}
+
+#[cfg(feature = "datetime")]
+fn parts(date_time: &DateTime) -> Option<Parts> {
+    fn date(d: &impl Datelike) -> Option<Date> {
+        Some((i64::from(d.year()), d.month(), d.day()))
+    }
+    fn time(t: &impl Timelike) -> Option<Time> {
+        Some((t.hour(), t.minute(), t.second(), t.nanosecond()))
+    }
+    Some(match *date_time {
+        DateTime::OffsetDateTime(ref dt) => {
+            let utc = dt.naive_utc();
+            Parts {
+                date: date(&utc),
+                time: time(&utc),
+                offset: Some(0),
+            }
+        }
+        DateTime::LocalDateTime(ref dt) => Parts {
+            date: date(dt),
+            time: time(dt),
+            offset: None,
+        },
+        DateTime::LocalDate(ref d) => Parts {
+            date: date(d),
+            ..Default::default()
+        },
+        DateTime::LocalTime(ref t) => Parts {
+            time: time(t),
+            ..Default::default()
+        },
+    })
+}
+
+// reads the date-time as it is written, returns `None` if it's not RFC 3339
+#[cfg(not(feature = "datetime"))]
+fn parts(date_time: &DateTime) -> Option<Parts> {
+    fn number(s: &str, digits: usize) -> Option<(u32, &str)> {
+        let (n, rest) = (s.get(..digits)?, &s[digits..]);
+        if !n.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        Some((n.parse().ok()?, rest))
+    }
+    fn date(s: &str) -> Option<(Date, &str)> {
+        let (year, s) = number(s, 4)?;
+        let (month, s) = number(s.strip_prefix('-')?, 2)?;
+        let (day, s) = number(s.strip_prefix('-')?, 2)?;
+        Some(((i64::from(year), month, day), s))
+    }
+    fn time(s: &str) -> Option<(Time, &str)> {
+        let (hour, s) = number(s, 2)?;
+        let (minute, s) = number(s.strip_prefix(':')?, 2)?;
+        let (second, mut s) = number(s.strip_prefix(':')?, 2)?;
+        let mut nanos = 0;
+        if let Some(fraction) = s.strip_prefix('.') {
+            let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+            if digits == 0 {
+                return None;
+            }
+            // the digits beyond nanoseconds are ignored
+            for (i, b) in fraction.bytes().take(9).take(digits).enumerate() {
+                nanos += u32::from(b - b'0') * 10u32.pow(8 - i as u32);
+            }
+            s = &fraction[digits..];
+        }
+        let (second, nanos) = if second == 60 {
+            (59, nanos + 1_000_000_000)
+        } else {
+            (second, nanos)
+        };
+        Some(((hour, minute, second, nanos), s))
+    }
+    fn offset(s: &str) -> Option<i64> {
+        if s == "Z" || s == "z" {
+            return Some(0);
+        }
+        let (sign, s) = match s.as_bytes().first()? {
+            b'+' => (1, &s[1..]),
+            b'-' => (-1, &s[1..]),
+            _ => return None,
+        };
+        let (hours, s) = number(s, 2)?;
+        let (minutes, s) = number(s.strip_prefix(':')?, 2)?;
+        Some(sign * i64::from(hours * 60 + minutes)).filter(|_| s.is_empty())
+    }
+
+    let (text, has_date, has_offset) = match *date_time {
+        DateTime::OffsetDateTime(ref s) => (s, true, true),
+        DateTime::LocalDateTime(ref s) => (s, true, false),
+        DateTime::LocalDate(ref s) => (s, true, false),
+        DateTime::LocalTime(ref s) => (s, false, false),
+    };
+    let mut parts = Parts::default();
+    let mut s = text.as_str();
+    if has_date {
+        let (date, rest) = date(s)?;
+        parts.date = Some(date);
+        s = rest;
+        if s.is_empty() {
+            return Some(parts).filter(|_| !has_offset);
+        }
+        s = s.strip_prefix(|c| c == 'T' || c == 't' || c == ' ')?;
+    }
+    let (time, rest) = time(s)?;
+    parts.time = Some(time);
+    if has_offset {
+        parts.offset = Some(offset(rest)?);
+    } else if !rest.is_empty() {
+        return None;
+    }
+    Some(parts)
+}
+
+// the number of days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((i64::from(month) + 9) % 12) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// 64-bit FNV-1a over a canonical encoding of the content, tables are encoded
+// with their keys sorted and every part is tagged or prefixed with its length.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn tag(&mut self, tag: u8) {
+        self.bytes(&[tag]);
+    }
+
+    fn u64(&mut self, n: u64) {
+        self.bytes(&n.to_le_bytes());
+    }
+
+    fn str(&mut self, s: &str) {
+        self.u64(s.len() as u64);
+        self.bytes(s.as_bytes());
+    }
+
+    // date-times are encoded as the seconds and nanoseconds since 1970-01-01T00:00:00,
+    // in UTC for offset date-times
+    fn date_time(&mut self, date_time: &DateTime) {
+        let parts = match parts(date_time) {
+            Some(parts) => parts,
+            None => {
+                self.tag(b'?');
+                self.str(&date_time.to_string());
+                return;
+            }
+        };
+        let days = parts.date.map(|(y, m, d)| days_from_civil(y, m, d));
+        let (seconds, nanos) = parts
+            .time
+            .map_or((0, 0), |(h, m, s, n)| (i64::from(h * 3600 + m * 60 + s), n));
+        let tag = match (days, parts.time, parts.offset) {
+            (Some(_), Some(_), Some(_)) => b'o',
+            (Some(_), Some(_), None) => b'l',
+            (Some(_), None, _) => b'd',
+            _ => b'h',
+        };
+        let seconds = days.unwrap_or(0) * 86_400 + seconds - parts.offset.unwrap_or(0) * 60;
+        self.tag(tag);
+        self.u64(seconds as u64);
+        self.u64(u64::from(nanos));
+    }
+
+    fn item(&mut self, item: &Item) {
+        match *item {
+            Item::Value(ref v) => self.value(v),
+            Item::Table(ref t) => self.table(t),
+            Item::ArrayOfTables(ref a) => {
+                self.tag(b'a');
+                self.u64(a.len() as u64);
+                for t in a.iter() {
+                    self.table(t);
+                }
+            }
+            Item::None => {}
+        }
+    }
+
+    fn table(&mut self, table: &dyn TableLike) {
+        let mut entries: Vec<_> = table.iter().filter(|(_, item)| !item.is_none()).collect();
+        entries.sort_by_key(|&(key, _)| key);
+        self.tag(b't');
+        self.u64(entries.len() as u64);
+        for (key, item) in entries {
+            self.str(key);
+            self.item(item);
+        }
+    }
+
+    fn value(&mut self, value: &Value) {
+        match *value {
+            Value::String(ref f) => {
+                self.tag(b's');
+                self.str(f.value());
+            }
+            Value::Integer(ref f) => {
+                self.tag(b'i');
+                self.bytes(&f.value().to_le_bytes());
+            }
+            Value::Float(ref f) => {
+                // all NaNs are the same, and -0.0 is 0.0 (`-0.0 + 0.0 == 0.0`)
+                let f = match *f.value() {
+                    f if f.is_nan() => f64::NAN,
+                    f => f + 0.0,
+                };
+                self.tag(b'f');
+                self.u64(f.to_bits());
+            }
+            Value::Boolean(ref f) => {
+                self.tag(b'b');
+                self.tag(*f.value() as u8);
+            }
+            Value::DateTime(ref f) => self.date_time(f.value()),
+            Value::Array(ref a) => {
+                self.tag(b'a');
+                self.u64(a.len() as u64);
+                for v in a.iter() {
+                    self.value(v);
+                }
+            }
+            Value::InlineTable(ref t) => self.table(t),
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use crate::decor::InternalString;
+use crate::document::Document;
+use crate::formatted;
+use crate::key::Key;
+use crate::parser::TomlError;
+use crate::reflow::{self, Reflow};
+use crate::table::{Item, KeyValuePairs, Table, TableKeyValue};
+use crate::value::Value;
+
+/// The formatting operations applied by `Document::format_matching`.
+///
+/// By default, the values are auto formatted, and the key/value pairs are neither
+/// sorted nor are their arrays written on multiple lines. Arrays and inline tables
+/// containing comments are kept as they are.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "parse")]
+/// # fn main() {
+/// use toml_edit::{Document, FmtOptions};
+///
+/// let mut doc = r#"
+/// [package]
+/// name="a"
+/// authors=["a","b"]
+///
+/// [dependencies]
+/// serde={version="1.0",features=["derive"]}
+/// log="0.4"
+/// "#.parse::<Document>().unwrap();
+///
+/// let options = FmtOptions::new().sort(true);
+/// assert_eq!(doc.format_matching(&["dependencies"], &options).unwrap(), 1);
+/// let options = FmtOptions::new().fmt(false).multiline_arrays(true);
+/// assert_eq!(doc.format_matching(&["*.authors"], &options).unwrap(), 1);
+///
+/// assert_eq!(doc.to_string(), r#"
+/// [package]
+/// name="a"
+/// authors=[
+///     "a",
+///     "b",
+/// ]
+///
+/// [dependencies]
+/// log = "0.4"
+/// serde = { features = ["derive"], version = "1.0" }
+/// "#);
+/// # }
+/// # #[cfg(not(feature = "parse"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FmtOptions {
+    fmt: bool,
+    sort: bool,
+    multiline_arrays: bool,
+    indent: InternalString,
+}
+
+impl Default for FmtOptions {
+    fn default() -> Self {
+        Self {
+            fmt: true,
+            sort: false,
+            multiline_arrays: false,
+            indent: InternalString::from("    "),
+        }
+    }
+}
+
+impl FmtOptions {
+    /// Creates the default options: auto formatting only.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets whether to auto format the values, like `Array::fmt` and `InlineTable::fmt`
+    /// do, and the spaces around the `=` of the key/value pairs.
+    pub fn fmt(mut self, yes: bool) -> Self {
+        self.fmt = yes;
+        self
+    }
+
+    /// Sets whether to sort the key/value pairs of the tables and inline tables by key.
+    pub fn sort(mut self, yes: bool) -> Self {
+        self.sort = yes;
+        self
+    }
+
+    /// Sets whether to write the non-empty arrays of key/value pairs with an element
+    /// per line, see `Reflow`.
+    pub fn multiline_arrays(mut self, yes: bool) -> Self {
+        self.multiline_arrays = yes;
+        self
+    }
+
+    /// Sets the indentation of the elements of multi-line arrays.
+    pub fn indent(mut self, indent: &str) -> Self {
+        self.indent = indent.into();
+        self
+    }
+
+    fn entry(&self, kv: &mut TableKeyValue, inline: bool) {
+        match kv.value {
+            Item::Value(ref mut v) => {
+                if self.fmt {
+                    kv.key.decor.suffix = InternalString::from(" ");
+                    if inline {
+                        kv.key.decor.prefix = InternalString::from(" ");
+                    }
+                    let suffix = v.decor().suffix().to_owned();
+                    formatted::decorate(v, " ", &suffix);
+                }
+                self.value(v);
+                if self.multiline_arrays && !inline && !reflow::is_commented(v) {
+                    if let Value::Array(ref mut a) = *v {
+                        if !a.is_empty() {
+                            let prefix = &kv.key.decor.prefix;
+                            let line_indent = &prefix[prefix.rfind('\n').map_or(0, |i| i + 1)..];
+                            let wrap = Reflow::new().indent(&self.indent);
+                            *a = wrap.wrap_array(reflow::compact_array(a), line_indent);
+                        }
+                    }
+                }
+            }
+            Item::Table(ref mut t) => self.table(t),
+            Item::ArrayOfTables(ref mut a) => {
+                for t in a.values.iter_mut().filter_map(Item::as_table_mut) {
+                    self.table(t);
+                }
+            }
+            Item::None => {}
+        }
+    }
+
+    fn table(&self, table: &mut Table) {
+        if self.sort {
+            table.sort_values();
+        }
+        for (_, kv) in table.items.iter_mut() {
+            self.entry(kv, false);
+        }
+    }
+
+    fn value(&self, value: &mut Value) {
+        if let Value::InlineTable(ref mut t) = *value {
+            if self.sort {
+                t.sort();
+            }
+        }
+        if self.fmt && !reflow::is_commented(value) {
+            reflow::compact(value);
+        }
+    }
+}
+
+// A path whose keys may be `*`, matching any key.
+struct Glob(Vec<Option<String>>);
+
+impl Glob {
+    fn parse(s: &str) -> Result<Self, TomlError> {
+        let mut segments = Vec::new();
+        let mut start = 0;
+        let mut quote = None;
+        for (i, c) in s.char_indices() {
+            match (c, quote) {
+                ('"', None) | ('\'', None) => quote = Some(c),
+                (c, Some(q)) if c == q => quote = None,
+                ('.', None) => {
+                    segments.push(&s[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        segments.push(&s[start..]);
+        let segments = segments
+            .into_iter()
+            .map(|segment| match segment.trim() {
+                "*" => Ok(None),
+                segment => Key::try_parse(segment).map(|k| Some(k.get().to_owned())),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Glob(segments))
+    }
+
+    fn matches(&self, path: &[&str]) -> bool {
+        self.0.len() == path.len()
+            && self
+                .0
+                .iter()
+                .zip(path)
+                .all(|(segment, key)| segment.as_deref().is_none_or(|s| s == *key))
+    }
+}
+
+// Applies the options to the entries whose paths match a glob, counting them.
+fn visit<'k>(
+    items: &'k mut KeyValuePairs,
+    inline: bool,
+    path: &mut Vec<&'k str>,
+    globs: &[Glob],
+    options: &FmtOptions,
+) -> usize {
+    let mut count = 0;
+    for (key, kv) in items.iter_mut() {
+        path.push(key);
+        if globs.iter().any(|g| g.matches(path)) {
+            options.entry(kv, inline);
+            count += 1;
+        } else {
+            match kv.value {
+                Item::Table(ref mut t) => count += visit(&mut t.items, false, path, globs, options),
+                Item::ArrayOfTables(ref mut a) => {
+                    for t in a.values.iter_mut().filter_map(Item::as_table_mut) {
+                        count += visit(&mut t.items, false, path, globs, options);
+                    }
+                }
+                Item::Value(Value::InlineTable(ref mut t)) => {
+                    count += visit(&mut t.items, true, path, globs, options)
+                }
+                _ => {}
+            }
+        }
+        path.pop();
+    }
+    count
+}
+
+impl Document {
+    /// Applies the formatting operations to the items whose paths match one of the globs,
+    /// including their subtables, and returns the number of matched items.
+    ///
+    /// Globs are dotted keys where `*` matches any key, e.g. `dependencies.*` or
+    /// `target.*.dependencies`. The tables of an array of tables have the path of
+    /// the array. Items within matched items are not matched again.
+    ///
+    /// Returns an error if a glob is not a valid dotted key.
+    pub fn format_matching(
+        &mut self,
+        globs: &[&str],
+        options: &FmtOptions,
+    ) -> Result<usize, TomlError> {
+        let globs = globs
+            .iter()
+            .map(|g| Glob::parse(g))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(visit(
+            &mut self.as_table_mut().items,
+            false,
+            &mut Vec::new(),
+            &globs,
+            options,
+        ))
+    }
+}
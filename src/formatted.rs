@@ -1,10 +1,12 @@
 use crate::decor::{Decor, Formatted, InternalString, Repr};
 use crate::key::Key;
-use crate::parser::strings;
-use crate::parser::TomlError;
+use crate::parser::lexer;
 use crate::table::{Item, KeyValuePairs, TableKeyValue};
 use crate::value::{Array, DateTime, InlineTable, Value};
-use combine::stream::position::Stream as PositionStream;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter, Write};
+use std::hash::BuildHasher;
 use std::iter::FromIterator;
 
 pub(crate) fn decorate_array(array: &mut Array) {
@@ -28,7 +30,7 @@ pub(crate) fn decorate_inline_table(table: &mut InlineTable) {
     for (i, (key, value)) in table
         .items
         .iter_mut()
-        .filter(|&(_, ref kv)| kv.value.is_value())
+        .filter(|(_, kv)| kv.value.is_value())
         .map(|(_, kv)| (&mut kv.key, kv.value.as_value_mut().unwrap()))
         .enumerate()
     {
@@ -72,6 +74,7 @@ pub fn decorated(mut value: Value, prefix: &str, suffix: &str) -> Value {
     value
 }
 
+#[cfg(feature = "parse")]
 pub(crate) fn value(mut val: Value, raw: &str) -> Value {
     match val {
         Value::Integer(ref mut f) => {
@@ -119,76 +122,282 @@ pub(crate) fn key_repr(raw: &str) -> Repr {
 
 impl From<i64> for Value {
     fn from(i: i64) -> Self {
-        Value::Integer(Formatted::new(
+        Value::Integer(Formatted::with_repr(
             i,
             Repr::new("".to_string(), i.to_string(), "".to_string()),
         ))
     }
 }
 
+/// An unsigned integer which doesn't fit into a TOML integer, i.e. an `i64`,
+/// see the `TryFrom` conversions of `Value`.
+///
+/// # Example
+/// ```rust
+/// # use std::convert::TryFrom;
+/// # use toml_edit::Value;
+/// assert_eq!(Value::try_from(42u64).unwrap().as_integer(), Some(42));
+/// let err = Value::try_from(u64::MAX).unwrap_err();
+/// assert_eq!(err.to_string(), "18446744073709551615 (u64) is out of the range of TOML integers");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntegerOverflow {
+    value: u128,
+    source: &'static str,
+}
+
+impl IntegerOverflow {
+    /// Returns the integer which doesn't fit.
+    pub fn value(&self) -> u128 {
+        self.value
+    }
+
+    /// Returns the name of the Rust type of the integer, e.g. `u64`.
+    pub fn source_type(&self) -> &'static str {
+        self.source
+    }
+}
+
+impl Display for IntegerOverflow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) is out of the range of TOML integers",
+            self.value, self.source
+        )
+    }
+}
+
+impl std::error::Error for IntegerOverflow {}
+
+macro_rules! try_from_unsigned {
+    ($($t:ident),*) => {$(
+        impl TryFrom<$t> for Value {
+            type Error = IntegerOverflow;
+
+            fn try_from(i: $t) -> Result<Self, Self::Error> {
+                i64::try_from(i).map(Value::from).map_err(|_| IntegerOverflow {
+                    value: i as u128,
+                    source: stringify!($t),
+                })
+            }
+        }
+    )*};
+}
+
+try_from_unsigned!(u64, u128, usize);
+
 impl From<f64> for Value {
     fn from(f: f64) -> Self {
-        Value::Float(Formatted::new(
+        // `Display` of infinities and NaN is not valid TOML
+        let raw = if f.is_nan() {
+            if f.is_sign_negative() { "-nan" } else { "nan" }.to_string()
+        } else if f.is_infinite() {
+            if f < 0.0 { "-inf" } else { "inf" }.to_string()
+        } else {
+            f.to_string()
+        };
+        Value::Float(Formatted::with_repr(
             f,
-            Repr::new("".to_string(), f.to_string(), "".to_string()),
+            Repr::new("".to_string(), raw, "".to_string()),
         ))
     }
 }
 
-macro_rules! try_parse {
-    ($s:expr, $p:expr) => {{
-        use combine::EasyParser;
-        let result = $p.easy_parse(PositionStream::new($s));
-        match result {
-            Ok((_, ref rest)) if !rest.input.is_empty() => {
-                Err(TomlError::from_unparsed(rest.positioner, $s))
+// A float in the default format, whose repr always includes a fractional part or an exponent,
+// unlike `Value::from(f64)` which writes `1.0` as `1`.
+pub(crate) fn float_value(f: f64) -> Value {
+    if !f.is_finite() {
+        return Value::from(f);
+    }
+    // `Debug` always includes a fractional part or an exponent
+    let raw = format!("{:?}", f);
+    Value::Float(Formatted::with_repr(f, Repr::new("", &raw, "")))
+}
+
+// A string in the default format, written as a basic string even if it looks quoted,
+// unlike `Value::from(&str)`.
+pub(crate) fn basic_string_value(s: &str) -> Value {
+    let raw = to_basic_string(s);
+    Value::String(Formatted::with_repr(s.to_owned(), Repr::new("", &raw, "")))
+}
+
+// Quotes and escapes `s` as a basic string.
+pub(crate) fn to_basic_string(s: &str) -> String {
+    let mut raw = String::with_capacity(s.len() + 2);
+    raw.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => raw.push_str("\\\""),
+            '\\' => raw.push_str("\\\\"),
+            '\n' => raw.push_str("\\n"),
+            '\r' => raw.push_str("\\r"),
+            '\t' => raw.push_str("\\t"),
+            '\u{8}' => raw.push_str("\\b"),
+            '\u{c}' => raw.push_str("\\f"),
+            c if c.is_control() => write!(raw, "\\u{:04X}", c as u32).unwrap(),
+            c => raw.push(c),
+        }
+    }
+    raw.push('"');
+    raw
+}
+
+/// The kinds of TOML strings, see `Value::try_string_in_style`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StringStyle {
+    /// Basic strings (`"..."`), with escapes.
+    Basic,
+    /// Literal strings (`'...'`), without escapes.
+    Literal,
+    /// Multi-line basic strings (`"""..."""`), with escapes.
+    MultilineBasic,
+    /// Multi-line literal strings (`'''...'''`), without escapes.
+    MultilineLiteral,
+}
+
+impl Display for StringStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            StringStyle::Basic => write!(f, "basic"),
+            StringStyle::Literal => write!(f, "literal"),
+            StringStyle::MultilineBasic => write!(f, "multi-line basic"),
+            StringStyle::MultilineLiteral => write!(f, "multi-line literal"),
+        }
+    }
+}
+
+/// A string which can't be written in a style without escapes,
+/// see `Value::try_string_in_style`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StringStyleError {
+    style: StringStyle,
+    character: char,
+}
+
+impl StringStyleError {
+    /// Returns the requested style.
+    pub fn style(&self) -> StringStyle {
+        self.style
+    }
+
+    /// Returns the first character which can't be written in the style.
+    pub fn character(&self) -> char {
+        self.character
+    }
+}
+
+impl Display for StringStyleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a {} string can't contain {:?}",
+            self.style, self.character
+        )
+    }
+}
+
+impl std::error::Error for StringStyleError {}
+
+// Quotes `s` as a string of the given style, escaping it if the style has escapes.
+pub(crate) fn to_string_repr(s: &str, style: StringStyle) -> Result<String, StringStyleError> {
+    let error = |character| Err(StringStyleError { style, character });
+    // a newline right after the opening delimiter is trimmed
+    let newline = if s.starts_with('\n') { "\n" } else { "" };
+    match style {
+        StringStyle::Basic => Ok(to_basic_string(s)),
+        StringStyle::Literal => match s.chars().find(|&c| c == '\'' || is_control(c, false)) {
+            Some(c) => error(c),
+            None => Ok(format!("'{}'", s)),
+        },
+        StringStyle::MultilineBasic => {
+            let mut raw = format!("\"\"\"{}", newline);
+            let mut chars = s.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    // `"` is escaped to not form or precede the closing delimiter
+                    '"' if chars.peek().is_none_or(|&next| next == '"') => raw.push_str("\\\""),
+                    '\\' => raw.push_str("\\\\"),
+                    '\r' => raw.push_str("\\r"),
+                    '\t' => raw.push_str("\\t"),
+                    c if is_control(c, true) => write!(raw, "\\u{:04X}", c as u32).unwrap(),
+                    c => raw.push(c),
+                }
+            }
+            raw.push_str("\"\"\"");
+            Ok(raw)
+        }
+        StringStyle::MultilineLiteral => {
+            if let Some(c) = s.chars().find(|&c| is_control(c, true)) {
+                error(c)
+            } else if s.contains("'''") || s.ends_with('\'') {
+                error('\'')
+            } else {
+                Ok(format!("'''{}{}'''", newline, s))
             }
-            Ok((s, _)) => Ok(s),
-            Err(e) => Err(TomlError::new(e.into(), $s)),
         }
-    }};
+    }
+}
+
+// Escapes a line of a multi-line string of the given style, lines can't contain newlines.
+pub(crate) fn to_multiline_line(
+    line: &str,
+    style: StringStyle,
+) -> Result<String, StringStyleError> {
+    if line.contains('\n') {
+        return Err(StringStyleError {
+            style,
+            character: '\n',
+        });
+    }
+    let raw = to_string_repr(line, style)?;
+    Ok(raw[3..raw.len() - 3].to_owned())
+}
+
+// Returns true for the control characters which can't be written unescaped,
+// tabs are allowed in literal strings and newlines in multi-line strings.
+fn is_control(c: char, multiline: bool) -> bool {
+    c.is_control() && c != '\t' && !(multiline && c == '\n')
 }
 
 // TODO: clean this mess
-fn parse_string_guess_delimiters(s: &str) -> (InternalString, InternalString) {
-    let basic = format!("\"{}\"", s);
-    let literal = format!("'{}'", s);
-    let ml_basic = format!("\"\"\"{}\"\"\"", s);
-    let ml_literal = format!("'''{}'''", s);
-    if let Ok(r) = try_parse!(s, strings::string()) {
-        (r, s.into())
-    } else if let Ok(r) = try_parse!(&basic[..], strings::basic_string()) {
-        (r, basic)
-    } else if let Ok(r) = try_parse!(&literal[..], strings::literal_string()) {
-        (r.into(), literal.clone())
-    } else if let Ok(r) = try_parse!(&ml_basic[..], strings::ml_basic_string()) {
-        (r, ml_literal)
-    } else {
-        try_parse!(&ml_literal[..], strings::ml_literal_string())
-            .map(|r| (r, ml_literal))
-            .unwrap_or_else(|e| panic!("toml string parse error: {}, {}", e, s))
+fn parse_string_guess_delimiters(s: &str) -> (String, String) {
+    if let Ok(r) = lexer::string(s) {
+        return (r, s.into());
     }
+    let escaped = s.chars().any(|c| c == '"' || c == '\\' || c.is_control());
+    let raw = if !escaped {
+        to_basic_string(s)
+    } else {
+        to_string_repr(s, StringStyle::Literal)
+            .or_else(|e| match s.contains('\n') {
+                true => to_string_repr(s, StringStyle::MultilineLiteral),
+                false => Err(e),
+            })
+            .unwrap_or_else(|_| to_basic_string(s))
+    };
+    (s.into(), raw)
 }
 
 impl<'b> From<&'b str> for Value {
     fn from(s: &'b str) -> Self {
         let (value, raw) = parse_string_guess_delimiters(s);
-        Value::String(Formatted::new(
+        Value::String(Formatted::with_repr(
             value,
             Repr::new("".to_string(), raw, "".to_string()),
         ))
     }
 }
 
-impl From<InternalString> for Value {
-    fn from(s: InternalString) -> Self {
-        Value::from(s.as_ref())
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::from(s.as_str())
     }
 }
 
 impl From<bool> for Value {
     fn from(b: bool) -> Self {
-        Value::Boolean(Formatted::new(
+        Value::Boolean(Formatted::with_repr(
             b,
             Repr::new("", if b { "true" } else { "false" }, ""),
         ))
@@ -198,7 +407,7 @@ impl From<bool> for Value {
 impl From<DateTime> for Value {
     fn from(d: DateTime) -> Self {
         let s = d.to_string();
-        Value::DateTime(Formatted::new(
+        Value::DateTime(Formatted::with_repr(
             d,
             Repr::new("".to_string(), s, "".to_string()),
         ))
@@ -232,6 +441,84 @@ impl<V: Into<Value>> FromIterator<V> for Value {
     }
 }
 
+/// `None` elements are skipped.
+impl<V: Into<Value>> FromIterator<Option<V>> for Value {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Option<V>>,
+    {
+        iter.into_iter().flatten().collect()
+    }
+}
+
+impl<V: Into<Value>> From<Vec<V>> for Value {
+    fn from(v: Vec<V>) -> Self {
+        v.into_iter().collect()
+    }
+}
+
+/// `None` elements are skipped.
+impl<V: Into<Value>> From<Vec<Option<V>>> for Value {
+    fn from(v: Vec<Option<V>>) -> Self {
+        v.into_iter().collect()
+    }
+}
+
+fn inline_table_from_pairs<K, V, I>(iter: I) -> Value
+where
+    K: AsRef<str>,
+    V: Into<Value>,
+    I: IntoIterator<Item = (K, V)>,
+{
+    let items = iter
+        .into_iter()
+        .map(|(k, v)| {
+            let key = Key::from_unquoted(k.as_ref());
+            (key.get().into(), to_key_value(key.raw(), v.into()))
+        })
+        .collect();
+    let mut table = InlineTable {
+        items,
+        ..Default::default()
+    };
+    decorate_inline_table(&mut table);
+    Value::InlineTable(table)
+}
+
+fn sorted<K: AsRef<str>, V, S>(map: HashMap<K, V, S>) -> Vec<(K, V)> {
+    let mut pairs: Vec<_> = map.into_iter().collect();
+    pairs.sort_by(|(a, _), (b, _)| a.as_ref().cmp(b.as_ref()));
+    pairs
+}
+
+impl<K: AsRef<str>, V: Into<Value>> From<BTreeMap<K, V>> for Value {
+    fn from(map: BTreeMap<K, V>) -> Self {
+        inline_table_from_pairs(map)
+    }
+}
+
+/// `None` values are skipped.
+impl<K: AsRef<str>, V: Into<Value>> From<BTreeMap<K, Option<V>>> for Value {
+    fn from(map: BTreeMap<K, Option<V>>) -> Self {
+        inline_table_from_pairs(map.into_iter().filter_map(|(k, v)| v.map(|v| (k, v))))
+    }
+}
+
+/// The keys are sorted to make the output deterministic.
+impl<K: AsRef<str>, V: Into<Value>, S: BuildHasher> From<HashMap<K, V, S>> for Value {
+    fn from(map: HashMap<K, V, S>) -> Self {
+        inline_table_from_pairs(sorted(map))
+    }
+}
+
+/// The keys are sorted to make the output deterministic, `None` values are skipped.
+impl<K: AsRef<str>, V: Into<Value>, S: BuildHasher> From<HashMap<K, Option<V>, S>> for Value {
+    fn from(map: HashMap<K, Option<V>, S>) -> Self {
+        let pairs = sorted(map).into_iter();
+        inline_table_from_pairs(pairs.filter_map(|(k, v)| v.map(|v| (k, v))))
+    }
+}
+
 pub(crate) fn to_key_value_pairs<'k, K, V, I>(iter: I) -> KeyValuePairs
 where
     K: Into<&'k Key>,
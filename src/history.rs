@@ -0,0 +1,464 @@
+use crate::decor::InternalString;
+use crate::document::Document;
+use crate::table::{Item, Table};
+use crate::value::{Array, InlineTable, Value};
+use std::fmt::{Debug, Formatter, Result};
+
+// The states recorded for `undo` and `redo`, see `Document::enable_history`.
+//
+// The states form a chain of which only the state of the document after the last
+// checkpoint, undo or redo is kept in full, the others are recorded by the changes
+// between consecutive states. The states before that state, and that state itself
+// if it is saved, are the ones to return to with `undo`, the states after it
+// the ones to return to with `redo`.
+#[derive(Clone)]
+pub(crate) struct History {
+    // the changes between consecutive states
+    patches: Vec<Patch>,
+    // the index of the kept state
+    cursor: usize,
+    // the content of the kept state
+    root: Item,
+    trailing: InternalString,
+    // whether the kept state was recorded by a checkpoint and not restored since
+    saved: bool,
+    limit: Option<usize>,
+}
+
+// A step of the path to a changed item.
+#[derive(Clone, Debug, PartialEq)]
+enum Step {
+    Key(InternalString),
+    Index(usize),
+}
+
+// An item replaced between two states.
+//
+// The items keep the ids of the nodes they were recorded from, so that the nodes
+// restored by `undo` and `redo` keep their metadata, see `NodeId`.
+struct Change {
+    path: Vec<Step>,
+    before: Item,
+    after: Item,
+}
+
+impl Clone for Change {
+    fn clone(&self) -> Self {
+        Change {
+            path: self.path.clone(),
+            before: self.before.clone_keeping_ids(),
+            after: self.after.clone_keeping_ids(),
+        }
+    }
+}
+
+// The changes between two states, at paths none of which is a prefix of another.
+#[derive(Clone, Default)]
+struct Patch {
+    changes: Vec<Change>,
+    trailing: Option<(InternalString, InternalString)>,
+}
+
+impl Debug for History {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let undo = self.cursor + self.saved as usize;
+        f.debug_struct("History")
+            .field("undo", &undo)
+            .field("redo", &(self.patches.len() - self.cursor))
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+impl Patch {
+    fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.trailing.is_none()
+    }
+
+    fn inverse(&self) -> Patch {
+        Patch {
+            changes: self
+                .changes
+                .iter()
+                .map(|c| Change {
+                    path: c.path.clone(),
+                    before: c.after.clone_keeping_ids(),
+                    after: c.before.clone_keeping_ids(),
+                })
+                .collect(),
+            trailing: self.trailing.clone().map(|(before, after)| (after, before)),
+        }
+    }
+
+    // replaces the changed items by their state after the patch
+    fn apply(&self, root: &mut Item, trailing: &mut InternalString) {
+        for c in &self.changes {
+            *at_mut(root, &c.path) = c.after.clone_keeping_ids();
+        }
+        if let Some((_, ref after)) = self.trailing {
+            *trailing = after.clone();
+        }
+    }
+}
+
+// chains the changes from a state to a middle state and from the middle state
+// to a third one, given the full content of the middle state
+fn compose(first: &Patch, second: &Patch, root: &Item, trailing: &str) -> Patch {
+    let paths: Vec<&Vec<Step>> = first
+        .changes
+        .iter()
+        .chain(&second.changes)
+        .map(|c| &c.path)
+        .collect();
+    let mut cover: Vec<&Vec<Step>> = Vec::new();
+    for path in &paths {
+        let covered = paths
+            .iter()
+            .any(|p| p.len() < path.len() && path.starts_with(p));
+        if !covered && !cover.contains(path) {
+            cover.push(path);
+        }
+    }
+    let changes = cover
+        .into_iter()
+        .map(|path| {
+            let middle = at(root, path);
+            let mut before = middle.clone_keeping_ids();
+            let mut after = middle.clone_keeping_ids();
+            for c in first.changes.iter().filter(|c| c.path.starts_with(path)) {
+                *at_mut(&mut before, &c.path[path.len()..]) = c.before.clone_keeping_ids();
+            }
+            for c in second.changes.iter().filter(|c| c.path.starts_with(path)) {
+                *at_mut(&mut after, &c.path[path.len()..]) = c.after.clone_keeping_ids();
+            }
+            Change {
+                path: path.clone(),
+                before,
+                after,
+            }
+        })
+        .collect();
+    let before = first.trailing.as_ref().map_or(trailing, |(b, _)| b);
+    let after = second.trailing.as_ref().map_or(trailing, |(_, a)| a);
+    Patch {
+        changes,
+        trailing: Some((before.into(), after.into())).filter(|(b, a)| b != a),
+    }
+}
+
+fn at<'i>(mut item: &'i Item, path: &[Step]) -> &'i Item {
+    for step in path {
+        item = match (step, item) {
+            (Step::Key(key), Item::Table(t)) => &t.items[key].value,
+            (Step::Index(i), Item::ArrayOfTables(a)) => &a.values[*i],
+            _ => unreachable!("the path of a change exists"),
+        };
+    }
+    item
+}
+
+fn at_mut<'i>(mut item: &'i mut Item, path: &[Step]) -> &'i mut Item {
+    for step in path {
+        item = match (step, item) {
+            (Step::Key(key), Item::Table(t)) => {
+                &mut t
+                    .items
+                    .get_mut(key)
+                    .expect("the path of a change exists")
+                    .value
+            }
+            (Step::Index(i), Item::ArrayOfTables(a)) => &mut a.values[*i],
+            _ => unreachable!("the path of a change exists"),
+        };
+    }
+    item
+}
+
+// collects the paths of the items replaced between `a` and `b`, descending into the tables
+// and arrays of tables which only differ by their items
+fn diff(a: &Item, b: &Item, path: &mut Vec<Step>, paths: &mut Vec<Vec<Step>>) {
+    match (a, b) {
+        (Item::Table(a), Item::Table(b)) if same_table_shape(a, b) => {
+            for ((key, a), (_, b)) in a.items.iter().zip(b.items.iter()) {
+                path.push(Step::Key(key.clone()));
+                diff(&a.value, &b.value, path, paths);
+                path.pop();
+            }
+        }
+        (Item::ArrayOfTables(a), Item::ArrayOfTables(b)) if a.values.len() == b.values.len() => {
+            for (i, (a, b)) in a.values.iter().zip(&b.values).enumerate() {
+                path.push(Step::Index(i));
+                diff(a, b, path, paths);
+                path.pop();
+            }
+        }
+        _ if same_item(a, b) => {}
+        _ => paths.push(path.clone()),
+    }
+}
+
+// whether the tables are the same but for the values of their entries
+fn same_table_shape(a: &Table, b: &Table) -> bool {
+    a.decor == b.decor
+        && a.implicit == b.implicit
+        && a.dotted == b.dotted
+        && a.position == b.position
+        && a.items.len() == b.items.len()
+        && a.items
+            .iter()
+            .zip(b.items.iter())
+            .all(|((ka, a), (kb, b))| ka == kb && a.key == b.key)
+}
+
+// whether the items are the same, including their formatting
+fn same_item(a: &Item, b: &Item) -> bool {
+    match (a, b) {
+        (Item::None, Item::None) => true,
+        (Item::Value(a), Item::Value(b)) => same_value(a, b),
+        (Item::Table(a), Item::Table(b)) => {
+            same_table_shape(a, b)
+                && a.items
+                    .values()
+                    .zip(b.items.values())
+                    .all(|(a, b)| same_item(&a.value, &b.value))
+        }
+        (Item::ArrayOfTables(a), Item::ArrayOfTables(b)) => {
+            a.values.len() == b.values.len()
+                && a.values.iter().zip(&b.values).all(|(a, b)| same_item(a, b))
+        }
+        _ => false,
+    }
+}
+
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Integer(a), Value::Integer(b)) => a == b,
+        (Value::String(a), Value::String(b)) => a == b,
+        // NaN is not equal to itself
+        (Value::Float(a), Value::Float(b)) => a.repr == b.repr,
+        (Value::DateTime(a), Value::DateTime(b)) => a == b,
+        (Value::Boolean(a), Value::Boolean(b)) => a == b,
+        (Value::Array(a), Value::Array(b)) => same_array(a, b),
+        (Value::InlineTable(a), Value::InlineTable(b)) => same_inline_table(a, b),
+        _ => false,
+    }
+}
+
+fn same_array(a: &Array, b: &Array) -> bool {
+    a.decor == b.decor
+        && a.trailing == b.trailing
+        && a.trailing_comma == b.trailing_comma
+        && a.values.len() == b.values.len()
+        && a.values.iter().zip(&b.values).all(|(a, b)| same_item(a, b))
+}
+
+fn same_inline_table(a: &InlineTable, b: &InlineTable) -> bool {
+    a.decor == b.decor
+        && a.preamble == b.preamble
+        && a.items.len() == b.items.len()
+        && a.items
+            .iter()
+            .zip(b.items.iter())
+            .all(|((ka, a), (kb, b))| ka == kb && a.key == b.key && same_item(&a.value, &b.value))
+}
+
+impl History {
+    fn new(root: Item, trailing: InternalString) -> Self {
+        History {
+            patches: Vec::new(),
+            cursor: 0,
+            root,
+            trailing,
+            saved: false,
+            limit: None,
+        }
+    }
+
+    fn can_undo(&self) -> bool {
+        self.cursor > 0 || self.saved
+    }
+
+    fn can_redo(&self) -> bool {
+        self.cursor < self.patches.len()
+    }
+
+    // records the edits of the document since the last checkpoint, undo or redo,
+    // as a new state if the kept state is saved, in place of the kept state otherwise
+    fn commit(&mut self, root: &Item, trailing: &str) {
+        let mut paths = Vec::new();
+        diff(&self.root, root, &mut Vec::new(), &mut paths);
+        let edits = Patch {
+            changes: paths
+                .into_iter()
+                .map(|path| Change {
+                    before: at(&self.root, &path).clone_keeping_ids(),
+                    after: at(root, &path).clone_keeping_ids(),
+                    path,
+                })
+                .collect(),
+            trailing: Some((self.trailing.clone(), trailing.into()))
+                .filter(|(before, after)| before != after),
+        };
+        if edits.is_empty() {
+            return;
+        }
+        if self.cursor < self.patches.len() {
+            let next = &self.patches[self.cursor];
+            self.patches[self.cursor] = compose(&edits.inverse(), next, &self.root, &self.trailing);
+        }
+        if self.saved {
+            self.patches.insert(self.cursor, edits.clone());
+            self.cursor += 1;
+            self.saved = false;
+        } else if self.cursor > 0 {
+            let previous = &self.patches[self.cursor - 1];
+            self.patches[self.cursor - 1] = compose(previous, &edits, &self.root, &self.trailing);
+        }
+        edits.apply(&mut self.root, &mut self.trailing);
+    }
+
+    // records a state with the content of the kept state after it,
+    // which becomes the kept state if `follow`
+    fn duplicate(&mut self, follow: bool) {
+        self.patches.insert(self.cursor, Patch::default());
+        if follow {
+            self.cursor += 1;
+        }
+    }
+
+    // drops the oldest states to return to with `undo` beyond the limit
+    fn truncate(&mut self) {
+        if let Some(limit) = self.limit {
+            let excess = (self.cursor + self.saved as usize).saturating_sub(limit);
+            let dropped = excess.min(self.cursor);
+            self.patches.drain(..dropped);
+            self.cursor -= dropped;
+            if excess > dropped {
+                self.saved = false;
+            }
+        }
+    }
+}
+
+impl Document {
+    /// Starts recording checkpoints for `undo` and `redo`, keeping at most `limit`
+    /// checkpoints if given. Enabling the history again only changes the limit.
+    ///
+    /// Edits are not recorded one by one: `checkpoint` records the changes since
+    /// the previous state, which is typically called before every user-level edit,
+    /// e.g. a command of an editor, rather than every keystroke. The changes are found
+    /// by comparing the document with its previous state, and only the changed items
+    /// are copied, so that the history grows with the edits rather than with the document.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::{value, Document};
+    ///
+    /// let mut doc = "[package]\nname = 'a'\n".parse::<Document>().unwrap();
+    /// doc.enable_history(None);
+    ///
+    /// doc.checkpoint();
+    /// doc["package"]["name"] = value("b");
+    /// doc.checkpoint();
+    /// doc["package"]["version"] = value("0.1.0");
+    ///
+    /// assert!(doc.undo());
+    /// assert_eq!(doc.to_string(), "[package]\nname = \"b\"\n");
+    /// assert!(doc.undo());
+    /// assert_eq!(doc.to_string(), "[package]\nname = 'a'\n");
+    /// assert!(!doc.undo());
+    ///
+    /// assert!(doc.redo());
+    /// assert_eq!(doc.to_string(), "[package]\nname = \"b\"\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn enable_history(&mut self, limit: Option<usize>) {
+        if self.history.is_none() {
+            let history = History::new(self.root.clone_keeping_ids(), self.trailing.clone());
+            self.history = Some(Box::new(history));
+        }
+        let history = self.history.as_mut().expect("the history is enabled");
+        history.limit = limit;
+        history.truncate();
+    }
+
+    /// Stops recording checkpoints and drops the recorded ones.
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    /// Returns true iff the history is enabled.
+    pub fn has_history(&self) -> bool {
+        self.history.is_some()
+    }
+
+    /// Records the current content of the document as a state to return to with `undo`,
+    /// and forgets the states undone so far. Does nothing if the history is not enabled.
+    pub fn checkpoint(&mut self) {
+        let history = match self.history {
+            Some(ref mut history) => history,
+            None => return,
+        };
+        history.commit(&self.root, &self.trailing);
+        if history.saved {
+            history.duplicate(true);
+        }
+        history.patches.truncate(history.cursor);
+        history.saved = true;
+        history.truncate();
+    }
+
+    /// Returns to the state of the last checkpoint, returning false if there is none.
+    pub fn undo(&mut self) -> bool {
+        if !self.can_undo() {
+            return false;
+        }
+        let history = self.history.as_mut().expect("the history is enabled");
+        history.commit(&self.root, &self.trailing);
+        if history.saved {
+            // the current state is the last checkpoint, it becomes the state to redo as well
+            history.duplicate(false);
+        } else {
+            history.cursor -= 1;
+            let patch = history.patches[history.cursor].inverse();
+            patch.apply(&mut history.root, &mut history.trailing);
+            patch.apply(&mut self.root, &mut self.trailing);
+        }
+        history.saved = false;
+        true
+    }
+
+    /// Returns to the state before the last `undo`, returning false if there is none.
+    pub fn redo(&mut self) -> bool {
+        if !self.can_redo() {
+            return false;
+        }
+        let history = self.history.as_mut().expect("the history is enabled");
+        history.commit(&self.root, &self.trailing);
+        if history.saved {
+            // the current state is recorded again as a checkpoint
+            history.duplicate(true);
+        }
+        let patch = &history.patches[history.cursor];
+        patch.apply(&mut history.root, &mut history.trailing);
+        patch.apply(&mut self.root, &mut self.trailing);
+        history.cursor += 1;
+        history.saved = false;
+        true
+    }
+
+    /// Returns true iff there is a checkpoint to return to with `undo`.
+    pub fn can_undo(&self) -> bool {
+        self.history.as_ref().is_some_and(|h| h.can_undo())
+    }
+
+    /// Returns true iff there is an undone state to return to with `redo`.
+    pub fn can_redo(&self) -> bool {
+        self.history.as_ref().is_some_and(|h| h.can_redo())
+    }
+}
@@ -0,0 +1,216 @@
+use crate::document::Document;
+use crate::merge::clear_positions;
+use crate::parser::TomlError;
+use crate::table::{Item, Table};
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io;
+
+/// Type representing an error while resolving includes.
+#[derive(Debug)]
+pub enum IncludeError {
+    /// The include directive is neither a string nor an array of strings.
+    InvalidDirective {
+        /// The key of the directive.
+        key: String,
+    },
+    /// The loader failed to load an included document.
+    Load {
+        /// The included path.
+        path: String,
+        /// The loader's error.
+        error: io::Error,
+    },
+    /// An included document failed to parse.
+    Parse {
+        /// The included path.
+        path: String,
+        /// The parse error.
+        error: TomlError,
+    },
+    /// A document includes itself, directly or indirectly.
+    Cycle {
+        /// The chain of included paths, starting and ending with the same path.
+        paths: Vec<String>,
+    },
+}
+
+impl Display for IncludeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match *self {
+            IncludeError::InvalidDirective { ref key } => write!(
+                f,
+                "Invalid `{}` directive, expected a string or an array of strings",
+                key
+            ),
+            IncludeError::Load {
+                ref path,
+                ref error,
+            } => write!(f, "Failed to load `{}`: {}", path, error),
+            IncludeError::Parse {
+                ref path,
+                ref error,
+            } => write!(f, "Failed to parse `{}`: {}", path, error),
+            IncludeError::Cycle { ref paths } => {
+                write!(f, "Include cycle: {}", paths.join(" -> "))
+            }
+        }
+    }
+}
+
+impl StdError for IncludeError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            IncludeError::Load { ref error, .. } => Some(error),
+            IncludeError::Parse { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Resolves include directives, merging the included documents into the including ones.
+///
+/// A directive is a key, `include` by default, in any table, whose value is a path
+/// or an array of paths. The paths are passed to the loader, which returns the
+/// contents of the included document. Included documents may include others, cycles are
+/// reported as errors.
+///
+/// The directive is removed and the included documents are merged into its table:
+/// tables are merged recursively, other items already present are kept,
+/// so that the including document takes precedence over the included ones,
+/// and later includes take precedence over earlier ones.
+/// Merged items retain their decor and are appended to the tables.
+///
+/// # Example
+///
+/// ```rust
+/// use toml_edit::{Document, IncludeResolver};
+///
+/// let mut doc = r#"
+/// include = "base.toml"
+///
+/// [server]
+/// port = 8080
+/// "#.parse::<Document>().unwrap();
+///
+/// IncludeResolver::new(|path| match path {
+///     "base.toml" => Ok("[server]\nhost = 'localhost' # default\nport = 80\n".to_owned()),
+///     _ => Err(std::io::ErrorKind::NotFound.into()),
+/// })
+/// .resolve(&mut doc)
+/// .unwrap();
+///
+/// assert_eq!(doc.to_string(), r#"
+/// [server]
+/// port = 8080
+/// host = 'localhost' # default
+/// "#);
+/// ```
+pub struct IncludeResolver<F> {
+    key: String,
+    loader: F,
+}
+
+impl<F> IncludeResolver<F>
+where
+    F: FnMut(&str) -> io::Result<String>,
+{
+    /// Creates a resolver of `include` directives using the given loader.
+    pub fn new(loader: F) -> Self {
+        Self {
+            key: "include".to_owned(),
+            loader,
+        }
+    }
+
+    /// Sets the key of the include directive.
+    pub fn key(mut self, key: &str) -> Self {
+        self.key = key.to_owned();
+        self
+    }
+
+    /// Resolves all include directives in the document.
+    pub fn resolve(&mut self, doc: &mut Document) -> Result<(), IncludeError> {
+        let mut stack = Vec::new();
+        self.resolve_table(doc.as_table_mut(), &mut stack)
+    }
+
+    fn resolve_table(
+        &mut self,
+        table: &mut Table,
+        stack: &mut Vec<String>,
+    ) -> Result<(), IncludeError> {
+        let paths = match table.remove(&self.key) {
+            Some(directive) => self.paths(directive)?,
+            None => Vec::new(),
+        };
+        for (_, kv) in table.items.iter_mut() {
+            match kv.value {
+                Item::Table(ref mut t) => self.resolve_table(t, stack)?,
+                Item::ArrayOfTables(ref mut a) => {
+                    for t in a.values.iter_mut().filter_map(Item::as_table_mut) {
+                        self.resolve_table(t, stack)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        // later includes take precedence, so they are merged first
+        for path in paths.into_iter().rev() {
+            if stack.contains(&path) {
+                let mut paths = stack.clone();
+                paths.push(path);
+                return Err(IncludeError::Cycle { paths });
+            }
+            let input = (self.loader)(&path).map_err(|error| IncludeError::Load {
+                path: path.clone(),
+                error,
+            })?;
+            let mut included = input
+                .parse::<Document>()
+                .map_err(|error| IncludeError::Parse {
+                    path: path.clone(),
+                    error,
+                })?;
+            stack.push(path);
+            self.resolve_table(included.as_table_mut(), stack)?;
+            stack.pop();
+            merge_into(table, std::mem::take(included.as_table_mut()));
+        }
+        Ok(())
+    }
+
+    fn paths(&self, directive: Item) -> Result<Vec<String>, IncludeError> {
+        let paths = match directive {
+            Item::Value(ref v) if v.is_str() => v.as_str().map(|s| vec![s.to_owned()]),
+            Item::Value(ref v) if v.is_array() => v
+                .as_array()
+                .and_then(|a| a.iter().map(|v| v.as_str().map(str::to_owned)).collect()),
+            _ => None,
+        };
+        paths.ok_or_else(|| IncludeError::InvalidDirective {
+            key: self.key.clone(),
+        })
+    }
+}
+
+// Adds the items of `source` missing in `target`, merging tables present in both.
+fn merge_into(target: &mut Table, source: Table) {
+    for (key, mut kv) in source.items {
+        match target.items.get_mut(&key) {
+            Some(existing) if existing.value.is_none() => {
+                clear_positions(&mut kv.value);
+                existing.value = kv.value;
+            }
+            Some(existing) => {
+                if let (Item::Table(t), Item::Table(s)) = (&mut existing.value, kv.value) {
+                    merge_into(t, s);
+                }
+            }
+            None => {
+                clear_positions(&mut kv.value);
+                target.items.insert(key, kv);
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::document::Document;
+use crate::key_path::KeyPath;
+use crate::table::{Item, Table, TableKeyValue};
+
+// indentation used by the items of a table
+struct Indents {
+    // of key/value pairs
+    key: String,
+    // of the headers of subtables
+    header: String,
+    // of key/value pairs in the bodies of subtables
+    body: String,
+}
+
+impl Indents {
+    fn of(table: &Table, inherited: &str) -> Self {
+        let mut key = None;
+        let mut header = None;
+        let mut body = None;
+        for kv in table.items.values() {
+            match kv.value {
+                Item::Value(..) => key = key.or_else(|| non_empty(&kv.key.decor.prefix)),
+                Item::Table(ref t) if t.dotted => key = key.or_else(|| key_indent(t)),
+                Item::Table(ref t) => {
+                    header = header.or_else(|| non_empty(&t.decor.prefix));
+                    body = body.or_else(|| key_indent(t));
+                }
+                Item::ArrayOfTables(ref a) => {
+                    for t in a.iter() {
+                        header = header.or_else(|| non_empty(&t.decor.prefix));
+                        body = body.or_else(|| key_indent(t));
+                    }
+                }
+                Item::None => {}
+            }
+        }
+        let key = key.unwrap_or(inherited).to_owned();
+        Indents {
+            header: header.unwrap_or_default().to_owned(),
+            body: body.map_or_else(|| key.clone(), str::to_owned),
+            key,
+        }
+    }
+}
+
+// returns the whitespaces after the last newline of a decor prefix
+fn indentation(prefix: &str) -> &str {
+    &prefix[prefix.rfind('\n').map_or(0, |i| i + 1)..]
+}
+
+fn non_empty(prefix: &str) -> Option<&str> {
+    Some(indentation(prefix)).filter(|i| !i.is_empty())
+}
+
+// returns the indentation of the first indented key/value pair of a table body
+fn key_indent(table: &Table) -> Option<&str> {
+    table.items.values().find_map(|kv| match kv.value {
+        Item::Value(..) => non_empty(&kv.key.decor.prefix),
+        Item::Table(ref t) if t.dotted => key_indent(t),
+        _ => None,
+    })
+}
+
+fn indent(prefix: &mut String, indent: &str) {
+    if indentation(prefix).is_empty() {
+        prefix.push_str(indent);
+    }
+}
+
+fn indent_table(table: &mut Table, inherited: &str) {
+    let indents = Indents::of(table, inherited);
+    for (_, kv) in table.items.iter_mut() {
+        indent_entry(kv, &indents);
+    }
+}
+
+fn indent_entry(kv: &mut TableKeyValue, indents: &Indents) {
+    match kv.value {
+        Item::Value(..) => indent(&mut kv.key.decor.prefix, &indents.key),
+        Item::Table(ref mut t) if t.dotted => {
+            for (_, kv) in t.items.iter_mut() {
+                indent_entry(kv, indents);
+            }
+        }
+        Item::Table(ref mut t) => {
+            indent(&mut t.decor.prefix, &indents.header);
+            indent_table(t, &indents.body);
+        }
+        Item::ArrayOfTables(ref mut a) => {
+            for t in a.values.iter_mut().filter_map(Item::as_table_mut) {
+                indent(&mut t.decor.prefix, &indents.header);
+                indent_table(t, &indents.body);
+            }
+        }
+        Item::None => {}
+    }
+}
+
+// indents the entry at the path like its siblings, leaving them as they are
+pub(crate) fn indent_created(root: &mut Table, path: &KeyPath) {
+    let (last, parents) = match path.keys().split_last() {
+        Some(split) => split,
+        None => return,
+    };
+    let mut indents = Indents::of(root, "");
+    let mut table = root;
+    for key in parents {
+        table = match table.items.get_mut(key.get()).map(|kv| &mut kv.value) {
+            Some(Item::Table(t)) => t,
+            // the entries of inline tables are not indented
+            _ => return,
+        };
+        // the pairs of dotted tables are indented like those of their parent
+        if !table.dotted {
+            indents = Indents::of(table, &indents.body);
+        }
+    }
+    if let Some(kv) = table.items.get_mut(last.get()) {
+        indent_entry(kv, &indents);
+    }
+}
+
+impl Table {
+    /// Indents the entry at the path, e.g. a key or a table created through the API,
+    /// like its siblings, leaving the other entries as they are.
+    ///
+    /// Items are created without indentation, which stands out in indented documents.
+    /// Key/value pairs copy the indentation of the first indented pair of the same table,
+    /// or of the parent table, headers copy the first indented header of their siblings.
+    /// The key/value pairs and headers without indentation within the entry are indented too.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{value, Document, Item, Table};
+    /// let mut doc = "[servers]\n\t[servers.alpha]\n\tip = 1\n".parse::<Document>().unwrap();
+    /// let mut beta = Table::new();
+    /// beta["ip"] = value(2);
+    /// doc["servers"]["beta"] = Item::Table(beta);
+    /// doc.inherit_indentation(&"servers.beta".parse().unwrap());
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "[servers]\n\t[servers.alpha]\n\tip = 1\n\n\t[servers.beta]\n\tip = 2\n"
+    /// );
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn inherit_indentation(&mut self, path: &KeyPath) {
+        indent_created(self, path);
+    }
+}
+
+impl Document {
+    /// Indents the entry at the path like its siblings, see `Table::inherit_indentation`.
+    pub fn inherit_indentation(&mut self, path: &KeyPath) {
+        self.as_table_mut().inherit_indentation(path)
+    }
+}
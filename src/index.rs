@@ -2,6 +2,7 @@ use crate::document::Document;
 use crate::formatted::to_table_key_value;
 use crate::table::{value, Item, Table};
 use crate::value::{InlineTable, Value};
+use std::fmt::{Display, Formatter, Result};
 use std::ops;
 
 // copied from
@@ -13,9 +14,45 @@ mod private {
     impl Sealed for usize {}
     impl Sealed for str {}
     impl Sealed for String {}
-    impl<'a, T: ?Sized> Sealed for &'a T where T: Sealed {}
+    impl<T: ?Sized> Sealed for &T where T: Sealed {}
 }
 
+/// A failed access by index, see `Item::try_index_mut`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum IndexError {
+    /// The index is out of the bounds of an array or an array of tables.
+    OutOfBounds {
+        /// The index.
+        index: usize,
+        /// The length of the array.
+        len: usize,
+    },
+    /// The indexed item is not an array or an array of tables.
+    NotAnArray {
+        /// The index.
+        index: usize,
+    },
+    /// The indexed item is not a table or an inline table.
+    NotATable {
+        /// The key.
+        key: String,
+    },
+}
+
+impl Display for IndexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match *self {
+            IndexError::OutOfBounds { index, len } => {
+                write!(f, "index {} out of bounds (len = {})", index, len)
+            }
+            IndexError::NotAnArray { index } => write!(f, "cannot access index {}", index),
+            IndexError::NotATable { ref key } => write!(f, "cannot access key {}", key),
+        }
+    }
+}
+
+impl std::error::Error for IndexError {}
+
 pub trait Index: private::Sealed {
     /// Return `Option::None` if the key is not already in the array or table.
     #[doc(hidden)]
@@ -26,7 +63,17 @@ pub trait Index: private::Sealed {
     /// indexed into, except if `v` is `Item::None` then it can be treated as an empty
     /// inline table.
     #[doc(hidden)]
-    fn index_or_insert<'v>(&self, v: &'v mut Item) -> &'v mut Item;
+    fn index_or_insert<'v>(&self, v: &'v mut Item) -> &'v mut Item {
+        self.try_index_or_insert(v)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `index_or_insert`, but return an error instead of panicking.
+    #[doc(hidden)]
+    fn try_index_or_insert<'v>(
+        &self,
+        v: &'v mut Item,
+    ) -> std::result::Result<&'v mut Item, IndexError>;
 }
 
 impl Index for usize {
@@ -37,19 +84,19 @@ impl Index for usize {
             _ => None,
         }
     }
-    fn index_or_insert<'v>(&self, v: &'v mut Item) -> &'v mut Item {
-        match *v {
-            Item::ArrayOfTables(ref mut vec) => {
-                vec.values.get_mut(*self).expect("index out of bounds")
-            }
-            Item::Value(ref mut a) if a.is_array() => a
-                .as_array_mut()
-                .unwrap()
-                .values
-                .get_mut(*self)
-                .expect("index out of bounds"),
-            _ => panic!("cannot access index {}", self),
-        }
+    fn try_index_or_insert<'v>(
+        &self,
+        v: &'v mut Item,
+    ) -> std::result::Result<&'v mut Item, IndexError> {
+        let values = match *v {
+            Item::ArrayOfTables(ref mut vec) => &mut vec.values,
+            Item::Value(Value::Array(ref mut a)) => &mut a.values,
+            _ => return Err(IndexError::NotAnArray { index: *self }),
+        };
+        let len = values.len();
+        values
+            .get_mut(*self)
+            .ok_or(IndexError::OutOfBounds { index: *self, len })
     }
 }
 
@@ -63,25 +110,28 @@ impl Index for str {
             _ => None,
         }
     }
-    fn index_or_insert<'v>(&self, v: &'v mut Item) -> &'v mut Item {
+    fn try_index_or_insert<'v>(
+        &self,
+        v: &'v mut Item,
+    ) -> std::result::Result<&'v mut Item, IndexError> {
         if let Item::None = *v {
             let mut t = InlineTable::default();
             t.items
-                .insert(self.to_owned(), to_table_key_value(self, Item::None));
+                .insert(self.into(), to_table_key_value(self, Item::None));
             *v = value(Value::InlineTable(t));
         }
         match *v {
-            Item::Table(ref mut t) => t.entry(self).or_insert(Item::None),
-            Item::Value(ref mut v) if v.is_inline_table() => {
-                &mut v
-                    .as_inline_table_mut()
-                    .unwrap()
-                    .items
-                    .entry(self.to_owned())
-                    .or_insert(to_table_key_value(self, Item::None))
-                    .value
-            }
-            _ => panic!("cannot access key {}", self),
+            Item::Table(ref mut t) => Ok(t.entry(self).or_insert(Item::None)),
+            Item::Value(ref mut v) if v.is_inline_table() => Ok(&mut v
+                .as_inline_table_mut()
+                .unwrap()
+                .items
+                .entry(self.into())
+                .or_insert(to_table_key_value(self, Item::None))
+                .value),
+            _ => Err(IndexError::NotATable {
+                key: self.to_owned(),
+            }),
         }
     }
 }
@@ -90,20 +140,26 @@ impl Index for String {
     fn index<'v>(&self, v: &'v Item) -> Option<&'v Item> {
         self[..].index(v)
     }
-    fn index_or_insert<'v>(&self, v: &'v mut Item) -> &'v mut Item {
-        self[..].index_or_insert(v)
+    fn try_index_or_insert<'v>(
+        &self,
+        v: &'v mut Item,
+    ) -> std::result::Result<&'v mut Item, IndexError> {
+        self[..].try_index_or_insert(v)
     }
 }
 
-impl<'a, T: ?Sized> Index for &'a T
+impl<T: ?Sized> Index for &T
 where
     T: Index,
 {
     fn index<'v>(&self, v: &'v Item) -> Option<&'v Item> {
         (**self).index(v)
     }
-    fn index_or_insert<'v>(&self, v: &'v mut Item) -> &'v mut Item {
-        (**self).index_or_insert(v)
+    fn try_index_or_insert<'v>(
+        &self,
+        v: &'v mut Item,
+    ) -> std::result::Result<&'v mut Item, IndexError> {
+        (**self).try_index_or_insert(v)
     }
 }
 
@@ -156,3 +212,81 @@ impl<'s> ops::IndexMut<&'s str> for Document {
         self.root.index_mut(key)
     }
 }
+
+/// A failed access by a path of keys and indices, see `Document::try_get_path_mut`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PathError {
+    segment: usize,
+    error: IndexError,
+}
+
+impl PathError {
+    /// Returns the position in the path of the key or index which couldn't be accessed.
+    pub fn segment(&self) -> usize {
+        self.segment
+    }
+
+    /// Returns the failed access.
+    pub fn error(&self) -> &IndexError {
+        &self.error
+    }
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{} at path segment {}", self.error, self.segment)
+    }
+}
+
+impl std::error::Error for PathError {}
+
+impl Item {
+    /// Accesses the item by key or index like `IndexMut`, returning an error
+    /// instead of panicking when the item is of another type or the index is out of bounds.
+    ///
+    /// Like with `IndexMut`, missing keys are inserted as `Item::None`
+    /// and `Item::None` is turned into an inline table.
+    pub fn try_index_mut<I: Index>(
+        &mut self,
+        index: I,
+    ) -> std::result::Result<&mut Item, IndexError> {
+        index.try_index_or_insert(self)
+    }
+}
+
+impl Document {
+    /// Accesses the item at a path of keys and indices like chained `IndexMut`,
+    /// e.g. `doc["a"][0]["b"]`, returning an error instead of panicking,
+    /// see `Item::try_index_mut`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{value, Document, IndexError};
+    /// let mut doc = "a = [1, 2]\nb = 3\n".parse::<Document>().unwrap();
+    /// let error = doc.try_get_path_mut(&[&"a", &5]).unwrap_err();
+    /// assert_eq!(error.segment(), 1);
+    /// assert_eq!(error.error(), &IndexError::OutOfBounds { index: 5, len: 2 });
+    /// let error = doc.try_get_path_mut(&[&"b", &"c"]).unwrap_err();
+    /// assert_eq!(error.to_string(), "cannot access key c at path segment 1");
+    ///
+    /// *doc.try_get_path_mut(&[&"a", &1]).unwrap() = value(5);
+    /// assert_eq!(doc.to_string(), "a = [1, 5]\nb = 3\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn try_get_path_mut(
+        &mut self,
+        path: &[&dyn Index],
+    ) -> std::result::Result<&mut Item, PathError> {
+        let mut item = &mut self.root;
+        for (segment, index) in path.iter().enumerate() {
+            item = index
+                .try_index_or_insert(item)
+                .map_err(|error| PathError { segment, error })?;
+        }
+        Ok(item)
+    }
+}
@@ -0,0 +1,202 @@
+//! Conversion of INI files into documents and back (`ini` feature).
+//!
+//! Sections become tables and `key = value` or `key: value` lines their key/value pairs,
+//! the pairs before the first section belong to the root table. Lines starting with `;`
+//! or `#` are kept as comments, blank lines are kept as well. Values are strings,
+//! or with `Inference::Values` TOML literals as long as they are not arrays or inline tables,
+//! e.g. `8080`, `true` or `"quoted"`.
+//!
+//! Documents are exported if their tables are sections of values which can be written
+//! on a single line, i.e. without nested tables, arrays or multi-line strings.
+//!
+//! # Example
+//!
+//! ```rust
+//! use toml_edit::ini::{self, Inference};
+//!
+//! let doc = ini::from_ini("; database\n[db]\nhost = localhost\nport = 5432\n", Inference::Values)
+//!     .unwrap();
+//! assert_eq!(doc.to_string(), "# database\n[db]\nhost = \"localhost\"\nport = 5432\n");
+//! assert_eq!(ini::to_ini(&doc).unwrap(), "[db]\nhost = localhost\nport = 5432\n");
+//! ```
+
+use crate::decor::{Decor, InternalString};
+use crate::document::Document;
+use crate::formatted::{decorated, StringStyle};
+use crate::key::Key;
+use crate::key_path::KeyPath;
+use crate::table::{value, Item, Table};
+use crate::value::Value;
+use std::fmt::{Display, Formatter, Result};
+
+/// How the values of INI files are converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Inference {
+    /// All values are strings.
+    #[default]
+    Strings,
+    /// Values which are TOML literals, except for arrays and inline tables,
+    /// are converted to their TOML values, the others are strings.
+    Values,
+}
+
+/// An error converting an INI file or exporting a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IniError {
+    /// A line which is neither a section header, a key/value pair nor a comment.
+    Syntax {
+        /// The line number, starting at 1.
+        line: usize,
+    },
+    /// A section or a key of a section defined twice.
+    Duplicate {
+        /// The line number, starting at 1.
+        line: usize,
+    },
+    /// An item which can't be written to an INI file.
+    Unsupported(KeyPath),
+}
+
+impl Display for IniError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match *self {
+            IniError::Syntax { line } => write!(f, "invalid INI line {}", line),
+            IniError::Duplicate { line } => write!(f, "duplicate INI key at line {}", line),
+            IniError::Unsupported(ref path) => {
+                write!(f, "`{}` can't be written to an INI file", path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IniError {}
+
+/// Converts an INI file into a document.
+pub fn from_ini(text: &str, inference: Inference) -> std::result::Result<Document, IniError> {
+    let mut doc = Document::new();
+    let mut section: Option<Key> = None;
+    // the comments and blank lines before the next key or section
+    let mut pending = InternalString::new();
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            pending.push('\n');
+        } else if line.starts_with(';') || line.starts_with('#') {
+            pending.push('#');
+            pending.push_str(&line[1..]);
+            pending.push('\n');
+        } else if line.starts_with('[') {
+            let name = match line.strip_suffix(']').map(|name| name[1..].trim()) {
+                Some(name) if !name.is_empty() => name,
+                _ => return Err(IniError::Syntax { line: number }),
+            };
+            let key = Key::from_unquoted(name);
+            if doc.as_table().contains_key(key.get()) {
+                return Err(IniError::Duplicate { line: number });
+            }
+            let mut table = Table::new();
+            table.decor = Decor::new(std::mem::take(&mut pending), InternalString::new());
+            doc.as_table_mut().insert(key.raw(), Item::Table(table));
+            section = Some(key);
+        } else {
+            let at = line
+                .find(['=', ':'])
+                .filter(|&at| !line[..at].trim().is_empty())
+                .ok_or(IniError::Syntax { line: number })?;
+            let key = Key::from_unquoted(line[..at].trim());
+            let table = match section {
+                Some(ref section) => doc.as_table_mut()[section.get()]
+                    .as_table_mut()
+                    .expect("sections are tables"),
+                None => doc.as_table_mut(),
+            };
+            if table.contains_key(key.get()) {
+                return Err(IniError::Duplicate { line: number });
+            }
+            table.insert(key.raw(), value(to_value(line[at + 1..].trim(), inference)));
+            if let Some(decor) = table.decor_mut(key.get()) {
+                decor.set_prefix(std::mem::take(&mut pending));
+            }
+        }
+    }
+    doc.trailing = pending;
+    Ok(doc)
+}
+
+fn to_value(s: &str, inference: Inference) -> Value {
+    // strings are basic strings, even if they look like TOML strings
+    let string = || Value::string_in_style(s, StringStyle::Basic);
+    match inference {
+        Inference::Strings => string(),
+        Inference::Values => match s.parse::<Value>() {
+            Ok(Value::Array(..)) | Ok(Value::InlineTable(..)) | Err(..) => string(),
+            Ok(v) => decorated(v, "", ""),
+        },
+    }
+}
+
+/// Exports the document as an INI file, the root values first and then a section
+/// for every table, without comments.
+///
+/// Strings are written as they are, the other values as written in the document.
+pub fn to_ini(doc: &Document) -> std::result::Result<String, IniError> {
+    let mut ini = String::new();
+    let mut path = KeyPath::new();
+    let root = doc.as_table();
+    for (key, item) in root.iter() {
+        if let Item::Value(ref v) = *item {
+            write_pair(&mut ini, &path, key, v)?;
+        }
+    }
+    for (key, item) in root.iter() {
+        path.push(Key::from_unquoted(key));
+        match *item {
+            Item::Value(..) | Item::None => {}
+            Item::Table(ref t) if !key.contains(']') => {
+                if !ini.is_empty() {
+                    ini.push('\n');
+                }
+                ini.push_str(&format!("[{}]\n", key));
+                for (key, item) in t.iter() {
+                    match *item {
+                        Item::Value(ref v) => write_pair(&mut ini, &path, key, v)?,
+                        Item::None => {}
+                        _ => return Err(unsupported(&path, key)),
+                    }
+                }
+            }
+            _ => return Err(IniError::Unsupported(path)),
+        }
+        path.pop();
+    }
+    Ok(ini)
+}
+
+fn write_pair(
+    ini: &mut String,
+    path: &KeyPath,
+    key: &str,
+    value: &Value,
+) -> std::result::Result<(), IniError> {
+    let text = match *value {
+        Value::String(ref s) => s.value().clone(),
+        Value::Array(..) | Value::InlineTable(..) => return Err(unsupported(path, key)),
+        _ => decorated(value.clone(), "", "").to_string(),
+    };
+    let valid_key = !key.is_empty()
+        && key.trim() == key
+        && !key.contains(['=', ':'])
+        && !key.starts_with(['[', ';', '#']);
+    if !valid_key || text.contains(['\n', '\r']) {
+        return Err(unsupported(path, key));
+    }
+    ini.push_str(&format!("{} = {}\n", key, text));
+    Ok(())
+}
+
+fn unsupported(path: &KeyPath, key: &str) -> IniError {
+    let mut path = path.clone();
+    path.push(Key::from_unquoted(key));
+    IniError::Unsupported(path)
+}
@@ -0,0 +1,132 @@
+use crate::document::Document;
+use crate::formatted::decorated;
+use crate::table::Item;
+use crate::value::Value;
+
+impl Document {
+    /// Substitutes `${NAME}` placeholders inside all string values,
+    /// including those nested in arrays and inline tables.
+    ///
+    /// `lookup` is called with `NAME` and placeholders for which it returns `None`
+    /// are left untouched. `$${NAME}` is an escaped placeholder and becomes `${NAME}`.
+    /// Substituted text is not interpolated again, keys are never interpolated.
+    ///
+    /// The decor of a changed string is preserved, its quoting may change.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::Document;
+    ///
+    /// let mut doc = r#"
+    /// url = "https://${HOST}:${PORT}/" # server
+    /// docs = "use $${HOST} for the host"
+    /// "#.parse::<Document>().unwrap();
+    /// doc.interpolate(|name| match name {
+    ///     "HOST" => Some("localhost".to_owned()),
+    ///     _ => None,
+    /// });
+    /// assert_eq!(doc.to_string(), r#"
+    /// url = "https://localhost:${PORT}/" # server
+    /// docs = "use ${HOST} for the host"
+    /// "#);
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    ///
+    /// To substitute environment variables use
+    /// `doc.interpolate(|name| std::env::var(name).ok())`.
+    pub fn interpolate<F>(&mut self, mut lookup: F)
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        interpolate_item(&mut self.root, &mut lookup);
+    }
+}
+
+fn interpolate_item<F>(item: &mut Item, lookup: &mut F)
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    match *item {
+        Item::None => {}
+        Item::Value(ref mut v) => interpolate_value(v, lookup),
+        Item::Table(ref mut t) => {
+            for (_, kv) in t.items.iter_mut() {
+                interpolate_item(&mut kv.value, lookup);
+            }
+        }
+        Item::ArrayOfTables(ref mut a) => {
+            for t in a.values.iter_mut() {
+                interpolate_item(t, lookup);
+            }
+        }
+    }
+}
+
+fn interpolate_value<F>(value: &mut Value, lookup: &mut F)
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let interpolated = match *value {
+        Value::String(ref f) => interpolate_str(f.value(), lookup)
+            .map(|s| decorated(Value::from(s), f.prefix(), f.suffix())),
+        Value::Array(ref mut a) => {
+            for v in a.values.iter_mut() {
+                interpolate_item(v, lookup);
+            }
+            None
+        }
+        Value::InlineTable(ref mut t) => {
+            for (_, kv) in t.items.iter_mut() {
+                interpolate_item(&mut kv.value, lookup);
+            }
+            None
+        }
+        _ => None,
+    };
+    if let Some(interpolated) = interpolated {
+        *value = interpolated;
+    }
+}
+
+// Returns `None` if the string is left unchanged.
+fn interpolate_str<F>(s: &str, lookup: &mut F) -> Option<String>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    if !s.contains("${") {
+        return None;
+    }
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('$') {
+        result.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            result.push_str("${");
+            rest = escaped;
+            continue;
+        }
+        if let Some(placeholder) = rest.strip_prefix("${") {
+            if let Some(end) = placeholder.find('}') {
+                if let Some(v) = lookup(&placeholder[..end]) {
+                    result.push_str(&v);
+                    rest = &placeholder[end + 1..];
+                    continue;
+                }
+            }
+        }
+        result.push('$');
+        rest = &rest[1..];
+    }
+    result.push_str(rest);
+    if result == s {
+        None
+    } else {
+        Some(result)
+    }
+}
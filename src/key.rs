@@ -1,6 +1,8 @@
-use crate::decor::InternalString;
-use crate::parser;
-use combine::stream::position::Stream;
+use crate::decor::{Decor, InternalString, Repr};
+use crate::formatted;
+use crate::parser::{self, lexer};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 
 /// Key as part of a Key/Value Pair or a table header.
@@ -25,10 +27,18 @@ use std::str::FromStr;
 /// For details see [toml spec](https://github.com/toml-lang/toml/#keyvalue-pair).
 ///
 /// To parse a key use `FromStr` trait implementation: `"string".parse::<Key>()`.
-#[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Clone)]
+///
+/// A key also carries its decor, i.e. the comments and whitespaces around it,
+/// which is used when the key is inserted into a table.
+/// A parsed key is followed by a single space.
+///
+/// Keys are compared and hashed by their value, regardless of their raw
+/// representation and decor, e.g. `"a"` and `a` are equal.
+#[derive(Debug, Clone)]
 pub struct Key {
     key: InternalString,
     raw: InternalString,
+    decor: Decor,
 }
 
 impl FromStr for Key {
@@ -47,22 +57,73 @@ impl FromStr for Key {
 }
 
 impl Key {
-    fn try_parse(s: &str) -> Result<Key, parser::TomlError> {
-        use combine::EasyParser;
-        let result = parser::key_parser().easy_parse(Stream::new(s));
-        match result {
-            Ok((_, ref rest)) if !rest.input.is_empty() => {
-                Err(parser::TomlError::from_unparsed(rest.positioner, s))
-            }
-            Ok(((raw, key), _)) => Ok(Key::new(raw, key)),
-            Err(e) => Err(parser::TomlError::new(e, s)),
-        }
+    pub(crate) fn try_parse(s: &str) -> Result<Key, parser::TomlError> {
+        lexer::key(s)
     }
 
-    pub(crate) fn new(raw: &str, key: InternalString) -> Self {
+    pub(crate) fn new<S: Into<InternalString>>(raw: &str, key: S) -> Self {
         Self {
             raw: raw.into(),
-            key,
+            key: key.into(),
+            decor: Decor::new("", " "),
+        }
+    }
+
+    pub(crate) fn with_repr<S: Into<InternalString>>(key: S, repr: Repr) -> Self {
+        Self {
+            key: key.into(),
+            raw: repr.raw_value,
+            decor: repr.decor,
+        }
+    }
+
+    pub(crate) fn to_repr(&self) -> Repr {
+        Repr {
+            decor: self.decor.clone(),
+            raw_value: self.raw.clone(),
+        }
+    }
+
+    /// Creates a key with the given value, which is quoted and escaped
+    /// unless it's a valid bare key.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use toml_edit::Key;
+    /// assert_eq!(Key::from_unquoted("serde").raw(), "serde");
+    /// assert_eq!(Key::from_unquoted("serde.json").raw(), r#""serde.json""#);
+    /// ```
+    pub fn from_unquoted(key: &str) -> Self {
+        let raw = if !key.is_empty() && key.chars().all(lexer::is_unquoted_char) {
+            key.to_owned()
+        } else {
+            formatted::to_basic_string(key)
+        };
+        Key::new(&raw, key)
+    }
+
+    /// Removes unnecessary quotes, i.e. makes the key bare if it's a valid bare key,
+    /// and quotes the key if its raw representation doesn't denote it, keeping the decor.
+    ///
+    /// Returns true iff the raw representation changed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use toml_edit::Key;
+    /// let mut key = "\"name\"".parse::<Key>().unwrap();
+    /// assert!(key.normalize());
+    /// assert_eq!(key.raw(), "name");
+    /// let mut key = "'a b'".parse::<Key>().unwrap();
+    /// assert!(!key.normalize());
+    /// assert_eq!(key.raw(), "'a b'");
+    /// ```
+    pub fn normalize(&mut self) -> bool {
+        match normalized_raw(&self.key, &self.raw) {
+            Some(raw) => {
+                self.raw = raw;
+                true
+            }
+            None => false,
         }
     }
 
@@ -75,11 +136,60 @@ impl Key {
     pub fn raw(&self) -> &str {
         &self.raw
     }
+
+    /// Returns the surrounding whitespaces and comments.
+    pub fn decor(&self) -> &Decor {
+        &self.decor
+    }
+
+    /// Returns the mutable surrounding whitespaces and comments.
+    pub fn decor_mut(&mut self) -> &mut Decor {
+        &mut self.decor
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Key) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl Eq for Key {}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Key) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Key {
+    fn cmp(&self, other: &Key) -> Ordering {
+        self.get().cmp(other.get())
+    }
+}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.get().hash(state)
+    }
 }
 
 #[doc(hidden)]
-impl From<Key> for InternalString {
-    fn from(key: Key) -> InternalString {
-        key.key
+impl From<Key> for String {
+    fn from(key: Key) -> String {
+        key.key.into()
+    }
+}
+
+// Returns the normalized raw representation of a key, see `Key::normalize`,
+// or `None` if `raw` is already normalized.
+pub(crate) fn normalized_raw(key: &str, raw: &str) -> Option<InternalString> {
+    let bare = !key.is_empty() && key.chars().all(lexer::is_unquoted_char);
+    if bare {
+        return if raw == key { None } else { Some(key.into()) };
+    }
+    match Key::try_parse(raw) {
+        Ok(ref parsed) if parsed.get() == key => None,
+        _ => Some(formatted::to_basic_string(key).into()),
     }
 }
@@ -0,0 +1,470 @@
+use crate::document::Document;
+use crate::key::Key;
+use crate::parser::{lexer, TomlError};
+use crate::table::{Item, KeyValuePairs, Table};
+use crate::value::{InlineTable, Value};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter, Result};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::str::FromStr;
+
+/// A sequence of keys addressing an item, e.g. `dependencies."serde.json".version`.
+///
+/// Paths are compared by the values of their keys, regardless of quoting.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "parse")]
+/// # fn main() {
+/// use toml_edit::{Document, Key, KeyPath};
+///
+/// let doc = r#"
+/// [dependencies]
+/// "serde.json" = { version = "1.0" }
+/// "#.parse::<Document>().unwrap();
+///
+/// let path = r#"dependencies."serde.json".version"#.parse::<KeyPath>().unwrap();
+/// assert_eq!(doc.get_path(&path).and_then(|v| v.as_str()), Some("1.0"));
+///
+/// let path = KeyPath::from(&["dependencies", "serde.json"][..]);
+/// assert_eq!(path.to_string(), r#"dependencies."serde.json""#);
+/// # }
+/// # #[cfg(not(feature = "parse"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct KeyPath {
+    keys: Vec<Key>,
+}
+
+impl KeyPath {
+    /// Creates an empty path.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the keys of the path.
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    /// Returns an iterator over the keys of the path.
+    pub fn iter(&self) -> std::slice::Iter<'_, Key> {
+        self.keys.iter()
+    }
+
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns true iff the path has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Appends a key to the path.
+    pub fn push(&mut self, key: Key) {
+        self.keys.push(key);
+    }
+
+    /// Removes the last key of the path and returns it.
+    pub fn pop(&mut self) -> Option<Key> {
+        self.keys.pop()
+    }
+
+    /// Returns the path without its last key, or `None` if the path is empty.
+    pub fn parent(&self) -> Option<KeyPath> {
+        self.keys
+            .split_last()
+            .map(|(_, parent)| parent.iter().cloned().collect())
+    }
+
+    /// Returns true iff `prefix` is a prefix of the path.
+    pub fn starts_with(&self, prefix: &KeyPath) -> bool {
+        prefix.len() <= self.len()
+            && self
+                .keys
+                .iter()
+                .zip(prefix.iter())
+                .all(|(a, b)| a.get() == b.get())
+    }
+}
+
+impl FromStr for KeyPath {
+    type Err = TomlError;
+
+    /// Parses a dotted path, e.g. `a."b.c".'d'`,
+    /// the keys may be surrounded by whitespaces.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        lexer::key_path(s).map(|keys| KeyPath { keys })
+    }
+}
+
+impl Display for KeyPath {
+    /// Displays the raw representations of the keys joined by `.`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        for (i, key) in self.keys.iter().enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", key.raw())?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for KeyPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.starts_with(other)
+    }
+}
+
+impl Eq for KeyPath {}
+
+impl Hash for KeyPath {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for key in &self.keys {
+            key.get().hash(state);
+        }
+    }
+}
+
+impl PartialOrd for KeyPath {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KeyPath {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().map(Key::get).cmp(other.iter().map(Key::get))
+    }
+}
+
+impl From<Vec<Key>> for KeyPath {
+    fn from(keys: Vec<Key>) -> Self {
+        KeyPath { keys }
+    }
+}
+
+impl<'a, S: AsRef<str>> From<&'a [S]> for KeyPath {
+    /// Creates a path from the values of the keys, quoting them as needed.
+    fn from(keys: &'a [S]) -> Self {
+        keys.iter()
+            .map(|k| Key::from_unquoted(k.as_ref()))
+            .collect()
+    }
+}
+
+impl FromIterator<Key> for KeyPath {
+    fn from_iter<I: IntoIterator<Item = Key>>(iter: I) -> Self {
+        KeyPath {
+            keys: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for KeyPath {
+    type Item = Key;
+    type IntoIter = std::vec::IntoIter<Key>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.keys.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a KeyPath {
+    type Item = &'a Key;
+    type IntoIter = std::slice::Iter<'a, Key>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.keys.iter()
+    }
+}
+
+impl Table {
+    /// Returns the item at the given path, descending into tables and inline tables,
+    /// or `None` if there is no such item or the path is empty.
+    pub fn get_path(&self, path: &KeyPath) -> Option<&Item> {
+        let (last, parents) = path.keys.split_last()?;
+        let mut items = &self.items;
+        for key in parents {
+            items = match items.get(key.get())?.value {
+                Item::Table(ref t) => &t.items,
+                Item::Value(Value::InlineTable(ref t)) => &t.items,
+                _ => return None,
+            };
+        }
+        items
+            .get(last.get())
+            .map(|kv| &kv.value)
+            .filter(|item| !item.is_none())
+    }
+
+    /// Returns the mutable item at the given path, descending into tables and inline tables,
+    /// or `None` if there is no such item or the path is empty.
+    pub fn get_path_mut(&mut self, path: &KeyPath) -> Option<&mut Item> {
+        let (last, parents) = path.keys.split_last()?;
+        let mut items: &mut KeyValuePairs = &mut self.items;
+        for key in parents {
+            items = match items.get_mut(key.get())?.value {
+                Item::Table(ref mut t) => &mut t.items,
+                Item::Value(Value::InlineTable(ref mut t)) => &mut t.items,
+                _ => return None,
+            };
+        }
+        items
+            .get_mut(last.get())
+            .map(|kv| &mut kv.value)
+            .filter(|item| !item.is_none())
+    }
+
+    /// Returns true iff there is an item at the given path, see `Table::get_path`.
+    pub fn contains_path(&self, path: &KeyPath) -> bool {
+        self.get_path(path).is_some()
+    }
+}
+
+impl Document {
+    /// Returns the item at the given path, see `Table::get_path`.
+    pub fn get_path(&self, path: &KeyPath) -> Option<&Item> {
+        self.as_table().get_path(path)
+    }
+
+    /// Returns the mutable item at the given path, see `Table::get_path_mut`.
+    pub fn get_path_mut(&mut self, path: &KeyPath) -> Option<&mut Item> {
+        self.as_table_mut().get_path_mut(path)
+    }
+
+    /// Returns true iff there is an item at the given path, see `Table::get_path`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{Document, KeyPath};
+    /// let doc = "[dependencies]\nserde = { version = '1.0' }\n[[bin]]\n".parse::<Document>().unwrap();
+    /// let path = |s: &str| s.parse::<KeyPath>().unwrap();
+    /// assert!(doc.contains_path(&path("dependencies.serde.version")));
+    /// assert!(!doc.contains_path(&path("dependencies.log")));
+    /// assert!(doc.contains_table(&path("dependencies")));
+    /// assert!(doc.contains_value(&path("dependencies.serde")));
+    /// assert!(doc.contains_array_of_tables(&path("bin")));
+    /// assert!(!doc.contains_table(&path("bin")));
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn contains_path(&self, path: &KeyPath) -> bool {
+        self.get_path(path).is_some()
+    }
+
+    /// Returns true iff there is a table at the given path.
+    pub fn contains_table(&self, path: &KeyPath) -> bool {
+        self.get_path(path).is_some_and(Item::is_table)
+    }
+
+    /// Returns true iff there is a value at the given path, including inline tables.
+    pub fn contains_value(&self, path: &KeyPath) -> bool {
+        self.get_path(path).is_some_and(Item::is_value)
+    }
+
+    /// Returns true iff there is an array of tables at the given path.
+    pub fn contains_array_of_tables(&self, path: &KeyPath) -> bool {
+        self.get_path(path).is_some_and(Item::is_array_of_tables)
+    }
+
+    /// Renames the table, the array of tables or the inline table at the path,
+    /// returning false if there is no such table or its parent already contains `new_key`.
+    ///
+    /// The headers of the table and its subtables, every `[[header]]` of an array of tables
+    /// and the dotted keys defining it are renamed in place: the entry keeps its place
+    /// among its siblings, and the tables keep their decor and their positions in the document.
+    ///
+    /// # Panics
+    ///
+    /// If `new_key` is not a valid key.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::{Document, KeyPath};
+    ///
+    /// let mut doc = "[[bin]] # first\nname = 'a'\n\n[lib]\n\n[[bin]]\nname = 'b'\n"
+    ///     .parse::<Document>()
+    ///     .unwrap();
+    /// assert!(doc.rename_table(&"bin".parse::<KeyPath>().unwrap(), "example"));
+    /// assert_eq!(
+    ///     doc.to_string_in_original_order(),
+    ///     "[[example]] # first\nname = 'a'\n\n[lib]\n\n[[example]]\nname = 'b'\n"
+    /// );
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn rename_table(&mut self, path: &KeyPath, new_key: &str) -> bool {
+        let new = new_key.parse::<Key>().expect("invalid key");
+        let (last, parents) = match path.keys.split_last() {
+            Some(split) => split,
+            None => return false,
+        };
+        let parent = KeyPath::from_iter(parents.iter().cloned());
+        let items = if parent.is_empty() {
+            &mut self.as_table_mut().items
+        } else {
+            match self.get_path_mut(&parent) {
+                Some(Item::Table(t)) => &mut t.items,
+                Some(Item::Value(Value::InlineTable(t))) => &mut t.items,
+                _ => return false,
+            }
+        };
+        match items.get(last.get()).map(|kv| &kv.value) {
+            Some(Item::Table(..))
+            | Some(Item::ArrayOfTables(..))
+            | Some(Item::Value(Value::InlineTable(..))) => {}
+            _ => return false,
+        }
+        let taken =
+            new.get() != last.get() && items.get(new.get()).is_some_and(|kv| !kv.value.is_none());
+        if taken {
+            return false;
+        }
+        rename_entry(items, last.get(), &new);
+        true
+    }
+
+    /// Creates a document from items at paths, see `try_extend`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use toml_edit::{Document, KeyPath, value};
+    ///
+    /// let env = vec![("APP__SERVER__PORT", "8080"), ("APP__NAME", "demo"), ("APP__SERVER", "x")];
+    /// let pairs = env.into_iter().map(|(var, v)| {
+    ///     let keys = var.to_lowercase().split("__").skip(1).map(str::to_owned).collect::<Vec<_>>();
+    ///     (KeyPath::from(&keys[..]), value(v))
+    /// });
+    /// let conflicts = Document::from_pairs(pairs.clone()).unwrap_err();
+    /// assert_eq!(conflicts[0].0.to_string(), "server");
+    ///
+    /// let doc = Document::from_pairs(pairs.take(2)).unwrap();
+    /// assert_eq!(doc.to_string(), "name = \"demo\"\n\n[server]\nport = \"8080\"\n");
+    /// ```
+    pub fn from_pairs<I>(pairs: I) -> std::result::Result<Self, Vec<(KeyPath, Item)>>
+    where
+        I: IntoIterator<Item = (KeyPath, Item)>,
+    {
+        let mut doc = Document::new();
+        doc.try_extend(pairs).map(|()| doc)
+    }
+
+    /// Inserts the items at their paths, creating the missing tables.
+    ///
+    /// The missing tables are implicit tables, or inline tables within inline tables,
+    /// the inline tables an item is inserted into are formatted.
+    /// Items are not inserted, and are returned instead, if the path is empty,
+    /// an item is already present at the path or at a prefix of the path which
+    /// is not a table, or if the item is a table or an array of tables and the parent
+    /// is an inline table.
+    pub fn try_extend<I>(&mut self, pairs: I) -> std::result::Result<(), Vec<(KeyPath, Item)>>
+    where
+        I: IntoIterator<Item = (KeyPath, Item)>,
+    {
+        let mut conflicts = Vec::new();
+        for (path, item) in pairs {
+            if let Err(item) = insert_path(self.as_table_mut(), path.keys(), item) {
+                conflicts.push((path, item));
+            }
+        }
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+}
+
+/// Inserts the items at their paths, skipping the conflicting ones, see `Document::try_extend`.
+impl Extend<(KeyPath, Item)> for Document {
+    fn extend<I: IntoIterator<Item = (KeyPath, Item)>>(&mut self, pairs: I) {
+        let _ = self.try_extend(pairs);
+    }
+}
+
+// Renames the entry `old`, keeping its place among the entries and its key's decor.
+fn rename_entry(items: &mut KeyValuePairs, old: &str, new: &Key) {
+    let following: Vec<_> = items
+        .keys()
+        .skip_while(|k| *k != old)
+        .skip(1)
+        .filter(|k| *k != new.get())
+        .cloned()
+        .collect();
+    let mut kv = items.remove(old).expect("the entry exists");
+    kv.key.raw_value = new.raw().into();
+    items.remove(new.get());
+    items.insert(new.get().into(), kv);
+    for k in following {
+        items.get_refresh(&k);
+    }
+}
+
+fn insert_path(table: &mut Table, keys: &[Key], item: Item) -> std::result::Result<(), Item> {
+    let (key, rest) = match keys.split_first() {
+        Some(split) => split,
+        None => return Err(item),
+    };
+    if rest.is_empty() {
+        if table.contains_key(key.get()) {
+            return Err(item);
+        }
+        table.insert(key.raw(), item);
+        return Ok(());
+    }
+    if !table.contains_key(key.get()) {
+        let mut t = Table::new();
+        t.set_implicit(true);
+        table.insert(key.raw(), Item::Table(t));
+    }
+    match table.get_mut(key.get()) {
+        Some(Item::Table(t)) => insert_path(t, rest, item),
+        Some(Item::Value(Value::InlineTable(t))) => insert_inline_path(t, rest, item),
+        _ => Err(item),
+    }
+}
+
+fn insert_inline_path(
+    table: &mut InlineTable,
+    keys: &[Key],
+    item: Item,
+) -> std::result::Result<(), Item> {
+    let (key, rest) = match keys.split_first() {
+        Some(split) => split,
+        None => return Err(item),
+    };
+    if rest.is_empty() {
+        match item {
+            Item::Value(v) if !table.contains_key(key.get()) => {
+                table.insert(key.raw(), v);
+            }
+            _ => return Err(item),
+        }
+    } else {
+        if !table.contains_key(key.get()) {
+            table.insert(key.raw(), InlineTable::default());
+        }
+        match table.get_mut(key.get()) {
+            Some(Value::InlineTable(t)) => insert_inline_path(t, rest, item)?,
+            _ => return Err(item),
+        }
+    }
+    // the inline tables along the path are formatted, see `InlineTable::fmt`
+    table.fmt();
+    Ok(())
+}
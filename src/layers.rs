@@ -0,0 +1,135 @@
+use crate::document::Document;
+use crate::key::Key;
+use crate::key_path::KeyPath;
+use crate::table::{Item, TableLike};
+
+/// A stack of named documents, e.g. defaults, system and user configuration,
+/// where the items of the later layers override the items of the earlier ones.
+///
+/// Tables are not merged: looking up a table returns the table of the last layer
+/// defining it, the values within tables are looked up by their own paths. A value
+/// at a prefix of the path, e.g. `a = 1` for `a.b`, hides the path in the earlier layers.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "parse")]
+/// # fn main() {
+/// use toml_edit::{Document, KeyPath, Layers};
+///
+/// let defaults = "[log]\nlevel = 'info'\nfile = 'app.log'\n".parse::<Document>().unwrap();
+/// let user = "log.level = 'debug'\n".parse::<Document>().unwrap();
+/// let layers = Layers::new().with_layer("defaults", defaults).with_layer("user", user);
+///
+/// let level = "log.level".parse::<KeyPath>().unwrap();
+/// let file = "log.file".parse::<KeyPath>().unwrap();
+/// assert_eq!(layers.get(&level).and_then(|i| i.as_str()), Some("debug"));
+/// assert_eq!(layers.layer_of(&level), Some("user"));
+/// assert_eq!(layers.get(&file).and_then(|i| i.as_str()), Some("app.log"));
+/// assert_eq!(layers.layer_of(&file), Some("defaults"));
+/// # }
+/// # #[cfg(not(feature = "parse"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Layers {
+    layers: Vec<(String, Document)>,
+}
+
+// The result of looking up a path in a single layer.
+enum Lookup<'a> {
+    Found(&'a Item),
+    Missing,
+    // a prefix of the path is not a table
+    Hidden,
+}
+
+impl Layers {
+    /// Creates an empty stack.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a layer on top of the stack.
+    pub fn with_layer<S: Into<String>>(mut self, name: S, doc: Document) -> Self {
+        self.push(name, doc);
+        self
+    }
+
+    /// Adds a layer on top of the stack.
+    pub fn push<S: Into<String>>(&mut self, name: S, doc: Document) {
+        self.layers.push((name.into(), doc));
+    }
+
+    /// Removes the top layer.
+    pub fn pop(&mut self) -> Option<(String, Document)> {
+        self.layers.pop()
+    }
+
+    /// Returns the number of layers.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns true iff there are no layers.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Returns the document of the last layer with the given name.
+    pub fn layer(&self, name: &str) -> Option<&Document> {
+        self.layers.iter().rev().find(|l| l.0 == name).map(|l| &l.1)
+    }
+
+    /// Returns the mutable document of the last layer with the given name.
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut Document> {
+        self.layers
+            .iter_mut()
+            .rev()
+            .find(|l| l.0 == name)
+            .map(|l| &mut l.1)
+    }
+
+    /// Returns an iterator over the names and documents of the layers, from the bottom up.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Document)> {
+        self.layers.iter().map(|l| (l.0.as_str(), &l.1))
+    }
+
+    /// Returns the effective item at the path, see `resolve`.
+    pub fn get(&self, path: &KeyPath) -> Option<&Item> {
+        self.resolve(path).map(|(_, item)| item)
+    }
+
+    /// Returns the name of the layer supplying the effective item at the path.
+    pub fn layer_of(&self, path: &KeyPath) -> Option<&str> {
+        self.resolve(path).map(|(name, _)| name)
+    }
+
+    /// Returns the effective item at the path together with the name of the layer
+    /// supplying it, or `None` if no layer defines the path or the path is empty.
+    pub fn resolve(&self, path: &KeyPath) -> Option<(&str, &Item)> {
+        for (name, doc) in self.layers.iter().rev() {
+            match lookup(doc.as_table(), path.keys()) {
+                Lookup::Found(item) => return Some((name, item)),
+                Lookup::Missing => {}
+                Lookup::Hidden => return None,
+            }
+        }
+        None
+    }
+}
+
+fn lookup<'a>(table: &'a dyn TableLike, keys: &[Key]) -> Lookup<'a> {
+    let (first, rest) = match keys.split_first() {
+        Some(split) => split,
+        None => return Lookup::Missing,
+    };
+    match table.get(first.get()).filter(|item| !item.is_none()) {
+        None => Lookup::Missing,
+        Some(item) if rest.is_empty() => Lookup::Found(item),
+        Some(item) => match item.as_table_like() {
+            Some(t) => lookup(t, rest),
+            None => Lookup::Hidden,
+        },
+    }
+}
@@ -0,0 +1,230 @@
+use crate::document::Document;
+use crate::parser::{self, TomlError};
+use crate::table::Table;
+use combine::stream::position::Stream;
+use combine::EasyParser;
+use std::fmt::{Display, Formatter, Result};
+
+/// A TOML document whose tables are parsed only when accessed.
+///
+/// The input is split into sections, each starting with a table header,
+/// and only the headers are parsed upfront. Sections which were not accessed
+/// are displayed verbatim.
+///
+/// Unlike `Document`, the lazy document doesn't validate the unparsed sections,
+/// nor the uniqueness of the tables, use `into_document` to fully parse it.
+/// Parse errors of a section refer to lines relative to its header.
+///
+/// # Example
+///
+/// ```rust
+/// use toml_edit::{value, LazyDocument};
+///
+/// let mut doc = LazyDocument::parse(r#"
+/// [package]
+/// name = "toml_edit"
+/// version = "0.2.1"
+///
+/// [dependencies]
+/// combine = "4.5.2"
+/// "#).unwrap();
+///
+/// let package = doc.table_mut(&["package"]).unwrap().unwrap();
+/// package["version"] = value("0.2.2");
+///
+/// assert_eq!(doc.to_string(), r#"
+/// [package]
+/// name = "toml_edit"
+/// version = "0.2.2"
+///
+/// [dependencies]
+/// combine = "4.5.2"
+/// "#);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazyDocument {
+    sections: Vec<Section>,
+}
+
+#[derive(Debug, Clone)]
+struct Section {
+    // empty for the root section
+    path: Vec<String>,
+    is_array_of_tables: bool,
+    raw: String,
+    parsed: Option<Document>,
+}
+
+impl LazyDocument {
+    /// Splits the input into sections, parsing only the table headers.
+    pub fn parse(input: &str) -> std::result::Result<Self, TomlError> {
+        let mut offsets = header_offsets(input);
+        offsets.push(input.len());
+        let mut sections = vec![Section {
+            path: Vec::new(),
+            is_array_of_tables: false,
+            raw: input[..offsets[0]].to_owned(),
+            parsed: None,
+        }];
+        for bounds in offsets.windows(2) {
+            let raw = &input[bounds[0]..bounds[1]];
+            let (path, is_array_of_tables) = parse_header(raw)?;
+            sections.push(Section {
+                path,
+                is_array_of_tables,
+                raw: raw.to_owned(),
+                parsed: None,
+            });
+        }
+        Ok(Self { sections })
+    }
+
+    /// Returns the standard table with the given header path, parsing its section
+    /// if it wasn't parsed before, or `None` if there is no such table.
+    ///
+    /// The empty path refers to the root table, containing the key/value pairs
+    /// preceding the first header.
+    pub fn table_mut(
+        &mut self,
+        path: &[&str],
+    ) -> std::result::Result<Option<&mut Table>, TomlError> {
+        let section = self
+            .sections
+            .iter_mut()
+            .find(|s| !s.is_array_of_tables && s.path.iter().eq(path.iter()));
+        let section = match section {
+            Some(section) => section,
+            None => return Ok(None),
+        };
+        if section.parsed.is_none() {
+            section.parsed = Some(section.raw.parse::<Document>()?);
+        }
+        let mut table = section
+            .parsed
+            .as_mut()
+            .map(Document::as_table_mut)
+            .expect("the section is parsed; qed");
+        for key in path {
+            table = match table.get_mut(key).and_then(|t| t.as_table_mut()) {
+                Some(t) => t,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(table))
+    }
+
+    /// Returns true iff the section of the standard table with the given header path was parsed.
+    pub fn is_parsed(&self, path: &[&str]) -> bool {
+        self.sections
+            .iter()
+            .any(|s| !s.is_array_of_tables && s.path.iter().eq(path.iter()) && s.parsed.is_some())
+    }
+
+    /// Parses the whole document, including the modifications.
+    pub fn into_document(self) -> std::result::Result<Document, TomlError> {
+        self.to_string().parse()
+    }
+}
+
+impl Display for LazyDocument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        for section in &self.sections {
+            match section.parsed {
+                Some(ref doc) => write!(f, "{}", doc)?,
+                None => write!(f, "{}", section.raw)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_header(raw: &str) -> std::result::Result<(Vec<String>, bool), TomlError> {
+    let header = raw.trim_start_matches([' ', '\t']);
+    let is_array_of_tables = header.starts_with("[[");
+    let keys = if is_array_of_tables {
+        &header[2..]
+    } else {
+        &header[1..]
+    };
+    let (path, _) = parser::key_path_parser()
+        .easy_parse(Stream::new(keys))
+        .map_err(|e| TomlError::new(e, keys))?;
+    let path = path.iter().map(|k| k.get().to_owned()).collect();
+    Ok((path, is_array_of_tables))
+}
+
+// Returns the offsets of the lines starting with a table header,
+// skipping over strings, comments and multiline arrays and inline tables.
+fn header_offsets(input: &str) -> Vec<usize> {
+    let bytes = input.as_bytes();
+    let mut offsets = Vec::new();
+    let mut depth = 0usize;
+    let mut line = 0;
+    let mut line_start = true;
+    let mut in_header = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        match bytes[i] {
+            b'\n' => {
+                line = i + 1;
+                line_start = true;
+                in_header = false;
+                i += 1;
+                continue;
+            }
+            b' ' | b'\t' | b'\r' => {
+                i += 1;
+                continue;
+            }
+            b'#' => {
+                i = skip_until(bytes, i, b'\n');
+                continue;
+            }
+            b'[' if line_start && depth == 0 => {
+                offsets.push(line);
+                in_header = true;
+                i += 1;
+            }
+            b'"' if rest.starts_with(b"\"\"\"") => i = skip_string(bytes, i + 3, b"\"\"\"", true),
+            b'"' => i = skip_string(bytes, i + 1, b"\"", true),
+            b'\'' if rest.starts_with(b"'''") => i = skip_string(bytes, i + 3, b"'''", false),
+            b'\'' => i = skip_string(bytes, i + 1, b"'", false),
+            b'[' | b'{' if !in_header => {
+                depth += 1;
+                i += 1;
+            }
+            b']' | b'}' if !in_header => {
+                depth = depth.saturating_sub(1);
+                i += 1;
+            }
+            _ => i += 1,
+        }
+        line_start = false;
+    }
+    offsets
+}
+
+fn skip_until(bytes: &[u8], start: usize, end: u8) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|&b| b == end)
+        .map_or(bytes.len(), |p| start + p)
+}
+
+// Returns the offset after the closing delimiter,
+// single-line strings are also terminated by a newline.
+fn skip_string(bytes: &[u8], start: usize, delim: &[u8], escapes: bool) -> usize {
+    let mut i = start;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(delim) {
+            return i + delim.len();
+        }
+        match bytes[i] {
+            b'\\' if escapes => i += 2,
+            b'\n' if delim.len() == 1 => return i,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
@@ -1,6 +1,8 @@
 #![deny(missing_docs)]
 // https://github.com/Marwes/combine/issues/172
 #![recursion_limit = "256"]
+// `Array` returns rejected values by value
+#![allow(clippy::result_large_err)]
 
 //! # `toml_edit`
 //!
@@ -13,6 +15,8 @@
 //! # Example
 //!
 //! ```rust
+//! # #[cfg(feature = "parse")]
+//! # fn main() {
 //! use toml_edit::{Document, value};
 //!
 //! let toml = r#"
@@ -31,8 +35,39 @@
 //! c = { d = "hello" }
 //! "#;
 //! assert_eq!(doc.to_string(), expected);
+//! # }
+//! # #[cfg(not(feature = "parse"))]
+//! # fn main() {}
 //! ```
 //!
+//! ## Features
+//!
+//! The `datetime` and `parse` features are enabled by default, the other features are disabled:
+//!
+//! * `parse` enables parsing documents and values, e.g. `Document::from_str`, with `combine`.
+//!   Without it, documents can only be built programmatically, keys, strings and decors
+//!   are still validated. The features parsing TOML imply it.
+//! * `datetime` interprets date-times with `chrono`, see `DateTime::as_offset_date_time`
+//!   and `DatetimeConversion`. Without it, date-times are kept as they are written,
+//!   so documents still round-trip unchanged, without depending on `chrono`.
+//! * `i128` enables `Value::as_i128` for integers beyond `i64`.
+//! * `serde` enables the `de` and `ser` modules and (de)serialization of `Document` itself,
+//!   implies `parse`.
+//! * `derive` enables `#[derive(TomlEdit)]`, see the `binding` module.
+//! * `verbatim` keeps the source of parsed documents for `Document::to_string_verbatim`,
+//!   implies `parse`.
+//! * `units` enables the `units` module for numbers with units like `"10s"` or `"5MB"`.
+//! * `wasm` enables the `wasm` module with string based entry points for `wasm-bindgen`,
+//!   implies `parse`.
+//! * `capi` enables the `capi` module with a C interface, implies `wasm`.
+//! * `test-util` enables the `test_util` module with generators of documents for fuzzing,
+//!   and the `testing` module with assertions on TOML output, implies `parse`.
+//! * `ini` enables the `ini` module converting INI files into documents and back, implies `parse`.
+//! * `yaml` enables the `yaml` module converting YAML values into documents and back, implies `serde`.
+//! * `cli` builds the `toml-edit` command-line tool for format-preserving edits, implies `wasm`.
+//! * `combinators` enables the `combinators` module with parsers of values and keys
+//!   for embedding TOML in other grammars, implies `parse`.
+//!
 //! ## Limitations
 //!
 //! Things it does not preserve:
@@ -68,21 +103,111 @@
 //! [test]: https://github.com/ordian/toml_edit/blob/f09bd5d075fdb7d2ef8d9bb3270a34506c276753/tests/test_valid.rs#L84
 
 mod array_of_tables;
+mod array_set;
+mod auto_inline;
+pub mod binding;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "combinators")]
+pub mod combinators;
+mod comment_out;
+mod comments;
+mod datetime;
+#[cfg(feature = "serde")]
+pub mod de;
 mod decor;
 mod display;
 mod document;
+mod dotted;
+mod find;
+mod fingerprint;
+mod format;
 pub(crate) mod formatted;
+mod history;
+#[cfg(feature = "parse")]
+mod include;
+mod indent;
 mod index;
+#[cfg(feature = "ini")]
+pub mod ini;
+mod interpolate;
 mod key;
+mod key_path;
+mod layers;
+#[cfg(feature = "parse")]
+mod lazy;
+mod merge;
+mod metadata;
+mod node;
 mod parser;
+mod preview;
+mod reconcile;
+mod reflow;
+#[cfg(feature = "serde")]
+pub mod ser;
+mod shape;
+mod shrink;
+mod stable_id;
+mod strip;
+mod style;
 mod table;
+#[cfg(feature = "parse")]
+mod template;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[cfg(feature = "test-util")]
+pub mod testing;
+mod transaction;
+#[cfg(feature = "units")]
+pub mod units;
+mod validate;
 mod value;
+#[cfg(feature = "verbatim")]
+mod verbatim;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "yaml")]
+pub mod yaml;
 
 pub use crate::array_of_tables::ArrayOfTables;
-pub use crate::decor::Decor;
-pub use crate::document::Document;
+pub use crate::auto_inline::AutoInline;
+pub use crate::comments::{Comment, CommentFilter, CommentMap};
+#[cfg(feature = "datetime")]
+pub use crate::datetime::DatetimeConversion;
+pub use crate::datetime::DatetimeKind;
+pub use crate::decor::{Decor, Formatted, FormattedValue, Repr};
+#[cfg(feature = "parse")]
+pub use crate::document::MultiDocumentIter;
+pub use crate::document::{Document, TablePlacement};
+pub use crate::format::FmtOptions;
+#[cfg(feature = "parse")]
+pub use crate::include::{IncludeError, IncludeResolver};
+pub use crate::index::{IndexError, PathError};
 pub use crate::key::Key;
-pub use crate::parser::TomlError;
-pub use crate::table::{array, table, value, Item, Iter, IterMut, Table, TableLike};
-pub use crate::value::{Array, ArrayIter, InlineTable, Value};
-pub use formatted::decorated;
+pub use crate::key_path::KeyPath;
+pub use crate::layers::Layers;
+#[cfg(feature = "parse")]
+pub use crate::lazy::LazyDocument;
+pub use crate::merge::MergeConflict;
+pub use crate::metadata::{Metadata, NodeId};
+pub use crate::node::Node;
+#[cfg(feature = "parse")]
+pub use crate::parser::TableConflict;
+pub use crate::parser::{
+    ExtensionLiteral, NumberPolicy, ParseOptions, TableConflicts, TomlError, TomlVersion,
+};
+pub use crate::reconcile::{reconcile, Change};
+pub use crate::reflow::Reflow;
+pub use crate::shape::{MismatchKind, Shape, ShapeMismatch, TableShape};
+pub use crate::stable_id::{SidecarError, StableIds};
+pub use crate::style::{Newline, StyleReport};
+pub use crate::table::{
+    array, table, value, ConflictPolicy, CursorMut, EntryMut, IntoFlattenedIter, Item, Iter,
+    IterFull, IterMut, Table, TableLike,
+};
+#[cfg(feature = "parse")]
+pub use crate::template::{Template, TemplateError};
+pub use crate::transaction::{EditError, Transaction};
+pub use crate::validate::{InvalidItem, InvalidKind, KeySpan};
+pub use crate::value::{Array, ArrayIter, ArrayIterMut, InlineTable, Value};
+pub use formatted::{decorated, IntegerOverflow, StringStyle, StringStyleError};
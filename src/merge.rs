@@ -0,0 +1,237 @@
+use crate::document::Document;
+use crate::key::Key;
+use crate::key_path::KeyPath;
+use crate::table::{Item, Table, TableLike};
+use crate::value::Value;
+use std::fmt::{Display, Formatter, Result};
+
+/// Conflicting changes of an item in a three-way merge, see `Document::merge3`.
+#[derive(Debug, Clone)]
+pub struct MergeConflict {
+    path: KeyPath,
+    base: Item,
+    ours: Item,
+    theirs: Item,
+}
+
+impl MergeConflict {
+    /// Returns the path of the conflicting item.
+    pub fn path(&self) -> &KeyPath {
+        &self.path
+    }
+
+    /// Returns the item in the base document, `Item::None` if it was missing.
+    pub fn base(&self) -> &Item {
+        &self.base
+    }
+
+    /// Returns the item in our document, `Item::None` if it was removed.
+    pub fn ours(&self) -> &Item {
+        &self.ours
+    }
+
+    /// Returns the item in their document, `Item::None` if it was removed.
+    pub fn theirs(&self) -> &Item {
+        &self.theirs
+    }
+}
+
+impl Display for MergeConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "conflicting changes of `{}`", self.path)
+    }
+}
+
+impl std::error::Error for MergeConflict {}
+
+impl Document {
+    /// Merges the changes made to `base` in `ours` and in `theirs`,
+    /// or returns the conflicting changes.
+    ///
+    /// Items changed on one side only are taken from that side, items changed
+    /// in the same way on both sides are taken from ours, and the standard tables
+    /// changed on both sides are merged recursively. Values are compared regardless
+    /// of their formatting, changes of formatting alone are not merged.
+    ///
+    /// The merged document keeps the formatting of ours, items taken from theirs
+    /// keep their formatting and new keys of theirs are appended to their tables.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::Document;
+    ///
+    /// let base = "[dependencies]\nserde = '1.0'\n".parse::<Document>().unwrap();
+    /// let ours = "[dependencies]\nserde = '1.0'\nlog = '0.4'\n".parse::<Document>().unwrap();
+    /// let theirs = "[dependencies]\nserde = '1.1'\nrand = '0.8'\n".parse::<Document>().unwrap();
+    ///
+    /// let merged = Document::merge3(&base, &ours, &theirs).unwrap();
+    /// assert_eq!(
+    ///     merged.to_string(),
+    ///     "[dependencies]\nserde = '1.1'\nlog = '0.4'\nrand = '0.8'\n"
+    /// );
+    ///
+    /// let theirs = "[dependencies]\nserde = '1.1'\nlog = '0.3'\n".parse::<Document>().unwrap();
+    /// let conflicts = Document::merge3(&base, &ours, &theirs).unwrap_err();
+    /// assert_eq!(conflicts[0].to_string(), "conflicting changes of `dependencies.log`");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn merge3(
+        base: &Document,
+        ours: &Document,
+        theirs: &Document,
+    ) -> std::result::Result<Document, Vec<MergeConflict>> {
+        let mut merged = ours.clone();
+        let mut conflicts = Vec::new();
+        merge_tables(
+            Some(base.as_table()),
+            merged.as_table_mut(),
+            theirs.as_table(),
+            &mut KeyPath::new(),
+            &mut conflicts,
+        );
+        if conflicts.is_empty() {
+            Ok(merged)
+        } else {
+            Err(conflicts)
+        }
+    }
+}
+
+fn get<'t>(table: Option<&'t Table>, key: &str) -> Option<&'t Item> {
+    table
+        .and_then(|t| t.get(key))
+        .filter(|item| !item.is_none())
+}
+
+fn merge_tables(
+    base: Option<&Table>,
+    ours: &mut Table,
+    theirs: &Table,
+    path: &mut KeyPath,
+    conflicts: &mut Vec<MergeConflict>,
+) {
+    let mut keys: Vec<&str> = ours.items.keys().map(|k| &k[..]).collect();
+    for key in theirs
+        .items
+        .keys()
+        .chain(base.iter().flat_map(|b| b.items.keys()))
+    {
+        if !keys.contains(&&key[..]) {
+            keys.push(key);
+        }
+    }
+    let keys: Vec<String> = keys.into_iter().map(str::to_owned).collect();
+
+    for key in keys {
+        let b = get(base, &key);
+        let o = get(Some(ours), &key);
+        let t = get(Some(theirs), &key);
+        if same(o, t) || same(t, b) {
+            continue;
+        }
+        if same(o, b) {
+            match t {
+                Some(t) => {
+                    let mut item = t.clone();
+                    clear_positions(&mut item);
+                    if let Some(kv) = ours.items.get_mut(key.as_str()) {
+                        kv.value = item;
+                    } else {
+                        let kv = &theirs.items[key.as_str()];
+                        let key = Key::with_repr(key.as_str(), kv.key.clone());
+                        ours.insert_formatted(&key, item);
+                    }
+                }
+                None => {
+                    ours.remove(&key);
+                }
+            }
+            continue;
+        }
+        path.push(Key::from_unquoted(&key));
+        match (o, t) {
+            (Some(Item::Table(..)), Some(Item::Table(t))) => {
+                let b = b.and_then(Item::as_table);
+                let o = ours.get_mut(&key).and_then(Item::as_table_mut).unwrap();
+                merge_tables(b, o, t, path, conflicts);
+            }
+            _ => conflicts.push(MergeConflict {
+                path: path.clone(),
+                base: b.cloned().unwrap_or_default(),
+                ours: o.cloned().unwrap_or_default(),
+                theirs: t.cloned().unwrap_or_default(),
+            }),
+        }
+        path.pop();
+    }
+}
+
+// returns true iff the items are equal regardless of their formatting
+fn same(a: Option<&Item>, b: Option<&Item>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => same_item(a, b),
+        _ => false,
+    }
+}
+
+pub(crate) fn same_item(a: &Item, b: &Item) -> bool {
+    match (a, b) {
+        (Item::Value(a), Item::Value(b)) => same_value(a, b),
+        (Item::ArrayOfTables(a), Item::ArrayOfTables(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_table(a, b))
+        }
+        _ => match (a.as_table_like(), b.as_table_like()) {
+            (Some(a), Some(b)) => same_table(a, b),
+            _ => false,
+        },
+    }
+}
+
+fn same_table(a: &dyn TableLike, b: &dyn TableLike) -> bool {
+    let non_empty = |(_, item): &(&str, &Item)| !item.is_none();
+    a.iter().filter(non_empty).count() == b.iter().filter(non_empty).count()
+        && a.iter()
+            .filter(non_empty)
+            .all(|(key, item)| b.get(key).is_some_and(|other| same_item(item, other)))
+}
+
+pub(crate) fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => a.value() == b.value(),
+        (Value::Integer(a), Value::Integer(b)) => a.value() == b.value(),
+        (Value::Float(a), Value::Float(b)) => {
+            a.value() == b.value() || (a.value().is_nan() && b.value().is_nan())
+        }
+        (Value::Boolean(a), Value::Boolean(b)) => a.value() == b.value(),
+        (Value::DateTime(a), Value::DateTime(b)) => a.value() == b.value(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_value(a, b))
+        }
+        (Value::InlineTable(a), Value::InlineTable(b)) => same_table(a, b),
+        _ => false,
+    }
+}
+
+// Positions of tables from another document are meaningless in this one.
+pub(crate) fn clear_positions(item: &mut Item) {
+    match *item {
+        Item::Table(ref mut t) => {
+            t.position = None;
+            for (_, kv) in t.items.iter_mut() {
+                clear_positions(&mut kv.value);
+            }
+        }
+        Item::ArrayOfTables(ref mut a) => {
+            for t in a.values.iter_mut() {
+                clear_positions(t);
+            }
+        }
+        _ => {}
+    }
+}
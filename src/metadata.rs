@@ -0,0 +1,330 @@
+use crate::array_of_tables::ArrayOfTables;
+use crate::document::Document;
+use crate::table::{Item, Table};
+use crate::value::{Array, InlineTable, Value};
+use std::collections::hash_map::{self, HashMap};
+use std::fmt::{Debug, Formatter, Result};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The identity of a value, a table or an array of tables, see `Item::node_id`.
+///
+/// Ids are assigned on first use and are unique within the process. A node keeps its id
+/// through edits which don't replace the node itself, e.g. changing its decor or
+/// its entries, or moving it to another place. Clones are new nodes with ids of their own,
+/// `Document::clone_keeping_ids` makes a copy sharing the ids of the original instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(u64);
+
+// the next id to assign, 0 marks nodes without an id
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+// The lazily assigned id of a node, ignored by comparisons and hashing of the node.
+#[derive(Default)]
+pub(crate) struct NodeIdCell(AtomicU64);
+
+impl NodeIdCell {
+    pub(crate) fn get(&self) -> NodeId {
+        let id = self.0.load(Ordering::Relaxed);
+        if id != 0 {
+            return NodeId(id);
+        }
+        let new = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        match self
+            .0
+            .compare_exchange(0, new, Ordering::Relaxed, Ordering::Relaxed)
+        {
+            Ok(_) => NodeId(new),
+            Err(id) => NodeId(id),
+        }
+    }
+
+    // gives the node the id of another one, assigning it first if needed
+    fn share(&self, other: &NodeIdCell) {
+        self.0.store(other.get().0, Ordering::Relaxed);
+    }
+}
+
+// clones are new nodes, their ids are assigned on first use
+impl Clone for NodeIdCell {
+    fn clone(&self) -> Self {
+        Default::default()
+    }
+}
+
+impl Debug for NodeIdCell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self.0.load(Ordering::Relaxed) {
+            0 => f.write_str("NodeId(_)"),
+            id => NodeId(id).fmt(f),
+        }
+    }
+}
+
+impl PartialEq for NodeIdCell {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for NodeIdCell {}
+
+impl Hash for NodeIdCell {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+/// User data attached to the nodes of documents, e.g. the source file or the layer
+/// a value came from, keyed by `NodeId`.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "parse")]
+/// # fn main() {
+/// use toml_edit::{Document, Metadata};
+///
+/// let mut doc = "[package]\nname = 'a'\n".parse::<Document>().unwrap();
+/// let mut origins = Metadata::new();
+/// origins.insert(doc["package"]["name"].node_id().unwrap(), "Cargo.toml:2");
+///
+/// // moving the value keeps its metadata
+/// let name = doc["package"].as_table_mut().unwrap().remove("name").unwrap();
+/// doc["name"] = name;
+/// assert_eq!(origins.get(doc["name"].node_id().unwrap()), Some(&"Cargo.toml:2"));
+///
+/// doc.as_table_mut().remove("name");
+/// origins.retain_nodes(&doc);
+/// assert!(origins.is_empty());
+/// # }
+/// # #[cfg(not(feature = "parse"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone)]
+pub struct Metadata<T> {
+    entries: HashMap<NodeId, T>,
+}
+
+impl<T> Default for Metadata<T> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Metadata<T> {
+    /// Creates an empty side-table.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Attaches data to the node, returning the data attached before.
+    pub fn insert(&mut self, id: NodeId, data: T) -> Option<T> {
+        self.entries.insert(id, data)
+    }
+
+    /// Returns the data attached to the node.
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.entries.get(&id)
+    }
+
+    /// Returns the mutable data attached to the node.
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut T> {
+        self.entries.get_mut(&id)
+    }
+
+    /// Detaches the data from the node.
+    pub fn remove(&mut self, id: NodeId) -> Option<T> {
+        self.entries.remove(&id)
+    }
+
+    /// Returns the number of nodes with data.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true iff no node has data.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the nodes and their data, in arbitrary order.
+    pub fn iter(&self) -> hash_map::Iter<'_, NodeId, T> {
+        self.entries.iter()
+    }
+
+    /// Drops the data of the nodes which are not in the document anymore.
+    pub fn retain_nodes(&mut self, doc: &Document) {
+        let mut ids = Vec::new();
+        table_ids(doc.as_table(), &mut ids);
+        let ids: std::collections::HashSet<NodeId> = ids.into_iter().collect();
+        self.entries.retain(|id, _| ids.contains(id));
+    }
+}
+
+fn table_ids(table: &Table, ids: &mut Vec<NodeId>) {
+    ids.push(table.node_id());
+    for (_, item) in table.iter() {
+        item_ids(item, ids);
+    }
+}
+
+fn item_ids(item: &Item, ids: &mut Vec<NodeId>) {
+    match *item {
+        Item::None => {}
+        Item::Value(ref v) => value_ids(v, ids),
+        Item::Table(ref t) => table_ids(t, ids),
+        Item::ArrayOfTables(ref a) => {
+            ids.push(a.node_id());
+            for t in a.iter() {
+                table_ids(t, ids);
+            }
+        }
+    }
+}
+
+fn value_ids(value: &Value, ids: &mut Vec<NodeId>) {
+    ids.push(value.node_id());
+    match *value {
+        Value::Array(ref a) => a.iter().for_each(|v| value_ids(v, ids)),
+        Value::InlineTable(ref t) => t.iter().for_each(|(_, v)| value_ids(v, ids)),
+        _ => {}
+    }
+}
+
+// gives the nodes of `copy`, a clone of `original`, the ids of the original nodes
+fn share_table_ids(original: &Table, copy: &Table) {
+    copy.id.share(&original.id);
+    for ((_, o), (_, c)) in original.iter().zip(copy.iter()) {
+        share_item_ids(o, c);
+    }
+}
+
+fn share_item_ids(original: &Item, copy: &Item) {
+    match (original, copy) {
+        (Item::Value(o), Item::Value(c)) => share_value_ids(o, c),
+        (Item::Table(o), Item::Table(c)) => share_table_ids(o, c),
+        (Item::ArrayOfTables(o), Item::ArrayOfTables(c)) => {
+            c.id.share(&o.id);
+            for (o, c) in o.iter().zip(c.iter()) {
+                share_table_ids(o, c);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn share_value_ids(original: &Value, copy: &Value) {
+    match (original, copy) {
+        (Value::Integer(o), Value::Integer(c)) => c.id.share(&o.id),
+        (Value::String(o), Value::String(c)) => c.id.share(&o.id),
+        (Value::Float(o), Value::Float(c)) => c.id.share(&o.id),
+        (Value::DateTime(o), Value::DateTime(c)) => c.id.share(&o.id),
+        (Value::Boolean(o), Value::Boolean(c)) => c.id.share(&o.id),
+        (Value::Array(o), Value::Array(c)) => {
+            c.id.share(&o.id);
+            for (o, c) in o.iter().zip(c.iter()) {
+                share_value_ids(o, c);
+            }
+        }
+        (Value::InlineTable(o), Value::InlineTable(c)) => {
+            c.id.share(&o.id);
+            for ((_, o), (_, c)) in o.iter().zip(c.iter()) {
+                share_value_ids(o, c);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl Item {
+    /// Returns a clone of the item whose nodes keep the ids of the original ones,
+    /// so that metadata attached to them applies to the copy, see `NodeId`.
+    pub fn clone_keeping_ids(&self) -> Item {
+        let copy = self.clone();
+        share_item_ids(self, &copy);
+        copy
+    }
+
+    /// Returns the id of the value, the table or the array of tables,
+    /// or `None` if the item is none.
+    pub fn node_id(&self) -> Option<NodeId> {
+        match *self {
+            Item::None => None,
+            Item::Value(ref v) => Some(v.node_id()),
+            Item::Table(ref t) => Some(t.node_id()),
+            Item::ArrayOfTables(ref a) => Some(a.node_id()),
+        }
+    }
+}
+
+impl Document {
+    /// Returns a clone of the document whose nodes keep the ids of the original ones,
+    /// e.g. to edit a copy which replaces the original afterwards
+    /// without losing the metadata attached to its nodes.
+    ///
+    /// The ids are then shared by two nodes, unlike with `clone`, so only one
+    /// of the documents should be kept.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let doc = "[package]\nname = 'a'\n".parse::<Document>().unwrap();
+    /// let id = doc["package"]["name"].node_id();
+    /// assert_eq!(doc.clone_keeping_ids()["package"]["name"].node_id(), id);
+    /// assert_ne!(doc.clone()["package"]["name"].node_id(), id);
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn clone_keeping_ids(&self) -> Document {
+        let copy = self.clone();
+        share_item_ids(&self.root, &copy.root);
+        copy
+    }
+}
+
+impl Value {
+    /// Returns the id of the value, see `NodeId`.
+    pub fn node_id(&self) -> NodeId {
+        match *self {
+            Value::Integer(ref f) => f.id.get(),
+            Value::String(ref f) => f.id.get(),
+            Value::Float(ref f) => f.id.get(),
+            Value::DateTime(ref f) => f.id.get(),
+            Value::Boolean(ref f) => f.id.get(),
+            Value::Array(ref a) => a.node_id(),
+            Value::InlineTable(ref t) => t.node_id(),
+        }
+    }
+}
+
+impl Array {
+    /// Returns the id of the array, see `NodeId`.
+    pub fn node_id(&self) -> NodeId {
+        self.id.get()
+    }
+}
+
+impl InlineTable {
+    /// Returns the id of the inline table, see `NodeId`.
+    pub fn node_id(&self) -> NodeId {
+        self.id.get()
+    }
+}
+
+impl Table {
+    /// Returns the id of the table, see `NodeId`.
+    pub fn node_id(&self) -> NodeId {
+        self.id.get()
+    }
+}
+
+impl ArrayOfTables {
+    /// Returns the id of the array of tables, see `NodeId`.
+    pub fn node_id(&self) -> NodeId {
+        self.id.get()
+    }
+}
@@ -0,0 +1,200 @@
+use crate::document::Document;
+use crate::formatted::{basic_string_value, decorate_inline_table, float_value, to_key_value};
+use crate::key::Key;
+use crate::table::{Item, TableLike};
+use crate::value::{InlineTable, Value};
+use std::fmt::{Debug, Formatter, Result};
+
+/// A read-only view of an item ignoring its representation.
+///
+/// Tables and inline tables are both `Node::Table`, arrays and arrays of tables
+/// are both `Node::Array`, so that analyses of documents need not tell them apart.
+///
+/// # Example
+/// ```rust
+/// # #[cfg(feature = "parse")]
+/// # fn main() {
+/// # use toml_edit::{Document, Node};
+/// let doc = r#"
+/// [[bin]]
+/// name = 'a'
+///
+/// [dependencies]
+/// serde = { version = "1.0" }
+/// log = "0.4"
+/// "#.parse::<Document>().unwrap();
+///
+/// fn strings(node: Node) -> usize {
+///     match node {
+///         Node::Scalar(v) => v.is_str() as usize,
+///         Node::Table(_) => node.entries().map(|(_, n)| strings(n)).sum(),
+///         Node::Array(_) => node.elements().map(strings).sum(),
+///     }
+/// }
+/// assert_eq!(strings(doc.as_node()), 3);
+/// assert_eq!(doc.as_node().get("bin").and_then(|n| n.at(0)).unwrap().len(), 1);
+/// assert_eq!(
+///     doc.to_value_tree().to_string(),
+///     r#"{ bin = [{ name = "a" }], dependencies = { serde = { version = "1.0" }, log = "0.4" } }"#
+/// );
+/// # }
+/// # #[cfg(not(feature = "parse"))]
+/// # fn main() {}
+/// ```
+#[derive(Clone, Copy)]
+pub enum Node<'a> {
+    /// A string, an integer, a float, a boolean or a datetime.
+    Scalar(&'a Value),
+    /// A table or an inline table.
+    Table(&'a dyn TableLike),
+    /// The elements of an array or an array of tables.
+    Array(&'a [Item]),
+}
+
+impl<'a> Node<'a> {
+    /// Returns the node of an item, or `None` if the item is none.
+    pub fn from_item(item: &'a Item) -> Option<Self> {
+        match *item {
+            Item::None => None,
+            Item::Value(ref v) => Some(Node::from_value(v)),
+            Item::Table(ref t) => Some(Node::Table(t)),
+            Item::ArrayOfTables(ref a) => Some(Node::Array(&a.values)),
+        }
+    }
+
+    /// Returns the node of a value.
+    pub fn from_value(value: &'a Value) -> Self {
+        match *value {
+            Value::Array(ref a) => Node::Array(&a.values),
+            Value::InlineTable(ref t) => Node::Table(t),
+            _ => Node::Scalar(value),
+        }
+    }
+
+    /// Returns true iff `self` is a scalar.
+    pub fn is_scalar(&self) -> bool {
+        matches!(*self, Node::Scalar(..))
+    }
+
+    /// Returns true iff `self` is a table.
+    pub fn is_table(&self) -> bool {
+        matches!(*self, Node::Table(..))
+    }
+
+    /// Returns true iff `self` is an array.
+    pub fn is_array(&self) -> bool {
+        matches!(*self, Node::Array(..))
+    }
+
+    /// Returns the value of a scalar, or `None` otherwise.
+    pub fn as_scalar(&self) -> Option<&'a Value> {
+        match *self {
+            Node::Scalar(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of entries of a table or elements of an array, 0 for scalars.
+    pub fn len(&self) -> usize {
+        match *self {
+            Node::Scalar(..) => 0,
+            Node::Table(t) => t.len(),
+            Node::Array(a) => a.len(),
+        }
+    }
+
+    /// Returns true iff `self` has no entries or elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the entry of a table with the given key.
+    pub fn get(&self, key: &str) -> Option<Node<'a>> {
+        match *self {
+            Node::Table(t) => t.get(key).and_then(Node::from_item),
+            _ => None,
+        }
+    }
+
+    /// Returns the element of an array with the given index.
+    pub fn at(&self, index: usize) -> Option<Node<'a>> {
+        match *self {
+            Node::Array(a) => a.get(index).and_then(Node::from_item),
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the entries of a table, which is empty for other nodes.
+    pub fn entries(&self) -> Box<dyn Iterator<Item = (&'a str, Node<'a>)> + 'a> {
+        match *self {
+            Node::Table(t) => Box::new(
+                t.iter()
+                    .filter_map(|(k, item)| Node::from_item(item).map(|n| (k, n))),
+            ),
+            _ => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Returns an iterator over the elements of an array, which is empty for other nodes.
+    pub fn elements(&self) -> Box<dyn Iterator<Item = Node<'a>> + 'a> {
+        match *self {
+            Node::Array(a) => Box::new(a.iter().filter_map(Node::from_item)),
+            _ => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Converts the node into a value in the default format,
+    /// dropping the decors and the raw representations of the scalars.
+    pub fn to_value(&self) -> Value {
+        match *self {
+            Node::Scalar(v) => match *v {
+                Value::Integer(ref f) => Value::from(*f.value()),
+                Value::String(ref f) => basic_string_value(f.value()),
+                Value::Float(ref f) => float_value(*f.value()),
+                Value::DateTime(ref f) => Value::from(f.value().clone()),
+                Value::Boolean(ref f) => Value::from(*f.value()),
+                Value::Array(..) | Value::InlineTable(..) => Node::from_value(v).to_value(),
+            },
+            Node::Table(..) => {
+                let mut table = InlineTable::default();
+                for (key, node) in self.entries() {
+                    let kv = to_key_value(Key::from_unquoted(key).raw(), node.to_value());
+                    table.items.insert(key.into(), kv);
+                }
+                decorate_inline_table(&mut table);
+                Value::InlineTable(table)
+            }
+            Node::Array(..) => self.elements().map(|n| n.to_value()).collect(),
+        }
+    }
+}
+
+impl Debug for Node<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match *self {
+            Node::Scalar(v) => f.debug_tuple("Scalar").field(v).finish(),
+            Node::Table(..) => f.debug_map().entries(self.entries()).finish(),
+            Node::Array(..) => f.debug_list().entries(self.elements()).finish(),
+        }
+    }
+}
+
+impl Item {
+    /// Returns the node view of the item, or `None` if the item is none.
+    pub fn as_node(&self) -> Option<Node<'_>> {
+        Node::from_item(self)
+    }
+}
+
+impl Document {
+    /// Returns the node view of the root table.
+    pub fn as_node(&self) -> Node<'_> {
+        Node::Table(self.as_table())
+    }
+
+    /// Converts the document into an inline table value in the default format,
+    /// see `Node::to_value`.
+    pub fn to_value_tree(&self) -> Value {
+        self.as_node().to_value()
+    }
+}
@@ -1,8 +1,11 @@
 use crate::decor::InternalString;
+use crate::display::MAX_DEPTH;
 use crate::formatted::decorated;
 use crate::parser::errors::CustomError;
+use crate::parser::options::{ParseOptions, TomlVersion};
 use crate::parser::trivia::ws_comment_newline;
-use crate::parser::value::value;
+use crate::parser::value::nested_value;
+use crate::table::Item;
 use crate::value::{Array, Value};
 use combine::parser::char::char;
 use combine::parser::range::recognize_with_value;
@@ -12,17 +15,35 @@ use combine::*;
 // ;; Array
 
 // array = array-open array-values array-close
-parse!(array() -> Array, {
-    between(char(ARRAY_OPEN), char(ARRAY_CLOSE),
-            array_values().and_then(|(v, c, t)| array_from_vec(v, c, t)))
+// note: arrays and inline tables are nested at most `MAX_DEPTH` levels deep
+parse!(array(options: ParseOptions, depth: usize) -> Array, {
+    let depth = *depth;
+    let open = char(ARRAY_OPEN).and_then(move |c| match depth < MAX_DEPTH {
+        true => Ok(c),
+        false => Err(CustomError::NestedTooDeeply),
+    });
+    between(open, char(ARRAY_CLOSE),
+            array_values(*options, depth + 1).and_then(move |(v, c, t)| {
+                let mixed = options.version >= TomlVersion::V1_0;
+                array_from_vec(v, c, t, mixed)
+            }))
 });
 
-fn array_from_vec(v: Vec<Value>, comma: bool, trailing: &str) -> Result<Array, CustomError> {
+fn array_from_vec(
+    v: Vec<Value>,
+    comma: bool,
+    trailing: &str,
+    mixed: bool,
+) -> Result<Array, CustomError> {
     let mut array = Array {
         trailing_comma: comma,
         trailing: InternalString::from(trailing),
         ..Default::default()
     };
+    if mixed {
+        array.values.extend(v.into_iter().map(Item::Value));
+        return Ok(array);
+    }
     for val in v {
         let err = Err(CustomError::MixedArrayType {
             got: format!("{:?}", val.get_type()),
@@ -47,11 +68,11 @@ const ARRAY_SEP: char = ',';
 // note: this rule is modified
 // array-values = [ ( array-value array-sep array-values ) /
 //                  array-value / ws-comment-newline ]
-parse!(array_values() -> (Vec<Value>, bool, &'a str), {
+parse!(array_values(options: ParseOptions, depth: usize) -> (Vec<Value>, bool, &'a str), {
     (
         optional(
             recognize_with_value(
-                sep_end_by1(array_value(), char(ARRAY_SEP))
+                sep_end_by1(array_value(*options, *depth), char(ARRAY_SEP))
             ).map(|(r, v): (&'a str, _)| (v, r.ends_with(',')))
         ),
         ws_comment_newline(),
@@ -61,10 +82,11 @@ parse!(array_values() -> (Vec<Value>, bool, &'a str), {
     })
 });
 
-parse!(array_value() -> Value, {
-    attempt((
-        ws_comment_newline(),
-        value(),
+parse!(array_value(options: ParseOptions, depth: usize) -> Value, {
+    (
+        // backtracks from the decor preceding the array close
+        attempt(ws_comment_newline().skip(look_ahead(satisfy(|c| c != ARRAY_CLOSE)))),
+        nested_value(*options, *depth),
         ws_comment_newline(),
-    )).map(|(ws1, v, ws2)| decorated(v, ws1, ws2))
+    ).map(|(ws1, v, ws2)| decorated(v, ws1, ws2))
 });
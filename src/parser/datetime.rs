@@ -18,6 +18,7 @@ pub fn repeat<I: Stream, P: Parser<I>>(count: usize, parser: P) -> SkipCountMinM
 // local-date = full-date
 // local-time = partial-time
 // full-time = partial-time time-offset
+#[cfg(feature = "datetime")]
 parse!(date_time() -> value::DateTime, {
     choice!(
         recognize_with_value((
@@ -57,6 +58,33 @@ parse!(date_time() -> value::DateTime, {
         .message("While parsing a Date-Time")
 });
 
+// Without the `datetime` feature, date-times are kept as they are written.
+#[cfg(not(feature = "datetime"))]
+parse!(date_time() -> value::DateTime, {
+    choice!(
+        recognize_with_value((
+            full_date(),
+            optional((
+                char('T'),
+                partial_time(),
+                optional(time_offset()),
+            ))
+        ))
+            .map(|(s, (_, opt)): (&str, _)| {
+                let s = s.to_owned();
+                match opt {
+                    Some((_, _, Some(_))) => value::DateTime::OffsetDateTime(s),
+                    Some(_) => value::DateTime::LocalDateTime(s),
+                    None => value::DateTime::LocalDate(s),
+                }
+            }),
+        recognize(partial_time())
+            .message("While parsing a Time")
+            .map(|s: &str| value::DateTime::LocalTime(s.to_owned()))
+    )
+        .message("While parsing a Date-Time")
+});
+
 // full-date      = date-fullyear "-" date-month "-" date-mday
 // date-fullyear  = 4DIGIT
 // date-month     = 2DIGIT  ; 01-12
@@ -1,17 +1,20 @@
-use crate::decor::{InternalString, Repr};
+use crate::decor::Repr;
+use crate::display::MAX_DEPTH;
 use crate::document::Document;
 use crate::formatted::decorated;
-use crate::parser::errors::CustomError;
+use crate::key::Key;
+use crate::parser::errors::{CustomError, DEFAULT_ERROR_CONTEXT};
 use crate::parser::inline_table::KEYVAL_SEP;
-use crate::parser::key::key;
-use crate::parser::table::table;
+use crate::parser::key::dotted_key;
+use crate::parser::options::ParseOptions;
+use crate::parser::table::{duplicate_key, header_name, table};
 use crate::parser::trivia::{comment, line_ending, line_trailing, newline, ws};
 use crate::parser::value::value;
-use crate::parser::{TomlError, TomlParser};
-use crate::table::{Item, TableKeyValue};
+use crate::parser::{TableConflict, TomlError, TomlParser};
+use crate::table::{Item, Table, TableKeyValue};
 use combine::parser::char::char;
 use combine::parser::range::recognize;
-use combine::stream::position::Stream;
+use combine::stream::position::{SourcePosition, Stream};
 use combine::stream::RangeStream;
 use combine::Parser;
 use combine::*;
@@ -34,12 +37,14 @@ toml_parser!(parse_newline, parser, {
 });
 
 toml_parser!(keyval, parser, {
-    parse_keyval().and_then(|(k, kv)| parser.borrow_mut().deref_mut().on_keyval(k, kv))
+    let options = parser.borrow().options;
+    parse_keyval(options)
+        .and_then(|(p, k, kv)| parser.borrow_mut().deref_mut().on_keyval(&p, k, kv))
 });
 
 // keyval = key keyval-sep val
 parser! {
-    fn parse_keyval['a, I]()(I) -> (InternalString, TableKeyValue)
+    fn parse_keyval['a, I](options: ParseOptions)(I) -> (Vec<Key>, String, TableKeyValue)
     where
         [I: RangeStream<
          Range = &'a str,
@@ -48,18 +53,20 @@ parser! {
          <I::Error as ParseError<char, &'a str, <I as StreamOnce>::Position>>::StreamError:
          From<std::num::ParseIntError> +
          From<std::num::ParseFloatError> +
-         From<chrono::ParseError> +
+         From<crate::parser::errors::DatetimeError> +
          From<crate::parser::errors::CustomError>
     ] {
         (
-            (key(), ws()),
+            (dotted_key(), ws()),
             char(KEYVAL_SEP),
-            (ws(), value(), line_trailing())
+            (ws(), value(*options), line_trailing())
         ).map(|(k, _, v)| {
             let (pre, v, suf) = v;
             let v = decorated(v, pre, suf);
-            let ((raw, key), suf) = k;
+            let (mut path, suf) = k;
+            let (raw, key) = path.pop().expect("at least one key; qed");
             (
+                path.into_iter().map(|(raw, key)| Key::new(raw, key)).collect(),
                 key,
                 TableKeyValue {
                     key: Repr::new("", raw, suf),
@@ -79,8 +86,19 @@ impl TomlParser {
     //                ( ws keyval ws [ comment ] ) /
     //                ( ws table ws [ comment ] ) /
     //                  ws )
-    pub fn parse(s: &str) -> Result<Document, TomlError> {
-        let parser = RefCell::new(Self::default());
+    pub fn parse(s: &str, options: &ParseOptions) -> Result<Document, TomlError> {
+        Self::parse_with_conflicts(s, options).map(|(doc, _)| doc)
+    }
+
+    // parses the document, returning the inline tables merged into headers
+    pub fn parse_with_conflicts(
+        s: &str,
+        options: &ParseOptions,
+    ) -> Result<(Document, Vec<TableConflict>), TomlError> {
+        let parser = RefCell::new(Self {
+            options: *options,
+            ..Default::default()
+        });
         let input = Stream::new(s);
 
         let parsed = parse_ws(&parser)
@@ -89,20 +107,76 @@ impl TomlParser {
                 skip_many1(
                     choice((
                         parse_comment(&parser),
-                        keyval(&parser),
-                        table(&parser),
+                        keyval(&parser)
+                            .with(position())
+                            .map(|p| parser.borrow_mut().on_expression_end(p)),
+                        (position(), table(&parser), position()).map(|(start, _, end)| {
+                            let mut parser = parser.borrow_mut();
+                            parser.on_header_span(start, end);
+                            parser.on_expression_end(end)
+                        }),
                         parse_newline(&parser),
                     ))
                     .skip(parse_ws(&parser)),
                 ),
             )))
             .easy_parse(input);
+        let context = options.error_context.unwrap_or(DEFAULT_ERROR_CONTEXT);
         match parsed {
-            Ok((_, ref rest)) if !rest.input.is_empty() => {
-                Err(TomlError::from_unparsed(rest.positioner, s))
+            Ok((_, ref rest)) if !rest.input.is_empty() => Err(
+                TomlError::from_unparsed_with_context(rest.positioner, s, context),
+            ),
+            Ok(..) => {
+                let mut parser = parser.into_inner();
+                let conflicts = mem::take(&mut parser.conflicts)
+                    .into_iter()
+                    .map(|(path, start, end)| {
+                        let span = offset(s, start)..offset(s, end);
+                        let header = s[span.clone()].trim_end();
+                        TableConflict::new(path, span.start..span.start + header.len(), start)
+                    })
+                    .collect();
+                let mut document = parser.into_document(s);
+                document.crlf = s.matches("\r\n").count() * 2 > s.matches('\n').count();
+                Ok((document, conflicts))
             }
-            Ok(..) => Ok(*parser.into_inner().document),
-            Err(e) => Err(TomlError::new(e, s)),
+            Err(e) => Err(TomlError::with_context(e, s, context)),
+        }
+    }
+
+    #[cfg(not(feature = "verbatim"))]
+    fn on_expression_end(&mut self, _end: SourcePosition) {}
+
+    #[cfg(not(feature = "verbatim"))]
+    fn into_document(self, _source: &str) -> Document {
+        *self.document
+    }
+
+    // records the end of the last key/value pair or header of the current table
+    #[cfg(feature = "verbatim")]
+    fn on_expression_end(&mut self, end: SourcePosition) {
+        let position = self.current_table_position;
+        if self.expression_ends.len() <= position {
+            self.expression_ends.resize(position + 1, None);
+        }
+        self.expression_ends[position] = Some(end);
+    }
+
+    #[cfg(feature = "verbatim")]
+    fn into_document(self, source: &str) -> Document {
+        let mut document = *self.document;
+        document.source = Some(Box::new(crate::verbatim::Source::new(
+            source,
+            &self.expression_ends,
+            &document,
+        )));
+        document
+    }
+
+    // records the span of the header into which inline tables were just merged
+    fn on_header_span(&mut self, start: SourcePosition, end: SourcePosition) {
+        for path in self.merged.drain(..) {
+            self.conflicts.push((path, start, end));
         }
     }
 
@@ -115,25 +189,70 @@ impl TomlParser {
         self.document.trailing.push_str(e);
     }
 
-    fn on_keyval(&mut self, key: InternalString, mut kv: TableKeyValue) -> Result<(), CustomError> {
-        let prefix = mem::take(&mut self.document.trailing);
-        kv.key.decor.prefix = prefix + &kv.key.decor.prefix;
+    fn on_keyval(
+        &mut self,
+        path: &[Key],
+        key: String,
+        mut kv: TableKeyValue,
+    ) -> Result<(), CustomError> {
+        if self.current_table_path.len() + path.len() > MAX_DEPTH {
+            return Err(CustomError::NestedTooDeeply);
+        }
+        let prefix = String::from(mem::take(&mut self.document.trailing));
+        kv.key.decor.prefix = (prefix + &kv.key.decor.prefix).into();
 
         let root = self.document.as_table_mut();
         let table = Self::descend_path(root, self.current_table_path.as_slice(), 0)
             .expect("the table path is valid; qed");
+        let current = &self.current_table_path;
+        let table = Self::descend_dotted(table, path).map_err(|i| {
+            let path: Vec<Key> = current.iter().chain(path).cloned().collect();
+            duplicate_key(&path, current.len() + i)
+        })?;
         if table.contains_key(&key) {
+            let path: Vec<Key> = current.iter().chain(path).cloned().collect();
             Err(CustomError::DuplicateKey {
                 key,
-                table: "<unknown>".into(), // TODO: get actual table name
+                table: header_name(&path),
             })
         } else {
             let tkv = TableKeyValue {
                 key: kv.key,
                 value: kv.value,
             };
-            table.items.insert(key, tkv);
+            table.items.insert(key.into(), tkv);
             Ok(())
         }
     }
+
+    // descends into the tables defined by the dotted key `path`, creating them if needed,
+    // or returns the index of the key in `path` which is defined as something else
+    fn descend_dotted<'t>(table: &'t mut Table, path: &[Key]) -> Result<&'t mut Table, usize> {
+        let (key, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return Ok(table),
+        };
+        let kv = table.items.entry(key.get().into()).or_insert_with(|| {
+            let mut dotted = Table::with_pos(None);
+            dotted.dotted = true;
+            TableKeyValue::new(Repr::new("", key.raw(), ""), Item::Table(dotted))
+        });
+        match kv.value {
+            Item::Table(ref mut t) if t.dotted => Self::descend_dotted(t, rest).map_err(|i| i + 1),
+            _ => Err(0),
+        }
+    }
+}
+
+// the byte offset of a position in `s`
+fn offset(s: &str, pos: SourcePosition) -> usize {
+    let start: usize = s
+        .split_inclusive('\n')
+        .take(pos.line as usize - 1)
+        .map(str::len)
+        .sum();
+    s[start..]
+        .char_indices()
+        .nth(pos.column as usize - 1)
+        .map_or(s.len(), |(i, _)| start + i)
 }
@@ -1,26 +1,192 @@
+#[cfg(feature = "parse")]
+use crate::key_path::KeyPath;
+#[cfg(feature = "parse")]
 use combine::easy::Errors as ParseError;
-use combine::stream::easy::Error;
+#[cfg(feature = "parse")]
+use combine::stream::easy::{Error, Info};
+#[cfg(feature = "parse")]
 use combine::stream::position::SourcePosition;
 use std::error::Error as StdError;
 use std::fmt::{Display, Formatter, Result};
+use std::ops::Range;
 
 /// Type representing a TOML parse error
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct TomlError {
     message: String,
+    line: usize,
+    column: usize,
+    notes: Vec<String>,
+    // the characters of the line shown in the message
+    excerpt: Range<usize>,
 }
 
+// The number of characters shown on each side of the error in the excerpt of its line
+// by default, see `ParseOptions::error_context`.
+pub(crate) const DEFAULT_ERROR_CONTEXT: usize = 60;
+
 impl TomlError {
+    #[cfg(feature = "parse")]
     pub(crate) fn new(error: ParseError<char, &str, SourcePosition>, input: &str) -> Self {
+        Self::with_context(error, input, DEFAULT_ERROR_CONTEXT)
+    }
+
+    #[cfg(feature = "parse")]
+    pub(crate) fn with_context(
+        error: ParseError<char, &str, SourcePosition>,
+        input: &str,
+        context: usize,
+    ) -> Self {
+        let SourcePosition { line, column } = error.position;
+        let notes = Notes(&error)
+            .to_string()
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(str::to_owned)
+            .collect();
+        let content = input.split('\n').nth((line - 1) as usize).expect("line");
+        let excerpt = excerpt(content, column as usize, context);
         Self {
-            message: format!("{}", FancyError::new(error, input)),
+            message: format!("{}", FancyError::new(error, input, excerpt.clone())),
+            line: line as usize,
+            column: column as usize,
+            notes,
+            excerpt,
+        }
+    }
+
+    // Creates an error at a byte offset of the input from its notes, see `lexer`.
+    pub(crate) fn from_notes(input: &str, offset: usize, notes: Vec<String>) -> Self {
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+        let content = input.split('\n').nth(line - 1).expect("line");
+        let excerpt = excerpt(content, column, DEFAULT_ERROR_CONTEXT);
+        let mut message = Position {
+            input,
+            line,
+            column,
+            excerpt: excerpt.clone(),
+        }
+        .to_string();
+        for note in &notes {
+            message.push_str(note);
+            message.push('\n');
+        }
+        Self {
+            message,
+            line,
+            column,
+            notes,
+            excerpt,
+        }
+    }
+
+    /// Returns the line and the column of the error, both starting at 1.
+    pub fn line_col(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+
+    /// Returns the columns, starting at 1, of the excerpt of the line shown with the error.
+    ///
+    /// Lines longer than twice the context around the error, e.g. of minified documents,
+    /// are cut to the context on each side of the error, see `ParseOptions::error_context`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::{Document, ParseOptions};
+    ///
+    /// let source = format!("a = [{}x, {}1]", "1, ".repeat(50), "2, ".repeat(50));
+    /// let options = ParseOptions::new().error_context(10);
+    /// let err = Document::parse_with(&source, &options).unwrap_err();
+    /// assert_eq!(err.line_col(), (1, 156));
+    /// assert_eq!(err.excerpt(), 146..167);
+    /// assert!(err.to_string().contains("\n1 | ... 1, 1, 1, x, 2, 2, 2,...\n  |              ^\n"));
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn excerpt(&self) -> Range<usize> {
+        self.excerpt.start + 1..self.excerpt.end + 1
+    }
+
+    /// Renders the error with an excerpt of `source`, the parsed input,
+    /// and `origin`, e.g. the path of the parsed file, if given.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::Document;
+    ///
+    /// let source = "[package]\nname = 'toml_edit\n";
+    /// let err = source.parse::<Document>().unwrap_err();
+    /// assert_eq!(err.render(source, Some("Cargo.toml")), r#"error: TOML parse error
+    ///  --> Cargo.toml:2:18
+    ///   |
+    /// 2 | name = 'toml_edit
+    ///   |                  ^
+    ///   = Unexpected `\n`
+    ///   = Expected `'`
+    ///   = While parsing a Literal String
+    /// "#);
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn render(&self, source: &str, origin: Option<&str>) -> String {
+        let gutter = self.line.to_string().len();
+        let pad = " ".repeat(gutter);
+        let mut rendered = String::from("error: TOML parse error\n");
+        let location = format!("{}:{}", self.line, self.column);
+        match origin {
+            Some(origin) => rendered += &format!("{}--> {}:{}\n", pad, origin, location),
+            None => rendered += &format!("{}--> {}\n", pad, location),
         }
+        if let Some(content) = source.lines().nth(self.line.saturating_sub(1)) {
+            let (content, indent) = cut(content, self.column, self.excerpt.clone());
+            rendered += &format!("{} |\n", pad);
+            rendered += &format!("{} | {}\n", self.line, content);
+            rendered += &format!("{} | {}^\n", pad, indent);
+        }
+        for note in &self.notes {
+            rendered += &format!("{} = {}\n", pad, note);
+        }
+        rendered
     }
 
+    #[cfg(feature = "parse")]
     pub(crate) fn from_unparsed(pos: SourcePosition, input: &str) -> Self {
-        Self::new(
+        Self::from_unparsed_with_context(pos, input, DEFAULT_ERROR_CONTEXT)
+    }
+
+    #[cfg(feature = "parse")]
+    pub(crate) fn from_unparsed_with_context(
+        pos: SourcePosition,
+        input: &str,
+        context: usize,
+    ) -> Self {
+        Self::with_context(
             ParseError::new(pos, CustomError::UnparsedLine.into()),
             input,
+            context,
+        )
+    }
+
+    #[cfg(feature = "parse")]
+    pub(crate) fn unexpected_type(got: String, expected: &str, input: &str) -> Self {
+        let position = SourcePosition { line: 1, column: 1 };
+        let expected = expected.to_owned();
+        Self::new(
+            ParseError::new(
+                position,
+                CustomError::UnexpectedType { got, expected }.into(),
+            ),
+            input,
         )
     }
 }
@@ -49,28 +215,203 @@ impl StdError for TomlError {
     }
 }
 
+#[cfg(feature = "parse")]
+/// An inline table extended by a table header, merged when parsing with
+/// `TableConflicts::Merge`, see `Document::parse_with_conflicts`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TableConflict {
+    path: KeyPath,
+    span: Range<usize>,
+    line: usize,
+    column: usize,
+}
+
+#[cfg(feature = "parse")]
+impl TableConflict {
+    pub(crate) fn new(path: KeyPath, span: Range<usize>, start: SourcePosition) -> Self {
+        Self {
+            path,
+            span,
+            line: start.line as usize,
+            column: start.column as usize,
+        }
+    }
+
+    /// Returns the path of the inline table, now a standard table.
+    pub fn path(&self) -> &KeyPath {
+        &self.path
+    }
+
+    /// Returns the byte range of the header extending the inline table in the parsed input,
+    /// including its trailing comment and excluding the line ending.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the line and the column of the header, both starting at 1.
+    pub fn line_col(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+}
+
+#[cfg(feature = "parse")]
+impl Display for TableConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "the inline table `{}` is extended by the table header at line {}, column {}",
+            self.path, self.line, self.column
+        )
+    }
+}
+
+#[cfg(feature = "parse")]
+// Formats the errors like `Error::fmt_errors`, escaping control characters
+// for the notes to span a single line each.
+struct Notes<'e, 'a>(&'e ParseError<char, &'a str, SourcePosition>);
+
+#[cfg(feature = "parse")]
+impl<'e, 'a> Display for Notes<'e, 'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let errors = &self.0.errors;
+        for error in errors {
+            if let Error::Unexpected(ref info) = *error {
+                writeln!(f, "Unexpected `{}`", escape(info))?;
+            }
+        }
+        let expected = errors
+            .iter()
+            .filter_map(|e| match *e {
+                Error::Expected(ref info) => Some(format!("`{}`", escape(info))),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if let Some((last, rest)) = expected.split_last() {
+            if rest.is_empty() {
+                writeln!(f, "Expected {}", last)?;
+            } else {
+                writeln!(f, "Expected {} or {}", rest.join(", "), last)?;
+            }
+        }
+        for error in errors {
+            if let Error::Message(..) | Error::Other(..) = *error {
+                writeln!(f, "{}", error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "parse")]
+fn escape(info: &Info<char, &str>) -> String {
+    let s = match *info {
+        Info::Token(c) => c.to_string(),
+        Info::Range(r) => r.to_owned(),
+        Info::Owned(ref s) => s.clone(),
+        Info::Static(s) => s.to_owned(),
+    };
+    s.chars()
+        .map(|c| {
+            if c.is_control() {
+                c.escape_default().to_string()
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+// The characters of `line` shown around the error at `column`, at most `context`
+// on each side, or as many as fit in the same width when the error is near an end.
+fn excerpt(line: &str, column: usize, context: usize) -> Range<usize> {
+    let len = line.chars().count();
+    let width = context.saturating_mul(2).saturating_add(1);
+    if len <= width {
+        return 0..len;
+    }
+    let start = column
+        .saturating_sub(1)
+        .saturating_sub(context)
+        .min(len - width);
+    start..start + width
+}
+
+// Cuts `line` to the excerpt, marking the cut ends with `...`, and returns it
+// with the indentation of the caret at `column`. Tabs are kept for the caret to be aligned.
+fn cut(line: &str, column: usize, excerpt: Range<usize>) -> (String, String) {
+    let len = line.chars().count();
+    let (start, end) = (excerpt.start.min(len), excerpt.end.min(len));
+    let mut content = String::new();
+    let mut indent = String::new();
+    if start > 0 {
+        content.push_str("...");
+        indent.push_str("   ");
+    }
+    content.extend(line.chars().skip(start).take(end - start));
+    if end < len {
+        content.push_str("...");
+    }
+    indent.extend(
+        line.chars()
+            .skip(start)
+            .take(column.saturating_sub(1).saturating_sub(start))
+            .map(|c| if c == '\t' { '\t' } else { ' ' }),
+    );
+    (content, indent)
+}
+
+#[cfg(feature = "parse")]
 #[derive(Debug)]
 pub(crate) struct FancyError<'a> {
     error: ParseError<char, &'a str, SourcePosition>,
     input: &'a str,
+    excerpt: Range<usize>,
 }
 
+#[cfg(feature = "parse")]
 impl<'a> FancyError<'a> {
-    pub(crate) fn new(error: ParseError<char, &'a str, SourcePosition>, input: &'a str) -> Self {
-        Self { error, input }
+    pub(crate) fn new(
+        error: ParseError<char, &'a str, SourcePosition>,
+        input: &'a str,
+        excerpt: Range<usize>,
+    ) -> Self {
+        Self {
+            error,
+            input,
+            excerpt,
+        }
     }
 }
 
+#[cfg(feature = "parse")]
 impl<'a> Display for FancyError<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         let SourcePosition { line, column } = self.error.position;
+        let position = Position {
+            input: self.input,
+            line: line as usize,
+            column: column as usize,
+            excerpt: self.excerpt.clone(),
+        };
+        write!(f, "{}", position)?;
+        Error::fmt_errors(self.error.errors.as_ref(), f)
+    }
+}
+
+// The header of the message of an error, with the excerpt of its line.
+struct Position<'a> {
+    input: &'a str,
+    line: usize,
+    column: usize,
+    excerpt: Range<usize>,
+}
+
+impl<'a> Display for Position<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let Position { line, column, .. } = *self;
 
         let offset = line.to_string().len();
-        let content = self
-            .input
-            .split('\n')
-            .nth((line - 1) as usize)
-            .expect("line");
+        let content = self.input.split('\n').nth(line - 1).expect("line");
 
         writeln!(f, "TOML parse error at line {}, column {}", line, column)?;
 
@@ -81,6 +422,7 @@ impl<'a> Display for FancyError<'a> {
         writeln!(f, "|")?;
 
         // 1 | 00:32:00.a999999
+        let (content, indent) = cut(content, column, self.excerpt.clone());
         write!(f, "{} | ", line)?;
         writeln!(f, "{}", content)?;
 
@@ -89,29 +431,39 @@ impl<'a> Display for FancyError<'a> {
             write!(f, " ")?;
         }
         write!(f, "|")?;
-        for _ in 0..column {
+        for _ in 0..=indent.len() {
             write!(f, " ")?;
         }
-        writeln!(f, "^")?;
-
-        Error::fmt_errors(self.error.errors.as_ref(), f)
+        writeln!(f, "^")
     }
 }
 
+// The error of parsing a date-time, which can't fail without the `datetime` feature.
+#[cfg(all(feature = "parse", feature = "datetime"))]
+pub type DatetimeError = chrono::ParseError;
+#[cfg(all(feature = "parse", not(feature = "datetime")))]
+pub type DatetimeError = CustomError;
+
+#[cfg(feature = "parse")]
 #[derive(Debug, Clone)]
 pub enum CustomError {
     MixedArrayType { got: String, expected: String },
     DuplicateKey { key: String, table: String },
     InvalidHexEscape(u32),
+    OutOfRange(String),
+    UnexpectedType { got: String, expected: String },
     UnparsedLine,
+    NestedTooDeeply,
 }
 
+#[cfg(feature = "parse")]
 impl StdError for CustomError {
     fn description(&self) -> &'static str {
         "TOML parse error"
     }
 }
 
+#[cfg(feature = "parse")]
 impl Display for CustomError {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match *self {
@@ -127,7 +479,23 @@ impl Display for CustomError {
             CustomError::InvalidHexEscape(ref h) => {
                 writeln!(f, "Invalid hex escape code: {:x} ", h)
             }
+            CustomError::OutOfRange(ref ty) => {
+                writeln!(f, "{} literal is out of range", ty)
+            }
+            CustomError::UnexpectedType {
+                ref got,
+                ref expected,
+            } => {
+                writeln!(f, "Unexpected {} value, expected {}", got, expected)
+            }
             CustomError::UnparsedLine => writeln!(f, "Could not parse the line"),
+            CustomError::NestedTooDeeply => {
+                writeln!(
+                    f,
+                    "Nested more than {} levels deep",
+                    crate::display::MAX_DEPTH
+                )
+            }
         }
     }
 }
@@ -1,9 +1,11 @@
 use crate::decor::{InternalString, Repr};
+use crate::display::MAX_DEPTH;
 use crate::formatted::decorated;
 use crate::parser::errors::CustomError;
 use crate::parser::key::key;
+use crate::parser::options::ParseOptions;
 use crate::parser::trivia::ws;
-use crate::parser::value::value;
+use crate::parser::value::nested_value;
 use crate::table::{Item, TableKeyValue};
 use crate::value::InlineTable;
 use combine::parser::char::char;
@@ -13,14 +15,20 @@ use combine::*;
 // ;; Inline Table
 
 // inline-table = inline-table-open inline-table-keyvals inline-table-close
-parse!(inline_table() -> InlineTable, {
-    between(char(INLINE_TABLE_OPEN), char(INLINE_TABLE_CLOSE),
-            inline_table_keyvals().and_then(|(p, v)| table_from_pairs(p, v)))
+// note: arrays and inline tables are nested at most `MAX_DEPTH` levels deep
+parse!(inline_table(options: ParseOptions, depth: usize) -> InlineTable, {
+    let depth = *depth;
+    let open = char(INLINE_TABLE_OPEN).and_then(move |c| match depth < MAX_DEPTH {
+        true => Ok(c),
+        false => Err(CustomError::NestedTooDeeply),
+    });
+    between(open, char(INLINE_TABLE_CLOSE),
+            inline_table_keyvals(*options, depth + 1).and_then(|(p, v)| table_from_pairs(p, v)))
 });
 
 fn table_from_pairs(
     preamble: &str,
-    v: Vec<(InternalString, TableKeyValue)>,
+    v: Vec<(String, TableKeyValue)>,
 ) -> Result<InlineTable, CustomError> {
     let mut table = InlineTable {
         preamble: InternalString::from(preamble),
@@ -34,7 +42,7 @@ fn table_from_pairs(
                 table: "inline".into(),
             });
         }
-        table.items.insert(k, kv);
+        table.items.insert(k.into(), kv);
     }
     Ok(table)
 }
@@ -53,20 +61,20 @@ pub(crate) const KEYVAL_SEP: char = '=';
 // ( key keyval-sep val inline-table-sep inline-table-keyvals-non-empty ) /
 // ( key keyval-sep val )
 
-parse!(inline_table_keyvals() -> (&'a str, Vec<(InternalString, TableKeyValue)>), {
+parse!(inline_table_keyvals(options: ParseOptions, depth: usize) -> (&'a str, Vec<(String, TableKeyValue)>), {
     (
-        sep_by(keyval(), char(INLINE_TABLE_SEP)),
+        sep_by(keyval(*options, *depth), char(INLINE_TABLE_SEP)),
         ws(),
     ).map(|(v, w)| {
         (w, v)
     })
 });
 
-parse!(keyval() -> (InternalString, TableKeyValue), {
+parse!(keyval(options: ParseOptions, depth: usize) -> (String, TableKeyValue), {
     (
         attempt((ws(), key(), ws())),
         char(KEYVAL_SEP),
-        (ws(), value(), ws()),
+        (ws(), nested_value(*options, *depth), ws()),
     ).map(|(k, _, v)| {
         let (pre, v, suf) = v;
         let v = decorated(v, pre, suf);
@@ -1,24 +1,27 @@
-use crate::decor::InternalString;
+use crate::parser::lexer::is_unquoted_char;
 use crate::parser::strings::{basic_string, literal_string};
+use crate::parser::trivia::ws;
+use combine::parser::char::char;
 use combine::parser::range::{recognize_with_value, take_while1};
 use combine::stream::RangeStream;
 use combine::*;
 
-#[inline]
-fn is_unquoted_char(c: char) -> bool {
-    matches!(c, 'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '_')
-}
-
 // unquoted-key = 1*( ALPHA / DIGIT / %x2D / %x5F ) ; A-Z / a-z / 0-9 / - / _
 parse!(unquoted_key() -> &'a str, {
     take_while1(is_unquoted_char)
 });
 
 // key = unquoted-key / basic-string / literal-string
-parse!(key() -> (&'a str, InternalString), {
+parse!(key() -> (&'a str, String), {
     recognize_with_value(choice((
         basic_string(),
         literal_string().map(|s: &'a str| s.into()),
         unquoted_key().map(|s: &'a str| s.into()),
     )))
 });
+
+// dotted-key = simple-key 1*( dot-sep simple-key )
+// note: this rule is modified to also accept a simple key
+parse!(dotted_key() -> Vec<(&'a str, String)>, {
+    sep_by1(key(), attempt((ws(), char('.'), ws())))
+});
@@ -0,0 +1,377 @@
+// Lexing of the tokens which are validated outside of documents, i.e. keys, key paths,
+// strings and the whitespaces, newlines and comments of decors. Unlike the rest of
+// the parser it doesn't depend on `combine`, so it's available without the `parse` feature.
+use crate::key::Key;
+use crate::parser::errors::TomlError;
+
+/// Lexes a simple key spanning the whole input.
+pub(crate) fn key(input: &str) -> Result<Key, TomlError> {
+    let mut lexer = Lexer::new(input);
+    lexer
+        .key()
+        .and_then(|key| lexer.end().map(|_| key))
+        .map_err(|e| e.into_toml(input))
+}
+
+/// Lexes the keys of a dotted key spanning the whole input,
+/// the keys may be surrounded by whitespaces.
+pub(crate) fn key_path(input: &str) -> Result<Vec<Key>, TomlError> {
+    let mut lexer = Lexer::new(input);
+    let mut keys = Vec::new();
+    loop {
+        lexer.ws();
+        let key = lexer.key().map_err(|e| e.into_toml(input))?;
+        keys.push(key);
+        lexer.ws();
+        if !lexer.eat(".") {
+            break;
+        }
+    }
+    lexer.end().map_err(|e| e.into_toml(input))?;
+    Ok(keys)
+}
+
+/// Lexes a string of any kind spanning the whole input.
+pub(crate) fn string(input: &str) -> Result<String, TomlError> {
+    let mut lexer = Lexer::new(input);
+    lexer
+        .string()
+        .and_then(|s| lexer.end().map(|_| s))
+        .map_err(|e| e.into_toml(input))
+}
+
+/// Checks that the input consists of whitespaces, newlines and comments.
+pub(crate) fn decor(input: &str) -> Result<(), TomlError> {
+    let mut lexer = Lexer::new(input);
+    lexer.ws_comment_newline().map_err(|e| e.into_toml(input))
+}
+
+// unquoted-key = 1*( ALPHA / DIGIT / %x2D / %x5F ) ; A-Z / a-z / 0-9 / - / _
+#[inline]
+pub(crate) fn is_unquoted_char(c: char) -> bool {
+    matches!(c, 'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '_')
+}
+
+// wschar = ( %x20 / %x09 )
+#[inline]
+fn is_wschar(c: char) -> bool {
+    matches!(c, ' ' | '\t')
+}
+
+// non-eol = %x09 / %x20-10FFFF
+#[inline]
+fn is_non_eol(c: char) -> bool {
+    matches!(c, '\u{09}' | '\u{20}'..='\u{10FFFF}')
+}
+
+// basic-unescaped = %x20-21 / %x23-5B / %x5D-10FFFF
+#[inline]
+fn is_basic_unescaped(c: char) -> bool {
+    matches!(c, '\u{20}'..='\u{21}' | '\u{23}'..='\u{5B}' | '\u{5D}'..='\u{10FFFF}')
+}
+
+// ml-basic-unescaped = %x20-5B / %x5D-10FFFF
+#[inline]
+fn is_ml_basic_unescaped(c: char) -> bool {
+    matches!(c, '\u{20}'..='\u{5B}' | '\u{5D}'..='\u{10FFFF}')
+}
+
+// literal-char = %x09 / %x20-26 / %x28-10FFFF
+#[inline]
+fn is_literal_char(c: char) -> bool {
+    matches!(c, '\u{09}' | '\u{20}'..='\u{26}' | '\u{28}'..='\u{10FFFF}')
+}
+
+// ml-literal-char = %x09 / %x20-10FFFF
+#[inline]
+fn is_ml_literal_char(c: char) -> bool {
+    matches!(c, '\u{09}' | '\u{20}'..='\u{10FFFF}')
+}
+
+// An error at a byte offset of the input, with the notes of `TomlError`
+// except for the unexpected character, which is taken from the input.
+struct Error {
+    offset: usize,
+    unexpected: bool,
+    expected: Option<&'static str>,
+    message: Option<String>,
+}
+
+impl Error {
+    fn expected(offset: usize, expected: &'static str) -> Self {
+        Self {
+            offset,
+            unexpected: true,
+            expected: Some(expected),
+            message: None,
+        }
+    }
+
+    fn message(offset: usize, message: String) -> Self {
+        Self {
+            offset,
+            unexpected: true,
+            expected: None,
+            message: Some(message),
+        }
+    }
+
+    // The input following a token, like `TomlError::from_unparsed`.
+    fn unparsed(offset: usize) -> Self {
+        Self {
+            offset,
+            unexpected: false,
+            expected: None,
+            message: Some("Could not parse the line".to_owned()),
+        }
+    }
+
+    // Adds the context of the error, unless it already has a message.
+    fn context(mut self, context: &str) -> Self {
+        if self.message.is_none() {
+            self.message = Some(format!("While parsing a {}", context));
+        }
+        self
+    }
+
+    fn into_toml(self, input: &str) -> TomlError {
+        let mut notes = Vec::new();
+        match input[self.offset..].chars().next() {
+            _ if !self.unexpected => {}
+            Some(c) if c.is_control() => notes.push(format!("Unexpected `{}`", c.escape_default())),
+            Some(c) => notes.push(format!("Unexpected `{}`", c)),
+            None => notes.push("Unexpected end of input".to_owned()),
+        }
+        if let Some(expected) = self.expected {
+            notes.push(format!("Expected `{}`", expected));
+        }
+        notes.extend(self.message);
+        TomlError::from_notes(input, self.offset, notes)
+    }
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    offset: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, offset: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let found = self.rest().starts_with(s);
+        if found {
+            self.offset += s.len();
+        }
+        found
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.offset;
+        while self.peek().is_some_and(&f) {
+            self.bump();
+        }
+        &self.input[start..self.offset]
+    }
+
+    fn end(&self) -> Result<(), Error> {
+        match self.peek() {
+            Some(..) => Err(Error::unparsed(self.offset)),
+            None => Ok(()),
+        }
+    }
+
+    // ws = *wschar
+    fn ws(&mut self) {
+        self.take_while(is_wschar);
+    }
+
+    // newline = ( %x0A / %x0D.0A )
+    fn newline(&mut self) -> bool {
+        self.eat("\n") || self.eat("\r\n")
+    }
+
+    // ws-comment-newline = *( ws-newline-nonempty / comment )
+    fn ws_comment_newline(&mut self) -> Result<(), Error> {
+        loop {
+            match self.peek() {
+                Some(c) if is_wschar(c) => self.ws(),
+                Some('#') => {
+                    self.bump();
+                    self.take_while(is_non_eol);
+                }
+                Some(..) if self.newline() => {}
+                Some(..) => return Err(Error::expected(self.offset, "a newline")),
+                None => return Ok(()),
+            }
+        }
+    }
+
+    // key = unquoted-key / basic-string / literal-string
+    fn key(&mut self) -> Result<Key, Error> {
+        let start = self.offset;
+        let key = match self.peek() {
+            Some('"') => self.basic_string()?,
+            Some('\'') => self.literal_string()?.into(),
+            Some(c) if is_unquoted_char(c) => self.take_while(is_unquoted_char).into(),
+            _ => return Err(Error::expected(start, "a key")),
+        };
+        Ok(Key::new(&self.input[start..self.offset], key))
+    }
+
+    // string = ml-basic-string / basic-string / ml-literal-string / literal-string
+    fn string(&mut self) -> Result<String, Error> {
+        let rest = self.rest();
+        if rest.starts_with("\"\"\"") {
+            self.ml_basic_string()
+        } else if rest.starts_with('"') {
+            self.basic_string()
+        } else if rest.starts_with("'''") {
+            self.ml_literal_string()
+        } else if rest.starts_with('\'') {
+            self.literal_string().map(Into::into)
+        } else {
+            Err(Error::expected(self.offset, "a string"))
+        }
+    }
+
+    // basic-string = quotation-mark *basic-char quotation-mark
+    fn basic_string(&mut self) -> Result<String, Error> {
+        self.eat("\"");
+        let mut s = String::new();
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.bump();
+                    return Ok(s);
+                }
+                Some('\\') => s.push(self.escape().map_err(|e| e.context("Basic String"))?),
+                Some(c) if is_basic_unescaped(c) => {
+                    self.bump();
+                    s.push(c);
+                }
+                _ => return Err(Error::expected(self.offset, "\"").context("Basic String")),
+            }
+        }
+    }
+
+    // ml-basic-string = ml-basic-string-delim ml-basic-body ml-basic-string-delim
+    // ml-basic-body = *( ( escape ws-newline ) / ml-basic-char / newline )
+    fn ml_basic_string(&mut self) -> Result<String, Error> {
+        const CONTEXT: &str = "Multiline Basic String";
+        self.eat("\"\"\"");
+        // a newline immediately following the opening delimiter is trimmed
+        self.newline();
+        self.escaped_newlines();
+        let mut s = String::new();
+        while !self.eat("\"\"\"") {
+            match self.peek() {
+                Some(..) if self.newline() => s.push('\n'),
+                Some('\\') => s.push(self.escape().map_err(|e| e.context(CONTEXT))?),
+                Some(c) if is_ml_basic_unescaped(c) => {
+                    self.bump();
+                    s.push(c);
+                }
+                _ => return Err(Error::expected(self.offset, "\"\"\"").context(CONTEXT)),
+            }
+            self.escaped_newlines();
+        }
+        Ok(s)
+    }
+
+    // When the last non-whitespace character on a line is a \,
+    // it will be trimmed along with all whitespace
+    // (including newlines) up to the next non-whitespace
+    // character or closing delimiter.
+    fn escaped_newlines(&mut self) {
+        loop {
+            let start = self.offset;
+            if self.eat("\\") {
+                self.ws();
+                if self.newline() {
+                    while self.newline() || !self.take_while(is_wschar).is_empty() {}
+                    continue;
+                }
+            }
+            self.offset = start;
+            return;
+        }
+    }
+
+    // escaped = escape ( %x22 / %x5C / %x2F / %x62 / %x66 / %x6E / %x72 / %x74 /
+    //                    %x75 4HEXDIG / %x55 8HEXDIG )
+    fn escape(&mut self) -> Result<char, Error> {
+        self.eat("\\");
+        let start = self.offset;
+        let invalid = || Error::message(start, "While parsing escape sequence".to_owned());
+        let digits = match self.bump() {
+            Some('b') => return Ok('\u{8}'),
+            Some('f') => return Ok('\u{c}'),
+            Some('n') => return Ok('\n'),
+            Some('r') => return Ok('\r'),
+            Some('t') => return Ok('\t'),
+            Some(c @ '\\') | Some(c @ '"') | Some(c @ '/') => return Ok(c),
+            Some('u') => 4,
+            Some('U') => 8,
+            _ => return Err(invalid()),
+        };
+        let hex_start = self.offset;
+        for _ in 0..digits {
+            self.bump().ok_or_else(invalid)?;
+        }
+        let hex = &self.input[hex_start..self.offset];
+        let code = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+        std::char::from_u32(code)
+            .ok_or_else(|| Error::message(start, format!("Invalid hex escape code: {:x} ", code)))
+    }
+
+    // literal-string = apostrophe *literal-char apostrophe
+    fn literal_string(&mut self) -> Result<&'a str, Error> {
+        self.eat("'");
+        let s = self.take_while(is_literal_char);
+        if !self.eat("'") {
+            return Err(Error::expected(self.offset, "'").context("Literal String"));
+        }
+        Ok(s)
+    }
+
+    // ml-literal-string = ml-literal-string-delim ml-literal-body ml-literal-string-delim
+    // ml-literal-body = *( ml-literal-char / newline )
+    fn ml_literal_string(&mut self) -> Result<String, Error> {
+        self.eat("'''");
+        // a newline immediately following the opening delimiter is trimmed
+        self.newline();
+        let mut s = String::new();
+        while !self.eat("'''") {
+            match self.peek() {
+                Some(..) if self.newline() => s.push('\n'),
+                Some(c) if is_ml_literal_char(c) => {
+                    self.bump();
+                    s.push(c);
+                }
+                _ => {
+                    return Err(
+                        Error::expected(self.offset, "'''").context("Multiline Literal String")
+                    )
+                }
+            }
+        }
+        Ok(s)
+    }
+}
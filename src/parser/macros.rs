@@ -12,8 +12,8 @@ macro_rules! parse (
                  <I::Error as ParseError<char, &'a str, <I as StreamOnce>::Position>>::StreamError:
                  From<std::num::ParseIntError> +
                  From<std::num::ParseFloatError> +
-                 From<chrono::ParseError> +
-                 From<crate::parser::errors::CustomError>
+                 From<$crate::parser::errors::DatetimeError> +
+                 From<$crate::parser::errors::CustomError>
                 ]
             {
                 $code
@@ -36,8 +36,8 @@ macro_rules! toml_parser (
                  <I::Error as ParseError<char, &'a str, <I as StreamOnce>::Position>>::StreamError:
                  From<std::num::ParseIntError> +
                  From<std::num::ParseFloatError> +
-                 From<chrono::ParseError> +
-                 From<crate::parser::errors::CustomError>
+                 From<$crate::parser::errors::DatetimeError> +
+                 From<$crate::parser::errors::CustomError>
                 ]
             {
                 $closure
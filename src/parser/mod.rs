@@ -1,44 +1,92 @@
 #![allow(clippy::unneeded_field_pattern)]
 #![allow(clippy::toplevel_ref_arg)]
 
+#[cfg(feature = "parse")]
 #[macro_use]
 mod macros;
+#[cfg(feature = "parse")]
 mod array;
+#[cfg(feature = "parse")]
 mod datetime;
+#[cfg(feature = "parse")]
 mod document;
 mod errors;
+#[cfg(feature = "parse")]
 mod inline_table;
+#[cfg(feature = "parse")]
 mod key;
+pub(crate) mod lexer;
+#[cfg(feature = "parse")]
 mod numbers;
-pub(crate) mod strings;
+mod options;
+#[cfg(feature = "parse")]
+mod strings;
+#[cfg(feature = "parse")]
 mod table;
+#[cfg(feature = "parse")]
 mod trivia;
+#[cfg(feature = "parse")]
 mod value;
 
+#[cfg(feature = "parse")]
+pub use self::errors::TableConflict;
 pub use self::errors::TomlError;
+#[cfg(feature = "combinators")]
+pub(crate) use self::inline_table::inline_table as inline_table_parser;
+#[cfg(feature = "combinators")]
+pub(crate) use self::key::dotted_key as dotted_key_parser;
+#[cfg(feature = "combinators")]
 pub(crate) use self::key::key as key_parser;
+#[cfg(feature = "parse")]
+pub(crate) use self::numbers::lenient_number;
+pub use self::options::{
+    ExtensionLiteral, NumberPolicy, ParseOptions, TableConflicts, TomlVersion,
+};
+#[cfg(feature = "parse")]
+pub(crate) use self::table::key_path as key_path_parser;
+#[cfg(feature = "parse")]
 pub(crate) use self::value::value as value_parser;
 
+#[cfg(feature = "parse")]
 use crate::document::Document;
+#[cfg(feature = "parse")]
 use crate::key::Key;
+#[cfg(feature = "parse")]
+use crate::key_path::KeyPath;
+#[cfg(feature = "parse")]
+use combine::stream::position::SourcePosition;
 
+#[cfg(feature = "parse")]
 pub struct TomlParser {
     document: Box<Document>,
     current_table_path: Vec<Key>,
     current_table_position: usize,
+    options: ParseOptions,
+    // the inline tables merged into the last header, and those merged so far with their headers
+    merged: Vec<KeyPath>,
+    conflicts: Vec<(KeyPath, SourcePosition, SourcePosition)>,
+    // the end of the last expression of each table, by position
+    #[cfg(feature = "verbatim")]
+    expression_ends: Vec<Option<combine::stream::position::SourcePosition>>,
 }
 
+#[cfg(feature = "parse")]
 impl Default for TomlParser {
     fn default() -> Self {
         Self {
             document: Box::new(Document::new()),
             current_table_path: Vec::new(),
             current_table_position: 0,
+            options: ParseOptions::default(),
+            merged: Vec::new(),
+            conflicts: Vec::new(),
+            #[cfg(feature = "verbatim")]
+            expression_ends: Vec::new(),
         }
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "parse"))]
 mod tests {
     use crate::parser::*;
     use combine::stream::position::Stream;
@@ -69,17 +117,17 @@ mod tests {
 
     macro_rules! parsed_float_eq {
         ($input:ident, $expected:expr) => {{
-            let parsed = numbers::float().easy_parse(Stream::new($input));
+            let parsed = numbers::float(NumberPolicy::Error).easy_parse(Stream::new($input));
             assert!(parsed.is_ok());
-            let (v, rest) = parsed.unwrap();
-            assert!(($expected - v).abs() < std::f64::EPSILON);
+            let ((v, _), rest) = parsed.unwrap();
+            assert!(($expected - v).abs() < f64::EPSILON);
             assert!(rest.input.is_empty());
         }};
     }
 
     macro_rules! parsed_value_eq {
         ($input:expr) => {
-            let parsed = value::value().easy_parse(Stream::new(*$input));
+            let parsed = value::value(ParseOptions::default()).easy_parse(Stream::new(*$input));
             assert!(parsed.is_ok());
             let (v, rest) = parsed.unwrap();
             assert_eq!(v.to_string(), *$input);
@@ -89,7 +137,7 @@ mod tests {
 
     macro_rules! parsed_date_time_eq {
         ($input:expr, $is:ident) => {{
-            let parsed = value::value().easy_parse(Stream::new(*$input));
+            let parsed = value::value(ParseOptions::default()).easy_parse(Stream::new(*$input));
             assert!(parsed.is_ok());
             let (v, rest) = parsed.unwrap();
             assert_eq!(v.to_string(), *$input);
@@ -112,17 +160,22 @@ mod tests {
             ("0xF", 15),
             ("0o0_755", 493),
             ("0b1_0_1", 5),
-            (&std::i64::MIN.to_string()[..], std::i64::MIN),
-            (&std::i64::MAX.to_string()[..], std::i64::MAX),
+            (&i64::MIN.to_string()[..], i64::MIN),
+            (&i64::MAX.to_string()[..], i64::MAX),
         ];
         for &(input, expected) in &cases {
-            let parsed = numbers::integer().easy_parse(Stream::new(input));
-            parsed_eq!(parsed, expected);
+            let parsed = numbers::integer(NumberPolicy::Error).easy_parse(Stream::new(input));
+            parsed_eq!(parsed, (expected, false));
         }
 
         let overflow = "1000000000000000000000000000000000";
-        let parsed = numbers::integer().easy_parse(Stream::new(overflow));
+        let parsed = numbers::integer(NumberPolicy::Error).easy_parse(Stream::new(overflow));
         assert!(parsed.is_err());
+        let parsed = numbers::integer(NumberPolicy::Saturate).easy_parse(Stream::new(overflow));
+        parsed_eq!(parsed, (i64::MAX, true));
+        let parsed = numbers::integer(NumberPolicy::Saturate)
+            .easy_parse(Stream::new("-9_223_372_036_854_775_809"));
+        parsed_eq!(parsed, (i64::MIN, true));
     }
 
     #[test]
@@ -136,15 +189,35 @@ mod tests {
             ("-2E-2", -2E-2),
             ("6.626e-34", 6.626e-34),
             ("9_224_617.445_991_228_313", 9_224_617.445_991_227),
-            ("-1.7976931348623157e+308", std::f64::MIN),
-            ("1.7976931348623157e+308", std::f64::MAX),
-            // ("1e+400", std::f64::INFINITY),
+            ("-1.7976931348623157e+308", f64::MIN),
+            ("1.7976931348623157e+308", f64::MAX),
+            // ("1e+400", f64::INFINITY),
         ];
         for &(input, expected) in &cases {
             parsed_float_eq!(input, expected);
         }
     }
 
+    #[test]
+    fn special_floats() {
+        let cases = [
+            ("inf", f64::INFINITY),
+            ("+inf", f64::INFINITY),
+            ("-inf", f64::NEG_INFINITY),
+        ];
+        for &(input, expected) in &cases {
+            let parsed = numbers::special_float().easy_parse(Stream::new(input));
+            parsed_eq!(parsed, expected);
+        }
+        for input in &["nan", "+nan", "-nan"] {
+            let parsed = value::value(ParseOptions::default()).easy_parse(Stream::new(*input));
+            let (v, rest) = parsed.unwrap();
+            assert!(v.is_nan());
+            assert_eq!(v.to_string(), *input);
+            assert!(rest.input.is_empty());
+        }
+    }
+
     #[test]
     fn basic_string() {
         let input =
@@ -356,7 +429,7 @@ trimmed in raw strings.
 
         let invalid_inputs = [r#"["#, r#"[,]"#, r#"[,2]"#, r#"[1e165,,]"#, r#"[ 1, 2.0 ]"#];
         for input in &invalid_inputs {
-            let parsed = array::array().easy_parse(Stream::new(*input));
+            let parsed = array::array(ParseOptions::default(), 0).easy_parse(Stream::new(*input));
             assert!(parsed.is_err());
         }
     }
@@ -374,7 +447,8 @@ trimmed in raw strings.
         }
         let invalid_inputs = [r#"{a = 1e165"#, r#"{ hello = "world", a = 2, hello = 1}"#];
         for input in &invalid_inputs {
-            let parsed = inline_table::inline_table().easy_parse(Stream::new(*input));
+            let parsed = inline_table::inline_table(ParseOptions::default(), 0)
+                .easy_parse(Stream::new(*input));
             assert!(parsed.is_err());
         }
     }
@@ -396,6 +470,64 @@ trimmed in raw strings.
         }
     }
 
+    #[test]
+    fn lexer() {
+        let strings = [
+            r#""a\tbé\U0001F600\/""#,
+            r#""a\x""#,
+            r#""\uD800""#,
+            "\"a\tb\"",
+            "\"\"\"\na\r\n\\  \n\n  b\\\n\"\"\"",
+            "\"\"\"a\"\"b\"\"\"",
+            "\"\"\"a\\ b\"\"\"",
+            "\"\"\"\"\"\"",
+            "\"\"\"a\"\"\"\"",
+            "'a\\n b'",
+            "'''\r\na\tb\n'''",
+            "'''a''b'''",
+            "'a\nb'",
+            "'a'b",
+            "\"",
+            "",
+        ];
+        for input in &strings {
+            let parsed = strings::string().easy_parse(Stream::new(*input));
+            let expected = match parsed {
+                Ok((s, ref rest)) if rest.input.is_empty() => Some(s),
+                _ => None,
+            };
+            assert_eq!(lexer::string(input).ok(), expected, "{:?}", input);
+        }
+
+        let keys = [
+            "a-b_1",
+            "'a.b'",
+            r#""a\"b""#,
+            "a.b",
+            "a b",
+            "'a",
+            r#""A""#,
+            "",
+        ];
+        for input in &keys {
+            let parsed = key::key().easy_parse(Stream::new(*input));
+            let expected = match parsed {
+                Ok(((raw, k), ref rest)) if rest.input.is_empty() => Some((raw.to_owned(), k)),
+                _ => None,
+            };
+            let lexed = lexer::key(input).ok();
+            let lexed = lexed.map(|k| (k.raw().to_owned(), k.get().to_owned()));
+            assert_eq!(lexed, expected, "{:?}", input);
+        }
+
+        let decors = [" \t\n# a\r\n  #b", "\r", "# a\u{7f}\n", "# a\u{7}", "x", ""];
+        for input in &decors {
+            let parsed = trivia::ws_comment_newline().easy_parse(Stream::new(*input));
+            let expected = matches!(parsed, Ok((_, ref rest)) if rest.input.is_empty());
+            assert_eq!(lexer::decor(input).is_ok(), expected, "{:?}", input);
+        }
+    }
+
     #[test]
     fn values() {
         let inputs = [
@@ -473,7 +605,7 @@ that
 "#,
         ];
         for document in &documents {
-            let doc = TomlParser::parse(document);
+            let doc = TomlParser::parse(document, &ParseOptions::default());
 
             assert!(doc.is_ok());
             let doc = doc.unwrap();
@@ -484,7 +616,7 @@ that
         let invalid_inputs = [r#" hello = 'darkness' # my old friend
 $"#];
         for document in &invalid_inputs {
-            let doc = TomlParser::parse(document);
+            let doc = TomlParser::parse(document, &ParseOptions::default());
 
             assert!(doc.is_err());
         }
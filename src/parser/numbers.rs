@@ -1,7 +1,12 @@
+use crate::decor::{Formatted, Repr};
+use crate::parser::errors::CustomError;
+use crate::parser::options::NumberPolicy;
+use crate::value::Value;
 use combine::parser::char::{char, digit, hex_digit, oct_digit, string};
 use combine::parser::range::{range, recognize};
 use combine::stream::RangeStream;
 use combine::*;
+use std::num::{IntErrorKind, ParseIntError};
 
 // ;; Boolean
 
@@ -34,19 +39,31 @@ parse!(parse_integer() -> &'a str, {
     ))
 });
 
-parse!(integer() -> i64, {
+// special-float = [ minus / plus ] ( inf / nan )
+parse!(special_float() -> f64, {
+    attempt((
+        optional(one_of("+-".chars())),
+        choice((range("inf"), range("nan"))),
+    )).map(|(sign, kw)| {
+        let f = if kw == "inf" { f64::INFINITY } else { f64::NAN };
+        if sign == Some('-') { -f } else { f }
+    })
+});
+
+// The returned flag is set iff the value was clamped according to the policy.
+parse!(integer(policy: NumberPolicy) -> (i64, bool), {
     choice!(
-        attempt(parse_hex_integer()),
-        attempt(parse_octal_integer()),
-        attempt(parse_binary_integer()),
+        attempt(parse_hex_integer(*policy)),
+        attempt(parse_octal_integer(*policy)),
+        attempt(parse_binary_integer(*policy)),
         parse_integer()
-            .and_then(|s| s.replace("_", "").parse())
+            .and_then(move |s| resolve_integer(&s.replace("_", ""), 10, *policy))
             .message("While parsing an Integer")
     )
 });
 
 // hex-int = "0x" HEXDIGIT *( HEXDIGIT / _ HEXDIGIT )
-parse!(parse_hex_integer() -> i64, {
+parse!(parse_hex_integer(policy: NumberPolicy) -> (i64, bool), {
     string("0x").with(
         recognize((
             hex_digit(),
@@ -55,12 +72,12 @@ parse!(parse_hex_integer() -> i64, {
                 skip_many1(hex_digit()),
             )),
         ).map(|t| t.0)
-    )).and_then(|s: &str| i64::from_str_radix(&s.replace("_", ""), 16))
+    )).and_then(move |s: &str| resolve_integer(&s.replace("_", ""), 16, *policy))
        .message("While parsing a hexadecimal Integer")
 });
 
 // oct-int = "0o" digit0-7 *( digit0-7 / _ digit0-7 )
-parse!(parse_octal_integer() -> i64, {
+parse!(parse_octal_integer(policy: NumberPolicy) -> (i64, bool), {
     string("0o").with(
         recognize((
             oct_digit(),
@@ -69,12 +86,12 @@ parse!(parse_octal_integer() -> i64, {
                 skip_many1(oct_digit()),
             )),
         ).map(|t| t.0)
-    )).and_then(|s: &str| i64::from_str_radix(&s.replace("_", ""), 8))
+    )).and_then(move |s: &str| resolve_integer(&s.replace("_", ""), 8, *policy))
        .message("While parsing an octal Integer")
 });
 
 // bin-int = "0b" digit0-1 *( digit0-1 / _ digit0-1 )
-parse!(parse_binary_integer() -> i64, {
+parse!(parse_binary_integer(policy: NumberPolicy) -> (i64, bool), {
     string("0b").with(
         recognize((
             satisfy(|c: char| c.is_digit(0x2)),
@@ -83,10 +100,26 @@ parse!(parse_binary_integer() -> i64, {
                 skip_many1(satisfy(|c: char| c.is_digit(0x2))),
             )),
         ).map(|t| t.0)
-    )).and_then(|s: &str| i64::from_str_radix(&s.replace("_", ""), 2))
+    )).and_then(move |s: &str| resolve_integer(&s.replace("_", ""), 2, *policy))
        .message("While parsing a binary Integer")
 });
 
+fn resolve_integer(
+    digits: &str,
+    radix: u32,
+    policy: NumberPolicy,
+) -> Result<(i64, bool), ParseIntError> {
+    match i64::from_str_radix(digits, radix) {
+        Ok(i) => Ok((i, false)),
+        Err(e) if policy == NumberPolicy::Error => Err(e),
+        Err(e) => match *e.kind() {
+            IntErrorKind::PosOverflow => Ok((i64::MAX, true)),
+            IntErrorKind::NegOverflow => Ok((i64::MIN, true)),
+            _ => Err(e),
+        },
+    }
+}
+
 // ;; Float
 
 // frac = decimal-point zero-prefixable-int
@@ -126,8 +159,62 @@ parse!(parse_float() -> &'a str, {
     ))
 });
 
-parse!(float() -> f64, {
+// The returned flag is set iff the value was clamped according to the policy.
+parse!(float(policy: NumberPolicy) -> (f64, bool), {
     parse_float()
         .and_then(|s| s.replace("_", "").parse())
+        .and_then(move |f| resolve_float(f, *policy))
         .message("While parsing a Float")
 });
+
+// Literals are always finite, so an infinite value means the literal is out of range.
+fn resolve_float(f: f64, policy: NumberPolicy) -> Result<(f64, bool), CustomError> {
+    if f.is_finite() || policy == NumberPolicy::Preserve {
+        Ok((f, false))
+    } else if policy == NumberPolicy::Saturate {
+        Ok((f64::MAX.copysign(f), true))
+    } else {
+        Err(CustomError::OutOfRange("Float".into()))
+    }
+}
+
+// ;; Lenient numbers, not part of the grammar
+
+// Parses a number which may have commas as thousands separators, e.g. `-1,234.5`,
+// into a value with the canonical literal, see `Value::parse_number_lenient`.
+pub(crate) fn lenient_number(s: &str) -> Option<Value> {
+    let s = s.trim();
+    let unsigned = s.trim_start_matches(['+', '-']);
+    let sign = &s[..s.len() - unsigned.len()];
+    let (int, rest) = unsigned.split_at(unsigned.find(['.', 'e', 'E']).unwrap_or(unsigned.len()));
+    if int.contains(',') {
+        // 1 to 3 leading decimal digits followed by groups of 3,
+        // hexadecimal, octal and binary integers have no separators
+        let digits = |g: &str| g.bytes().all(|b| b.is_ascii_digit());
+        let mut groups = int.split(',');
+        let first = groups.next().expect("at least one group");
+        if !(1..=3).contains(&first.len())
+            || !digits(first)
+            || groups.any(|g| g.len() != 3 || !digits(g))
+        {
+            return None;
+        }
+    }
+    if sign.len() > 1 || rest.contains(',') || unsigned.contains('_') {
+        return None;
+    }
+    let literal = format!("{}{}{}", sign, int.replace(',', ""), rest);
+    match literal.parse::<Value>().ok()? {
+        Value::Integer(i) => Some(Value::from(*i.value())),
+        Value::Float(f) if f.value().is_finite() => {
+            // `Debug` always includes a fractional part or an exponent
+            let raw = format!("{:?}", f.value());
+            Some(Value::Float(Formatted::with_repr(
+                *f.value(),
+                Repr::new("", &raw, ""),
+            )))
+        }
+        Value::Float(f) => Some(Value::from(*f.value())),
+        _ => None,
+    }
+}
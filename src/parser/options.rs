@@ -0,0 +1,157 @@
+/// Determines how number literals that don't fit into `i64` or `f64` are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NumberPolicy {
+    /// Out of range literals are rejected with a parse error.
+    #[default]
+    Error,
+    /// Out of range literals are clamped to the closest representable value,
+    /// the literal is rewritten to match it.
+    Saturate,
+    /// Out of range literals are clamped to the closest representable value
+    /// (infinity for floats), the original literal is kept and is available via `Value::as_raw`.
+    Preserve,
+}
+
+/// The version of the TOML specification to accept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum TomlVersion {
+    /// TOML v0.5.0.
+    #[default]
+    V0_5,
+    /// TOML v1.0.0, currently this only allows arrays with mixed types.
+    V1_0,
+}
+
+/// Determines how tables defined both by an inline table and by a table header are handled,
+/// e.g. `a = { x = 1 }` followed by `[a.b]` or `[a]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TableConflicts {
+    /// The header is rejected with a parse error, as required by the specification.
+    #[default]
+    Error,
+    /// The inline table is converted into a standard table which the header extends,
+    /// each conversion is reported by `Document::parse_with_conflicts`.
+    /// A comment following the inline table is kept on a line above the header.
+    Merge,
+}
+
+/// Options controlling how a TOML document is parsed.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "parse")]
+/// # fn main() {
+/// use toml_edit::{Document, NumberPolicy, ParseOptions};
+///
+/// let options = ParseOptions::new().number_policy(NumberPolicy::Preserve);
+/// let doc = Document::parse_with("a = 18446744073709551615", &options).unwrap();
+/// assert_eq!(doc["a"].as_integer(), Some(i64::MAX));
+/// assert_eq!(doc["a"].as_value().and_then(|v| v.as_raw()), Some("18446744073709551615"));
+/// # }
+/// # #[cfg(not(feature = "parse"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+    pub(crate) number_policy: NumberPolicy,
+    pub(crate) version: TomlVersion,
+    pub(crate) extensions: &'static [ExtensionLiteral],
+    pub(crate) table_conflicts: TableConflicts,
+    pub(crate) error_context: Option<usize>,
+}
+
+impl ParseOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the policy for out of range number literals, `NumberPolicy::Error` by default.
+    pub fn number_policy(mut self, policy: NumberPolicy) -> Self {
+        self.number_policy = policy;
+        self
+    }
+
+    /// Sets the version of the TOML specification to accept, `TomlVersion::V0_5` by default.
+    pub fn version(mut self, version: TomlVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the extension literals to accept where values are expected, none by default.
+    ///
+    /// The literals are captured as strings as written, see `ExtensionLiteral`.
+    pub fn extension_literals(mut self, extensions: &'static [ExtensionLiteral]) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Sets the handling of inline tables extended by table headers,
+    /// `TableConflicts::Error` by default.
+    pub fn table_conflicts(mut self, policy: TableConflicts) -> Self {
+        self.table_conflicts = policy;
+        self
+    }
+
+    /// Sets the number of characters shown on each side of a parse error in the excerpt
+    /// of its line, 60 by default, see `TomlError::excerpt`.
+    pub fn error_context(mut self, chars: usize) -> Self {
+        self.error_context = Some(chars);
+        self
+    }
+}
+
+/// A syntax outside of TOML accepted where values are expected, e.g. the placeholders
+/// of a templating system, see `ParseOptions::extension_literals`.
+///
+/// An extension literal starts with the opening, ends with the first closing after it
+/// and is on a single line. It is captured as a string value whose content is the literal
+/// as written, so that it is written back unchanged, and is tagged with `tag`,
+/// see `Value::extension_tag`. Being strings, they can't be mixed with other types in arrays
+/// unless TOML v1.0.0 is accepted, see `TomlVersion`. Documents with extension literals are not valid TOML,
+/// `Document::assert_valid` reports the literals.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "parse")]
+/// # fn main() {
+/// use toml_edit::{Document, ExtensionLiteral, ParseOptions};
+///
+/// const JINJA: &[ExtensionLiteral] = &[ExtensionLiteral::new("jinja", "{{", "}}")];
+///
+/// let toml = "version = {{ version }}\nports = [{{ port }}, '8080']\n";
+/// assert!(toml.parse::<Document>().is_err());
+///
+/// let options = ParseOptions::new().extension_literals(JINJA);
+/// let doc = Document::parse_with(toml, &options).unwrap();
+/// assert_eq!(doc["version"].as_str(), Some("{{ version }}"));
+/// assert_eq!(doc["version"].as_value().and_then(|v| v.extension_tag()), Some("jinja"));
+/// assert_eq!(doc["ports"][1].as_value().and_then(|v| v.extension_tag()), None);
+/// assert_eq!(doc.to_string(), toml);
+/// # }
+/// # #[cfg(not(feature = "parse"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExtensionLiteral {
+    pub(crate) tag: &'static str,
+    pub(crate) open: &'static str,
+    pub(crate) close: &'static str,
+}
+
+impl ExtensionLiteral {
+    /// Creates an extension literal with the given tag, opening and closing.
+    ///
+    /// The opening and the closing must not be empty.
+    pub const fn new(tag: &'static str, open: &'static str, close: &'static str) -> Self {
+        assert!(!open.is_empty() && !close.is_empty());
+        Self { tag, open, close }
+    }
+
+    /// Returns the tag of the captured values.
+    pub fn tag(&self) -> &'static str {
+        self.tag
+    }
+}
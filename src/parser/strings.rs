@@ -1,4 +1,3 @@
-use crate::decor::InternalString;
 use crate::parser::errors::CustomError;
 use crate::parser::trivia::{newline, ws, ws_newlines};
 use combine::error::{Commit, Info};
@@ -11,7 +10,7 @@ use std::char;
 // ;; String
 
 // string = ml-basic-string / basic-string / ml-literal-string / literal-string
-parse!(string() -> InternalString, {
+parse!(string() -> String, {
     choice((
         ml_basic_string(),
         basic_string(),
@@ -88,7 +87,7 @@ parse!(basic_char() -> char, {
 const QUOTATION_MARK: char = '"';
 
 // basic-string = quotation-mark *basic-char quotation-mark
-parse!(basic_string() -> InternalString, {
+parse!(basic_string() -> String, {
     between(char(QUOTATION_MARK), char(QUOTATION_MARK),
             many(basic_char()))
         .message("While parsing a Basic String")
@@ -129,7 +128,7 @@ parse!(try_eat_escaped_newline() -> (), {
 });
 
 // ml-basic-body = *( ( escape ws-newline ) / ml-basic-char / newline )
-parse!(ml_basic_body() -> InternalString, {
+parse!(ml_basic_body() -> String, {
     //  A newline immediately following the opening delimiter will be trimmed.
     optional(newline())
         .skip(try_eat_escaped_newline())
@@ -150,7 +149,7 @@ parse!(ml_basic_body() -> InternalString, {
 });
 
 // ml-basic-string = ml-basic-string-delim ml-basic-body ml-basic-string-delim
-parse!(ml_basic_string() -> InternalString, {
+parse!(ml_basic_string() -> String, {
     between(range(ML_BASIC_STRING_DELIM),
             range(ML_BASIC_STRING_DELIM),
             ml_basic_body())
@@ -187,7 +186,7 @@ fn is_ml_literal_char(c: char) -> bool {
 }
 
 // ml-literal-body = *( ml-literal-char / newline )
-parse!(ml_literal_body() -> InternalString, {
+parse!(ml_literal_body() -> String, {
     //  A newline immediately following the opening delimiter will be trimmed.
     optional(newline())
         .with(
@@ -206,7 +205,7 @@ parse!(ml_literal_body() -> InternalString, {
 });
 
 // ml-literal-string = ml-literal-string-delim ml-literal-body ml-literal-string-delim
-parse!(ml_literal_string() -> InternalString, {
+parse!(ml_literal_string() -> String, {
     between(range(ML_LITERAL_STRING_DELIM),
             range(ML_LITERAL_STRING_DELIM),
             ml_literal_body())
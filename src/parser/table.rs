@@ -1,11 +1,15 @@
 use crate::array_of_tables::ArrayOfTables;
 use crate::decor::Decor;
+use crate::display::MAX_DEPTH;
 use crate::key::Key;
+use crate::key_path::KeyPath;
 use crate::parser::errors::CustomError;
 use crate::parser::key::key;
+use crate::parser::options::TableConflicts;
 use crate::parser::trivia::{line_trailing, ws};
 use crate::parser::TomlParser;
 use crate::table::{Item, Table};
+use crate::value::Value;
 use combine::parser::char::char;
 use combine::parser::range::range;
 use combine::stream::RangeStream;
@@ -73,7 +77,7 @@ parser! {
          <I::Error as ParseError<char, &'a str, <I as StreamOnce>::Position>>::StreamError:
          From<std::num::ParseIntError> +
          From<std::num::ParseFloatError> +
-         From<chrono::ParseError> +
+         From<crate::parser::errors::DatetimeError> +
          From<crate::parser::errors::CustomError>
     ]    {
         array_table(parser)
@@ -84,13 +88,18 @@ parser! {
 
 pub(crate) fn duplicate_key(path: &[Key], i: usize) -> CustomError {
     assert!(i < path.len());
-    let header: Vec<&str> = path[..i].iter().map(Key::raw).collect();
     CustomError::DuplicateKey {
         key: path[i].raw().into(),
-        table: format!("[{}]", header.join(".")),
+        table: header_name(&path[..i]),
     }
 }
 
+// the header of the table at `path` as written, e.g. `[a."b"]`
+pub(crate) fn header_name(path: &[Key]) -> String {
+    let header: Vec<&str> = path.iter().map(Key::raw).collect();
+    format!("[{}]", header.join("."))
+}
+
 impl TomlParser {
     pub(crate) fn descend_path<'a>(
         table: &'a mut Table,
@@ -125,9 +134,47 @@ impl TomlParser {
         }
     }
 
+    // converts the inline tables along `path` into standard tables if they are to be merged,
+    // returning true iff the last key of `path` was one of them
+    fn merge_inline_tables(&mut self, path: &[Key]) -> bool {
+        if self.options.table_conflicts != TableConflicts::Merge {
+            return false;
+        }
+        let mut table = self.document.as_table_mut();
+        for (i, key) in path.iter().enumerate() {
+            let kv = match table.items.get_mut(key.get()) {
+                Some(kv) => kv,
+                None => return false,
+            };
+            if let Item::Value(Value::InlineTable(ref inline)) = kv.value {
+                let decor = Decor::new(kv.key.decor.prefix.clone(), inline.decor.suffix.clone());
+                let mut t = kv.value.take().into_table().expect("an inline table");
+                t.decor = decor;
+                kv.value = Item::Table(t);
+                self.merged.push(KeyPath::from(path[..=i].to_vec()));
+                if i == path.len() - 1 {
+                    return true;
+                }
+            }
+            table = match kv.value {
+                Item::Table(ref mut t) => t,
+                Item::ArrayOfTables(ref mut a) if !a.is_empty() => {
+                    let index = a.len() - 1;
+                    a.get_mut(index).expect("a non-empty array")
+                }
+                _ => return false,
+            };
+        }
+        false
+    }
+
     fn on_std_header(&mut self, path: &[Key], trailing: &str) -> Result<(), CustomError> {
         debug_assert!(!path.is_empty());
+        if path.len() > MAX_DEPTH {
+            return Err(CustomError::NestedTooDeeply);
+        }
 
+        let merged = self.merge_inline_tables(path);
         let leading = mem::take(&mut self.document.trailing);
         let table = self.document.as_table_mut();
         self.current_table_position += 1;
@@ -137,7 +184,7 @@ impl TomlParser {
 
         match table {
             Ok(table) => {
-                let decor = Decor::new(leading, trailing.into());
+                let mut decor = Decor::new(leading, trailing.into());
 
                 let entry = table.entry(key.raw());
                 if entry.is_none() {
@@ -150,9 +197,21 @@ impl TomlParser {
                 }
                 match *entry {
                     // if [a.b.c] header preceded [a.b]
-                    Item::Table(ref mut t) if t.implicit => {
-                        debug_assert!(t.values_len() == 0);
+                    // or if the table was defined by an inline table
+                    Item::Table(ref mut t) if t.implicit || merged => {
+                        debug_assert!(merged || t.values_len() == 0);
 
+                        if merged {
+                            // keeps the comments preceding the inline table,
+                            // and the one following it on a line of its own
+                            let mut prefix = mem::take(&mut t.decor.prefix);
+                            let comment = t.decor.suffix.trim();
+                            if !comment.is_empty() {
+                                prefix.push_str(comment);
+                                prefix.push('\n');
+                            }
+                            decor.prefix = (String::from(prefix) + &decor.prefix).into();
+                        }
                         t.decor = decor;
                         t.position = Some(self.current_table_position);
                         t.set_implicit(false);
@@ -170,6 +229,11 @@ impl TomlParser {
 
     fn on_array_header(&mut self, path: &[Key], trailing: &str) -> Result<(), CustomError> {
         debug_assert!(!path.is_empty());
+        if path.len() > MAX_DEPTH {
+            return Err(CustomError::NestedTooDeeply);
+        }
+
+        self.merge_inline_tables(&path[..path.len() - 1]);
 
         let leading = mem::take(&mut self.document.trailing);
         let table = self.document.as_table_mut();
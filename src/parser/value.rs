@@ -3,34 +3,84 @@ use crate::formatted;
 use crate::parser::array::array;
 use crate::parser::datetime::date_time;
 use crate::parser::inline_table::inline_table;
-use crate::parser::numbers::{boolean, float, integer};
+use crate::parser::numbers::{boolean, float, integer, special_float};
+use crate::parser::options::{ExtensionLiteral, NumberPolicy, ParseOptions};
 use crate::parser::strings::string;
 use crate::value as v;
-use combine::parser::range::recognize_with_value;
+use combine::error::Commit;
+use combine::parser::char::string as text;
+use combine::parser::range::{range, recognize, recognize_with_value};
 use combine::stream::RangeStream;
 use combine::*;
 
 // val = string / boolean / array / inline-table / date-time / float / integer
-parse!(value() -> v::Value, {
+parse!(value(options: ParseOptions) -> v::Value, {
+    nested_value(*options, 0)
+});
+
+// A value within `depth` arrays and inline tables.
+parse!(nested_value(options: ParseOptions, depth: usize) -> v::Value, {
+    let policy = options.number_policy;
+    let saturate = policy == NumberPolicy::Saturate;
     recognize_with_value(choice((
+        extension_literals(options.extensions)
+            .map(|(tag, raw)| {
+                let mut s = Formatted::with_repr(raw.to_owned(), Repr::new("", raw, ""));
+                s.tag = Some(tag);
+                (v::Value::String(s), None)
+            }),
         string()
             .map(|s|
-                 v::Value::String(Formatted::new(
+                 (v::Value::String(Formatted::with_repr(
                      s,
                      Repr::new("".to_string(), "who cares?".into(), "".to_string()),
-                 ))
+                 )), None)
             ),
         boolean()
-            .map(v::Value::from),
-        array()
-            .map(v::Value::Array),
-        inline_table()
-            .map(v::Value::InlineTable),
+            .map(|b| (v::Value::from(b), None)),
+        array(*options, *depth)
+            .map(|a| (v::Value::Array(a), None)),
+        inline_table(*options, *depth)
+            .map(|t| (v::Value::InlineTable(t), None)),
         date_time()
-            .map(v::Value::from),
-        float()
-            .map(v::Value::from),
-        integer()
-            .map(v::Value::from),
-    ))).map(|(raw, value)| formatted::value(value, raw))
+            .map(|d| (v::Value::from(d), None)),
+        special_float()
+            .map(|f| (v::Value::from(f), None)),
+        // a saturated literal is rewritten to the value it was clamped to
+        float(policy)
+            .map(move |(f, clamped)| {
+                (v::Value::from(f), if clamped && saturate { Some(format!("{:e}", f)) } else { None })
+            }),
+        integer(policy)
+            .map(move |(i, clamped)| {
+                (v::Value::from(i), if clamped && saturate { Some(i.to_string()) } else { None })
+            }),
+    ))).map(|(raw, (value, clamped)): (&str, (v::Value, Option<String>))| {
+        formatted::value(value, clamped.as_deref().unwrap_or(raw))
+    })
+});
+
+// The first of the extension literals, with its tag.
+parse!(extension_literals(extensions: &'static [ExtensionLiteral]) -> (&'static str, &'a str), {
+    let extensions = *extensions;
+    parser(move |input: &mut I| {
+        for e in extensions {
+            match attempt(extension_literal(*e)).parse_stream(input).into_result() {
+                Ok((raw, commit)) => return Ok(((e.tag, raw), commit)),
+                Err(Commit::Peek(..)) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Err(Commit::Peek(I::Error::empty(input.position()).into()))
+    })
+});
+
+// An extension literal: the opening, the text on the same line up to the closing, the closing.
+parse!(extension_literal(extension: ExtensionLiteral) -> &'a str, {
+    let close = extension.close;
+    recognize((
+        range(extension.open),
+        skip_many((not_followed_by(text(close)), satisfy(|c| c != '\n' && c != '\r'))),
+        range(close),
+    ))
 });
@@ -0,0 +1,137 @@
+use crate::display::MAX_DEPTH;
+use crate::formatted::to_basic_string;
+use crate::table::Item;
+use crate::value::Value;
+
+impl Item {
+    /// Returns a single-line preview of the item of about `max_len` characters,
+    /// e.g. for logs and error messages.
+    ///
+    /// Tables are written like inline tables and arrays of tables like arrays of them,
+    /// without the decor. The entries which don't fit are left out and counted, e.g.
+    /// `{name = "foo", … 12 more}`, so the count may exceed `max_len`, and the first
+    /// entry is always kept. Values too long, e.g. strings, are cut with `…`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let doc = r#"
+    /// [package]
+    /// name = "foo"   # the name
+    /// version = "0.1.0"
+    /// authors = ["a", "b"]
+    /// "#.parse::<Document>().unwrap();
+    /// assert_eq!(doc["package"].preview(80), r#"{name = "foo", version = "0.1.0", authors = ["a", "b"]}"#);
+    /// assert_eq!(doc["package"].preview(30), r#"{name = "foo", … 2 more}"#);
+    /// assert_eq!(doc["package"]["version"].preview(5), r#""0.1…"#);
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn preview(&self, max_len: usize) -> String {
+        preview_item(self, max_len, 0).0
+    }
+}
+
+// the preview and whether anything was left out
+fn preview_item(item: &Item, max_len: usize, depth: usize) -> (String, bool) {
+    match *item {
+        Item::None => (String::new(), false),
+        Item::Value(ref v) => preview_value(v, max_len, depth),
+        Item::Table(ref t) => {
+            let entries = t
+                .items
+                .values()
+                .map(|kv| (Some(&kv.key.raw_value[..]), &kv.value));
+            preview_entries(entries, ('{', '}'), max_len, depth)
+        }
+        Item::ArrayOfTables(ref a) => {
+            let entries = a.values.iter().map(|t| (None, t));
+            preview_entries(entries, ('[', ']'), max_len, depth)
+        }
+    }
+}
+
+fn preview_value(value: &Value, max_len: usize, depth: usize) -> (String, bool) {
+    match *value {
+        Value::Array(ref a) => {
+            let entries = a.values.iter().map(|v| (None, v));
+            preview_entries(entries, ('[', ']'), max_len, depth)
+        }
+        Value::InlineTable(ref t) => {
+            let entries = t
+                .items
+                .values()
+                .map(|kv| (Some(&kv.key.raw_value[..]), &kv.value));
+            preview_entries(entries, ('{', '}'), max_len, depth)
+        }
+        // multi-line strings are written on a single line
+        Value::String(ref s) if s.raw().contains('\n') => cut(to_basic_string(s.value()), max_len),
+        _ => cut(value.as_raw().expect("a scalar").to_owned(), max_len),
+    }
+}
+
+fn preview_entries<'a, I>(
+    entries: I,
+    (open, close): (char, char),
+    max_len: usize,
+    depth: usize,
+) -> (String, bool)
+where
+    I: Iterator<Item = (Option<&'a str>, &'a Item)>,
+{
+    let entries: Vec<_> = entries.filter(|(_, item)| !item.is_none()).collect();
+    if depth >= MAX_DEPTH && !entries.is_empty() {
+        return (format!("{}…{}", open, close), true);
+    }
+    let mut preview = open.to_string();
+    let mut len = 1;
+    let mut kept = 0;
+    let mut cut_entry = false;
+    for (i, (key, item)) in entries.iter().enumerate() {
+        let mut entry = String::from(if i > 0 { ", " } else { "" });
+        if let Some(key) = key {
+            entry.push_str(key);
+            entry.push_str(" = ");
+        }
+        let entry_len = entry.chars().count();
+        // keeps room for the closing bracket
+        let room = max_len.saturating_sub(len + entry_len + 1);
+        let (value, cut) = preview_item(item, room, depth + 1);
+        if cut && i > 0 {
+            break;
+        }
+        entry.push_str(&value);
+        len += entry_len + value.chars().count();
+        preview.push_str(&entry);
+        kept += 1;
+        if cut {
+            cut_entry = true;
+            break;
+        }
+    }
+    let left_out = entries.len() - kept;
+    if left_out > 0 {
+        preview.push_str(&format!(", … {} more", left_out));
+    }
+    preview.push(close);
+    (preview, cut_entry || left_out > 0)
+}
+
+// cuts `s` to `max_len` characters, including the ellipsis
+fn cut(mut s: String, max_len: usize) -> (String, bool) {
+    match s.char_indices().nth(max_len) {
+        None => (s, false),
+        Some(_) => {
+            let end = s
+                .char_indices()
+                .nth(max_len.saturating_sub(1))
+                .map_or(s.len(), |(i, _)| i);
+            s.truncate(end);
+            s.push('…');
+            (s, true)
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::document::Document;
+use crate::key::Key;
+use crate::key_path::KeyPath;
+use crate::merge::clear_positions;
+use crate::merge::same_item;
+use crate::table::{Item, KeyValuePairs};
+use crate::transaction::{self, Edit, EditError};
+use crate::value::Value;
+
+/// An edit of a document, see `reconcile`.
+#[derive(Debug, Clone)]
+pub enum Change {
+    /// Inserts the item, missing from the document, at the path.
+    Insert(KeyPath, Item),
+    /// Removes the item at the path.
+    Remove(KeyPath),
+    /// Replaces the item at the path, keeping the decor of a replaced value.
+    Update(KeyPath, Item),
+}
+
+impl Change {
+    /// Returns the path of the edited item.
+    pub fn path(&self) -> &KeyPath {
+        match *self {
+            Change::Insert(ref path, _)
+            | Change::Remove(ref path)
+            | Change::Update(ref path, _) => path,
+        }
+    }
+
+    fn to_edit(&self) -> Edit {
+        match *self {
+            Change::Insert(ref path, ref item) | Change::Update(ref path, ref item) => {
+                Edit::Set(path.clone(), item.clone())
+            }
+            Change::Remove(ref path) => Edit::Remove(path.clone()),
+        }
+    }
+}
+
+/// Returns the edits turning `source` into `target`, to be applied to `source`
+/// with `Document::apply_changes`, e.g. to write a serialized desired state
+/// to a document without losing its comments.
+///
+/// Tables and inline tables present in both documents are edited key by key, so
+/// the items which are the same regardless of their formatting are left untouched.
+/// Other items are replaced, arrays of tables with the tables of the source edited
+/// in place, and the items missing from the source are inserted with the formatting
+/// of the target, after the other items of their tables.
+/// The comments at the end of the documents are not compared.
+///
+/// Returns an error if editing the tables of an array of tables in place fails.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "parse")]
+/// # fn main() {
+/// use toml_edit::{reconcile, Document};
+///
+/// let mut source = r#"# the package
+/// [package]
+/// name = "a" # the name
+/// version = "0.1.0"
+/// authors = ["b"]
+/// "#.parse::<Document>().unwrap();
+/// let target = "[package]\nname = 'a'\nversion = '0.2.0'\nedition = '2018'\n"
+///     .parse::<Document>()
+///     .unwrap();
+///
+/// let changes = reconcile(&source, &target).unwrap();
+/// let paths: Vec<_> = changes.iter().map(|c| c.path().to_string()).collect();
+/// assert_eq!(paths, vec!["package.version", "package.authors", "package.edition"]);
+///
+/// source.apply_changes(&changes).unwrap();
+/// assert_eq!(source.to_string(), r#"# the package
+/// [package]
+/// name = "a" # the name
+/// version = '0.2.0'
+/// edition = '2018'
+/// "#);
+/// # }
+/// # #[cfg(not(feature = "parse"))]
+/// # fn main() {}
+/// ```
+pub fn reconcile(
+    source: &Document,
+    target: &Document,
+) -> std::result::Result<Vec<Change>, EditError> {
+    let mut changes = Vec::new();
+    diff(
+        &source.as_table().items,
+        &target.as_table().items,
+        &mut KeyPath::new(),
+        &mut changes,
+    )?;
+    Ok(changes)
+}
+
+fn get<'t>(items: &'t KeyValuePairs, key: &str) -> Option<&'t Item> {
+    items
+        .get(key)
+        .map(|kv| &kv.value)
+        .filter(|item| !item.is_none())
+}
+
+fn diff(
+    source: &KeyValuePairs,
+    target: &KeyValuePairs,
+    path: &mut KeyPath,
+    changes: &mut Vec<Change>,
+) -> std::result::Result<(), EditError> {
+    for (key, kv) in source.iter().filter(|(_, kv)| !kv.value.is_none()) {
+        path.push(Key::with_repr(key.clone(), kv.key.clone()));
+        match get(target, key) {
+            None => changes.push(Change::Remove(path.clone())),
+            Some(t) => diff_item(&kv.value, t, path, changes)?,
+        }
+        path.pop();
+    }
+    for (key, kv) in target.iter().filter(|(_, kv)| !kv.value.is_none()) {
+        if get(source, key).is_none() {
+            path.push(Key::with_repr(key.clone(), kv.key.clone()));
+            let mut item = kv.value.clone();
+            clear_positions(&mut item);
+            changes.push(Change::Insert(path.clone(), item));
+            path.pop();
+        }
+    }
+    Ok(())
+}
+
+fn diff_item(
+    source: &Item,
+    target: &Item,
+    path: &mut KeyPath,
+    changes: &mut Vec<Change>,
+) -> std::result::Result<(), EditError> {
+    if same_item(source, target) {
+        return Ok(());
+    }
+    match (source, target) {
+        (Item::Table(s), Item::Table(t)) => diff(&s.items, &t.items, path, changes)?,
+        (Item::Value(Value::InlineTable(s)), Item::Value(Value::InlineTable(t))) => {
+            diff(&s.items, &t.items, path, changes)?
+        }
+        (Item::ArrayOfTables(s), Item::ArrayOfTables(t)) => {
+            let mut array = s.clone();
+            array.values.truncate(t.len());
+            for (i, t) in t.iter().enumerate() {
+                match array.get_mut(i) {
+                    Some(s) => {
+                        let mut edits = Vec::new();
+                        diff(&s.items, &t.items, &mut KeyPath::new(), &mut edits)?;
+                        for edit in &edits {
+                            transaction::apply(s, edit.to_edit())?;
+                        }
+                    }
+                    None => {
+                        let mut item = Item::Table(t.clone());
+                        clear_positions(&mut item);
+                        array.values.push(item);
+                    }
+                }
+            }
+            changes.push(Change::Update(path.clone(), Item::ArrayOfTables(array)));
+        }
+        _ => {
+            let mut item = target.clone();
+            clear_positions(&mut item);
+            changes.push(Change::Update(path.clone(), item));
+        }
+    }
+    Ok(())
+}
+
+impl Document {
+    /// Applies the edits returned by `reconcile`, all at once or none of them,
+    /// like `Document::transaction`.
+    pub fn apply_changes(
+        &mut self,
+        changes: &[Change],
+    ) -> std::result::Result<(), Vec<(usize, EditError)>> {
+        self.transaction(|tx| {
+            for change in changes {
+                match *change {
+                    Change::Insert(ref path, ref item) | Change::Update(ref path, ref item) => {
+                        tx.set(path.clone(), item.clone());
+                    }
+                    Change::Remove(ref path) => {
+                        tx.remove(path.clone());
+                    }
+                }
+            }
+        })
+    }
+}
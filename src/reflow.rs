@@ -0,0 +1,202 @@
+use crate::decor::{Decor, InternalString};
+use crate::formatted;
+use crate::table::{Item, Table};
+use crate::value::{Array, InlineTable, Value};
+
+/// A formatting pass which wraps and joins arrays and inline tables
+/// according to the width of the lines they are on.
+///
+/// Arrays which don't fit into `max_width` columns are written with an element per line,
+/// arrays which fit are joined into a single line.
+/// Inline tables which don't fit are written as standard tables,
+/// inline tables which fit are joined into a single line.
+/// Arrays and inline tables containing comments are kept as they are.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "parse")]
+/// # fn main() {
+/// use toml_edit::{Document, Reflow};
+///
+/// let mut doc = r#"
+/// features = ["derive", "std", "alloc", "unstable"]
+/// authors = [
+///     "Andronik",
+/// ]
+/// "#.parse::<Document>().unwrap();
+///
+/// Reflow::new().max_width(40).apply(doc.as_table_mut());
+///
+/// assert_eq!(doc.to_string(), r#"
+/// features = [
+///     "derive",
+///     "std",
+///     "alloc",
+///     "unstable",
+/// ]
+/// authors = ["Andronik"]
+/// "#);
+/// # }
+/// # #[cfg(not(feature = "parse"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Reflow {
+    max_width: usize,
+    indent: InternalString,
+}
+
+impl Default for Reflow {
+    fn default() -> Self {
+        Self {
+            max_width: 100,
+            indent: InternalString::from("    "),
+        }
+    }
+}
+
+impl Reflow {
+    /// Creates the default settings: 100 columns and an indentation of four spaces.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the maximum width of a line.
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Sets the indentation of the elements of wrapped arrays,
+    /// e.g. the one detected by `Document::detect_style`.
+    pub fn indent(mut self, indent: &str) -> Self {
+        self.indent = indent.into();
+        self
+    }
+
+    /// Applies the reflow to `table` and its subtables.
+    pub fn apply(&self, table: &mut Table) {
+        self.apply_with_path(table, &mut Vec::new());
+    }
+
+    // `path` contains the keys of the enclosing dotted tables
+    fn apply_with_path(&self, table: &mut Table, path: &mut Vec<InternalString>) {
+        for (_, kv) in table.items.iter_mut() {
+            let key = &mut kv.key;
+            match kv.value {
+                Item::Value(ref mut v) if is_commented(v) => {}
+                Item::Value(Value::Array(ref mut a)) => {
+                    let compact = compact_array(a);
+                    let line_indent = last_line(&key.decor.prefix);
+                    let width = line_indent.chars().count()
+                        + path.iter().map(|k| k.chars().count() + 1).sum::<usize>()
+                        + key.raw_value.chars().count()
+                        + key.decor.suffix.chars().count()
+                        + "=".len()
+                        + compact.to_string().chars().count();
+                    *a = if width <= self.max_width {
+                        compact
+                    } else {
+                        self.wrap_array(compact, line_indent)
+                    };
+                }
+                Item::Value(Value::InlineTable(ref mut t)) => {
+                    let mut compact = t.clone();
+                    compact_inline_table(&mut compact);
+                    let width = last_line(&key.decor.prefix).chars().count()
+                        + path.iter().map(|k| k.chars().count() + 1).sum::<usize>()
+                        + key.raw_value.chars().count()
+                        + key.decor.suffix.chars().count()
+                        + "=".len()
+                        + compact.to_string().chars().count();
+                    if width <= self.max_width {
+                        *t = compact;
+                        continue;
+                    }
+                    let mut table = std::mem::take(t).into_table();
+                    // keep comments preceding the key
+                    if !key.decor.prefix.trim().is_empty() {
+                        table.decor = Decor::new(
+                            format!("\n{}", key.decor.prefix.trim_start_matches('\n')),
+                            String::new(),
+                        );
+                    }
+                    self.apply(&mut table);
+                    kv.value = Item::Table(table);
+                }
+                Item::Table(ref mut t) if t.dotted => {
+                    path.push(key.raw_value.clone());
+                    self.apply_with_path(t, path);
+                    path.pop();
+                }
+                Item::Table(ref mut t) => self.apply(t),
+                Item::ArrayOfTables(ref mut a) => {
+                    for t in a.values.iter_mut().filter_map(Item::as_table_mut) {
+                        self.apply(t);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn wrap_array(&self, mut array: Array, line_indent: &str) -> Array {
+        let prefix = format!("\n{}{}", line_indent, self.indent);
+        for value in array.values.iter_mut().filter_map(Item::as_value_mut) {
+            formatted::decorate(value, &prefix, "");
+        }
+        array.trailing_comma = true;
+        array.trailing = format!("\n{}", line_indent).into();
+        array
+    }
+}
+
+// Returns the indentation of the last line of a key's prefix.
+fn last_line(prefix: &str) -> &str {
+    prefix.rsplit('\n').next().unwrap_or_default()
+}
+
+pub(crate) fn is_commented(value: &Value) -> bool {
+    match *value {
+        Value::Array(ref a) => a.trailing.contains('#') || a.iter().any(is_commented_element),
+        Value::InlineTable(ref t) => {
+            t.preamble.contains('#') || t.iter().any(|(_, v)| is_commented_element(v))
+        }
+        _ => false,
+    }
+}
+
+pub(crate) fn is_commented_element(value: &Value) -> bool {
+    let decor = value.decor();
+    decor.prefix().contains('#') || decor.suffix().contains('#') || is_commented(value)
+}
+
+pub(crate) fn compact(value: &mut Value) {
+    match *value {
+        Value::Array(ref mut a) => *a = compact_array(a),
+        Value::InlineTable(ref mut t) => compact_inline_table(t),
+        _ => {}
+    }
+}
+
+pub(crate) fn compact_array(array: &Array) -> Array {
+    let mut array = array.clone();
+    for value in array.values.iter_mut().filter_map(Item::as_value_mut) {
+        compact(value);
+    }
+    formatted::decorate_array(&mut array);
+    array.trailing_comma = false;
+    array.trailing = InternalString::new();
+    array
+}
+
+fn compact_inline_table(table: &mut InlineTable) {
+    for (_, kv) in table.items.iter_mut() {
+        if let Item::Value(ref mut v) = kv.value {
+            compact(v);
+        }
+    }
+    table.preamble = InternalString::new();
+    formatted::decorate_inline_table(table);
+}
@@ -0,0 +1,793 @@
+//! Serializing Rust structures into TOML documents using [serde](https://serde.rs).
+//!
+//! The layout of the output is controlled by `Settings`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use serde::Serialize;
+//! use toml_edit::ser::{self, Settings, StringStyle};
+//!
+//! #[derive(Serialize)]
+//! struct Package {
+//!     name: &'static str,
+//!     keywords: Vec<&'static str>,
+//! }
+//!
+//! let package = Package {
+//!     name: "toml_edit",
+//!     keywords: vec!["toml", "edit"],
+//! };
+//! let settings = Settings::new()
+//!     .string_style(StringStyle::Literal)
+//!     .multiline_array_threshold(Some(1))
+//!     .indent("  ");
+//! let expected = r#"name = 'toml_edit'
+//! keywords = [
+//!   'toml',
+//!   'edit',
+//! ]
+//! "#;
+//! assert_eq!(ser::to_string_with(&package, &settings).unwrap(), expected);
+//! ```
+
+use crate::array_of_tables::ArrayOfTables;
+use crate::decor::{Formatted, InternalString, Repr};
+use crate::document::Document;
+use crate::formatted::{self, to_key_value};
+use crate::key::Key;
+use crate::table::{Item, KeyValuePairs, Table, TableKeyValue};
+use crate::value::{Array, InlineTable, Value};
+use serde::ser::{self, Serialize};
+use std::cell::Cell;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+
+/// Type representing a serialization error.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Error {
+    message: String,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self {
+            message: msg.to_string(),
+        }
+    }
+}
+
+fn error<T: Display>(msg: T) -> Error {
+    ser::Error::custom(msg)
+}
+
+/// Preferred quoting of serialized strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StringStyle {
+    /// Literal strings for strings that would need escaping otherwise, basic strings for the rest.
+    #[default]
+    Auto,
+    /// Basic strings (`"..."`) only.
+    Basic,
+    /// Literal strings (`'...'`) where possible, basic strings for the rest.
+    Literal,
+}
+
+/// Handling of serialized infinite and not-a-number floats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NonFiniteFloats {
+    /// Serializing them is an error.
+    #[default]
+    Error,
+    /// They are written as `inf`, `-inf` and `nan`.
+    Emit,
+}
+
+/// Handling of serialized missing values, e.g. `Option::None`, `()` or a YAML `null`.
+///
+/// Missing values written as strings are read back as missing values
+/// with the same policy, see `de::Settings::nulls`.
+///
+/// # Example
+///
+/// ```rust
+/// use serde::{Deserialize, Serialize};
+/// use toml_edit::{de, ser};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Config {
+///     name: Option<String>,
+///     tags: Vec<Option<String>>,
+/// }
+///
+/// let config = Config { name: None, tags: vec![Some("a".into()), None] };
+/// assert!(ser::to_string(&config).is_err());
+///
+/// let nulls = ser::Nulls::Sentinel("none");
+/// let s = ser::to_string_with(&config, &ser::Settings::new().nulls(nulls)).unwrap();
+/// assert_eq!(s, "name = \"none\"\ntags = [\"a\", \"none\"]\n");
+/// let back: Config = de::from_str_with(&s, &de::Settings::new().nulls(nulls)).unwrap();
+/// assert_eq!(back, config);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Nulls {
+    /// They are left out of tables, within arrays they are an error.
+    #[default]
+    Skip,
+    /// Serializing them is an error.
+    Error,
+    /// They are written as empty strings.
+    EmptyString,
+    /// They are written as the given string, e.g. `"none"`.
+    Sentinel(&'static str),
+}
+
+impl Nulls {
+    // The string missing values are written as, if any.
+    pub(crate) fn sentinel(self) -> Option<&'static str> {
+        match self {
+            Nulls::Skip | Nulls::Error => None,
+            Nulls::EmptyString => Some(""),
+            Nulls::Sentinel(s) => Some(s),
+        }
+    }
+}
+
+/// Settings controlling the layout of serialized documents.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    array_of_tables: bool,
+    string_style: StringStyle,
+    multiline_array_threshold: Option<usize>,
+    indent: InternalString,
+    non_finite_floats: NonFiniteFloats,
+    nulls: Nulls,
+    // whether YAML tags are an error, see `yaml::Tags`
+    pub(crate) reject_tags: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            array_of_tables: true,
+            string_style: StringStyle::Auto,
+            multiline_array_threshold: None,
+            indent: InternalString::from("    "),
+            non_finite_floats: NonFiniteFloats::Error,
+            nulls: Nulls::Skip,
+            reject_tags: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Creates the default settings, which produce a compact output.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates the settings used by `to_string_pretty`,
+    /// arrays with more than one element are written one element per line.
+    pub fn pretty() -> Self {
+        Self::new().multiline_array_threshold(Some(1))
+    }
+
+    /// Sets whether arrays of tables are written as `[[header]]` sections (the default)
+    /// or as inline arrays of inline tables.
+    pub fn array_of_tables(mut self, enabled: bool) -> Self {
+        self.array_of_tables = enabled;
+        self
+    }
+
+    /// Sets the preferred quoting of strings, `StringStyle::Auto` by default.
+    pub fn string_style(mut self, style: StringStyle) -> Self {
+        self.string_style = style;
+        self
+    }
+
+    /// Arrays with more elements than `threshold` are written one element per line,
+    /// `None` (the default) keeps all arrays on a single line.
+    pub fn multiline_array_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.multiline_array_threshold = threshold;
+        self
+    }
+
+    /// Sets the indentation of elements of multiline arrays, four spaces by default.
+    pub fn indent(mut self, indent: &str) -> Self {
+        self.indent = indent.into();
+        self
+    }
+
+    /// Sets the handling of infinite and not-a-number floats, `NonFiniteFloats::Error` by default.
+    pub fn non_finite_floats(mut self, policy: NonFiniteFloats) -> Self {
+        self.non_finite_floats = policy;
+        self
+    }
+
+    /// Sets the handling of missing values, `Nulls::Skip` by default.
+    pub fn nulls(mut self, policy: Nulls) -> Self {
+        self.nulls = policy;
+        self
+    }
+}
+
+/// Serializes `value` into a document with the default settings.
+pub fn to_document<T: Serialize + ?Sized>(value: &T) -> Result<Document, Error> {
+    to_document_with(value, &Settings::default())
+}
+
+/// Serializes `value` into a document with the given settings.
+///
+/// Only types serializing to a map, e.g. structs, can be serialized into a document.
+pub fn to_document_with<T: Serialize + ?Sized>(
+    value: &T,
+    settings: &Settings,
+) -> Result<Document, Error> {
+    let root = match value.serialize(ValueSerializer {
+        settings,
+        collected: None,
+    })? {
+        Some(Value::InlineTable(t)) => t,
+        _ => {
+            return Err(error(
+                "only maps and structs can be serialized as a document",
+            ))
+        }
+    };
+    let mut doc = Document::new();
+    *doc.as_table_mut() = to_table(root, settings);
+    // don't start the document with an empty line
+    let root = doc.as_table_mut();
+    if root.values_len() == 0 {
+        if let Some(first) = root.items.iter_mut().find_map(|(_, kv)| match kv.value {
+            Item::Table(ref mut t) => Some(t),
+            Item::ArrayOfTables(ref mut a) => a.get_mut(0),
+            _ => None,
+        }) {
+            first.decor.prefix.clear();
+        }
+    }
+    Ok(doc)
+}
+
+/// Serializes `value` into a string with the default settings.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    to_string_with(value, &Settings::default())
+}
+
+/// Serializes `value` into a string with `Settings::pretty`.
+pub fn to_string_pretty<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    to_string_with(value, &Settings::pretty())
+}
+
+/// Serializes `value` into a string with the given settings.
+pub fn to_string_with<T: Serialize + ?Sized>(
+    value: &T,
+    settings: &Settings,
+) -> Result<String, Error> {
+    to_document_with(value, settings).map(|doc| doc.to_string())
+}
+
+/// Serializes `value` into an item with the default settings.
+pub fn to_item<T: Serialize + ?Sized>(value: &T) -> Result<Item, Error> {
+    to_item_with(value, &Settings::default())
+}
+
+/// Serializes `value` into an item with the given settings, to be inserted
+/// at any position of an existing document.
+///
+/// Maps and structs become tables and sequences of them arrays of tables, like
+/// in `to_document_with`, other values become values. A missing value,
+/// e.g. `Option::None`, becomes `Item::None`.
+///
+/// # Example
+/// ```rust
+/// # use serde::Serialize;
+/// # use toml_edit::{ser, Document};
+/// #[derive(Serialize)]
+/// struct Dependency {
+///     version: &'static str,
+///     features: Vec<&'static str>,
+/// }
+///
+/// let mut doc = "[package]\nname = 'hello'\n\n[dependencies]\n".parse::<Document>().unwrap();
+/// let serde = Dependency { version: "1.0", features: vec!["derive"] };
+/// doc["dependencies"]["serde"] = ser::to_item(&serde).unwrap();
+/// doc["package"]["edition"] = ser::to_item(&2018).unwrap();
+/// assert_eq!(doc.to_string(), r#"[package]
+/// name = 'hello'
+/// edition = 2018
+///
+/// [dependencies]
+///
+/// [dependencies.serde]
+/// version = "1.0"
+/// features = ["derive"]
+/// "#);
+/// ```
+pub fn to_item_with<T: Serialize + ?Sized>(value: &T, settings: &Settings) -> Result<Item, Error> {
+    match value.serialize(ValueSerializer {
+        settings,
+        collected: None,
+    })? {
+        Some(value) => Ok(to_item_of(value, settings)),
+        None => Ok(Item::None),
+    }
+}
+
+/// Serializes `value` into a value with the default settings.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, Error> {
+    to_value_with(value, &Settings::default())
+}
+
+/// Serializes `value` into a value with the given settings,
+/// maps and structs become inline tables.
+pub fn to_value_with<T: Serialize + ?Sized>(
+    value: &T,
+    settings: &Settings,
+) -> Result<Value, Error> {
+    value
+        .serialize(ValueSerializer {
+            settings,
+            collected: None,
+        })?
+        .ok_or_else(|| error("a missing value can't be serialized as a value"))
+}
+
+// Converts an inline table into a standard table,
+// nested inline tables become subtables.
+fn to_table(inline: InlineTable, settings: &Settings) -> Table {
+    let mut table = Table::new();
+    for (key, kv) in inline.items {
+        let value = match kv.value {
+            Item::Value(v) => to_item_of(v, settings),
+            v => v,
+        };
+        let key_repr = formatted::key_repr(&kv.key.raw_value);
+        table.items.insert(key, TableKeyValue::new(key_repr, value));
+    }
+    table
+}
+
+// Converts a value into the item of a key/value pair, inline tables become subtables
+// and arrays of inline tables become arrays of tables, depending on the settings.
+fn to_item_of(value: Value, settings: &Settings) -> Item {
+    match value {
+        Value::InlineTable(t) => Item::Table(to_table(t, settings)),
+        Value::Array(a) if settings.array_of_tables && is_array_of_tables(&a) => {
+            let mut array = ArrayOfTables::new();
+            for value in a.values {
+                if let Item::Value(Value::InlineTable(t)) = value {
+                    array.append(to_table(t, settings));
+                }
+            }
+            Item::ArrayOfTables(array)
+        }
+        v => Item::Value(formatted::decorated(v, " ", "")),
+    }
+}
+
+fn is_array_of_tables(array: &Array) -> bool {
+    !array.is_empty() && array.iter().all(Value::is_inline_table)
+}
+
+fn to_string_value(s: &str, style: StringStyle) -> Value {
+    let needs_escaping = s.chars().any(|c| c == '"' || c == '\\');
+    let literal = !s.chars().any(|c| c == '\'' || c.is_control());
+    let raw = match style {
+        StringStyle::Literal if literal => format!("'{}'", s),
+        StringStyle::Auto if literal && needs_escaping => format!("'{}'", s),
+        _ => formatted::to_basic_string(s),
+    };
+    Value::String(Formatted::with_repr(s.to_owned(), Repr::new("", &raw, "")))
+}
+
+fn to_null(settings: &Settings) -> Result<Option<Value>, Error> {
+    match settings.nulls {
+        Nulls::Skip => Ok(None),
+        Nulls::Error => Err(error("missing values can't be represented in TOML")),
+        Nulls::EmptyString | Nulls::Sentinel(_) => Ok(settings
+            .nulls
+            .sentinel()
+            .map(|s| to_string_value(s, settings.string_style))),
+    }
+}
+
+fn to_float_value(f: f64, policy: NonFiniteFloats) -> Result<Value, Error> {
+    if !f.is_finite() {
+        return match policy {
+            NonFiniteFloats::Error => Err(error(format!("{} can't be represented in TOML", f))),
+            NonFiniteFloats::Emit => Ok(Value::from(f)),
+        };
+    }
+    Ok(formatted::float_value(f))
+}
+
+fn to_array(values: Vec<Value>, settings: &Settings) -> Result<Array, Error> {
+    let mut array = Array::default();
+    for value in values {
+        array
+            .push_formatted(value)
+            .map_err(|v| error(format!("mixed types in array: {}", v)))?;
+    }
+    match settings.multiline_array_threshold {
+        Some(threshold) if array.len() > threshold => {
+            let prefix = format!("\n{}", settings.indent);
+            for value in array.values.iter_mut().filter_map(Item::as_value_mut) {
+                formatted::decorate(value, &prefix, "");
+            }
+            array.trailing_comma = true;
+            array.trailing = "\n".into();
+        }
+        _ => formatted::decorate_array(&mut array),
+    }
+    Ok(array)
+}
+
+fn to_inline_table(items: Vec<(String, Value)>) -> Result<InlineTable, Error> {
+    let mut pairs = KeyValuePairs::new();
+    for (key, value) in items {
+        let parsed = Key::from_unquoted(&key);
+        if pairs.contains_key(parsed.get()) {
+            return Err(error(format!("duplicate key `{}`", key)));
+        }
+        pairs.insert(parsed.get().into(), to_key_value(parsed.raw(), value));
+    }
+    let mut table = InlineTable {
+        items: pairs,
+        ..Default::default()
+    };
+    formatted::decorate_inline_table(&mut table);
+    Ok(table)
+}
+
+fn to_variant(variant: &'static str, value: Value) -> Result<Value, Error> {
+    to_inline_table(vec![(variant.into(), value)]).map(Value::InlineTable)
+}
+
+// `None` represents a missing value, e.g. `Option::None`.
+struct ValueSerializer<'s> {
+    settings: &'s Settings,
+    // set if the value is written with `collect_str`
+    collected: Option<&'s Cell<bool>>,
+}
+
+macro_rules! serialize_integer {
+    ($($name:ident($ty:ty)),*) => {
+        $(
+            fn $name(self, v: $ty) -> Result<Self::Ok, Error> {
+                i64::try_from(v)
+                    .map(|i| Some(Value::from(i)))
+                    .map_err(|_| error(format!("{} is out of range for a TOML integer", v)))
+            }
+        )*
+    };
+}
+
+impl<'s> ser::Serializer for ValueSerializer<'s> {
+    type Ok = Option<Value>;
+    type Error = Error;
+    type SerializeSeq = SerializeArray<'s>;
+    type SerializeTuple = SerializeArray<'s>;
+    type SerializeTupleStruct = SerializeArray<'s>;
+    type SerializeTupleVariant = SerializeArray<'s>;
+    type SerializeMap = SerializeTable<'s>;
+    type SerializeStruct = SerializeTable<'s>;
+    type SerializeStructVariant = SerializeTable<'s>;
+
+    serialize_integer!(
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128)
+    );
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Error> {
+        Ok(Some(Value::from(v)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Error> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Error> {
+        to_float_value(v, self.settings.non_finite_floats).map(Some)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Error> {
+        Ok(Some(to_string_value(v, self.settings.string_style)))
+    }
+
+    fn collect_str<T: Display + ?Sized>(self, value: &T) -> Result<Self::Ok, Error> {
+        if let Some(collected) = self.collected {
+            collected.set(true);
+        }
+        self.serialize_str(&value.to_string())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Error> {
+        let values = v.iter().map(|&b| Value::from(i64::from(b))).collect();
+        to_array(values, self.settings).map(|a| Some(Value::Array(a)))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Error> {
+        to_null(self.settings)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Error> {
+        to_null(self.settings)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Error> {
+        to_null(self.settings)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Error> {
+        match value.serialize(self)? {
+            Some(value) => to_variant(variant, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Ok(SerializeArray {
+            settings: self.settings,
+            values: Vec::with_capacity(len.unwrap_or(0)),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        let mut array = self.serialize_seq(Some(len))?;
+        array.variant = Some(variant);
+        Ok(array)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(SerializeTable {
+            settings: self.settings,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+            variant: None,
+            keys: 0,
+            tag: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        let mut table = self.serialize_map(Some(len))?;
+        table.variant = Some(variant);
+        Ok(table)
+    }
+}
+
+struct SerializeArray<'s> {
+    settings: &'s Settings,
+    values: Vec<Value>,
+    variant: Option<&'static str>,
+}
+
+impl<'s> SerializeArray<'s> {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        match value.serialize(ValueSerializer {
+            settings: self.settings,
+            collected: None,
+        })? {
+            Some(value) => {
+                self.values.push(value);
+                Ok(())
+            }
+            None => Err(error("arrays can't contain missing values")),
+        }
+    }
+
+    fn finish(self) -> Result<Option<Value>, Error> {
+        let array = Value::Array(to_array(self.values, self.settings)?);
+        match self.variant {
+            Some(variant) => to_variant(variant, array).map(Some),
+            None => Ok(Some(array)),
+        }
+    }
+}
+
+macro_rules! serialize_array {
+    ($($trait:ident::$method:ident),*) => {
+        $(
+            impl<'s> ser::$trait for SerializeArray<'s> {
+                type Ok = Option<Value>;
+                type Error = Error;
+
+                fn $method<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+                    self.push(value)
+                }
+
+                fn end(self) -> Result<Self::Ok, Error> {
+                    self.finish()
+                }
+            }
+        )*
+    };
+}
+
+serialize_array!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field
+);
+
+struct SerializeTable<'s> {
+    settings: &'s Settings,
+    items: Vec<(String, Value)>,
+    key: Option<String>,
+    variant: Option<&'static str>,
+    // the number of keys, and the first one if it's a rejected YAML tag
+    keys: usize,
+    tag: Option<String>,
+}
+
+impl<'s> SerializeTable<'s> {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), Error> {
+        if let Some(value) = value.serialize(ValueSerializer {
+            settings: self.settings,
+            collected: None,
+        })? {
+            self.items.push((key, value));
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Option<Value>, Error> {
+        if let (Some(tag), 1) = (self.tag, self.keys) {
+            return Err(error(format!(
+                "the tagged value `{}` can't be represented in TOML",
+                tag
+            )));
+        }
+        let table = Value::InlineTable(to_inline_table(self.items)?);
+        match self.variant {
+            Some(variant) => to_variant(variant, table).map(Some),
+            None => Ok(Some(table)),
+        }
+    }
+}
+
+impl<'s> ser::SerializeMap for SerializeTable<'s> {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let collected = Cell::new(false);
+        let key = match key.serialize(ValueSerializer {
+            settings: self.settings,
+            collected: Some(&collected),
+        })? {
+            Some(Value::String(s)) => s.value().clone(),
+            Some(Value::Integer(i)) => i.value().to_string(),
+            Some(Value::Boolean(b)) => b.value().to_string(),
+            _ => return Err(error("keys must be strings, integers or booleans")),
+        };
+        // `serde_yaml` serializes a tagged value as a single-entry map
+        // whose key is the tag, written with `collect_str`
+        self.tag = Some(key.clone()).filter(|key| {
+            self.keys == 0 && collected.get() && key.starts_with('!') && self.settings.reject_tags
+        });
+        self.keys += 1;
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| error("serialize_value called before serialize_key"))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        self.finish()
+    }
+}
+
+macro_rules! serialize_struct {
+    ($($trait:ident),*) => {
+        $(
+            impl<'s> ser::$trait for SerializeTable<'s> {
+                type Ok = Option<Value>;
+                type Error = Error;
+
+                fn serialize_field<T: Serialize + ?Sized>(
+                    &mut self,
+                    key: &'static str,
+                    value: &T,
+                ) -> Result<(), Error> {
+                    self.insert(key.into(), value)
+                }
+
+                fn end(self) -> Result<Self::Ok, Error> {
+                    self.finish()
+                }
+            }
+        )*
+    };
+}
+
+serialize_struct!(SerializeStruct, SerializeStructVariant);
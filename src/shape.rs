@@ -0,0 +1,368 @@
+use crate::comments::join;
+use crate::table::Item;
+use crate::value::Value;
+use std::fmt::{Display, Formatter, Result};
+
+/// The expected structure of an item, see `Item::matches_shape`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// Any item.
+    Any,
+    /// A string.
+    String,
+    /// An integer.
+    Integer,
+    /// A float.
+    Float,
+    /// A boolean.
+    Boolean,
+    /// A date-time.
+    DateTime,
+    /// An array or an array of tables whose elements match the shape.
+    Array(Box<Shape>),
+    /// A table or an inline table with the given keys.
+    Table(TableShape),
+    /// An item matching one of the shapes.
+    OneOf(Vec<Shape>),
+}
+
+impl Shape {
+    /// Creates the shape of arrays whose elements match `element`.
+    pub fn array(element: Shape) -> Self {
+        Shape::Array(Box::new(element))
+    }
+
+    /// Creates the shape of items matching one of `shapes`.
+    pub fn one_of(shapes: Vec<Shape>) -> Self {
+        Shape::OneOf(shapes)
+    }
+
+    fn describe(&self) -> String {
+        match *self {
+            Shape::Any => "any item".to_owned(),
+            Shape::String => "a string".to_owned(),
+            Shape::Integer => "an integer".to_owned(),
+            Shape::Float => "a float".to_owned(),
+            Shape::Boolean => "a boolean".to_owned(),
+            Shape::DateTime => "a date-time".to_owned(),
+            Shape::Array(_) => "an array".to_owned(),
+            Shape::Table(_) => "a table".to_owned(),
+            Shape::OneOf(ref shapes) => shapes
+                .iter()
+                .map(Shape::describe)
+                .collect::<Vec<_>>()
+                .join(" or "),
+        }
+    }
+}
+
+impl From<TableShape> for Shape {
+    fn from(table: TableShape) -> Self {
+        Shape::Table(table)
+    }
+}
+
+/// The expected keys of a table, see `Shape::Table`.
+///
+/// Keys which are neither required nor optional are allowed unless
+/// `deny_unknown_keys` is set.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TableShape {
+    // the keys with their shapes, and whether they are required
+    keys: Vec<(String, Shape, bool)>,
+    deny_unknown_keys: bool,
+}
+
+impl TableShape {
+    /// Creates the shape of tables with any keys.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Requires the key, with a value matching the shape.
+    pub fn required(mut self, key: &str, shape: impl Into<Shape>) -> Self {
+        self.keys.push((key.to_owned(), shape.into(), true));
+        self
+    }
+
+    /// Allows the key, with a value matching the shape.
+    pub fn optional(mut self, key: &str, shape: impl Into<Shape>) -> Self {
+        self.keys.push((key.to_owned(), shape.into(), false));
+        self
+    }
+
+    /// Sets whether keys which are neither required nor optional are mismatches.
+    pub fn deny_unknown_keys(mut self, yes: bool) -> Self {
+        self.deny_unknown_keys = yes;
+        self
+    }
+}
+
+/// The kind of a `ShapeMismatch`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum MismatchKind {
+    /// The item is not of the expected type.
+    Type,
+    /// A required key is missing.
+    Missing,
+    /// A key is neither required nor optional, see `TableShape::deny_unknown_keys`.
+    Unknown,
+}
+
+/// A part of an item which doesn't match a `Shape`, see `Item::matches_shape`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ShapeMismatch {
+    path: String,
+    kind: MismatchKind,
+    expected: String,
+    found: &'static str,
+}
+
+impl ShapeMismatch {
+    /// Returns the dotted key path of the mismatch relative to the checked item,
+    /// array indices are written as `[i]`, e.g. `bin[1].name`.
+    /// The path is empty if the checked item itself doesn't match.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the kind of the mismatch.
+    pub fn kind(&self) -> MismatchKind {
+        self.kind
+    }
+
+    /// Returns the description of the expected shape, e.g. `a string or an integer`,
+    /// empty for unknown keys.
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    /// Returns the description of the type found, e.g. `a table` or `nothing`
+    /// for missing keys.
+    pub fn found(&self) -> &str {
+        self.found
+    }
+}
+
+impl Display for ShapeMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self.kind {
+            MismatchKind::Type => write!(f, "expected {}, found {}", self.expected, self.found)?,
+            MismatchKind::Missing => write!(f, "missing {}", self.expected)?,
+            MismatchKind::Unknown => write!(f, "unknown key")?,
+        }
+        if !self.path.is_empty() {
+            write!(f, " at `{}`", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ShapeMismatch {}
+
+// items and values are checked alike
+#[derive(Clone, Copy)]
+enum Node<'a> {
+    Item(&'a Item),
+    Value(&'a Value),
+}
+
+impl<'a> Node<'a> {
+    fn value(self) -> Option<&'a Value> {
+        match self {
+            Node::Item(item) => item.as_value(),
+            Node::Value(value) => Some(value),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Node::Item(Item::None) => "nothing",
+            Node::Item(Item::Table(_)) => "a table",
+            Node::Item(Item::ArrayOfTables(_)) => "an array of tables",
+            Node::Item(Item::Value(v)) | Node::Value(v) => match *v {
+                Value::String(_) => "a string",
+                Value::Integer(_) => "an integer",
+                Value::Float(_) => "a float",
+                Value::Boolean(_) => "a boolean",
+                Value::DateTime(_) => "a date-time",
+                Value::Array(_) => "an array",
+                Value::InlineTable(_) => "an inline table",
+            },
+        }
+    }
+
+    fn elements(self) -> Option<Vec<Node<'a>>> {
+        match self {
+            Node::Item(Item::ArrayOfTables(a)) => Some(a.values.iter().map(Node::Item).collect()),
+            _ => match self.value() {
+                Some(Value::Array(a)) => Some(a.iter().map(Node::Value).collect()),
+                _ => None,
+            },
+        }
+    }
+
+    fn entries(self) -> Option<Vec<(&'a str, Node<'a>)>> {
+        match self {
+            Node::Item(Item::Table(t)) => Some(
+                t.iter()
+                    .filter(|(_, item)| !item.is_none())
+                    .map(|(k, item)| (k, Node::Item(item)))
+                    .collect(),
+            ),
+            _ => match self.value() {
+                Some(Value::InlineTable(t)) => {
+                    Some(t.iter().map(|(k, v)| (k, Node::Value(v))).collect())
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+fn mismatch(
+    path: &str,
+    kind: MismatchKind,
+    expected: String,
+    found: &'static str,
+) -> ShapeMismatch {
+    ShapeMismatch {
+        path: path.to_owned(),
+        kind,
+        expected,
+        found,
+    }
+}
+
+fn check(node: Node<'_>, shape: &Shape, path: &str, mismatches: &mut Vec<ShapeMismatch>) {
+    let matches = match (shape, node.value()) {
+        (Shape::Any, _) => true,
+        (Shape::String, Some(v)) => v.is_str(),
+        (Shape::Integer, Some(v)) => v.is_integer(),
+        (Shape::Float, Some(v)) => v.is_float(),
+        (Shape::Boolean, Some(v)) => v.is_bool(),
+        (Shape::DateTime, Some(v)) => v.is_date_time(),
+        (Shape::Array(ref element), _) => match node.elements() {
+            Some(elements) => {
+                for (i, e) in elements.into_iter().enumerate() {
+                    check(e, element, &format!("{}[{}]", path, i), mismatches);
+                }
+                true
+            }
+            None => false,
+        },
+        (Shape::Table(ref table), _) => match node.entries() {
+            Some(entries) => {
+                check_table(&entries, table, path, mismatches);
+                true
+            }
+            None => false,
+        },
+        // the mismatches of the closest alternative are not reported
+        (Shape::OneOf(ref shapes), _) => shapes.iter().any(|shape| {
+            let mut alternative = Vec::new();
+            check(node, shape, path, &mut alternative);
+            alternative.is_empty()
+        }),
+        _ => false,
+    };
+    if !matches {
+        mismatches.push(mismatch(
+            path,
+            MismatchKind::Type,
+            shape.describe(),
+            node.describe(),
+        ));
+    }
+}
+
+fn check_table(
+    entries: &[(&str, Node<'_>)],
+    table: &TableShape,
+    path: &str,
+    mismatches: &mut Vec<ShapeMismatch>,
+) {
+    for (key, shape, required) in &table.keys {
+        let path = join(path, key);
+        match entries.iter().find(|(k, _)| k == key) {
+            Some(&(_, node)) => check(node, shape, &path, mismatches),
+            None if *required => mismatches.push(mismatch(
+                &path,
+                MismatchKind::Missing,
+                shape.describe(),
+                "nothing",
+            )),
+            None => {}
+        }
+    }
+    if table.deny_unknown_keys {
+        for &(key, node) in entries {
+            if !table.keys.iter().any(|(k, _, _)| k == key) {
+                mismatches.push(mismatch(
+                    &join(path, key),
+                    MismatchKind::Unknown,
+                    String::new(),
+                    node.describe(),
+                ));
+            }
+        }
+    }
+}
+
+fn result(node: Node<'_>, shape: &Shape) -> std::result::Result<(), Vec<ShapeMismatch>> {
+    let mut mismatches = Vec::new();
+    check(node, shape, "", &mut mismatches);
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(mismatches)
+    }
+}
+
+impl Item {
+    /// Checks that the item has the expected structure, returning all the mismatches
+    /// with their paths otherwise, e.g. to validate a subtree before editing it.
+    ///
+    /// Integers don't match `Shape::Float`, and `Item::None` only matches `Shape::Any`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{Document, MismatchKind, Shape, TableShape};
+    /// let doc = r#"
+    /// [dependencies]
+    /// serde = { version = "1.0", features = "derive" }
+    /// log = { path = "../log" }
+    /// "#.parse::<Document>().unwrap();
+    ///
+    /// let dependency = TableShape::new()
+    ///     .required("version", Shape::String)
+    ///     .optional("features", Shape::array(Shape::String));
+    /// let dependencies = TableShape::new()
+    ///     .optional("serde", dependency.clone())
+    ///     .optional("log", dependency);
+    ///
+    /// let mismatches = doc["dependencies"].matches_shape(&dependencies.into()).unwrap_err();
+    /// assert_eq!(mismatches.len(), 2);
+    /// assert_eq!(
+    ///     mismatches[0].to_string(),
+    ///     "expected an array, found a string at `serde.features`"
+    /// );
+    /// assert_eq!(mismatches[1].kind(), MismatchKind::Missing);
+    /// assert_eq!(mismatches[1].to_string(), "missing a string at `log.version`");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn matches_shape(&self, shape: &Shape) -> std::result::Result<(), Vec<ShapeMismatch>> {
+        result(Node::Item(self), shape)
+    }
+}
+
+impl Value {
+    /// Checks that the value has the expected structure, see `Item::matches_shape`.
+    pub fn matches_shape(&self, shape: &Shape) -> std::result::Result<(), Vec<ShapeMismatch>> {
+        result(Node::Value(self), shape)
+    }
+}
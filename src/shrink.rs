@@ -0,0 +1,149 @@
+use crate::decor::{Decor, Formatted, InternalString, Repr};
+use crate::document::Document;
+use crate::table::{Item, KeyValuePairs, Table};
+use crate::value::Value;
+use std::collections::HashSet;
+use std::mem;
+
+impl Document {
+    /// Releases the memory over-allocated by edits, e.g. by strings which were appended to
+    /// or arrays from which values were removed, returning the number of bytes released
+    /// by strings and arrays.
+    ///
+    /// Strings and arrays are trimmed to their lengths and the empty ones don't keep
+    /// any allocation. The maps of tables are rebuilt with the capacity they need.
+    /// The document is displayed as before.
+    ///
+    /// Identical keys, representations and decor share a single string, e.g. the
+    /// indentation of the lines or the raw values repeated across a document. A shared
+    /// string is copied again when one of its nodes is edited.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::{Document, value};
+    ///
+    /// let mut doc = "a = [1, 2, 3]\n".parse::<Document>().unwrap();
+    /// for i in 0..100 {
+    ///     doc[format!("key{}", i).as_str()] = value(i);
+    /// }
+    /// doc.as_table_mut().retain(|key, _| key == "a");
+    /// doc["a"].as_array_mut().unwrap().remove(2);
+    /// doc.shrink_to_fit();
+    /// assert_eq!(doc.to_string(), "a = [1, 2]\n");
+    /// assert_eq!(doc.shrink_to_fit(), 0);
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn shrink_to_fit(&mut self) -> usize {
+        let mut strings = Strings::default();
+        let mut released = strings.shrink(&mut self.trailing);
+        if let Item::Table(ref mut t) = self.root {
+            released += strings.shrink_table(t);
+        }
+        released
+    }
+}
+
+// The strings met so far, shared by the nodes with the same text.
+#[derive(Default)]
+struct Strings(HashSet<InternalString>);
+
+impl Strings {
+    fn shrink(&mut self, s: &mut InternalString) -> usize {
+        if s.is_empty() {
+            let released = s.owned_capacity();
+            *s = InternalString::new();
+            return released;
+        }
+        if let Some(shared) = self.0.get(s.as_str()) {
+            let released = s.owned_capacity();
+            *s = shared.clone();
+            return released;
+        }
+        let released = s.shrink_to_fit();
+        self.0.insert(s.clone());
+        released
+    }
+
+    fn shrink_decor(&mut self, decor: &mut Decor) -> usize {
+        self.shrink(&mut decor.prefix) + self.shrink(&mut decor.suffix)
+    }
+
+    fn shrink_repr(&mut self, repr: &mut Repr) -> usize {
+        self.shrink_decor(&mut repr.decor) + self.shrink(&mut repr.raw_value)
+    }
+
+    fn shrink_formatted<T>(&mut self, f: &mut Formatted<T>) -> usize {
+        self.shrink_repr(&mut f.repr)
+    }
+
+    // Rebuilds the map with the capacity it needs, the keys are shrunk on the way.
+    fn shrink_items(&mut self, items: &mut KeyValuePairs) -> usize {
+        let mut released = 0;
+        let entries: Vec<_> = mem::take(items).into_iter().collect();
+        let mut shrunk = KeyValuePairs::with_capacity(entries.len());
+        for (mut key, mut kv) in entries {
+            released += self.shrink(&mut key)
+                + self.shrink_repr(&mut kv.key)
+                + self.shrink_item(&mut kv.value);
+            shrunk.insert(key, kv);
+        }
+        *items = shrunk;
+        released
+    }
+
+    fn shrink_table(&mut self, table: &mut Table) -> usize {
+        self.shrink_decor(&mut table.decor) + self.shrink_items(&mut table.items)
+    }
+
+    fn shrink_item(&mut self, item: &mut Item) -> usize {
+        match *item {
+            Item::None => 0,
+            Item::Value(ref mut v) => self.shrink_value(v),
+            Item::Table(ref mut t) => self.shrink_table(t),
+            Item::ArrayOfTables(ref mut a) => {
+                let released = shrink_vec(&mut a.values);
+                a.values
+                    .iter_mut()
+                    .map(|t| self.shrink_item(t))
+                    .sum::<usize>()
+                    + released
+            }
+        }
+    }
+
+    fn shrink_value(&mut self, value: &mut Value) -> usize {
+        match *value {
+            Value::String(ref mut f) => f.shrink_value() + self.shrink_formatted(f),
+            Value::Integer(ref mut f) => self.shrink_formatted(f),
+            Value::Float(ref mut f) => self.shrink_formatted(f),
+            Value::Boolean(ref mut f) => self.shrink_formatted(f),
+            Value::DateTime(ref mut f) => self.shrink_formatted(f),
+            Value::Array(ref mut a) => {
+                let released = self.shrink_decor(&mut a.decor)
+                    + self.shrink(&mut a.trailing)
+                    + shrink_vec(&mut a.values);
+                a.values
+                    .iter_mut()
+                    .map(|v| self.shrink_item(v))
+                    .sum::<usize>()
+                    + released
+            }
+            Value::InlineTable(ref mut t) => {
+                self.shrink_decor(&mut t.decor)
+                    + self.shrink(&mut t.preamble)
+                    + self.shrink_items(&mut t.items)
+            }
+        }
+    }
+}
+
+fn shrink_vec<T>(v: &mut Vec<T>) -> usize {
+    let capacity = v.capacity();
+    v.shrink_to_fit();
+    (capacity - v.capacity()) * mem::size_of::<T>()
+}
@@ -0,0 +1,231 @@
+use crate::comments::join;
+use crate::document::Document;
+use crate::fingerprint::hash_text;
+use crate::metadata::NodeId;
+use crate::table::{Item, Table};
+use crate::value::Value;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+// the first line of a side-car, followed by the hash of the document
+const HEADER: &str = "toml-edit-ids 1";
+
+/// Stable identifiers of the nodes of a document which, unlike `NodeId`s, can be saved
+/// in a side-car file next to the document and associated with the same nodes
+/// after parsing the unmodified document again, e.g. to key lint suppressions or
+/// review comments by node rather than by line.
+///
+/// Ids are assigned on demand and are kept by the nodes through edits, like `NodeId`s.
+/// The side-car records the location of every node with an id, e.g. `bin[1].name`,
+/// and a hash of the displayed document, not the ids themselves into the TOML.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "parse")]
+/// # fn main() {
+/// use toml_edit::{Document, StableIds};
+///
+/// let mut doc = "[package]\nname = 'a'\n".parse::<Document>().unwrap();
+/// let mut ids = StableIds::new();
+/// let id = ids.assign(doc["package"]["name"].node_id().unwrap()).to_owned();
+///
+/// // edits keep the ids, the side-car is written with the edited document
+/// let name = doc["package"].as_table_mut().unwrap().remove("name").unwrap();
+/// doc["name"] = name;
+/// let saved = doc.to_string();
+/// let sidecar = ids.to_sidecar(&doc);
+///
+/// let reparsed = saved.parse::<Document>().unwrap();
+/// let ids = StableIds::from_sidecar(&sidecar, &reparsed).unwrap();
+/// assert_eq!(ids.node(&id), reparsed["name"].node_id());
+///
+/// let modified = saved.replace("'a'", "'b'").parse::<Document>().unwrap();
+/// assert!(StableIds::from_sidecar(&sidecar, &modified).is_err());
+/// # }
+/// # #[cfg(not(feature = "parse"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StableIds {
+    ids: HashMap<NodeId, String>,
+    nodes: HashMap<String, NodeId>,
+    // the number of the next id to assign
+    next: u64,
+}
+
+impl StableIds {
+    /// Creates an empty set of ids.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the stable id of the node, assigning a new one if it has none.
+    pub fn assign(&mut self, node: NodeId) -> &str {
+        if !self.ids.contains_key(&node) {
+            let id = loop {
+                self.next += 1;
+                let id = format!("n{}", self.next);
+                if !self.nodes.contains_key(&id) {
+                    break id;
+                }
+            };
+            self.nodes.insert(id.clone(), node);
+            self.ids.insert(node, id);
+        }
+        &self.ids[&node]
+    }
+
+    /// Assigns stable ids to all the nodes of the document which have none.
+    pub fn assign_all(&mut self, doc: &Document) {
+        for (_, node) in locations(doc) {
+            self.assign(node);
+        }
+    }
+
+    /// Returns the stable id of the node.
+    pub fn get(&self, node: NodeId) -> Option<&str> {
+        self.ids.get(&node).map(String::as_str)
+    }
+
+    /// Returns the node with the stable id.
+    pub fn node(&self, id: &str) -> Option<NodeId> {
+        self.nodes.get(id).copied()
+    }
+
+    /// Returns the number of nodes with stable ids.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns true iff no node has a stable id.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Drops the ids of the nodes which are not in the document anymore.
+    pub fn retain_nodes(&mut self, doc: &Document) {
+        let nodes: std::collections::HashSet<NodeId> =
+            locations(doc).into_iter().map(|(_, node)| node).collect();
+        self.ids.retain(|node, _| nodes.contains(node));
+        self.nodes.retain(|_, node| nodes.contains(node));
+    }
+
+    /// Returns the side-car of the document: a line with the hash of `doc.to_string()`,
+    /// followed by a line per node of the document with a stable id,
+    /// the id and the location of the node separated by a tab.
+    ///
+    /// Nodes with ids which are not in the document are left out, and nodes found
+    /// at several locations, e.g. copies made with `Item::clone_keeping_ids`,
+    /// are written at the first one only, so that the side-car always reads back.
+    pub fn to_sidecar(&self, doc: &Document) -> String {
+        let mut sidecar = format!("{} {:016x}\n", HEADER, hash_text(&doc.to_string()));
+        let mut written = std::collections::HashSet::new();
+        for (location, node) in locations(doc) {
+            if let Some(id) = self.get(node).filter(|_| written.insert(node)) {
+                sidecar.push_str(id);
+                sidecar.push('\t');
+                sidecar.push_str(&location);
+                sidecar.push('\n');
+            }
+        }
+        sidecar
+    }
+
+    /// Reads a side-car written by `to_sidecar`, associating the ids with the nodes
+    /// of the document at the recorded locations.
+    ///
+    /// Returns an error if the side-car is malformed, or if it was written for
+    /// another document, including the same document with other formatting.
+    pub fn from_sidecar(sidecar: &str, doc: &Document) -> Result<Self, SidecarError> {
+        let mut lines = sidecar.lines().enumerate();
+        let hash = lines
+            .next()
+            .and_then(|(_, line)| line.strip_prefix(HEADER))
+            .and_then(|hash| u64::from_str_radix(hash.trim(), 16).ok())
+            .ok_or(SidecarError::Malformed(1))?;
+        if hash != hash_text(&doc.to_string()) {
+            return Err(SidecarError::Modified);
+        }
+        let nodes: HashMap<String, NodeId> = locations(doc).into_iter().collect();
+        let mut ids = StableIds::new();
+        for (i, line) in lines {
+            let (id, location) = match line.split_once('\t') {
+                Some((id, location)) if !id.is_empty() && !ids.nodes.contains_key(id) => {
+                    (id, location)
+                }
+                _ => return Err(SidecarError::Malformed(i + 1)),
+            };
+            let node = *nodes.get(location).ok_or(SidecarError::Malformed(i + 1))?;
+            if let Some(n) = id.strip_prefix('n').and_then(|n| n.parse::<u64>().ok()) {
+                ids.next = ids.next.max(n);
+            }
+            ids.ids.insert(node, id.to_owned());
+            ids.nodes.insert(id.to_owned(), node);
+        }
+        Ok(ids)
+    }
+}
+
+/// An error reading a side-car, see `StableIds::from_sidecar`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SidecarError {
+    /// The line with the given number, starting at 1, is malformed
+    /// or refers to a missing node.
+    Malformed(usize),
+    /// The side-car was written for another document.
+    Modified,
+}
+
+impl Display for SidecarError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            SidecarError::Malformed(line) => write!(f, "malformed side-car line {}", line),
+            SidecarError::Modified => write!(f, "the document was modified"),
+        }
+    }
+}
+
+impl std::error::Error for SidecarError {}
+
+// the locations of the nodes of the document, the root table at the empty location
+fn locations(doc: &Document) -> Vec<(String, NodeId)> {
+    let mut locations = Vec::new();
+    table_locations(doc.as_table(), String::new(), &mut locations);
+    locations
+}
+
+fn table_locations(table: &Table, location: String, locations: &mut Vec<(String, NodeId)>) {
+    locations.push((location.clone(), table.node_id()));
+    for (key, item) in table.iter() {
+        let location = join(&location, key);
+        match *item {
+            Item::None => {}
+            Item::Value(ref v) => value_locations(v, location, locations),
+            Item::Table(ref t) => table_locations(t, location, locations),
+            Item::ArrayOfTables(ref a) => {
+                locations.push((location.clone(), a.node_id()));
+                for (i, t) in a.iter().enumerate() {
+                    table_locations(t, format!("{}[{}]", location, i), locations);
+                }
+            }
+        }
+    }
+}
+
+fn value_locations(value: &Value, location: String, locations: &mut Vec<(String, NodeId)>) {
+    locations.push((location.clone(), value.node_id()));
+    match *value {
+        Value::Array(ref a) => {
+            for (i, v) in a.iter().enumerate() {
+                value_locations(v, format!("{}[{}]", location, i), locations);
+            }
+        }
+        Value::InlineTable(ref t) => {
+            for (key, v) in t.iter() {
+                value_locations(v, join(&location, key), locations);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,86 @@
+use crate::decor::{Decor, InternalString};
+use crate::formatted;
+use crate::table::{Item, KeyValuePairs, Table};
+use crate::value::Value;
+
+impl Item {
+    /// Returns a copy of the item with the default decor, that is without the comments
+    /// and the whitespaces of the original, e.g. to duplicate a template entry.
+    ///
+    /// The values are decorated as by `value`, the tables as by `Table::new`, and the
+    /// arrays and inline tables are auto formatted. The representations of the values,
+    /// e.g. literal strings or hexadecimal integers, are kept.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let mut doc = "[[bin]]  # original entry\nname = 'a'   # the name\n"
+    ///     .parse::<Document>()
+    ///     .unwrap();
+    /// let bin = doc["bin"].as_array_of_tables().unwrap().get(0).unwrap().clone();
+    /// let copy = toml_edit::Item::Table(bin).clone_stripped();
+    /// doc["bin"]
+    ///     .as_array_of_tables_mut()
+    ///     .unwrap()
+    ///     .append(copy.into_table().unwrap());
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "[[bin]]  # original entry\nname = 'a'   # the name\n\n[[bin]]\nname = 'a'\n"
+    /// );
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn clone_stripped(&self) -> Item {
+        let mut item = self.clone();
+        if let Item::Value(ref mut v) = item {
+            formatted::decorate(v, " ", "");
+        }
+        strip_item(&mut item);
+        item
+    }
+}
+
+fn strip_items(items: &mut KeyValuePairs) {
+    for (_, kv) in items.iter_mut() {
+        kv.key.decor = formatted::key_repr("").decor;
+        if let Item::Value(ref mut v) = kv.value {
+            formatted::decorate(v, " ", "");
+        }
+        strip_item(&mut kv.value);
+    }
+}
+
+fn strip_table(table: &mut Table) {
+    table.decor = Decor::new("\n", "");
+    strip_items(&mut table.items);
+}
+
+// strips the nested decor, the decor of a value is set by its container
+fn strip_item(item: &mut Item) {
+    match *item {
+        Item::None => {}
+        Item::Value(ref mut v) => strip_value(v),
+        Item::Table(ref mut t) => strip_table(t),
+        Item::ArrayOfTables(ref mut a) => a.values.iter_mut().for_each(strip_item),
+    }
+}
+
+fn strip_value(value: &mut Value) {
+    match *value {
+        Value::Array(ref mut a) => {
+            a.trailing = InternalString::new();
+            a.trailing_comma = false;
+            a.values.iter_mut().for_each(strip_item);
+            a.fmt();
+        }
+        Value::InlineTable(ref mut t) => {
+            t.preamble = InternalString::new();
+            strip_items(&mut t.items);
+            t.fmt();
+        }
+        _ => {}
+    }
+}
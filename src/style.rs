@@ -0,0 +1,429 @@
+use crate::decor::InternalString;
+use crate::document::Document;
+use crate::formatted;
+use crate::indent::indent_created;
+use crate::key_path::KeyPath;
+use crate::table::{Item, Table, TableKeyValue};
+use crate::transaction::Created;
+use crate::value::{Array, Value};
+
+/// The line endings of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Newline {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
+
+impl Newline {
+    /// Returns the characters ending a line.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        }
+    }
+}
+
+/// The formatting conventions of a document, see `Document::detect_style`.
+///
+/// The default style is the one of the items created through the API:
+/// four spaces of indentation, `\n` line endings, `key = value`,
+/// `[1, 2]` for single-line arrays and a trailing comma in multi-line arrays.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StyleReport {
+    indent: String,
+    newline: Newline,
+    before_eq: String,
+    after_eq: String,
+    array_padding: bool,
+    array_trailing_comma: bool,
+}
+
+impl Default for StyleReport {
+    fn default() -> Self {
+        Self {
+            indent: "    ".to_owned(),
+            newline: Newline::Lf,
+            before_eq: " ".to_owned(),
+            after_eq: " ".to_owned(),
+            array_padding: false,
+            array_trailing_comma: true,
+        }
+    }
+}
+
+impl StyleReport {
+    /// Creates the default style.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the unit of indentation, e.g. a tab or four spaces.
+    pub fn indent(&self) -> &str {
+        &self.indent
+    }
+
+    /// Sets the unit of indentation.
+    pub fn set_indent(mut self, indent: &str) -> Self {
+        self.indent = indent.to_owned();
+        self
+    }
+
+    /// Returns the line endings.
+    pub fn newline(&self) -> Newline {
+        self.newline
+    }
+
+    /// Sets the line endings.
+    pub fn set_newline(mut self, newline: Newline) -> Self {
+        self.newline = newline;
+        self
+    }
+
+    /// Returns the whitespaces before and after the `=` of key/value pairs.
+    pub fn eq_spacing(&self) -> (&str, &str) {
+        (&self.before_eq, &self.after_eq)
+    }
+
+    /// Sets the whitespaces before and after the `=` of key/value pairs.
+    pub fn set_eq_spacing(mut self, before: &str, after: &str) -> Self {
+        self.before_eq = before.to_owned();
+        self.after_eq = after.to_owned();
+        self
+    }
+
+    /// Returns true iff single-line arrays are padded with spaces, e.g. `[ 1, 2 ]`.
+    pub fn array_padding(&self) -> bool {
+        self.array_padding
+    }
+
+    /// Sets whether single-line arrays are padded with spaces.
+    pub fn set_array_padding(mut self, yes: bool) -> Self {
+        self.array_padding = yes;
+        self
+    }
+
+    /// Returns true iff multi-line arrays end with a comma.
+    pub fn array_trailing_comma(&self) -> bool {
+        self.array_trailing_comma
+    }
+
+    /// Sets whether multi-line arrays end with a comma.
+    pub fn set_array_trailing_comma(mut self, yes: bool) -> Self {
+        self.array_trailing_comma = yes;
+        self
+    }
+}
+
+// occurrences of the conventions found in a document
+#[derive(Default)]
+struct Votes {
+    indents: Vec<String>,
+    spacing: Vec<((String, String), usize)>,
+    padding: Vec<(bool, usize)>,
+    trailing_comma: Vec<(bool, usize)>,
+}
+
+impl Votes {
+    fn table(&mut self, table: &Table) {
+        self.indent(&table.decor.prefix);
+        self.body(table);
+    }
+
+    fn body(&mut self, table: &Table) {
+        for kv in table.items.values() {
+            self.entry(kv);
+        }
+    }
+
+    fn entry(&mut self, kv: &TableKeyValue) {
+        match kv.value {
+            Item::Value(ref v) => {
+                self.indent(&kv.key.decor.prefix);
+                let spacing = (
+                    kv.key.decor.suffix.to_string(),
+                    v.decor().prefix().to_owned(),
+                );
+                vote(&mut self.spacing, spacing);
+                self.value(v);
+            }
+            Item::Table(ref t) if t.dotted => {
+                for kv in t.items.values() {
+                    self.entry(kv);
+                }
+            }
+            Item::Table(ref t) => self.table(t),
+            Item::ArrayOfTables(ref a) => {
+                for t in a.iter() {
+                    self.table(t);
+                }
+            }
+            Item::None => {}
+        }
+    }
+
+    fn value(&mut self, value: &Value) {
+        match *value {
+            Value::Array(ref a) if !a.is_empty() => {
+                if is_multiline(a) {
+                    vote(&mut self.trailing_comma, a.trailing_comma);
+                } else {
+                    vote(&mut self.padding, is_padded(a));
+                }
+                a.iter().for_each(|v| self.value(v));
+            }
+            Value::InlineTable(ref t) => t.iter().for_each(|(_, v)| self.value(v)),
+            _ => {}
+        }
+    }
+
+    fn indent(&mut self, prefix: &str) {
+        let indent = &prefix[prefix.rfind('\n').map_or(0, |i| i + 1)..];
+        if !indent.is_empty() {
+            self.indents.push(indent.to_owned());
+        }
+    }
+}
+
+fn vote<K: PartialEq>(votes: &mut Vec<(K, usize)>, key: K) {
+    match votes.iter_mut().find(|(k, _)| *k == key) {
+        Some((_, n)) => *n += 1,
+        None => votes.push((key, 1)),
+    }
+}
+
+// returns the most frequent key, the first one found among the equally frequent ones
+fn most_frequent<K>(votes: Vec<(K, usize)>) -> Option<K> {
+    let mut best: Option<(K, usize)> = None;
+    for (key, n) in votes {
+        if best.as_ref().is_none_or(|&(_, m)| n > m) {
+            best = Some((key, n));
+        }
+    }
+    best.map(|(key, _)| key)
+}
+
+fn is_multiline(array: &Array) -> bool {
+    array.trailing.contains('\n')
+        || array
+            .iter()
+            .any(|v| v.decor().prefix().contains('\n') || v.decor().suffix().contains('\n'))
+}
+
+fn is_padded(array: &Array) -> bool {
+    array
+        .iter()
+        .next()
+        .is_some_and(|v| v.decor().prefix().starts_with(' '))
+}
+
+// the decor of arrays created through the API
+fn is_default_array(array: &Array) -> bool {
+    array.trailing.is_empty()
+        && array.iter().enumerate().all(|(i, v)| {
+            v.decor().prefix() == if i == 0 { "" } else { " " } && v.decor().suffix().is_empty()
+        })
+}
+
+fn restyle_table(table: &mut Table, style: &StyleReport) {
+    for (_, kv) in table.items.iter_mut() {
+        restyle_entry(kv, style);
+    }
+}
+
+fn restyle_entry(kv: &mut TableKeyValue, style: &StyleReport) {
+    match kv.value {
+        Item::Value(ref mut v) => {
+            if kv.key.decor.suffix == " " && v.decor().prefix() == " " {
+                kv.key.decor.suffix = InternalString::from(style.before_eq.as_str());
+                let suffix = v.decor().suffix().to_owned();
+                formatted::decorate(v, &style.after_eq, &suffix);
+            }
+            restyle_value(v, style);
+        }
+        Item::Table(ref mut t) => restyle_table(t, style),
+        Item::ArrayOfTables(ref mut a) => {
+            for t in a.values.iter_mut().filter_map(Item::as_table_mut) {
+                restyle_table(t, style);
+            }
+        }
+        Item::None => {}
+    }
+}
+
+fn restyle_value(value: &mut Value, style: &StyleReport) {
+    match *value {
+        Value::Array(ref mut a) => {
+            if style.array_padding && !a.is_empty() && is_default_array(a) {
+                let n = a.len();
+                for (i, v) in a
+                    .values
+                    .iter_mut()
+                    .filter_map(Item::as_value_mut)
+                    .enumerate()
+                {
+                    let suffix = if i + 1 == n { " " } else { "" };
+                    formatted::decorate(v, " ", suffix);
+                }
+            } else if is_multiline(a) && a.trailing.contains('\n') {
+                // the newline before `]` is after the trailing comma, as written by `Reflow`
+                a.trailing_comma = style.array_trailing_comma;
+            }
+            for v in a.values.iter_mut().filter_map(Item::as_value_mut) {
+                restyle_value(v, style);
+            }
+        }
+        Value::InlineTable(ref mut t) => {
+            for (_, kv) in t.items.iter_mut() {
+                if let Item::Value(ref mut v) = kv.value {
+                    restyle_value(v, style);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// restyles the entry created at the path, or only its value if it replaced another value
+fn restyle_created(root: &mut Table, created: &Created, style: &StyleReport) {
+    let (last, parents) = match created.path.keys().split_last() {
+        Some(split) => split,
+        None => return,
+    };
+    let mut items = &mut root.items;
+    let mut inline = false;
+    for key in parents {
+        items = match items.get_mut(key.get()).map(|kv| &mut kv.value) {
+            Some(Item::Table(t)) => &mut t.items,
+            Some(Item::Value(Value::InlineTable(t))) => {
+                inline = true;
+                &mut t.items
+            }
+            _ => return,
+        };
+    }
+    match items.get_mut(last.get()) {
+        Some(kv) if !created.new_entry || inline => {
+            if let Item::Value(ref mut v) = kv.value {
+                restyle_value(v, style);
+            } else {
+                restyle_entry(kv, style);
+            }
+        }
+        Some(kv) => restyle_entry(kv, style),
+        None => {}
+    }
+}
+
+impl Document {
+    /// Detects the formatting conventions of the document, the most frequent ones
+    /// where the document is not consistent, and the default ones where it has no example.
+    ///
+    /// The unit of indentation is the shortest indentation of the key/value pairs and
+    /// table headers, and the line endings are those of the parsed source.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{Document, Newline};
+    /// let doc = "[package]\r\n\tname=\"a\"\r\n\tfeatures=[ 'std' ]\r\n".parse::<Document>().unwrap();
+    /// let style = doc.detect_style();
+    /// assert_eq!(style.indent(), "\t");
+    /// assert_eq!(style.newline(), Newline::CrLf);
+    /// assert_eq!(style.eq_spacing(), ("", ""));
+    /// assert!(style.array_padding());
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn detect_style(&self) -> StyleReport {
+        let mut votes = Votes::default();
+        votes.body(self.as_table());
+        let default = StyleReport::default();
+        let (before_eq, after_eq) =
+            most_frequent(votes.spacing).unwrap_or((default.before_eq, default.after_eq));
+        StyleReport {
+            indent: votes
+                .indents
+                .into_iter()
+                .min_by_key(String::len)
+                .unwrap_or(default.indent),
+            newline: if self.crlf {
+                Newline::CrLf
+            } else {
+                Newline::Lf
+            },
+            before_eq,
+            after_eq,
+            array_padding: most_frequent(votes.padding).unwrap_or(default.array_padding),
+            array_trailing_comma: most_frequent(votes.trailing_comma)
+                .unwrap_or(default.array_trailing_comma),
+        }
+    }
+
+    /// Returns the style set by `set_default_style`.
+    pub fn default_style(&self) -> Option<&StyleReport> {
+        self.style.as_deref()
+    }
+
+    /// Sets the style the edits of the document should follow, usually the one
+    /// returned by `detect_style`.
+    ///
+    /// The document is then displayed with the line endings of the style,
+    /// and `apply_default_style` as well as successful transactions restyle
+    /// the items created through the API. `Reflow::new().indent(style.indent())`
+    /// wraps arrays with the indentation of the style.
+    pub fn set_default_style(&mut self, style: StyleReport) {
+        self.style = Some(Box::new(style));
+    }
+
+    /// Formats the entry at the path, e.g. a key or a table created through the API,
+    /// according to the default style, and indents it like its siblings,
+    /// see `Document::inherit_indentation`. The other entries are left as they are.
+    /// Does nothing without a default style.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{value, Document, Value};
+    /// let mut doc = "[package]\n  name=\"a\"\n  features=[ 'std' ]\n".parse::<Document>().unwrap();
+    /// doc.set_default_style(doc.detect_style());
+    /// doc["package"]["edition"] = value("2018");
+    /// doc["package"]["authors"] = value(vec!["b", "c"].into_iter().collect::<Value>());
+    /// doc.apply_default_style(&"package.edition".parse().unwrap());
+    /// doc.apply_default_style(&"package.authors".parse().unwrap());
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "[package]\n  name=\"a\"\n  features=[ 'std' ]\n  edition=\"2018\"\n  authors=[ \"b\", \"c\" ]\n"
+    /// );
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn apply_default_style(&mut self, path: &KeyPath) {
+        self.restyle_created(&[Created {
+            path: path.clone(),
+            new_entry: true,
+        }]);
+    }
+
+    // restyles the items created by a transaction, without touching the other items
+    pub(crate) fn restyle_created(&mut self, created: &[Created]) {
+        let style = match self.style.take() {
+            Some(style) => style,
+            None => return,
+        };
+        for c in created {
+            restyle_created(self.as_table_mut(), c, &style);
+            if c.new_entry {
+                indent_created(self.as_table_mut(), &c.path);
+            }
+        }
+        self.style = Some(style);
+    }
+}
@@ -1,7 +1,9 @@
 use crate::array_of_tables::ArrayOfTables;
 use crate::decor::{Decor, InternalString, Repr};
-use crate::formatted::{decorated, key_repr};
-use crate::key::Key;
+use crate::formatted::{self, decorated, key_repr, to_table_key_value};
+use crate::key::{normalized_raw, Key};
+use crate::key_path::KeyPath;
+use crate::metadata::NodeIdCell;
 use crate::value::{sort_key_value_pairs, Array, DateTime, InlineTable, Value};
 use linked_hash_map::LinkedHashMap;
 
@@ -9,23 +11,30 @@ use linked_hash_map::LinkedHashMap;
 
 /// Type representing a TOML non-inline table
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Table {
     pub(crate) items: KeyValuePairs,
     // comments/spaces before and after the header
     pub(crate) decor: Decor,
     // whether to hide an empty table
     pub(crate) implicit: bool,
+    // whether the table is defined by dotted keys in its parent's body
+    pub(crate) dotted: bool,
     // used for putting tables back in their original order when serialising.
     // Will be None when the Table wasn't parsed from a file.
     pub(crate) position: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) id: NodeIdCell,
 }
 
 pub(crate) type KeyValuePairs = LinkedHashMap<InternalString, TableKeyValue>;
 
 /// Type representing either a value, a table, an array of tables, or none.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Item {
     /// Type representing none.
+    #[default]
     None,
     /// Type representing value.
     Value(Value),
@@ -35,15 +44,10 @@ pub enum Item {
     ArrayOfTables(ArrayOfTables),
 }
 
-impl Default for Item {
-    fn default() -> Self {
-        Item::None
-    }
-}
-
 // TODO: make pub(crate)
 #[doc(hidden)]
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TableKeyValue {
     pub(crate) key: Repr,
     pub(crate) value: Item,
@@ -55,10 +59,114 @@ impl TableKeyValue {
     }
 }
 
+/// What `Table::insert_with_policy` does when the key holds an array of tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ConflictPolicy {
+    /// The array of tables is kept and the item is returned as an error.
+    #[default]
+    Error,
+    /// The array of tables is replaced, like with `Table::insert`.
+    Replace,
+    /// Tables, inline tables, arrays of tables and arrays of inline tables are appended
+    /// to the array of tables, other items are returned as an error.
+    Append,
+}
+
 /// An iterator type over `Table`'s key/value pairs.
 pub type Iter<'a> = Box<dyn Iterator<Item = (&'a str, &'a Item)> + 'a>;
 /// A mutable iterator type over `Table`'s key/value pairs.
 pub type IterMut<'a> = Box<dyn Iterator<Item = (&'a str, &'a mut Item)> + 'a>;
+/// An iterator type over `Table`'s entries with their keys and positions,
+/// see `Table::iter_full`.
+pub type IterFull<'a> = Box<dyn Iterator<Item = (Key, &'a Item, Option<usize>)> + 'a>;
+
+/// A cursor over `Table`'s entries, allowing to remove or replace
+/// the current entry while iterating.
+///
+/// Created by `Table::cursor_mut`.
+pub struct CursorMut<'a> {
+    entries: linked_hash_map::Entries<'a, InternalString, TableKeyValue>,
+}
+
+/// An entry of a `Table` visited by a `CursorMut`.
+pub struct EntryMut<'a> {
+    entry: linked_hash_map::OccupiedEntry<'a, InternalString, TableKeyValue>,
+}
+
+/// A consuming iterator over the leaf items of a `Table` and their paths.
+///
+/// Created by `Table::into_flattened_iter`.
+pub struct IntoFlattenedIter {
+    stack: Vec<(
+        KeyPath,
+        linked_hash_map::IntoIter<InternalString, TableKeyValue>,
+    )>,
+}
+
+impl Iterator for IntoFlattenedIter {
+    type Item = (KeyPath, Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((parent, entries)) = self.stack.last_mut() {
+            let (key, kv) = match entries.next() {
+                Some(entry) => entry,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            let mut path = parent.clone();
+            path.push(Key::with_repr(key, kv.key));
+            match kv.value {
+                Item::None => {}
+                Item::Table(t) if !t.is_empty() => self.stack.push((path, t.items.into_iter())),
+                item => return Some((path, item)),
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Iterator for CursorMut<'a> {
+    type Item = EntryMut<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().map(|entry| EntryMut { entry })
+    }
+}
+
+impl<'a> EntryMut<'a> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &str {
+        self.entry.key()
+    }
+
+    /// Returns the item of the entry.
+    pub fn get(&self) -> &Item {
+        &self.entry.get().value
+    }
+
+    /// Returns the mutable item of the entry.
+    pub fn get_mut(&mut self) -> &mut Item {
+        &mut self.entry.get_mut().value
+    }
+
+    /// Converts the entry into a mutable reference to its item.
+    pub fn into_mut(self) -> &'a mut Item {
+        &mut self.entry.into_mut().value
+    }
+
+    /// Replaces the item of the entry, preserving the decor of the key,
+    /// and returns the old one.
+    pub fn replace(&mut self, item: Item) -> Item {
+        std::mem::replace(self.get_mut(), item)
+    }
+
+    /// Removes the entry from the table, returning its item.
+    pub fn remove(self) -> Item {
+        self.entry.remove().value
+    }
+}
 
 impl Table {
     /// Creates an empty table.
@@ -66,6 +174,19 @@ impl Table {
         Self::with_decor_and_pos(Decor::new("\n", ""), None)
     }
 
+    /// Creates an empty table with room for `capacity` key/value pairs,
+    /// e.g. for tables built with thousands of keys, see also `Extend`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut table = Self::new();
+        table.items = KeyValuePairs::with_capacity(capacity);
+        table
+    }
+
+    /// Reserves room for at least `additional` more key/value pairs.
+    pub fn reserve(&mut self, additional: usize) {
+        self.items.reserve(additional);
+    }
+
     pub(crate) fn with_pos(position: Option<usize>) -> Self {
         Self {
             position,
@@ -117,11 +238,80 @@ impl Table {
         }
     }
 
+    /// Returns the non-empty item with the given key together with the key,
+    /// including its raw representation and decor.
+    ///
+    /// The key is built on demand, like the keys of `iter_full`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let doc = "'name' = 'a' # the name\n".parse::<Document>().unwrap();
+    /// let (key, item) = doc.as_table().get_key_value("name").unwrap();
+    /// assert_eq!((key.get(), key.raw()), ("name", "'name'"));
+    /// assert_eq!(item.as_str(), Some("a"));
+    /// assert!(doc.as_table().get_key_value("version").is_none());
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn get_key_value(&self, key: &str) -> Option<(Key, &Item)> {
+        self.items
+            .get(key)
+            .filter(|kv| !kv.value.is_none())
+            .map(|kv| (Key::with_repr(key, kv.key.clone()), &kv.value))
+    }
+
     /// Returns an iterator over all key/value pairs, including empty.
     pub fn iter(&self) -> Iter<'_> {
         Box::new(self.items.iter().map(|(key, kv)| (&key[..], &kv.value)))
     }
 
+    /// Returns an iterator over the non-empty entries with their keys, including
+    /// the raw representations and the decor of the keys, and the positions
+    /// of the child tables, see `Table::position`.
+    ///
+    /// The position of an array of tables is the position of its first table,
+    /// values have no position.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let doc = "[package]\n# the name\n'name' = 'a'\n[\"b c\"]\n".parse::<Document>().unwrap();
+    /// let root: Vec<_> = doc.as_table().iter_full().map(|(k, _, p)| (k.raw().to_owned(), p)).collect();
+    /// assert_eq!(root, vec![("package".to_owned(), Some(1)), ("\"b c\"".to_owned(), Some(2))]);
+    ///
+    /// let (key, item, position) = doc["package"].as_table().unwrap().iter_full().next().unwrap();
+    /// assert_eq!((key.raw(), key.decor().prefix()), ("'name'", "# the name\n"));
+    /// assert_eq!((item.as_str(), position), (Some("a"), None));
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn iter_full(&self) -> IterFull<'_> {
+        Box::new(
+            self.items
+                .iter()
+                .filter(|&(_, kv)| !kv.value.is_none())
+                .map(|(key, kv)| {
+                    let position = match kv.value {
+                        Item::Table(ref t) => t.position,
+                        Item::ArrayOfTables(ref a) => a.first().and_then(Table::position),
+                        _ => None,
+                    };
+                    (
+                        Key::with_repr(key.clone(), kv.key.clone()),
+                        &kv.value,
+                        position,
+                    )
+                }),
+        )
+    }
+
     /// Returns an mutable iterator over all key/value pairs, including empty.
     pub fn iter_mut(&mut self) -> IterMut<'_> {
         Box::new(
@@ -131,32 +321,489 @@ impl Table {
         )
     }
 
+    /// Returns a cursor over all entries, including empty,
+    /// which allows to remove or replace them during the traversal.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{value, Document};
+    /// let mut doc = "a = 1\nb = 2\nc = 3\n".parse::<Document>().unwrap();
+    /// for mut entry in doc.as_table_mut().cursor_mut() {
+    ///     if entry.key() == "a" {
+    ///         entry.remove();
+    ///     } else if entry.key() == "c" {
+    ///         entry.replace(value(4));
+    ///     }
+    /// }
+    /// assert_eq!(doc.to_string(), "b = 2\nc = 4\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn cursor_mut(&mut self) -> CursorMut<'_> {
+        CursorMut {
+            entries: self.items.entries(),
+        }
+    }
+
+    /// Retains only the items specified by the predicate,
+    /// in a single pass over the table.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&str, &mut Item) -> bool,
+    {
+        for mut entry in self.cursor_mut() {
+            let mut item = std::mem::take(entry.get_mut());
+            if keep(entry.key(), &mut item) {
+                *entry.get_mut() = item;
+            } else {
+                entry.remove();
+            }
+        }
+    }
+
+    /// Converts the table into an iterator over its leaf items and their paths.
+    ///
+    /// Subtables are flattened, except for empty ones, which are yielded as is.
+    /// Values, including arrays and inline tables, and arrays of tables are leaves.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let doc = "a = 1\n[b.c]\nd = 2\n".parse::<Document>().unwrap();
+    /// let paths: Vec<_> = doc
+    ///     .into_iter()
+    ///     .map(|(path, _)| path.to_string())
+    ///     .collect();
+    /// assert_eq!(paths, ["a", "b.c.d"]);
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn into_flattened_iter(self) -> IntoFlattenedIter {
+        IntoFlattenedIter {
+            stack: vec![(KeyPath::new(), self.items.into_iter())],
+        }
+    }
+
     /// Removes an item given the key.
     pub fn remove(&mut self, key: &str) -> Option<Item> {
         self.items.remove(key).map(|kv| kv.value)
     }
 
+    /// Removes all the items, keeping the decor of the table, e.g. the comments above its header.
+    pub fn clear(&mut self) {
+        self.items.clear()
+    }
+
+    /// Removes an item given the key, returning it together with the key,
+    /// including the key's decor.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let mut doc = "# comment\n'a' = 1\n".parse::<Document>().unwrap();
+    /// let (key, item) = doc.as_table_mut().remove_entry("a").unwrap();
+    /// assert_eq!(key.raw(), "'a'");
+    /// assert_eq!(key.decor().prefix(), "# comment\n");
+    /// assert_eq!(item.as_integer(), Some(1));
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn remove_entry(&mut self, key: &str) -> Option<(Key, Item)> {
+        self.items
+            .remove(key)
+            .map(|kv| (Key::with_repr(key, kv.key), kv.value))
+    }
+
+    /// Inserts an item, returning the previous one if the key was present.
+    ///
+    /// The decor of an existing key is preserved, new keys are appended.
+    pub fn insert(&mut self, key: &str, item: Item) -> Option<Item> {
+        let parsed = key.parse::<Key>().expect("invalid key");
+        self.insert_parsed(&parsed, item)
+    }
+
+    fn insert_parsed(&mut self, parsed: &Key, item: Item) -> Option<Item> {
+        match self.items.get_mut(parsed.get()) {
+            Some(kv) => Some(std::mem::replace(&mut kv.value, item)),
+            None => {
+                let kv = to_table_key_value(parsed.raw(), item);
+                self.items.insert(parsed.get().into(), kv);
+                None
+            }
+        }
+    }
+
+    /// Inserts a key/value pair like `insert`, unless the key holds an array of tables,
+    /// e.g. `[[bin]]` sections, in which case `policy` decides what happens.
+    ///
+    /// Returns the replaced item, `Ok(None)` if nothing was replaced,
+    /// or `Err(item)` if the item was not inserted.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{table, value, ConflictPolicy, Document};
+    /// let mut doc = "[[bin]]\nname = 'a'\n".parse::<Document>().unwrap();
+    /// let root = doc.as_table_mut();
+    /// assert!(root.insert_with_policy("bin", value("b"), ConflictPolicy::Error).is_err());
+    ///
+    /// let mut bin = table();
+    /// bin["name"] = value("b");
+    /// assert!(root.insert_with_policy("bin", bin, ConflictPolicy::Append).is_ok());
+    /// assert_eq!(doc.to_string(), "[[bin]]\nname = 'a'\n\n[[bin]]\nname = \"b\"\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn insert_with_policy(
+        &mut self,
+        key: &str,
+        item: Item,
+        policy: ConflictPolicy,
+    ) -> Result<Option<Item>, Item> {
+        let parsed = key.parse::<Key>().expect("invalid key");
+        let array = match self.get_mut(parsed.get()) {
+            Some(Item::ArrayOfTables(array)) => array,
+            _ => return Ok(self.insert_parsed(&parsed, item)),
+        };
+        match policy {
+            ConflictPolicy::Error => Err(item),
+            ConflictPolicy::Replace => Ok(self.insert_parsed(&parsed, item)),
+            ConflictPolicy::Append => {
+                let item = match item.into_table() {
+                    Ok(table) => {
+                        array.append(table);
+                        return Ok(None);
+                    }
+                    Err(item) => item,
+                };
+                for table in item.into_array_of_tables()?.values {
+                    if let Item::Table(table) = table {
+                        array.append(table);
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Replaces the item of an existing entry, returning the previous one,
+    /// or inserts it like `insert` if the key is not present.
+    ///
+    /// Unlike `insert`, the decor of the replaced value, or the decor and the position
+    /// of the replaced table, is applied to the new one.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{value, Document};
+    /// let mut doc = "a = 1 # one\n".parse::<Document>().unwrap();
+    /// doc.as_table_mut().replace("a", value(2));
+    /// assert_eq!(doc.to_string(), "a = 2 # one\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn replace(&mut self, key: &str, mut item: Item) -> Option<Item> {
+        if let Some(old) = self.get(key) {
+            match (old, &mut item) {
+                (Item::Value(old), Item::Value(new)) => {
+                    formatted::decorate(new, old.decor().prefix(), old.decor().suffix())
+                }
+                (Item::Table(old), Item::Table(new)) => {
+                    new.decor = old.decor.clone();
+                    new.position = old.position;
+                }
+                _ => {}
+            }
+        }
+        self.insert(key, item)
+    }
+
+    /// Takes the item out of an entry, leaving `Item::None` in its place.
+    ///
+    /// The entry keeps its key, with its decor, and its position,
+    /// so that an item inserted later with the same key takes its place.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let mut doc = "a = 1\n# the b\nb = 2\n".parse::<Document>().unwrap();
+    /// let b = doc.as_table_mut().take("b").unwrap();
+    /// let a = doc.as_table_mut().replace("a", b);
+    /// doc.as_table_mut().insert("b", a.unwrap());
+    /// assert_eq!(doc.to_string(), "a = 2\n# the b\nb = 1\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn take(&mut self, key: &str) -> Option<Item> {
+        self.get_mut(key)
+            .map(Item::take)
+            .filter(|item| !item.is_none())
+    }
+
+    /// Inserts an item with an already formatted key,
+    /// returning the previous one if the key was present.
+    ///
+    /// The key's representation and decor replace the existing ones,
+    /// new keys are appended.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{value, Document, Key};
+    /// let mut doc = "a = 1\n".parse::<Document>().unwrap();
+    /// let mut key = "'b'".parse::<Key>().unwrap();
+    /// key.decor_mut().set_prefix("# comment\n");
+    /// doc.as_table_mut().insert_formatted(&key, value(2));
+    /// assert_eq!(doc.to_string(), "a = 1\n# comment\n'b' = 2\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn insert_formatted(&mut self, key: &Key, item: Item) -> Option<Item> {
+        let kv = TableKeyValue::new(key.to_repr(), item);
+        match self.items.get_mut(key.get()) {
+            Some(old) => Some(std::mem::replace(old, kv).value),
+            None => {
+                self.items.insert(key.get().into(), kv);
+                None
+            }
+        }
+    }
+
+    /// Inserts a table right after the sibling item with the key `after`,
+    /// returning the previous item if the key was present.
+    ///
+    /// Unless the decor prefix of the table has comments, it is replaced by the blank lines
+    /// and the indentation preceding the header of the sibling table. If there is no such sibling,
+    /// the table is appended like with `insert`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{value, Document, Table};
+    /// let mut doc = "[servers]\n\n  [servers.alpha]\n  ip = 1\n\n  [servers.gamma]\n  ip = 3\n"
+    ///     .parse::<Document>()
+    ///     .unwrap();
+    /// let mut beta = Table::new();
+    /// beta["ip"] = value(2);
+    /// doc["servers"].as_table_mut().unwrap().insert_table_after("beta", beta, "alpha");
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "[servers]\n\n  [servers.alpha]\n  ip = 1\n\n  [servers.beta]\nip = 2\n\n  [servers.gamma]\n  ip = 3\n"
+    /// );
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn insert_table_after(&mut self, key: &str, table: Table, after: &str) -> Option<Item> {
+        self.insert_table_next_to(key, table, after, true)
+    }
+
+    /// Inserts a table right before the sibling item with the key `before`,
+    /// returning the previous item if the key was present.
+    ///
+    /// See `insert_table_after`.
+    pub fn insert_table_before(&mut self, key: &str, table: Table, before: &str) -> Option<Item> {
+        self.insert_table_next_to(key, table, before, false)
+    }
+
+    fn insert_table_next_to(
+        &mut self,
+        key: &str,
+        mut table: Table,
+        anchor: &str,
+        after: bool,
+    ) -> Option<Item> {
+        let parsed = key.parse::<Key>().expect("invalid key");
+        let old = self.items.remove(parsed.get()).map(|kv| kv.value);
+        let anchor_item = self.items.get(anchor).map(|kv| &kv.value);
+        let anchored = anchor_item.is_some();
+        let first = match anchor_item {
+            Some(Item::Table(t)) => Some(t),
+            Some(Item::ArrayOfTables(a)) => a.iter().next(),
+            _ => None,
+        };
+        if let Some(first) = first {
+            if table.decor.prefix.trim().is_empty() {
+                table.decor.prefix = whitespace_lines(&first.decor.prefix);
+            }
+            // tables without a position are displayed after the preceding ones
+            // in `Document::to_string_in_original_order`
+            table.position = if after {
+                anchor_item.and_then(last_position)
+            } else {
+                first.position
+            };
+        }
+        let kv = to_table_key_value(parsed.raw(), Item::Table(table));
+        self.items.insert(parsed.get().into(), kv);
+        if anchored {
+            // move the items following the new one to the back
+            let moved: Vec<_> = self
+                .items
+                .keys()
+                .skip_while(|k| *k != anchor)
+                .skip(if after { 1 } else { 0 })
+                .take_while(|k| *k != parsed.get())
+                .cloned()
+                .collect();
+            for k in moved {
+                self.items.get_refresh(&k);
+            }
+        }
+        old
+    }
+
     /// Sorts Key/Value Pairs of the table,
     /// doesn't affect subtables or subarrays.
     pub fn sort_values(&mut self) {
         sort_key_value_pairs(&mut self.items);
     }
 
-    /// Returns the number of non-empty items in the table.
+    /// Sorts Key/Value Pairs of the table by group and then by key,
+    /// doesn't affect subtables or subarrays.
+    ///
+    /// The groups are given by `group` for every key and value, and are ordered
+    /// by their own ordering. The comments and whitespaces before a key move with it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::{Document, Value};
+    ///
+    /// let mut doc = r#"
+    /// [dependencies]
+    /// toml = { git = "https://github.com/alexcrichton/toml-rs" }
+    /// serde = "1.0"
+    /// ## shared with the other crates
+    /// log = { workspace = true }
+    /// chrono = "0.4"
+    /// "#.parse::<Document>().unwrap();
+    ///
+    /// // std deps, then workspace deps, then git deps
+    /// let group = |_: &str, v: &Value| match v.as_inline_table() {
+    ///     Some(t) if t.contains_key("git") => 2,
+    ///     Some(t) if t.contains_key("workspace") => 1,
+    ///     _ => 0,
+    /// };
+    /// doc["dependencies"].as_table_mut().unwrap().sort_values_by_group(group);
+    /// assert_eq!(doc.to_string(), r#"
+    /// [dependencies]
+    /// chrono = "0.4"
+    /// serde = "1.0"
+    /// ## shared with the other crates
+    /// log = { workspace = true }
+    /// toml = { git = "https://github.com/alexcrichton/toml-rs" }
+    /// "#);
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn sort_values_by_group<F, G>(&mut self, mut group: F)
+    where
+        F: FnMut(&str, &Value) -> G,
+        G: Ord,
+    {
+        let mut keys: Vec<(G, InternalString)> = self
+            .items
+            .iter()
+            .filter_map(|(key, kv)| kv.value.as_value().map(|v| (group(key, v), key.clone())))
+            .collect();
+        keys.sort();
+        for (_, key) in keys {
+            self.items.get_refresh(&key);
+        }
+    }
+
+    /// Returns the number of non-empty items in the table,
+    /// i.e. of key/value pairs, subtables and arrays of tables.
+    ///
+    /// Entries set to `Item::None` are not counted, see `len_all`.
     pub fn len(&self) -> usize {
         self.items.iter().filter(|i| !(i.1).value.is_none()).count()
     }
 
-    /// Returns the number of key/value pairs in the table.
+    /// Returns the number of key/value pairs in the table, same as `len_values`.
     pub fn values_len(&self) -> usize {
+        self.len_values()
+    }
+
+    /// Returns the number of key/value pairs in the table,
+    /// not counting the pairs with dotted keys, which belong to dotted subtables.
+    pub fn len_values(&self) -> usize {
         self.items.iter().filter(|i| (i.1).value.is_value()).count()
     }
 
-    /// Returns true iff the table is empty.
+    /// Returns the number of subtables and arrays of tables in the table,
+    /// including dotted and implicit subtables.
+    pub fn len_subtables(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|i| (i.1).value.is_table() || (i.1).value.is_array_of_tables())
+            .count()
+    }
+
+    /// Returns the number of entries in the table, including those set to `Item::None`.
+    pub fn len_all(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true iff the table has no non-empty items, see `len`.
+    ///
+    /// A table with empty subtables only is not empty, see `is_logically_empty`.
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
+    /// Returns true iff the table holds no data: it has no key/value pairs
+    /// and no non-empty arrays of tables, neither directly nor in its subtables.
+    ///
+    /// Such a table is e.g. `[dependencies]` with nothing but empty or implicit subtables.
+    /// Arrays of tables with empty tables are data, their tables are counted.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let doc = "[dependencies]\n[dependencies.serde]\n[[bin]]\n".parse::<Document>().unwrap();
+    /// let dependencies = doc["dependencies"].as_table().unwrap();
+    /// assert!(!dependencies.is_empty());
+    /// assert!(dependencies.is_logically_empty());
+    /// assert!(!doc.as_table().is_logically_empty());
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn is_logically_empty(&self) -> bool {
+        self.items.values().all(|kv| match kv.value {
+            Item::None => true,
+            Item::Value(..) => false,
+            Item::Table(ref t) => t.is_logically_empty(),
+            Item::ArrayOfTables(ref a) => a.is_empty(),
+        })
+    }
+
     /// Given the `key`, return a mutable reference to the value.
     /// If there is no entry associated with the given key in the table,
     /// a `Item::None` value will be inserted.
@@ -167,7 +814,7 @@ impl Table {
         let parsed_key = key.parse::<Key>().expect("invalid key");
         &mut self
             .items
-            .entry(parsed_key.get().to_owned())
+            .entry(parsed_key.get().into())
             .or_insert(TableKeyValue::new(key_repr(parsed_key.raw()), Item::None))
             .value
     }
@@ -193,21 +840,229 @@ impl Table {
     /// In the document above, tables `target` and `target."x86_64/windows.json"` are implicit.
     ///
     /// ```
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
     /// use toml_edit::Document;
     /// let mut doc = "[a]\n[a.b]\n".parse::<Document>().expect("invalid toml");
     ///
     /// doc["a"].as_table_mut().unwrap().set_implicit(true);
     /// assert_eq!(doc.to_string(), "[a.b]\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
     /// ```
     pub fn set_implicit(&mut self, implicit: bool) {
         self.implicit = implicit;
     }
 
+    /// Returns true iff the table is implicit, see `set_implicit`.
+    pub fn is_implicit(&self) -> bool {
+        self.implicit
+    }
+
+    /// Returns the paths of the implicit subtables, e.g. `a` and `a.b` for `[a.b.c]`,
+    /// in the order of their display.
+    ///
+    /// Subtables of arrays of tables are not visited.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{Document, KeyPath};
+    /// let doc = "[a.b.c]\nx = 1\n[a.d]\n".parse::<Document>().unwrap();
+    /// let paths: Vec<_> = doc.as_table().implicit_tables().iter().map(KeyPath::to_string).collect();
+    /// assert_eq!(paths, ["a", "a.b"]);
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn implicit_tables(&self) -> Vec<KeyPath> {
+        fn visit(table: &Table, path: &mut KeyPath, paths: &mut Vec<KeyPath>) {
+            for (key, kv) in table.items.iter() {
+                if let Item::Table(ref t) = kv.value {
+                    path.push(Key::with_repr(key.clone(), kv.key.clone()));
+                    if t.implicit {
+                        paths.push(path.clone());
+                    }
+                    visit(t, path, paths);
+                    path.pop();
+                }
+            }
+        }
+        let mut paths = Vec::new();
+        visit(self, &mut KeyPath::new(), &mut paths);
+        paths
+    }
+
+    /// Makes all implicit subtables explicit, so that their headers are displayed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let mut doc = "[a.b.c]\nx = 1\n".parse::<Document>().unwrap();
+    /// doc.as_table_mut().materialize_implicit_tables();
+    /// assert_eq!(doc.to_string(), "\n[a]\n\n[a.b]\n[a.b.c]\nx = 1\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn materialize_implicit_tables(&mut self) {
+        self.visit_subtables_mut(&mut |t| t.implicit = false);
+    }
+
+    /// Makes all subtables without key/value pairs which have subtables implicit,
+    /// so that their headers are omitted. Empty tables without subtables are kept,
+    /// as are tables with comments before or after their headers.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let mut doc = "[a]\n[a.b]\n[a.b.c]\nx = 1\n[d]\n".parse::<Document>().unwrap();
+    /// doc.as_table_mut().elide_intermediate_tables();
+    /// assert_eq!(doc.to_string(), "[a.b.c]\nx = 1\n[d]\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn elide_intermediate_tables(&mut self) {
+        self.visit_subtables_mut(&mut |t| {
+            let commented = t.decor.prefix.contains('#') || t.decor.suffix.contains('#');
+            let intermediate = t.values_len() == 0
+                && !commented
+                && t.items.values().any(|kv| match kv.value {
+                    Item::Table(ref t) => !t.dotted,
+                    Item::ArrayOfTables(..) => true,
+                    _ => false,
+                });
+            if intermediate {
+                t.implicit = true;
+            }
+        });
+    }
+
+    /// Normalizes the keys of the table and of its subtables, arrays of tables and
+    /// inline tables, see `Key::normalize`, keeping the values and comments.
+    ///
+    /// Returns the number of changed keys.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let mut doc = "\"name\" = 1 # one\n['dependencies'.\"serde\"]\n\"a b\" = { 'c' = 2 }\n"
+    ///     .parse::<Document>()
+    ///     .unwrap();
+    /// assert_eq!(doc.bareify_keys(), 4);
+    /// assert_eq!(doc.to_string(), "name = 1 # one\n[dependencies.serde]\n\"a b\" = { c = 2 }\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn bareify_keys(&mut self) -> usize {
+        bareify_items(&mut self.items)
+    }
+
+    /// Removes the entries set to `Item::None`, e.g. those left by `entry` or by indexing
+    /// with missing keys, and the implicit and dotted subtables which are left empty.
+    ///
+    /// Returns the paths of the removed entries, subtables after their removed entries.
+    /// Entries of inline tables are removed as well, entries of the tables of arrays of tables
+    /// are reported with the path of the array.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{Document, KeyPath};
+    /// let mut doc = "[a.b]\n[c]\n".parse::<Document>().unwrap();
+    /// doc.as_table_mut().entry("d");
+    /// doc["a"].as_table_mut().unwrap().remove("b");
+    /// let removed: Vec<_> = doc.compact().iter().map(KeyPath::to_string).collect();
+    /// assert_eq!(removed, ["a", "d"]);
+    /// assert_eq!(doc.to_string(), "[c]\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn compact(&mut self) -> Vec<KeyPath> {
+        let mut removed = Vec::new();
+        compact_items(&mut self.items, &mut KeyPath::new(), &mut removed);
+        removed
+    }
+
+    // calls `f` on all subtables with headers, including those of arrays of tables
+    fn visit_subtables_mut<F: FnMut(&mut Table)>(&mut self, f: &mut F) {
+        for (_, kv) in self.items.iter_mut() {
+            match kv.value {
+                Item::Table(ref mut t) if !t.dotted => {
+                    f(t);
+                    t.visit_subtables_mut(f);
+                }
+                Item::ArrayOfTables(ref mut a) => {
+                    for t in a.iter_mut() {
+                        t.visit_subtables_mut(f);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns true iff the table is defined by dotted keys, e.g. `a.b = 1`,
+    /// in the body of its parent table instead of having a header.
+    pub fn is_dotted(&self) -> bool {
+        self.dotted
+    }
+
+    /// If a table is dotted, its key/value pairs are displayed as dotted keys
+    /// in the body of its parent table, and the table has no header.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{value, Document};
+    /// let mut doc = "[a]\nb = 1\n".parse::<Document>().unwrap();
+    /// doc["a"].as_table_mut().unwrap().set_dotted(true);
+    /// assert_eq!(doc.to_string(), "a.b = 1\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn set_dotted(&mut self, dotted: bool) {
+        self.dotted = dotted;
+    }
+
     /// Returns the decor associated with a given key of the table.
     pub fn decor(&self, key: &str) -> Option<&Decor> {
         self.items.get(key).map(|kv| &kv.key.decor)
     }
 
+    /// Returns the mutable decor associated with a given key of the table.
+    pub fn decor_mut(&mut self, key: &str) -> Option<&mut Decor> {
+        self.items.get_mut(key).map(|kv| &mut kv.key.decor)
+    }
+
+    /// Converts the table into an inline table,
+    /// subtables and arrays of tables are converted into inline values as well.
+    pub fn into_inline_table(self) -> InlineTable {
+        let mut table = InlineTable::default();
+        for (key, kv) in self.items {
+            if let Ok(value) = kv.value.into_value() {
+                let kv = formatted::to_key_value(&kv.key.raw_value, value);
+                table.items.insert(key, kv);
+            }
+        }
+        table.fmt();
+        table
+    }
+
     /// Sets the position of the `Table` within the `Document`.
     ///
     /// Setting the position of a table will only affect output when
@@ -226,6 +1081,11 @@ impl Table {
 }
 
 impl Item {
+    /// Takes the item out, leaving `Item::None` in its place.
+    pub fn take(&mut self) -> Item {
+        std::mem::take(self)
+    }
+
     /// Sets `self` to the given item iff `self` is none and
     /// returns a mutable reference to `self`.
     pub fn or_insert(&mut self, item: Item) -> &mut Item {
@@ -234,6 +1094,77 @@ impl Item {
         }
         self
     }
+
+    /// Converts `self` into a value, tables become inline tables and
+    /// arrays of tables become arrays of inline tables.
+    ///
+    /// Returns `Err(Item::None)` iff `self` is none.
+    pub fn into_value(self) -> Result<Value, Self> {
+        match self {
+            Item::None => Err(self),
+            Item::Value(v) => Ok(v),
+            Item::Table(t) => Ok(Value::InlineTable(t.into_inline_table())),
+            Item::ArrayOfTables(a) => {
+                let mut array = Array::default();
+                for t in a.values.into_iter().filter_map(|t| t.into_value().ok()) {
+                    array.push(t).expect("only inline tables");
+                }
+                Ok(Value::Array(array))
+            }
+        }
+    }
+
+    /// Converts `self` into a table, inline tables become standard tables
+    /// keeping their nested values inline.
+    ///
+    /// Returns `Err(self)` if `self` is neither a table nor an inline table.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{Document, Item};
+    /// let mut doc = "serde = { version = '1.0', features = { std = true } }"
+    ///     .parse::<Document>()
+    ///     .unwrap();
+    /// let serde = doc["serde"].take();
+    /// doc["serde"] = Item::Table(serde.into_table().unwrap());
+    /// assert_eq!(doc.to_string(), "\n[serde]\nversion = '1.0'\nfeatures = { std = true }\n");
+    /// assert!(doc["serde"]["version"].clone().into_table().is_err());
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn into_table(self) -> Result<Table, Self> {
+        match self {
+            Item::Table(t) => Ok(t),
+            Item::Value(Value::InlineTable(t)) => Ok(t.into_table()),
+            _ => Err(self),
+        }
+    }
+
+    /// Converts `self` into an array of tables, arrays of inline tables become
+    /// arrays of standard tables keeping their nested values inline.
+    ///
+    /// Returns `Err(self)` if `self` is neither an array of tables nor a non-empty array
+    /// of inline tables, since an empty array of tables is not written at all.
+    pub fn into_array_of_tables(self) -> Result<ArrayOfTables, Self> {
+        match self {
+            Item::ArrayOfTables(a) => Ok(a),
+            Item::Value(Value::Array(a))
+                if !a.is_empty() && a.iter().all(Value::is_inline_table) =>
+            {
+                let mut array = ArrayOfTables::new();
+                for item in a.values {
+                    if let Item::Value(Value::InlineTable(t)) = item {
+                        array.append(t.into_table());
+                    }
+                }
+                Ok(array)
+            }
+            _ => Err(self),
+        }
+    }
 }
 // TODO: This should be generated by macro or derive
 /// Downcasting
@@ -304,6 +1235,16 @@ impl Item {
         self.as_value().and_then(Value::as_integer)
     }
 
+    /// Casts `self` to a non-negative integer, see `Value::as_u64`.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_value().and_then(Value::as_u64)
+    }
+
+    /// Casts `self` to an integer in the range of `usize`, see `Value::as_usize`.
+    pub fn as_usize(&self) -> Option<usize> {
+        self.as_value().and_then(Value::as_usize)
+    }
+
     /// Returns true iff `self` is an integer.
     pub fn is_integer(&self) -> bool {
         self.as_integer().is_some()
@@ -386,6 +1327,15 @@ impl Item {
             .or_else(|| self.as_inline_table().map(|t| t as &dyn TableLike))
     }
 
+    /// Casts `self` to either a mutable table or a mutable inline table.
+    pub fn as_table_like_mut(&mut self) -> Option<&mut dyn TableLike> {
+        match *self {
+            Item::Table(ref mut t) => Some(t as &mut dyn TableLike),
+            Item::Value(Value::InlineTable(ref mut t)) => Some(t as &mut dyn TableLike),
+            _ => None,
+        }
+    }
+
     /// Returns true iff `self` is either a table, or an inline table.
     pub fn is_table_like(&self) -> bool {
         self.as_table_like().is_some()
@@ -408,6 +1358,23 @@ pub trait TableLike {
     fn get<'s>(&'s self, key: &str) -> Option<&'s Item>;
     /// Returns an optional mutable reference to an item given the key.
     fn get_mut<'s>(&'s mut self, key: &str) -> Option<&'s mut Item>;
+    /// Returns true iff the table contains a nonempty item with the given key.
+    fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some_and(|i| !i.is_none())
+    }
+    /// Returns a mutable iterator over key/value pairs.
+    fn iter_mut(&mut self) -> IterMut<'_>;
+    /// Inserts an item, returning the previous one if the key was present.
+    ///
+    /// Inline tables convert the item into a value with `Item::into_value`,
+    /// inserting `Item::None` into them is the same as removing the key.
+    fn insert(&mut self, key: &str, item: Item) -> Option<Item>;
+    /// Removes an item given the key.
+    fn remove(&mut self, key: &str) -> Option<Item>;
+    /// Returns the decor associated with a given key of the table.
+    fn decor(&self, key: &str) -> Option<&Decor>;
+    /// Returns the mutable decor associated with a given key of the table.
+    fn decor_mut(&mut self, key: &str) -> Option<&mut Decor>;
 }
 
 impl TableLike for Table {
@@ -421,6 +1388,40 @@ impl TableLike for Table {
     fn get_mut<'s>(&'s mut self, key: &str) -> Option<&'s mut Item> {
         self.get_mut(key)
     }
+    fn iter_mut(&mut self) -> IterMut<'_> {
+        self.iter_mut()
+    }
+    fn insert(&mut self, key: &str, item: Item) -> Option<Item> {
+        self.insert(key, item)
+    }
+    fn remove(&mut self, key: &str) -> Option<Item> {
+        self.remove(key)
+    }
+    fn decor(&self, key: &str) -> Option<&Decor> {
+        self.decor(key)
+    }
+    fn decor_mut(&mut self, key: &str) -> Option<&mut Decor> {
+        self.decor_mut(key)
+    }
+}
+
+/// The pairs are inserted as by `Table::insert`, reserving room for all of them at once.
+///
+/// # Example
+/// ```rust
+/// # use toml_edit::{value, Table};
+/// let mut table = Table::with_capacity(3);
+/// table.extend((0..3).map(|i| (format!("key{}", i), value(i))));
+/// assert_eq!(table.to_string(), "key0 = 0\nkey1 = 1\nkey2 = 2\n");
+/// ```
+impl<K: AsRef<str>> Extend<(K, Item)> for Table {
+    fn extend<I: IntoIterator<Item = (K, Item)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (key, item) in iter {
+            self.insert(key.as_ref(), item);
+        }
+    }
 }
 
 /// Returns a formatted value.
@@ -459,3 +1460,104 @@ pub fn table() -> Item {
 pub fn array() -> Item {
     Item::ArrayOfTables(ArrayOfTables::new())
 }
+
+// returns the lines of `prefix` consisting of whitespaces only, dropping comments
+fn whitespace_lines(prefix: &str) -> InternalString {
+    prefix
+        .split('\n')
+        .filter(|line| line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .into()
+}
+
+// returns the greatest position of the tables in `item`, including the nested ones
+fn last_position(item: &Item) -> Option<usize> {
+    match *item {
+        Item::Table(ref t) => t
+            .items
+            .values()
+            .filter_map(|kv| last_position(&kv.value))
+            .chain(t.position)
+            .max(),
+        Item::ArrayOfTables(ref a) => a.values.iter().filter_map(last_position).max(),
+        _ => None,
+    }
+}
+
+// removes the entries set to `Item::None` and the emptied implicit and dotted tables
+fn compact_items(items: &mut KeyValuePairs, path: &mut KeyPath, removed: &mut Vec<KeyPath>) {
+    let mut empty = Vec::new();
+    for (key, kv) in items.iter_mut() {
+        path.push(Key::with_repr(key.clone(), kv.key.clone()));
+        let remove = match kv.value {
+            Item::None => true,
+            Item::Value(ref mut v) => {
+                compact_value(v, path, removed);
+                false
+            }
+            Item::Table(ref mut t) => {
+                compact_items(&mut t.items, path, removed);
+                (t.implicit || t.dotted) && t.items.is_empty()
+            }
+            Item::ArrayOfTables(ref mut a) => {
+                for t in a.iter_mut() {
+                    compact_items(&mut t.items, path, removed);
+                }
+                false
+            }
+        };
+        if remove {
+            empty.push(key.clone());
+            removed.push(path.clone());
+        }
+        path.pop();
+    }
+    for key in empty {
+        items.remove(&key);
+    }
+}
+
+fn compact_value(value: &mut Value, path: &mut KeyPath, removed: &mut Vec<KeyPath>) {
+    match *value {
+        Value::InlineTable(ref mut t) => compact_items(&mut t.items, path, removed),
+        Value::Array(ref mut a) => {
+            for v in a.values.iter_mut().filter_map(Item::as_value_mut) {
+                compact_value(v, path, removed);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn bareify_items(items: &mut KeyValuePairs) -> usize {
+    let mut changed = 0;
+    for (key, kv) in items.iter_mut() {
+        if let Some(raw) = normalized_raw(key, &kv.key.raw_value) {
+            kv.key.raw_value = raw;
+            changed += 1;
+        }
+        changed += match kv.value {
+            Item::None => 0,
+            Item::Value(ref mut v) => bareify_value(v),
+            Item::Table(ref mut t) => bareify_items(&mut t.items),
+            Item::ArrayOfTables(ref mut a) => {
+                a.iter_mut().map(|t| bareify_items(&mut t.items)).sum()
+            }
+        };
+    }
+    changed
+}
+
+fn bareify_value(value: &mut Value) -> usize {
+    match *value {
+        Value::InlineTable(ref mut t) => bareify_items(&mut t.items),
+        Value::Array(ref mut a) => a
+            .values
+            .iter_mut()
+            .filter_map(Item::as_value_mut)
+            .map(bareify_value)
+            .sum(),
+        _ => 0,
+    }
+}
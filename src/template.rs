@@ -0,0 +1,232 @@
+use crate::document::Document;
+use crate::formatted::decorated;
+use crate::parser::TomlError;
+use crate::table::Item;
+use crate::value::Value;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result};
+use std::hash::{BuildHasher, Hash};
+use std::str::FromStr;
+
+/// A document with `{{name}}` placeholders in its string values.
+///
+/// A string value consisting of a single placeholder is replaced by the value of
+/// any type, keeping the decor of the string, e.g. `port = "{{port}}"` may become `port = 8080`.
+/// Placeholders inside of longer strings are replaced by the values written as TOML,
+/// without quotes for strings. Whitespaces around names are ignored, keys are never replaced.
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use toml_edit::{Template, Value};
+///
+/// let template = r#"
+/// [package]
+/// name = "{{crate_name}}"  # the name
+/// authors = ["{{ author }} <{{email}}>"]
+/// edition = "{{edition}}"
+/// "#.parse::<Template>().unwrap();
+/// assert_eq!(template.placeholders(), ["crate_name", "author", "email", "edition"]);
+///
+/// let mut values = HashMap::new();
+/// values.insert("crate_name", Value::from("hello"));
+/// values.insert("author", Value::from("Jane"));
+/// values.insert("email", Value::from("jane@example.com"));
+/// values.insert("edition", Value::from(2018));
+/// let doc = template.instantiate(&values).unwrap();
+/// assert_eq!(doc.to_string(), r#"
+/// [package]
+/// name = "hello"  # the name
+/// authors = ["Jane <jane@example.com>"]
+/// edition = 2018
+/// "#);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Template {
+    document: Document,
+}
+
+/// Placeholders of a `Template` without values.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TemplateError {
+    missing: Vec<String>,
+}
+
+impl TemplateError {
+    /// Returns the names of the placeholders without values, in the order of their first use.
+    pub fn missing(&self) -> &[String] {
+        &self.missing
+    }
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "missing values for placeholders ")?;
+        for (i, name) in self.missing.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "`{}`", name)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl Template {
+    /// Returns the template document.
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// Returns the names of the placeholders, in the order of their first use.
+    pub fn placeholders(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.document
+            .clone()
+            .replace_placeholders(&mut |name: &str| {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_owned());
+                }
+                None
+            });
+        names
+    }
+
+    /// Creates a document replacing the placeholders with the given values,
+    /// failing if there are placeholders without values.
+    pub fn instantiate<K, V, S>(
+        &self,
+        values: &HashMap<K, V, S>,
+    ) -> std::result::Result<Document, TemplateError>
+    where
+        K: Borrow<str> + Hash + Eq,
+        V: Clone + Into<Value>,
+        S: BuildHasher,
+    {
+        let mut missing = Vec::new();
+        let mut document = self.document.clone();
+        document.replace_placeholders(&mut |name: &str| {
+            let value = values.get(name).cloned().map(Into::into);
+            if value.is_none() && !missing.iter().any(|n| n == name) {
+                missing.push(name.to_owned());
+            }
+            value
+        });
+        if missing.is_empty() {
+            Ok(document)
+        } else {
+            Err(TemplateError { missing })
+        }
+    }
+}
+
+impl From<Document> for Template {
+    fn from(document: Document) -> Self {
+        Self { document }
+    }
+}
+
+impl FromStr for Template {
+    type Err = TomlError;
+
+    /// Parses a template from a &str
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        s.parse::<Document>().map(Template::from)
+    }
+}
+
+impl Document {
+    fn replace_placeholders<F>(&mut self, lookup: &mut F)
+    where
+        F: FnMut(&str) -> Option<Value>,
+    {
+        replace_in_item(&mut self.root, lookup);
+    }
+}
+
+fn replace_in_item<F>(item: &mut Item, lookup: &mut F)
+where
+    F: FnMut(&str) -> Option<Value>,
+{
+    match *item {
+        Item::None => {}
+        Item::Value(ref mut v) => replace_in_value(v, lookup),
+        Item::Table(ref mut t) => {
+            for (_, kv) in t.items.iter_mut() {
+                replace_in_item(&mut kv.value, lookup);
+            }
+        }
+        Item::ArrayOfTables(ref mut a) => {
+            for t in a.values.iter_mut() {
+                replace_in_item(t, lookup);
+            }
+        }
+    }
+}
+
+fn replace_in_value<F>(value: &mut Value, lookup: &mut F)
+where
+    F: FnMut(&str) -> Option<Value>,
+{
+    let replaced = match *value {
+        Value::String(ref f) => {
+            replace_in_str(f.value(), lookup).map(|v| decorated(v, f.prefix(), f.suffix()))
+        }
+        Value::Array(ref mut a) => {
+            for v in a.values.iter_mut() {
+                replace_in_item(v, lookup);
+            }
+            None
+        }
+        Value::InlineTable(ref mut t) => {
+            for (_, kv) in t.items.iter_mut() {
+                replace_in_item(&mut kv.value, lookup);
+            }
+            None
+        }
+        _ => None,
+    };
+    if let Some(replaced) = replaced {
+        *value = replaced;
+    }
+}
+
+// Returns `None` if the string has no placeholders or some of them have no values.
+fn replace_in_str<F>(s: &str, lookup: &mut F) -> Option<Value>
+where
+    F: FnMut(&str) -> Option<Value>,
+{
+    let mut parts = Vec::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start + 2..].find("}}") {
+            Some(end) => start + 2 + end,
+            None => break,
+        };
+        parts.push((&rest[..start], rest[start + 2..end].trim()));
+        rest = &rest[end + 2..];
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    // look up all the placeholders for the missing ones to be reported
+    let values: Vec<_> = parts.iter().map(|&(_, name)| lookup(name)).collect();
+    let mut values = values.into_iter().collect::<Option<Vec<_>>>()?;
+    if parts.len() == 1 && parts[0].0.is_empty() && rest.is_empty() {
+        return values.pop().map(|v| decorated(v, "", ""));
+    }
+    let mut result = String::with_capacity(s.len());
+    for ((text, _), value) in parts.into_iter().zip(values) {
+        result.push_str(text);
+        match value {
+            Value::String(ref f) => result.push_str(f.value()),
+            value => result.push_str(&decorated(value, "", "").to_string()),
+        }
+    }
+    result.push_str(rest);
+    Some(Value::from(result))
+}
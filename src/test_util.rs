@@ -0,0 +1,339 @@
+//! Generators and mutations of documents for property-based tests and fuzzing
+//! (`test-util` feature).
+//!
+//! A `Generator` draws its choices from bytes, e.g. the input of a fuzzer or bytes generated
+//! by a property-based testing framework. Once the bytes are exhausted, the smallest choices
+//! are made, so shorter inputs generate smaller documents and shrinking the bytes shrinks
+//! the documents. The generated documents mix the representations found in real documents,
+//! e.g. string styles, hexadecimal integers, comments, subtables and arrays of tables,
+//! and are valid: they pass `Document::assert_valid` and are parsed back as written.
+//!
+//! Mutations are plain values which may be applied in any order or dropped,
+//! so that a sequence of them can be shrunk as well.
+//!
+//! `Document`, `Table` and `Value` implement `arbitrary::Arbitrary` with a `Generator`,
+//! e.g. for `cargo fuzz` targets or property-based tests built on `arbitrary`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use toml_edit::test_util::Generator;
+//! use toml_edit::Document;
+//!
+//! let bytes: Vec<u8> = (0..200u32).map(|i| (i * 37 % 251) as u8).collect();
+//! let mut gen = Generator::new(&bytes);
+//! let mut doc = gen.document();
+//! assert!(doc.assert_valid().is_ok());
+//!
+//! let mutations = gen.mutations(&doc, 3);
+//! for mutation in &mutations {
+//!     mutation.apply(&mut doc);
+//! }
+//! let s = doc.to_string();
+//! assert_eq!(s.parse::<Document>().unwrap().to_string(), s);
+//! ```
+
+use crate::array_of_tables::ArrayOfTables;
+use crate::document::Document;
+use crate::formatted::{decorated, StringStyle};
+use crate::key::Key;
+use crate::key_path::KeyPath;
+use crate::table::{value, Item, Table, TableLike};
+use crate::value::{Array, InlineTable, Value};
+
+const MAX_DEPTH: usize = 3;
+const MAX_LEN: usize = 4;
+
+const KEYS: &[&str] = &[
+    "name",
+    "version",
+    "a",
+    "b-c",
+    "d_e",
+    "1",
+    "serde.json",
+    "with space",
+    "\"quoted\"",
+    "",
+];
+
+const STRINGS: &[&str] = &[
+    "",
+    "hello",
+    "it's",
+    "say \"hi\"",
+    "C:\\path",
+    "tab\there",
+    "two\nlines",
+    "unicode é ✓",
+];
+
+const STYLES: &[StringStyle] = &[
+    StringStyle::Basic,
+    StringStyle::Literal,
+    StringStyle::MultilineBasic,
+    StringStyle::MultilineLiteral,
+];
+
+const INTEGERS: &[&str] = &[
+    "0",
+    "1",
+    "-17",
+    "1_000",
+    "0xff",
+    "0o17",
+    "0b101",
+    "9223372036854775807",
+];
+
+const FLOATS: &[&str] = &[
+    "0.0", "1.5", "-2.25", "6.02e23", "1_000.5", "inf", "-inf", "nan",
+];
+
+const DATETIMES: &[&str] = &[
+    "1979-05-27T07:32:00Z",
+    "1979-05-27T00:32:00.999999-07:00",
+    "1979-05-27T07:32:00",
+    "1979-05-27",
+    "07:32:00",
+];
+
+const COMMENTS: &[&str] = &["", " # comment", "  #", " # ✓"];
+
+/// Generates keys, values, tables and documents from bytes.
+#[derive(Debug, Clone)]
+pub struct Generator<'a> {
+    data: &'a [u8],
+    depth: usize,
+}
+
+impl<'a> Generator<'a> {
+    /// Creates a generator drawing its choices from `data`.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, depth: 0 }
+    }
+
+    /// Returns true iff all the bytes are used, the remaining choices are the smallest.
+    pub fn is_exhausted(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn byte(&mut self) -> u8 {
+        match self.data.split_first() {
+            Some((&b, rest)) => {
+                self.data = rest;
+                b
+            }
+            None => 0,
+        }
+    }
+
+    // chooses a number below `n`, 0 once the bytes are exhausted
+    fn below(&mut self, n: usize) -> usize {
+        if n <= 1 {
+            0
+        } else {
+            usize::from(self.byte()) % n
+        }
+    }
+
+    fn pick<T: Copy>(&mut self, options: &[T]) -> T {
+        options[self.below(options.len())]
+    }
+
+    fn len(&mut self) -> usize {
+        self.below(MAX_LEN + 1)
+    }
+
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Generates a key, with bare and quoted representations.
+    pub fn key(&mut self) -> Key {
+        let key = self.pick(KEYS);
+        key.parse::<Key>()
+            .unwrap_or_else(|_| Key::from_unquoted(key))
+    }
+
+    /// Generates a scalar, an array or an inline table without decor.
+    pub fn value(&mut self) -> Value {
+        // nested values are scalars once the maximum depth is reached
+        let kinds = if self.depth < MAX_DEPTH { 7 } else { 5 };
+        let parse = |s: &str| s.parse::<Value>().expect("valid value");
+        match self.below(kinds) {
+            0 => parse(self.pick(INTEGERS)),
+            1 => {
+                let s = self.pick(STRINGS);
+                let style = self.pick(STYLES);
+                Value::try_string_in_style(s, style).unwrap_or_else(|_| Value::from(s))
+            }
+            2 => parse(self.pick(FLOATS)),
+            3 => Value::from(self.below(2) == 1),
+            4 => parse(self.pick(DATETIMES)),
+            5 => Value::Array(self.nested(Self::array)),
+            _ => Value::InlineTable(self.nested(Self::inline_table)),
+        }
+    }
+
+    fn array(&mut self) -> Array {
+        let mut array = Array::default();
+        for _ in 0..self.len() {
+            // arrays of mixed types are skipped to support TOML v0.5 as well
+            let _ = array.push(self.value());
+        }
+        array
+    }
+
+    fn inline_table(&mut self) -> InlineTable {
+        let mut table = InlineTable::default();
+        for _ in 0..self.len() {
+            let key = self.key();
+            let value = self.value();
+            table.insert(key.raw(), value);
+        }
+        table.fmt();
+        table
+    }
+
+    /// Generates a table with values, subtables and arrays of tables.
+    pub fn table(&mut self) -> Table {
+        let mut table = Table::new();
+        for _ in 0..self.len() {
+            let key = self.key();
+            let kinds = if self.depth < MAX_DEPTH { 4 } else { 1 };
+            let item = match self.below(kinds) {
+                0 | 1 => {
+                    let comment = self.pick(COMMENTS);
+                    Item::Value(decorated(self.value(), " ", comment))
+                }
+                2 => Item::Table(self.nested(Self::table)),
+                _ => {
+                    let mut array = ArrayOfTables::new();
+                    for _ in 0..=self.len() {
+                        array.append(self.nested(Self::table));
+                    }
+                    Item::ArrayOfTables(array)
+                }
+            };
+            table.insert(key.raw(), item);
+        }
+        table
+    }
+
+    /// Generates a document.
+    pub fn document(&mut self) -> Document {
+        let mut doc = Document::new();
+        *doc.as_table_mut() = self.table();
+        doc
+    }
+
+    /// Generates a mutation of the document, removing or replacing one of its items
+    /// or inserting a value into one of its tables.
+    pub fn mutation(&mut self, doc: &Document) -> Mutation {
+        let leaves: Vec<KeyPath> = doc.clone().into_iter().map(|(path, _)| path).collect();
+        let value = self.value();
+        if leaves.is_empty() {
+            return Mutation::Set(std::iter::once(self.key()).collect(), value);
+        }
+        let mut path = leaves[self.below(leaves.len())].clone();
+        match self.below(3) {
+            0 => Mutation::Remove(path),
+            1 => Mutation::Set(path, value),
+            _ => {
+                path.pop();
+                path.push(self.key());
+                Mutation::Set(path, value)
+            }
+        }
+    }
+
+    /// Generates `n` mutations of the document.
+    ///
+    /// The mutations are generated for the given document, later mutations may not apply
+    /// once the earlier ones are applied.
+    pub fn mutations(&mut self, doc: &Document, n: usize) -> Vec<Mutation> {
+        (0..n).map(|_| self.mutation(doc)).collect()
+    }
+}
+
+/// A change of a document, see `Generator::mutation`.
+#[derive(Debug, Clone)]
+pub enum Mutation {
+    /// Removes the item at the path.
+    Remove(KeyPath),
+    /// Sets the value at the path, keeping the decor of a replaced value.
+    /// The parent of the path has to be a table or an inline table.
+    Set(KeyPath, Value),
+}
+
+impl Mutation {
+    /// Applies the mutation, returning false if the path or its parent is missing.
+    pub fn apply(&self, doc: &mut Document) -> bool {
+        let path = match *self {
+            Mutation::Remove(ref path) | Mutation::Set(ref path, _) => path,
+        };
+        let (last, parents) = match path.keys().split_last() {
+            Some(split) => split,
+            None => return false,
+        };
+        let parent = parents.iter().cloned().collect::<KeyPath>();
+        let table = if parent.is_empty() {
+            Some(doc.as_table_mut() as &mut dyn TableLike)
+        } else {
+            doc.get_path_mut(&parent).and_then(Item::as_table_like_mut)
+        };
+        let table = match table {
+            Some(table) => table,
+            None => return false,
+        };
+        match *self {
+            Mutation::Remove(..) => table.remove(last.get()).is_some(),
+            Mutation::Set(_, ref new) => {
+                match table.get_mut(last.get()) {
+                    Some(Item::Value(old)) => {
+                        let decor = old.decor();
+                        *old = decorated(new.clone(), decor.prefix(), decor.suffix());
+                    }
+                    _ => {
+                        table.insert(last.raw(), value(new.clone()));
+                    }
+                }
+                true
+            }
+        }
+    }
+}
+
+// Generates an item with the remaining bytes, consuming only those the generator used.
+fn arbitrary_with<'a, T>(
+    u: &mut arbitrary::Unstructured<'a>,
+    generate: impl FnOnce(&mut Generator<'a>) -> T,
+) -> arbitrary::Result<T> {
+    let data = u.peek_bytes(u.len()).unwrap_or_default();
+    let mut gen = Generator::new(data);
+    let item = generate(&mut gen);
+    u.bytes(data.len() - gen.data.len())?;
+    Ok(item)
+}
+
+impl<'a> arbitrary::Arbitrary<'a> for Document {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        arbitrary_with(u, Generator::document)
+    }
+}
+
+impl<'a> arbitrary::Arbitrary<'a> for Table {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        arbitrary_with(u, Generator::table)
+    }
+}
+
+impl<'a> arbitrary::Arbitrary<'a> for Value {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        arbitrary_with(u, Generator::value)
+    }
+}
@@ -0,0 +1,212 @@
+//! Assertions on TOML output for the tests of downstream crates (`test-util` feature).
+//!
+//! `assert_toml_eq!` compares documents regardless of their formatting, as
+//! `Document::fingerprint` does, and `assert_toml_text_eq!` compares them as written,
+//! e.g. to check that an edit preserved the formatting. On failure, both report
+//! a line diff of the documents, the former preceded by the paths of the items which differ.
+//!
+//! # Example
+//!
+//! ```rust
+//! use toml_edit::{assert_toml_eq, assert_toml_text_eq, value, Document};
+//!
+//! let mut doc = "[package]\nname = 'a'  # the name\n".parse::<Document>().unwrap();
+//! doc["package"]["version"] = value("0.1.0");
+//! assert_toml_eq!(doc, "package = { version = '0.1.0', name = 'a' }");
+//! assert_toml_text_eq!(doc, "[package]\nname = 'a'  # the name\nversion = \"0.1.0\"\n");
+//!
+//! let report = toml_edit::testing::compare(&doc, "[package]\nname = 'b'\n").unwrap_err();
+//! assert!(report.contains("`package.name`: expected 'b', got 'a'"));
+//! assert!(report.contains("unexpected `package.version` = \"0.1.0\""));
+//! ```
+
+use crate::document::Document;
+use crate::key::Key;
+use crate::key_path::KeyPath;
+use crate::merge::same_value;
+use crate::table::{Item, TableLike};
+use crate::value::Value;
+use std::fmt::Display;
+
+// the length of the previews of differing items
+const PREVIEW_LEN: usize = 60;
+
+/// Asserts that two documents have the same content regardless of their formatting,
+/// see `testing::compare`.
+///
+/// The actual document is anything displayed as TOML, e.g. a `Document` or a `&str`,
+/// the expected one is a string.
+#[macro_export]
+macro_rules! assert_toml_eq {
+    ($actual:expr, $expected:expr $(,)?) => {
+        if let Err(report) = $crate::testing::compare(&$actual, &$expected) {
+            panic!("{}", report);
+        }
+    };
+}
+
+/// Asserts that two documents are written the same, see `testing::compare_text`.
+#[macro_export]
+macro_rules! assert_toml_text_eq {
+    ($actual:expr, $expected:expr $(,)?) => {
+        if let Err(report) = $crate::testing::compare_text(&$actual, &$expected) {
+            panic!("{}", report);
+        }
+    };
+}
+
+/// Compares the content of the displayed `actual` with the document `expected`
+/// regardless of their formatting, returning a report of the differences if any.
+///
+/// Either document failing to parse is reported as a difference.
+pub fn compare<A: Display + ?Sized>(actual: &A, expected: &str) -> Result<(), String> {
+    let actual = actual.to_string();
+    let parsed_actual = actual
+        .parse::<Document>()
+        .map_err(|e| format!("the actual document is invalid: {}", e))?;
+    let parsed_expected = expected
+        .parse::<Document>()
+        .map_err(|e| format!("the expected document is invalid: {}", e))?;
+    let mut differences = Vec::new();
+    diff_tables(
+        parsed_actual.as_table(),
+        parsed_expected.as_table(),
+        &mut KeyPath::new(),
+        &mut differences,
+    );
+    if differences.is_empty() {
+        return Ok(());
+    }
+    let mut report = String::from("TOML documents differ:\n");
+    for difference in differences {
+        report.push_str(&difference);
+        report.push('\n');
+    }
+    report.push('\n');
+    report.push_str(&line_diff(&actual, expected));
+    Err(report)
+}
+
+/// Compares the displayed `actual` with `expected` as written, returning a line diff
+/// if they differ.
+pub fn compare_text<A: Display + ?Sized>(actual: &A, expected: &str) -> Result<(), String> {
+    let actual = actual.to_string();
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "TOML documents are written differently:\n\n{}",
+            line_diff(&actual, expected)
+        ))
+    }
+}
+
+fn diff_tables(
+    actual: &dyn TableLike,
+    expected: &dyn TableLike,
+    path: &mut KeyPath,
+    differences: &mut Vec<String>,
+) {
+    for (key, e) in expected.iter().filter(|(_, item)| !item.is_none()) {
+        path.push(Key::from_unquoted(key));
+        match get(actual, key) {
+            None => differences.push(format!(
+                "missing `{}`, expected {}",
+                path,
+                e.preview(PREVIEW_LEN)
+            )),
+            Some(a) => match (a.as_table_like(), e.as_table_like()) {
+                (Some(a), Some(e)) => diff_tables(a, e, path, differences),
+                _ if !same(a, e) => differences.push(format!(
+                    "`{}`: expected {}, got {}",
+                    path,
+                    e.preview(PREVIEW_LEN),
+                    a.preview(PREVIEW_LEN)
+                )),
+                _ => {}
+            },
+        }
+        path.pop();
+    }
+    for (key, a) in actual.iter().filter(|(_, item)| !item.is_none()) {
+        if get(expected, key).is_none() {
+            path.push(Key::from_unquoted(key));
+            differences.push(format!(
+                "unexpected `{}` = {}",
+                path,
+                a.preview(PREVIEW_LEN)
+            ));
+            path.pop();
+        }
+    }
+}
+
+fn get<'t>(table: &'t dyn TableLike, key: &str) -> Option<&'t Item> {
+    table.get(key).filter(|item| !item.is_none())
+}
+
+// returns true iff the items are equal regardless of their formatting, where unlike
+// in `merge`, arrays of tables and arrays of inline tables are compared by their tables
+fn same(a: &Item, b: &Item) -> bool {
+    if let (Some(a), Some(b)) = (tables(a), tables(b)) {
+        return a.len() == b.len() && a.iter().zip(&b).all(|(a, b)| same_table(*a, *b));
+    }
+    match (a.as_table_like(), b.as_table_like(), a, b) {
+        (Some(a), Some(b), _, _) => same_table(a, b),
+        (_, _, Item::Value(a), Item::Value(b)) => same_value(a, b),
+        _ => false,
+    }
+}
+
+fn same_table(a: &dyn TableLike, b: &dyn TableLike) -> bool {
+    let entries = |t: &dyn TableLike| t.iter().filter(|(_, item)| !item.is_none()).count();
+    entries(a) == entries(b)
+        && a.iter()
+            .filter(|(_, item)| !item.is_none())
+            .all(|(key, item)| get(b, key).is_some_and(|other| same(item, other)))
+}
+
+// the tables of an array of tables or of a non-empty array of inline tables
+fn tables(item: &Item) -> Option<Vec<&dyn TableLike>> {
+    match *item {
+        Item::ArrayOfTables(ref a) => Some(a.iter().map(|t| t as &dyn TableLike).collect()),
+        Item::Value(Value::Array(ref a)) if !a.is_empty() => a
+            .iter()
+            .map(|v| v.as_inline_table().map(|t| t as &dyn TableLike))
+            .collect(),
+        _ => None,
+    }
+}
+
+// a diff of the lines of both texts, from the longest common subsequence of lines
+fn line_diff(actual: &str, expected: &str) -> String {
+    let a: Vec<&str> = actual.lines().collect();
+    let e: Vec<&str> = expected.lines().collect();
+    // common[i][j] is the length of the longest common subsequence of e[i..] and a[j..]
+    let mut common = vec![vec![0usize; a.len() + 1]; e.len() + 1];
+    for i in (0..e.len()).rev() {
+        for j in (0..a.len()).rev() {
+            common[i][j] = if e[i] == a[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let mut diff = String::from("--- expected\n+++ actual\n");
+    let (mut i, mut j) = (0, 0);
+    while i < e.len() || j < a.len() {
+        if i < e.len() && j < a.len() && e[i] == a[j] {
+            diff.push_str(&format!(" {}\n", e[i]));
+            i += 1;
+            j += 1;
+        } else if j == a.len() || (i < e.len() && common[i + 1][j] >= common[i][j + 1]) {
+            diff.push_str(&format!("-{}\n", e[i]));
+            i += 1;
+        } else {
+            diff.push_str(&format!("+{}\n", a[j]));
+            j += 1;
+        }
+    }
+    diff
+}
@@ -0,0 +1,235 @@
+use crate::document::Document;
+use crate::formatted;
+use crate::key_path::KeyPath;
+use crate::table::{Item, Table, TableLike};
+use crate::value::{Array, Value};
+use std::fmt::{Display, Formatter, Result};
+use std::iter::FromIterator;
+
+/// A batch of edits applied by `Document::transaction`.
+#[derive(Debug, Default)]
+pub struct Transaction {
+    edits: Vec<Edit>,
+}
+
+#[derive(Debug)]
+pub(crate) enum Edit {
+    Set(KeyPath, Item),
+    Remove(KeyPath),
+    Append(KeyPath, Value),
+}
+
+/// A failed edit of a `Transaction`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum EditError {
+    /// The path of the edit is empty.
+    EmptyPath,
+    /// The item at the path, a prefix of the path of the edit, is not a table.
+    NotATable(KeyPath),
+    /// There is no item to remove at the path.
+    Missing(KeyPath),
+    /// The item at the path is not an array.
+    NotAnArray(KeyPath),
+    /// The appended value is of a different type than the values of the array at the path.
+    MixedTypes(KeyPath),
+}
+
+impl Display for EditError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match *self {
+            EditError::EmptyPath => write!(f, "the path is empty"),
+            EditError::NotATable(ref path) => write!(f, "`{}` is not a table", path),
+            EditError::Missing(ref path) => write!(f, "there is no item at `{}`", path),
+            EditError::NotAnArray(ref path) => write!(f, "`{}` is not an array", path),
+            EditError::MixedTypes(ref path) => {
+                write!(f, "`{}` contains values of another type", path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+impl Transaction {
+    /// Sets the item at the path, creating the missing tables.
+    ///
+    /// The decor of a replaced value is preserved.
+    pub fn set(&mut self, path: KeyPath, item: Item) -> &mut Self {
+        self.edits.push(Edit::Set(path, item));
+        self
+    }
+
+    /// Removes the item at the path, which has to be present.
+    pub fn remove(&mut self, path: KeyPath) -> &mut Self {
+        self.edits.push(Edit::Remove(path));
+        self
+    }
+
+    /// Appends the value to the array at the path, creating the array if there is no item.
+    pub fn append<V: Into<Value>>(&mut self, path: KeyPath, value: V) -> &mut Self {
+        self.edits.push(Edit::Append(path, value.into()));
+        self
+    }
+
+    /// Returns the number of edits.
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    /// Returns true iff there are no edits.
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+}
+
+impl Document {
+    /// Applies the edits recorded by `edits` all at once, or none of them.
+    ///
+    /// The edits are applied in order, each one to the result of the previous ones.
+    /// If any of them fails, the document is left unchanged and the failed edits are
+    /// returned with their indices, so that all of them can be reported at once.
+    /// The items created by a successful transaction follow the style set by
+    /// `Document::set_default_style`, the other items are left as they are.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// use toml_edit::{value, Document, EditError, KeyPath};
+    ///
+    /// let path = |s: &str| s.parse::<KeyPath>().unwrap();
+    /// let mut doc = "[dependencies]\nserde = '1.0' # serialization\nlog = '0.4'\n"
+    ///     .parse::<Document>()
+    ///     .unwrap();
+    ///
+    /// let errors = doc.transaction(|tx| {
+    ///     tx.set(path("dependencies.serde"), value("1.0.100"));
+    ///     tx.remove(path("dependencies.rand"));
+    ///     tx.set(path("dependencies.log.version"), value("0.4.8"));
+    /// }).unwrap_err();
+    /// assert_eq!(errors, vec![
+    ///     (1, EditError::Missing(path("dependencies.rand"))),
+    ///     (2, EditError::NotATable(path("dependencies.log"))),
+    /// ]);
+    /// assert_eq!(doc["dependencies"]["serde"].as_str(), Some("1.0"));
+    ///
+    /// doc.transaction(|tx| {
+    ///     tx.set(path("dependencies.serde"), value("1.0.100"));
+    ///     tx.remove(path("dependencies.log"));
+    /// }).unwrap();
+    /// assert_eq!(doc.to_string(), "[dependencies]\nserde = \"1.0.100\" # serialization\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn transaction<F>(&mut self, edits: F) -> std::result::Result<(), Vec<(usize, EditError)>>
+    where
+        F: FnOnce(&mut Transaction),
+    {
+        let mut transaction = Transaction::default();
+        edits(&mut transaction);
+        let mut doc = self.clone_keeping_ids();
+        let mut created = Vec::new();
+        let mut errors = Vec::new();
+        for (i, edit) in transaction.edits.into_iter().enumerate() {
+            match apply(doc.as_table_mut(), edit) {
+                Ok(c) => created.extend(c),
+                Err(e) => errors.push((i, e)),
+            }
+        }
+        if errors.is_empty() {
+            doc.restyle_created(&created);
+            *self = doc;
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+// An item created by an edit, to be restyled: the entry at the path, or only the value
+// at the path if it replaced an existing value, keeping the decor of its key.
+pub(crate) struct Created {
+    pub(crate) path: KeyPath,
+    pub(crate) new_entry: bool,
+}
+
+pub(crate) fn apply(
+    root: &mut Table,
+    edit: Edit,
+) -> std::result::Result<Option<Created>, EditError> {
+    match edit {
+        Edit::Set(path, item) => set(root, &path, item).map(Some),
+        Edit::Remove(path) => {
+            let (last, parents) = path.keys().split_last().ok_or(EditError::EmptyPath)?;
+            let parent = KeyPath::from_iter(parents.iter().cloned());
+            let table = if parent.is_empty() {
+                Some(root as &mut dyn TableLike)
+            } else {
+                root.get_path_mut(&parent).and_then(Item::as_table_like_mut)
+            };
+            match table.and_then(|t| t.remove(last.get())) {
+                Some(item) if !item.is_none() => Ok(None),
+                _ => Err(EditError::Missing(path)),
+            }
+        }
+        Edit::Append(path, value) => match root.get_path_mut(&path) {
+            None | Some(Item::None) => {
+                let mut array = Array::default();
+                array
+                    .push(value)
+                    .expect("an empty array takes values of any type");
+                set(root, &path, crate::table::value(array)).map(Some)
+            }
+            Some(Item::Value(Value::Array(array))) => array
+                .push(value)
+                .map(|_| None)
+                .map_err(|_| EditError::MixedTypes(path)),
+            Some(_) => Err(EditError::NotAnArray(path)),
+        },
+    }
+}
+
+fn set(root: &mut Table, path: &KeyPath, item: Item) -> std::result::Result<Created, EditError> {
+    let (last, parents) = path.keys().split_last().ok_or(EditError::EmptyPath)?;
+    let mut table: &mut dyn TableLike = root;
+    // the number of keys of the path to the topmost created entry
+    let mut created = None;
+    for (i, key) in parents.iter().enumerate() {
+        if table.get(key.get()).is_none_or(Item::is_none) {
+            let mut t = Table::new();
+            t.set_implicit(true);
+            table.insert(key.raw(), Item::Table(t));
+            created = created.or(Some(i + 1));
+        }
+        table = table
+            .get_mut(key.get())
+            .and_then(Item::as_table_like_mut)
+            .ok_or_else(|| {
+                EditError::NotATable(KeyPath::from_iter(parents[..=i].iter().cloned()))
+            })?;
+    }
+    let new_entry = match (table.get_mut(last.get()), item) {
+        (Some(Item::Value(old)), Item::Value(mut new)) => {
+            formatted::decorate(&mut new, old.decor().prefix(), old.decor().suffix());
+            *old = new;
+            false
+        }
+        (old, item) => {
+            let new_entry = old.is_none_or(|old| old.is_none());
+            table.insert(last.raw(), item);
+            new_entry
+        }
+    };
+    Ok(match created {
+        Some(n) => Created {
+            path: KeyPath::from_iter(path.keys()[..n].iter().cloned()),
+            new_entry: true,
+        },
+        None => Created {
+            path: path.clone(),
+            new_entry,
+        },
+    })
+}
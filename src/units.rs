@@ -0,0 +1,234 @@
+//! Numbers with units written as strings, e.g. `"10s"` or `"5MB"` (`units` feature).
+//!
+//! A `Quantity` keeps the string it was read from, which is written back as long as
+//! the value is unchanged, so that e.g. `"90s"` is not rewritten as `"1m30s"` or `"90000ms"`.
+//! The replaced strings keep the decor of the previous values.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[cfg(feature = "parse")]
+//! # fn main() {
+//! use std::time::Duration;
+//! use toml_edit::units::{ByteSize, Quantity};
+//! use toml_edit::Document;
+//!
+//! let mut doc = "timeout = '1500ms' # per request\ncache = '64 MiB'\n".parse::<Document>().unwrap();
+//!
+//! let mut timeout = Quantity::<Duration>::from_value(doc["timeout"].as_value().unwrap()).unwrap();
+//! let cache = Quantity::<ByteSize>::from_value(doc["cache"].as_value().unwrap()).unwrap();
+//! assert_eq!(*timeout.value(), Duration::from_millis(1500));
+//! assert_eq!(*cache.value(), ByteSize(64 << 20));
+//!
+//! timeout.set(Duration::from_secs(3));
+//! timeout.update_value(doc["timeout"].as_value_mut().unwrap());
+//! cache.update_value(doc["cache"].as_value_mut().unwrap());
+//! assert_eq!(doc.to_string(), "timeout = \"3s\" # per request\ncache = '64 MiB'\n");
+//! # }
+//! # #[cfg(not(feature = "parse"))]
+//! # fn main() {}
+//! ```
+
+use crate::formatted;
+use crate::value::Value;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+/// A type of values written as numbers with units.
+pub trait Unit: Sized + PartialEq {
+    /// Parses a number followed by a unit, optionally separated by whitespaces.
+    fn parse(s: &str) -> Option<Self>;
+
+    /// Formats the value with the largest unit it is a whole multiple of.
+    fn format(&self) -> String;
+}
+
+/// A number of bytes, written with the decimal units `B`, `KB`, `MB`, `GB`, `TB` and `PB`
+/// or the binary units `KiB`, `MiB`, `GiB`, `TiB` and `PiB`, ignoring their case.
+/// Numbers without unit are bytes.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub struct ByteSize(pub u64);
+
+const BYTE_UNITS: &[(&str, u128)] = &[
+    ("PiB", 1 << 50),
+    ("PB", 1_000_000_000_000_000),
+    ("TiB", 1 << 40),
+    ("TB", 1_000_000_000_000),
+    ("GiB", 1 << 30),
+    ("GB", 1_000_000_000),
+    ("MiB", 1 << 20),
+    ("MB", 1_000_000),
+    ("KiB", 1 << 10),
+    ("KB", 1_000),
+    ("B", 1),
+];
+
+impl Unit for ByteSize {
+    fn parse(s: &str) -> Option<Self> {
+        let (number, unit) = split(s)?;
+        let multiple = if unit.is_empty() {
+            1
+        } else {
+            BYTE_UNITS
+                .iter()
+                .find(|(u, _)| u.eq_ignore_ascii_case(unit))?
+                .1
+        };
+        let bytes = number.times(multiple)?;
+        u64::try_from(bytes).ok().map(ByteSize)
+    }
+
+    fn format(&self) -> String {
+        format_with(u128::from(self.0), BYTE_UNITS, "B")
+    }
+}
+
+// `Duration` is written with the units `ns`, `us` (or `µs`), `ms`, `s`, `m` (or `min`), `h` and `d`.
+const DURATION_UNITS: &[(&str, u128)] = &[
+    ("d", 86_400_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+impl Unit for Duration {
+    fn parse(s: &str) -> Option<Self> {
+        let (number, unit) = split(s)?;
+        let unit = match unit {
+            "min" => "m",
+            "µs" => "us",
+            unit => unit,
+        };
+        let multiple = DURATION_UNITS.iter().find(|(u, _)| *u == unit)?.1;
+        let nanos = number.times(multiple)?;
+        let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+        Some(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+    }
+
+    fn format(&self) -> String {
+        format_with(self.as_nanos(), DURATION_UNITS, "s")
+    }
+}
+
+// a non-negative decimal number, e.g. `1.5` or `1_000`
+struct Number<'s> {
+    integer: u128,
+    fraction: &'s str,
+}
+
+impl<'s> Number<'s> {
+    // multiplies the number by the unit, rounding to an integer
+    fn times(&self, multiple: u128) -> Option<u128> {
+        let integer = self.integer.checked_mul(multiple)?;
+        if self.fraction.is_empty() {
+            return Some(integer);
+        }
+        let fraction = format!("0.{}", self.fraction).parse::<f64>().ok()?;
+        integer.checked_add((fraction * multiple as f64).round() as u128)
+    }
+}
+
+fn split(s: &str) -> Option<(Number<'_>, &str)> {
+    let s = s.trim();
+    let end = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '_'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(end);
+    let (integer, fraction) = match number.find('.') {
+        Some(i) => (&number[..i], &number[i + 1..]),
+        None => (number, ""),
+    };
+    let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit() || c == '_');
+    if integer.is_empty() || !digits(integer) || !digits(fraction) {
+        return None;
+    }
+    let integer = integer.replace('_', "").parse().ok()?;
+    let number = Number {
+        integer,
+        fraction: fraction.trim_end_matches('_'),
+    };
+    Some((number, unit.trim_start()))
+}
+
+fn format_with(n: u128, units: &[(&str, u128)], zero_unit: &str) -> String {
+    match units.iter().find(|(_, m)| n != 0 && n.is_multiple_of(*m)) {
+        Some((unit, multiple)) => format!("{}{}", n / multiple, unit),
+        None => format!("0{}", zero_unit),
+    }
+}
+
+/// A value with a unit, together with the string it was written as.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Quantity<U> {
+    value: U,
+    raw: String,
+}
+
+impl<U: Unit> Quantity<U> {
+    /// Creates a quantity written in the default format of the unit.
+    pub fn new(value: U) -> Self {
+        let raw = value.format();
+        Self { value, raw }
+    }
+
+    /// Parses a quantity, keeping the string.
+    pub fn parse(s: &str) -> Option<Self> {
+        U::parse(s).map(|value| Self {
+            value,
+            raw: s.to_owned(),
+        })
+    }
+
+    /// Parses a quantity from a string value.
+    pub fn from_value(value: &Value) -> Option<Self> {
+        value.as_str().and_then(Self::parse)
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> &U {
+        &self.value
+    }
+
+    /// Returns the string the quantity is written as.
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// Sets the value, the quantity is written in the default format
+    /// of the unit unless the value is unchanged.
+    pub fn set(&mut self, value: U) {
+        if value != self.value {
+            *self = Self::new(value);
+        }
+    }
+
+    /// Returns a string value of the quantity.
+    pub fn to_value(&self) -> Value {
+        Value::from(self.raw.as_str())
+    }
+
+    /// Replaces the value with the quantity unless it is written as the same string,
+    /// keeping its decor.
+    pub fn update_value(&self, value: &mut Value) {
+        if value.as_str() != Some(&self.raw) {
+            let (prefix, suffix) = (value.decor().prefix(), value.decor().suffix());
+            *value = formatted::decorated(self.to_value(), prefix, suffix);
+        }
+    }
+}
+
+impl<U: Unit> From<U> for Quantity<U> {
+    fn from(value: U) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<U> Display for Quantity<U> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
@@ -0,0 +1,357 @@
+use crate::comments::Scanner;
+use crate::decor::{Decor, Repr};
+use crate::document::Document;
+use crate::key::Key;
+use crate::key_path::KeyPath;
+#[cfg(feature = "parse")]
+use crate::merge::same_value;
+use crate::parser::lexer;
+#[cfg(feature = "parse")]
+use crate::parser::{self, NumberPolicy, ParseOptions, TomlVersion};
+use crate::table::{Item, Table, TableKeyValue};
+use crate::value::{Array, InlineTable, Value};
+#[cfg(feature = "parse")]
+use combine::stream::position::Stream;
+#[cfg(feature = "parse")]
+use combine::EasyParser;
+use linked_hash_map::LinkedHashMap;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result};
+use std::ops::Range;
+
+/// The kind of an `InvalidItem`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum InvalidKind {
+    /// The raw representation of a key is not a valid key.
+    Key,
+    /// The raw representation of a key is valid, but denotes another key.
+    KeyMismatch,
+    /// The raw representation of a value is not a valid value.
+    Value,
+    /// The raw representation of a value is valid, but denotes another value,
+    /// e.g. `nan` for a float which is not NaN.
+    ValueMismatch,
+    /// A decor, or the whitespaces inside of an array or an inline table, contains
+    /// anything but the whitespaces, newlines and comments allowed in its place.
+    Decor,
+}
+
+/// A part of a document which would not be written as valid TOML
+/// or would not be read back as the same value, see `Document::assert_valid`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct InvalidItem {
+    path: KeyPath,
+    kind: InvalidKind,
+    text: String,
+}
+
+impl InvalidItem {
+    /// Returns the path of the key/value pair or the table the invalid part belongs to,
+    /// elements of arrays are reported at the path of the array.
+    /// The path is empty for the whitespaces and comments at the end of the document.
+    pub fn path(&self) -> &KeyPath {
+        &self.path
+    }
+
+    /// Returns the kind of the invalid part.
+    pub fn kind(&self) -> InvalidKind {
+        self.kind
+    }
+
+    /// Returns the text of the invalid part, i.e. a raw representation or a decor.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Display for InvalidItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self.kind {
+            InvalidKind::Key => write!(f, "invalid key {:?}", self.text)?,
+            InvalidKind::KeyMismatch => write!(f, "key {:?} denotes another key", self.text)?,
+            InvalidKind::Value => write!(f, "invalid value {:?}", self.text)?,
+            InvalidKind::ValueMismatch => write!(f, "value {:?} denotes another value", self.text)?,
+            InvalidKind::Decor => write!(f, "invalid decor {:?}", self.text)?,
+        }
+        if self.path.is_empty() {
+            write!(f, " at the end of the document")
+        } else {
+            write!(f, " at `{}`", self.path)
+        }
+    }
+}
+
+impl std::error::Error for InvalidItem {}
+
+/// A key with the byte range of its first occurrence in a displayed table,
+/// see `Table::find_duplicate_keys_case_variant`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct KeySpan {
+    key: Key,
+    span: Range<usize>,
+}
+
+impl KeySpan {
+    /// Returns the key with its raw representation, e.g. quoted.
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    /// Returns the byte range of the raw representation of the key.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl Table {
+    /// Returns the groups of keys of the table which differ only in case, whatever their
+    /// quoting, e.g. `Serde`, `serde` and `"SERDE"`, with the byte ranges of their first
+    /// occurrences in `to_string()`. Such keys are distinct in TOML, but usually not
+    /// for the tools reading the document, e.g. package names in a manifest.
+    ///
+    /// The keys of the subtables are found in their headers, so the spans of the keys
+    /// of the root table of a document are also their spans in the displayed document.
+    /// Keys which are not displayed, e.g. of empty implicit tables, are left out.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let doc = r#"[dependencies]
+    /// serde = "1.0"
+    /// "Serde" = "1.0"
+    ///
+    /// [dependencies.SERDE]
+    /// version = "1.0"
+    /// "#.parse::<Document>().unwrap();
+    /// let deps = doc["dependencies"].as_table().unwrap();
+    /// let duplicates = deps.find_duplicate_keys_case_variant();
+    /// let keys: Vec<_> = duplicates[0].iter().map(|k| (k.key().raw(), k.span())).collect();
+    /// assert_eq!(keys, vec![("serde", 0..5), ("\"Serde\"", 14..21), ("SERDE", 32..37)]);
+    /// assert_eq!(&deps.to_string()[32..37], "SERDE");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn find_duplicate_keys_case_variant(&self) -> Vec<Vec<KeySpan>> {
+        let mut spans: HashMap<&str, Range<usize>> = HashMap::new();
+        let key_spans = Scanner::key_spans(self);
+        for (path, span) in &key_spans {
+            if let [ref key] = *path.keys() {
+                spans.entry(key.get()).or_insert_with(|| span.clone());
+            }
+        }
+        let mut groups: LinkedHashMap<String, Vec<KeySpan>> = LinkedHashMap::new();
+        for (key, kv) in self.items.iter().filter(|(_, kv)| !kv.value.is_none()) {
+            if let Some(span) = spans.get(&key[..]) {
+                groups.entry(key.to_lowercase()).or_default().push(KeySpan {
+                    key: Key::with_repr(key.clone(), kv.key.clone()),
+                    span: span.clone(),
+                });
+            }
+        }
+        let mut groups: Vec<_> = groups
+            .into_iter()
+            .map(|(_, keys)| keys)
+            .filter(|keys| keys.len() > 1)
+            .collect();
+        for keys in &mut groups {
+            keys.sort_by_key(|k| k.span.start);
+        }
+        groups
+    }
+}
+
+impl Document {
+    /// Checks that the document would be written as valid TOML and read back as the same document.
+    ///
+    /// Keys and values are created valid, but decors can be set to any string and may e.g. contain
+    /// a newline inside of an inline table or a comment which swallows the rest of the line.
+    /// The raw representations of keys and scalar values are checked to denote the keys and values,
+    /// without the `parse` feature only those of keys and strings are checked.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{decorated, Document, InvalidKind};
+    /// let mut doc = "point = { x = 1, y = 2 }\n".parse::<Document>().unwrap();
+    /// assert!(doc.assert_valid().is_ok());
+    ///
+    /// let point = doc["point"].as_inline_table_mut().unwrap();
+    /// *point.get_mut("x").unwrap() = decorated(1.into(), " ", " # one\n");
+    /// let errors = doc.assert_valid().unwrap_err();
+    /// assert_eq!(errors[0].kind(), InvalidKind::Decor);
+    /// assert_eq!(errors[0].to_string(), r#"invalid decor " # one\n" at `point.x`"#);
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn assert_valid(&self) -> std::result::Result<(), Vec<InvalidItem>> {
+        let mut validator = Validator {
+            path: KeyPath::new(),
+            errors: Vec::new(),
+        };
+        validator.table(self.as_table());
+        validator.decor(&self.trailing, Context::EndOfLine);
+        if validator.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(validator.errors)
+        }
+    }
+}
+
+// the places of decors, determining what they may contain
+#[derive(Clone, Copy)]
+enum Context {
+    // whitespaces only, e.g. before `=` or inside of inline tables
+    Inline,
+    // whitespaces, newlines and comments followed by a newline, e.g. before keys and headers
+    Lines,
+    // like `Lines`, but the last comment may end the decor,
+    // e.g. after values of key/value pairs and at the end of the document
+    EndOfLine,
+}
+
+struct Validator {
+    path: KeyPath,
+    errors: Vec<InvalidItem>,
+}
+
+impl Validator {
+    fn error(&mut self, kind: InvalidKind, text: &str) {
+        self.errors.push(InvalidItem {
+            path: self.path.clone(),
+            kind,
+            text: text.to_owned(),
+        });
+    }
+
+    fn decor(&mut self, s: &str, context: Context) {
+        let valid = match context {
+            Context::Inline => s.chars().all(|c| c == ' ' || c == '\t'),
+            _ if !is_trivia(s) => false,
+            Context::Lines => s.rfind('#').is_none_or(|i| s[i..].contains('\n')),
+            Context::EndOfLine => true,
+        };
+        if !valid {
+            self.error(InvalidKind::Decor, s);
+        }
+    }
+
+    fn decors(&mut self, decor: &Decor, prefix: Context, suffix: Context) {
+        self.decor(&decor.prefix, prefix);
+        self.decor(&decor.suffix, suffix);
+    }
+
+    fn key(&mut self, key: &str, repr: &Repr) {
+        let raw = &repr.raw_value;
+        match lexer::key(raw) {
+            Ok(ref parsed) if parsed.get() != key => self.error(InvalidKind::KeyMismatch, raw),
+            Ok(..) => {}
+            Err(..) => self.error(InvalidKind::Key, raw),
+        }
+    }
+
+    fn table(&mut self, table: &Table) {
+        for (key, kv) in table.items.iter() {
+            self.path.push(Key::from_unquoted(key));
+            self.key(key, &kv.key);
+            self.table_entry(kv);
+            self.path.pop();
+        }
+    }
+
+    fn table_entry(&mut self, kv: &TableKeyValue) {
+        match kv.value {
+            Item::Value(ref value) => {
+                self.decors(&kv.key.decor, Context::Lines, Context::Inline);
+                self.value(value, Context::Inline, Context::EndOfLine);
+            }
+            Item::Table(ref t) => self.subtable(t),
+            Item::ArrayOfTables(ref a) => {
+                for t in a.iter() {
+                    self.subtable(t);
+                }
+            }
+            Item::None => {}
+        }
+    }
+
+    fn subtable(&mut self, table: &Table) {
+        // dotted tables have no header, their decor is not displayed
+        if !table.dotted {
+            self.decors(&table.decor, Context::Lines, Context::EndOfLine);
+        }
+        self.table(table);
+    }
+
+    fn value(&mut self, value: &Value, prefix: Context, suffix: Context) {
+        self.decors(value.decor(), prefix, suffix);
+        match *value {
+            Value::Array(ref a) => self.array(a),
+            Value::InlineTable(ref t) => self.inline_table(t),
+            _ => self.scalar(value),
+        }
+    }
+
+    #[cfg(feature = "parse")]
+    fn scalar(&mut self, value: &Value) {
+        let raw = match value.as_raw() {
+            Some(raw) => raw,
+            None => return,
+        };
+        // out of range literals are valid, they may be kept with `NumberPolicy::Preserve`
+        let options = ParseOptions::new()
+            .number_policy(NumberPolicy::Preserve)
+            .version(TomlVersion::V1_0);
+        match parser::value_parser(options).easy_parse(Stream::new(raw)) {
+            Ok((ref parsed, ref rest)) if rest.input.is_empty() => {
+                if !same_value(parsed, value) {
+                    self.error(InvalidKind::ValueMismatch, raw);
+                }
+            }
+            _ => self.error(InvalidKind::Value, raw),
+        }
+    }
+
+    // without the parser, only the raw representations of strings are checked
+    #[cfg(not(feature = "parse"))]
+    fn scalar(&mut self, value: &Value) {
+        let (raw, s) = match (value.as_raw(), value.as_str()) {
+            (Some(raw), Some(s)) => (raw, s),
+            _ => return,
+        };
+        match lexer::string(raw) {
+            Ok(ref parsed) if parsed != s => self.error(InvalidKind::ValueMismatch, raw),
+            Ok(..) => {}
+            Err(..) => self.error(InvalidKind::Value, raw),
+        }
+    }
+
+    fn array(&mut self, array: &Array) {
+        for value in array.iter() {
+            self.value(value, Context::Lines, Context::Lines);
+        }
+        self.decor(&array.trailing, Context::Lines);
+    }
+
+    fn inline_table(&mut self, table: &InlineTable) {
+        self.decor(&table.preamble, Context::Inline);
+        for (key, kv) in table.items.iter() {
+            if let Item::Value(ref value) = kv.value {
+                self.path.push(Key::from_unquoted(key));
+                self.key(key, &kv.key);
+                self.decors(&kv.key.decor, Context::Inline, Context::Inline);
+                self.value(value, Context::Inline, Context::Inline);
+                self.path.pop();
+            }
+        }
+    }
+}
+
+fn is_trivia(s: &str) -> bool {
+    lexer::decor(s).is_ok()
+}
@@ -1,16 +1,26 @@
-use crate::decor::{Decor, Formatted, InternalString};
+use crate::decor::{Decor, Formatted, InternalString, Repr};
+use crate::formatted::{StringStyle, StringStyleError};
 use crate::key::Key;
+use crate::metadata::NodeIdCell;
+#[cfg(feature = "parse")]
 use crate::parser;
-use crate::table::{Item, Iter, KeyValuePairs, TableKeyValue, TableLike};
+use crate::parser::TomlVersion;
+use crate::table::{value, Item, Iter, IterMut, KeyValuePairs, Table, TableKeyValue, TableLike};
 use crate::{decorated, formatted};
+#[cfg(feature = "datetime")]
 use chrono::{self, FixedOffset};
+#[cfg(feature = "parse")]
 use combine::stream::position::Stream;
 use linked_hash_map::LinkedHashMap;
+use std::convert::TryFrom;
 use std::mem;
+use std::ops::{Bound, Range, RangeBounds};
+#[cfg(feature = "parse")]
 use std::str::FromStr;
 
 /// Representation of a TOML Value (as part of a Key/Value Pair).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Value {
     /// A 64-bit integer value.
     Integer(Formatted<i64>),
@@ -30,7 +40,10 @@ pub enum Value {
 
 /// Type representing a TOML Date-Time,
 /// payload of the `Value::DateTime` variant's value
+#[cfg(feature = "datetime")]
 #[derive(Eq, PartialEq, Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::enum_variant_names)]
 pub enum DateTime {
     /// An RFC 3339 formatted date-time with offset.
     OffsetDateTime(chrono::DateTime<FixedOffset>),
@@ -42,9 +55,30 @@ pub enum DateTime {
     LocalTime(chrono::NaiveTime),
 }
 
+/// Type representing a TOML Date-Time,
+/// payload of the `Value::DateTime` variant's value
+///
+/// Without the `datetime` feature, date-times are not interpreted,
+/// each variant holds the date-time as it is written.
+#[cfg(not(feature = "datetime"))]
+#[derive(Eq, PartialEq, Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::enum_variant_names)]
+pub enum DateTime {
+    /// An RFC 3339 formatted date-time with offset.
+    OffsetDateTime(String),
+    /// An RFC 3339 formatted date-time without offset.
+    LocalDateTime(String),
+    /// Date portion of an RFC 3339 formatted date-time.
+    LocalDate(String),
+    /// Time portion of an RFC 3339 formatted date-time.
+    LocalTime(String),
+}
+
 /// Type representing a TOML array,
 /// payload of the `Value::Array` variant's value
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Array {
     // always Vec<Item::Value>
     pub(crate) values: Vec<Item>,
@@ -54,17 +88,22 @@ pub struct Array {
     pub(crate) trailing_comma: bool,
     // prefix before `[` and suffix after `]`
     pub(crate) decor: Decor,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) id: NodeIdCell,
 }
 
 /// Type representing a TOML inline table,
 /// payload of the `Value::InlineTable` variant
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InlineTable {
     pub(crate) items: KeyValuePairs,
     // `preamble` represents whitespaces in an empty table
     pub(crate) preamble: InternalString,
     // prefix before `{` and suffix after `}`
     pub(crate) decor: Decor,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) id: NodeIdCell,
 }
 
 #[derive(Eq, PartialEq, Clone, Copy, Debug, Hash)]
@@ -82,7 +121,23 @@ pub(crate) enum ValueType {
 /// An iterator type over `Array`'s values.
 pub type ArrayIter<'a> = Box<dyn Iterator<Item = &'a Value> + 'a>;
 
+/// A mutable iterator type over `Array`'s values.
+pub type ArrayIterMut<'a> = Box<dyn Iterator<Item = &'a mut Value> + 'a>;
+
 impl Array {
+    /// Creates an empty array with room for `capacity` values, see also `Extend`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            values: Vec::with_capacity(capacity),
+            ..Default::default()
+        }
+    }
+
+    /// Reserves room for at least `additional` more values.
+    pub fn reserve(&mut self, additional: usize) {
+        self.values.reserve(additional);
+    }
+
     /// Returns the length of the underlying Vec.
     /// To get the actual number of items use `a.iter().count()`.
     pub fn len(&self) -> usize {
@@ -99,6 +154,59 @@ impl Array {
         Box::new(self.values.iter().filter_map(Item::as_value))
     }
 
+    /// Returns a mutable iterator over all values, in the order of their indices.
+    ///
+    /// The values are edited in place, keeping their decor unless replaced as a whole.
+    /// Changing the type of a value is not checked and may produce an invalid array.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::{Document, Value};
+    /// let mut doc = "sizes = [1, 2, 3]  # in KiB".parse::<Document>().unwrap();
+    /// let sizes = doc["sizes"].as_array_mut().unwrap();
+    /// for (i, size) in sizes.iter_mut().enumerate() {
+    ///     let scaled = size.as_integer().unwrap() * 1024 + i as i64;
+    ///     *size = toml_edit::decorated(Value::from(scaled), size.decor().prefix(), "");
+    /// }
+    /// assert_eq!(doc.to_string(), "sizes = [1024, 2049, 3074]  # in KiB\n");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn iter_mut(&mut self) -> ArrayIterMut<'_> {
+        Box::new(self.values.iter_mut().filter_map(Item::as_value_mut))
+    }
+
+    /// Returns an iterator over the string values, skipping values of other types.
+    pub fn iter_str(&self) -> impl Iterator<Item = &str> + '_ {
+        self.iter().filter_map(Value::as_str)
+    }
+
+    /// Returns an iterator over the integer values, skipping values of other types.
+    pub fn iter_int(&self) -> impl Iterator<Item = i64> + '_ {
+        self.iter().filter_map(Value::as_integer)
+    }
+
+    /// Returns all values as strings, or `None` if any of them is not a string.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Document;
+    /// let doc = "features = ['derive', 'std']".parse::<Document>().unwrap();
+    /// let features = doc["features"].as_array().unwrap();
+    /// assert_eq!(features.as_slice_of_str(), Some(vec!["derive", "std"]));
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn as_slice_of_str(&self) -> Option<Vec<&str>> {
+        self.iter().map(Value::as_str).collect()
+    }
+
     /// Appends a new value to the end of the array, applying default formatting to it.
     ///
     /// Returns an error if the value was of a different type than the values in the array.
@@ -108,6 +216,64 @@ impl Array {
         })
     }
 
+    /// Appends the values as by `push`, reserving room for all of them at once.
+    ///
+    /// Values of another type than the array are not appended, and are returned instead.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use toml_edit::{Array, Value};
+    /// let mut array = Array::default();
+    /// let rejected = array.try_extend(vec![Value::from("a"), Value::from(1), Value::from("b")]);
+    /// assert_eq!(rejected.unwrap_err()[0].as_integer(), Some(1));
+    /// assert_eq!(array.to_string(), r#"["a", "b"]"#);
+    /// ```
+    pub fn try_extend<V, I>(&mut self, iter: I) -> Result<(), Vec<Value>>
+    where
+        V: Into<Value>,
+        I: IntoIterator<Item = V>,
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        let mut rejected = Vec::new();
+        for value in iter {
+            if let Err(value) = self.push(value) {
+                rejected.push(value);
+            }
+        }
+        if rejected.is_empty() {
+            Ok(())
+        } else {
+            Err(rejected)
+        }
+    }
+
+    /// Appends a new value to the end of the array, applying default formatting to it.
+    ///
+    /// Arrays of mixed types are only valid since TOML v1.0.0: the value is appended
+    /// regardless of its type with `TomlVersion::V1_0`, and an error is returned as by `push`
+    /// if it is of a different type than the values in the array with `TomlVersion::V0_5`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use toml_edit::{Array, TomlVersion};
+    /// let mut array = Array::default();
+    /// array.push(1).unwrap();
+    /// assert!(array.push_any("two", TomlVersion::V0_5).is_err());
+    /// array.push_any("two", TomlVersion::V1_0).unwrap();
+    /// assert_eq!(array.to_string(), r#"[1, "two"]"#);
+    /// ```
+    pub fn push_any<V: Into<Value>>(&mut self, v: V, version: TomlVersion) -> Result<(), Value> {
+        if version < TomlVersion::V1_0 {
+            return self.push(v);
+        }
+        let mut value = v.into();
+        let prefix = if self.is_empty() { "" } else { " " };
+        formatted::decorate(&mut value, prefix, "");
+        self.values.push(Item::Value(value));
+        Ok(())
+    }
+
     /// Appends a new, already formatted value to the end of the array.
     ///
     /// Returns an error if the value was of a different type than the array.
@@ -168,12 +334,110 @@ impl Array {
         })
     }
 
+    /// Replaces the values in the range with the given ones like `Vec::splice`,
+    /// returning the removed values.
+    ///
+    /// The new values take the decor of the removed ones in order, additional values
+    /// are formatted like the other elements, and the first element keeps the decor
+    /// the array starts with. Returns an error with the first value of a different type
+    /// than the values in the array, leaving the array unchanged.
+    ///
+    /// Panics if the range is out of bounds.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Value;
+    /// let mut v = "[1, 2, 3, 4]".parse::<Value>().unwrap();
+    /// let array = v.as_array_mut().unwrap();
+    /// let removed = array.splice(1..3, vec![5, 6, 7]).unwrap();
+    /// assert_eq!(removed.iter().filter_map(Value::as_integer).collect::<Vec<_>>(), [2, 3]);
+    /// assert_eq!(array.to_string(), "[1, 5, 6, 7, 4]");
+    /// array.splice(..2, vec![8]).unwrap();
+    /// assert_eq!(array.to_string(), "[8, 6, 7, 4]");
+    /// assert!(array.splice(.., vec!["a"]).is_ok());
+    /// assert!(array.splice(..0, vec![9]).is_err());
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn splice<R, I, V>(&mut self, range: R, values: I) -> Result<Vec<Value>, Value>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        let range = to_range(range, self.len());
+        let mut values: Vec<Value> = values.into_iter().map(Into::into).collect();
+        let kept = self.values[..range.start]
+            .iter()
+            .chain(&self.values[range.end..])
+            .filter_map(Item::as_value);
+        if let Some(first) = kept.chain(values.iter()).next().map(Value::get_type) {
+            if let Some(i) = values.iter().position(|v| v.get_type() != first) {
+                return Err(values.swap_remove(i));
+            }
+        }
+
+        // the first element of an empty array is not separated, as with `push`
+        let lead = self
+            .get(0)
+            .map_or_else(Decor::default, |v| v.decor().clone());
+        // the indentation of multiline arrays, without the comments of the previous elements
+        let separator = match self.get(1).or_else(|| self.get(0)) {
+            Some(v) => match v.decor().prefix().rfind('\n') {
+                Some(i) => Decor::new(&v.decor().prefix()[i..], ""),
+                None => Decor::new(" ", ""),
+            },
+            None => Decor::new(" ", ""),
+        };
+        let decors: Vec<Decor> = self.values[range.clone()]
+            .iter()
+            .filter_map(Item::as_value)
+            .map(|v| v.decor().clone())
+            .collect();
+        let n = values.len();
+        let inserted = values.into_iter().enumerate().map(|(i, v)| {
+            let decor = decors.get(i).unwrap_or(&separator);
+            Item::Value(decorated(v, decor.prefix(), decor.suffix()))
+        });
+        let removed = self
+            .values
+            .splice(range.clone(), inserted)
+            .filter_map(|item| item.into_value().ok())
+            .collect();
+        if range.start == 0 {
+            if let Some(first) = self.values.first_mut().and_then(Item::as_value_mut) {
+                let suffix = first.decor().suffix().to_owned();
+                formatted::decorate(first, lead.prefix(), &suffix);
+            }
+            // the previous first element is shifted, it is separated like the other elements
+            if range.end == 0 && n > 0 {
+                if let Some(next) = self.values.get_mut(n).and_then(Item::as_value_mut) {
+                    let suffix = next.decor().suffix().to_owned();
+                    formatted::decorate(next, separator.prefix(), &suffix);
+                }
+            }
+        }
+        if self.is_empty() {
+            self.trailing_comma = false;
+        }
+        Ok(removed)
+    }
+
     /// Returns a reference to the value at the given index, or `None` if the index is out of
     /// bounds.
     pub fn get(&self, index: usize) -> Option<&Value> {
         self.values.get(index).and_then(Item::as_value)
     }
 
+    /// Returns a mutable reference to the value at the given index, or `None` if the index
+    /// is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Value> {
+        self.values.get_mut(index).and_then(Item::as_value_mut)
+    }
+
     /// Removes the value at the given index.
     pub fn remove(&mut self, index: usize) -> Value {
         let removed = self.values.remove(index);
@@ -186,6 +450,51 @@ impl Array {
         }
     }
 
+    /// Removes all the values, keeping the decor and the trailing whitespaces and comments.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.trailing_comma = false;
+    }
+
+    /// Returns true iff the array is written with a comma after the last element.
+    pub fn trailing_comma(&self) -> bool {
+        self.trailing_comma
+    }
+
+    /// Sets whether the array is written with a comma after the last element.
+    ///
+    /// The comma is never written for an empty array.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// let mut v = "[\n    1,\n    2,\n]".parse::<toml_edit::Value>().unwrap();
+    /// let array = v.as_array_mut().unwrap();
+    /// assert!(array.trailing_comma());
+    /// assert_eq!(array.trailing(), "\n");
+    /// array.set_trailing_comma(false);
+    /// assert_eq!(array.to_string(), "[\n    1,\n    2\n]");
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn set_trailing_comma(&mut self, yes: bool) {
+        self.trailing_comma = yes;
+    }
+
+    /// Returns whitespaces, newlines and comments after the last element
+    /// (and the trailing comma), or inside an empty array.
+    pub fn trailing(&self) -> &str {
+        &self.trailing
+    }
+
+    /// Sets whitespaces, newlines and comments after the last element
+    /// (and the trailing comma), or inside an empty array.
+    pub fn set_trailing(&mut self, trailing: &str) {
+        self.trailing = InternalString::from(trailing);
+    }
+
     /// Auto formats the array.
     pub fn fmt(&mut self) {
         formatted::decorate_array(self);
@@ -211,7 +520,7 @@ impl Array {
     }
 
     pub(crate) fn value_type(&self) -> ValueType {
-        if let Some(value) = self.values.get(0).and_then(Item::as_value) {
+        if let Some(value) = self.values.first().and_then(Item::as_value) {
             value.get_type()
         } else {
             ValueType::None
@@ -219,8 +528,35 @@ impl Array {
     }
 }
 
+/// The values are appended as by `Array::push`, reserving room for all of them at once.
+///
+/// Panics if a value is of another type than the array, see `Array::try_extend`
+/// and `Array::push_any`.
+///
+/// # Example
+/// ```rust
+/// # use toml_edit::{Array, Value};
+/// let mut array = Array::with_capacity(3);
+/// array.extend(vec!["a", "b", "c"]);
+/// assert_eq!(array.to_string(), r#"["a", "b", "c"]"#);
+/// ```
+impl<V: Into<Value>> Extend<V> for Array {
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        if let Err(rejected) = self.try_extend(iter) {
+            panic!(
+                "can't extend an array of {:?} with {:?}",
+                self.value_type(),
+                rejected[0].get_type()
+            );
+        }
+    }
+}
+
 /// An iterator type over key/value pairs of an inline table.
 pub type InlineTableIter<'a> = Box<dyn Iterator<Item = (&'a str, &'a Value)> + 'a>;
+/// An iterator type over `InlineTable`'s key/value pairs with their keys,
+/// see `InlineTable::iter_full`.
+pub type InlineTableIterFull<'a> = Box<dyn Iterator<Item = (Key, &'a Value)> + 'a>;
 
 impl InlineTable {
     /// Returns the number of key/value pairs.
@@ -243,6 +579,29 @@ impl InlineTable {
         )
     }
 
+    /// Returns an iterator over key/value pairs with their keys, including
+    /// the raw representations and the decor of the keys.
+    pub fn iter_full(&self) -> InlineTableIterFull<'_> {
+        Box::new(self.items.iter().filter_map(|(k, kv)| {
+            kv.value
+                .as_value()
+                .map(|v| (Key::with_repr(k.clone(), kv.key.clone()), v))
+        }))
+    }
+
+    /// Converts the inline table into a standard table,
+    /// nested inline tables are kept as values.
+    pub fn into_table(self) -> Table {
+        let mut table = Table::new();
+        for (key, kv) in self.items {
+            if let Item::Value(v) = kv.value {
+                let kv = TableKeyValue::new(formatted::key_repr(&kv.key.raw_value), value(v));
+                table.items.insert(key, kv);
+            }
+        }
+        table
+    }
+
     /// Sorts the key/value pairs by key.
     pub fn sort(&mut self) {
         sort_key_value_pairs(&mut self.items);
@@ -271,7 +630,7 @@ impl InlineTable {
     pub fn get_or_insert<V: Into<Value>>(&mut self, key: &str, value: V) -> &mut Value {
         let parsed = key.parse::<Key>().expect("invalid key");
         self.items
-            .entry(parsed.get().to_owned())
+            .entry(parsed.get().into())
             .or_insert(formatted::to_key_value(key, value.into()))
             .value
             .as_value_mut()
@@ -290,6 +649,54 @@ impl InlineTable {
             .and_then(|kv| kv.value.as_value().cloned())
     }
 
+    /// Removes all the key/value pairs, keeping the decor of the table.
+    pub fn clear(&mut self) {
+        self.items.clear()
+    }
+
+    /// Takes the value out of a key/value pair, leaving its key, with its decor,
+    /// and its position in place for a value inserted later with the same key.
+    pub fn take(&mut self, key: &str) -> Option<Value> {
+        self.items
+            .get_mut(key)
+            .and_then(|kv| kv.value.take().into_value().ok())
+    }
+
+    /// Inserts a key/value pair, returning the previous value if the key was present.
+    ///
+    /// The decor of an existing key and value is preserved,
+    /// new pairs are appended with default formatting.
+    pub fn insert<V: Into<Value>>(&mut self, key: &str, value: V) -> Option<Value> {
+        let parsed = key.parse::<Key>().expect("invalid key");
+        match self.items.get_mut(parsed.get()) {
+            Some(kv) => {
+                let mut value = value.into();
+                match kv.value.as_value().map(Value::decor) {
+                    Some(decor) => formatted::decorate(&mut value, decor.prefix(), decor.suffix()),
+                    None => formatted::decorate(&mut value, " ", ""),
+                }
+                mem::replace(&mut kv.value, Item::Value(value))
+                    .into_value()
+                    .ok()
+            }
+            None => {
+                let kv = formatted::to_key_value(parsed.raw(), value.into());
+                self.items.insert(parsed.get().into(), kv);
+                None
+            }
+        }
+    }
+
+    /// Returns the decor associated with a given key of the table.
+    pub fn decor(&self, key: &str) -> Option<&Decor> {
+        self.items.get(key).map(|kv| &kv.key.decor)
+    }
+
+    /// Returns the mutable decor associated with a given key of the table.
+    pub fn decor_mut(&mut self, key: &str) -> Option<&mut Decor> {
+        self.items.get_mut(key).map(|kv| &mut kv.key.decor)
+    }
+
     /// Return an optional reference to the value at the given the key.
     pub fn get(&self, key: &str) -> Option<&Value> {
         self.items.get(key).and_then(|kv| kv.value.as_value())
@@ -313,11 +720,34 @@ impl TableLike for InlineTable {
     fn get_mut<'s>(&'s mut self, key: &str) -> Option<&'s mut Item> {
         self.items.get_mut(key).map(|kv| &mut kv.value)
     }
+    fn iter_mut(&mut self) -> IterMut<'_> {
+        Box::new(
+            self.items
+                .iter_mut()
+                .map(|(key, kv)| (&key[..], &mut kv.value)),
+        )
+    }
+    fn insert(&mut self, key: &str, item: Item) -> Option<Item> {
+        match item.into_value() {
+            Ok(value) => self.insert(key, value).map(Item::Value),
+            Err(_) => TableLike::remove(self, key),
+        }
+    }
+    fn remove(&mut self, key: &str) -> Option<Item> {
+        self.items.remove(key).map(|kv| kv.value)
+    }
+    fn decor(&self, key: &str) -> Option<&Decor> {
+        self.decor(key)
+    }
+    fn decor_mut(&mut self, key: &str) -> Option<&mut Decor> {
+        self.decor_mut(key)
+    }
 }
 
 /// Downcasting
 impl DateTime {
     /// Casts `self` to offset date-time.
+    #[cfg(feature = "datetime")]
     pub fn as_offset_date_time(&self) -> Option<&chrono::DateTime<FixedOffset>> {
         match *self {
             DateTime::OffsetDateTime(ref dt) => Some(dt),
@@ -325,6 +755,7 @@ impl DateTime {
         }
     }
     /// Casts `self` to local date-time.
+    #[cfg(feature = "datetime")]
     pub fn as_local_date_time(&self) -> Option<&chrono::NaiveDateTime> {
         match *self {
             DateTime::LocalDateTime(ref dt) => Some(dt),
@@ -332,6 +763,7 @@ impl DateTime {
         }
     }
     /// Casts `self` to local date.
+    #[cfg(feature = "datetime")]
     pub fn as_local_date(&self) -> Option<&chrono::NaiveDate> {
         match *self {
             DateTime::LocalDate(ref d) => Some(d),
@@ -339,6 +771,7 @@ impl DateTime {
         }
     }
     /// Casts `self` to local time.
+    #[cfg(feature = "datetime")]
     pub fn as_local_time(&self) -> Option<&chrono::NaiveTime> {
         match *self {
             DateTime::LocalTime(ref t) => Some(t),
@@ -347,19 +780,147 @@ impl DateTime {
     }
     /// Returns true iff `self` is an offset date-time.
     pub fn is_offset_date_time(&self) -> bool {
-        self.as_offset_date_time().is_some()
+        matches!(*self, DateTime::OffsetDateTime(..))
     }
     /// Returns true iff `self` is a local date-time.
     pub fn is_local_date_time(&self) -> bool {
-        self.as_local_date_time().is_some()
+        matches!(*self, DateTime::LocalDateTime(..))
     }
     /// Returns true iff `self` is a local date.
     pub fn is_local_date(&self) -> bool {
-        self.as_local_date().is_some()
+        matches!(*self, DateTime::LocalDate(..))
     }
     /// Returns true iff `self` is a local time.
     pub fn is_local_time(&self) -> bool {
-        self.as_local_time().is_some()
+        matches!(*self, DateTime::LocalTime(..))
+    }
+}
+
+impl Value {
+    /// Creates a string value written in the given style, failing if the string
+    /// can't be written in the style, e.g. control characters in literal strings,
+    /// which have no escapes.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use toml_edit::{StringStyle, Value};
+    /// let v = Value::try_string_in_style("C:\\Users", StringStyle::Literal).unwrap();
+    /// assert_eq!(v.to_string(), "'C:\\Users'");
+    ///
+    /// let err = Value::try_string_in_style("a\u{7}", StringStyle::Literal).unwrap_err();
+    /// assert_eq!(err.to_string(), r"a literal string can't contain '\u{7}'");
+    /// assert_eq!(
+    ///     Value::string_in_style("a\u{7}", StringStyle::Literal).to_string(),
+    ///     r#""a\u0007""#
+    /// );
+    /// ```
+    pub fn try_string_in_style(s: &str, style: StringStyle) -> Result<Value, StringStyleError> {
+        let raw = formatted::to_string_repr(s, style)?;
+        Ok(Value::String(Formatted::with_repr(
+            s.to_owned(),
+            Repr::new("", &raw, ""),
+        )))
+    }
+
+    /// Creates a string value written in the given style, or with escapes if the string
+    /// can't be written in the style, i.e. as a basic or a multi-line basic string instead of
+    /// a literal or a multi-line literal string.
+    pub fn string_in_style(s: &str, style: StringStyle) -> Value {
+        Value::try_string_in_style(s, style).unwrap_or_else(|_| {
+            let style = match style {
+                StringStyle::MultilineLiteral => StringStyle::MultilineBasic,
+                _ => StringStyle::Basic,
+            };
+            Value::try_string_in_style(s, style).expect("strings with escapes can't fail")
+        })
+    }
+
+    /// Returns the lines of a multi-line string as written between the delimiters,
+    /// or `None` if `self` is not a multi-line string.
+    ///
+    /// The lines are raw, i.e. with escapes and line ending backslashes, the newline
+    /// right after the opening delimiter and the line of the closing delimiter, if it is on
+    /// its own line, are not lines.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "parse")]
+    /// # fn main() {
+    /// # use toml_edit::Value;
+    /// let mut v = "'''\nset -e\ncargo build \\\n'''".parse::<Value>().unwrap();
+    /// assert_eq!(v.as_multiline_lines(), Some(vec!["set -e", "cargo build \\"]));
+    ///
+    /// assert_eq!(v.push_line("cargo test"), Ok(true));
+    /// assert_eq!(v.to_string(), "'''\nset -e\ncargo build \\\ncargo test\n'''");
+    /// assert_eq!(v.as_str(), Some("set -e\ncargo build \\\ncargo test\n"));
+    /// assert!(v.push_line("it'''s").is_err());
+    /// # }
+    /// # #[cfg(not(feature = "parse"))]
+    /// # fn main() {}
+    /// ```
+    pub fn as_multiline_lines(&self) -> Option<Vec<&str>> {
+        let (_, body) = self.multiline_body()?;
+        let body = match split_closing_line(body) {
+            Some((head, _)) => head,
+            None => body,
+        };
+        let body = body
+            .strip_prefix("\r\n")
+            .or_else(|| body.strip_prefix('\n'))
+            .unwrap_or(body);
+        Some(body.lines().collect())
+    }
+
+    /// Appends a line to a multi-line string, escaping it in the style of the string,
+    /// returning false if `self` is not a multi-line string.
+    ///
+    /// The delimiters and the other lines are kept as written, if the closing delimiter is
+    /// on its own line, possibly indented, it stays there. A line pushed after a line ending with a backslash
+    /// of a basic string continues that line. Fails if the line contains a newline or can't
+    /// be written in the style, e.g. `'''` in a literal string.
+    pub fn push_line(&mut self, line: &str) -> Result<bool, StringStyleError> {
+        let (style, body) = match self.multiline_body() {
+            Some(split) => split,
+            None => return Ok(false),
+        };
+        let line = formatted::to_multiline_line(line, style)?;
+        let body = match split_closing_line(body) {
+            Some((head, closing)) => {
+                let newline = if head.ends_with("\r\n") { "\r\n" } else { "\n" };
+                format!("{}{}{}{}", head, line, newline, closing)
+            }
+            None if body.is_empty() => line,
+            None => {
+                let newline = if body.contains("\r\n") { "\r\n" } else { "\n" };
+                format!("{}{}{}", body, newline, line)
+            }
+        };
+        let delimiter = match style {
+            StringStyle::MultilineLiteral => "'''",
+            _ => "\"\"\"",
+        };
+        let raw = format!("{}{}{}", delimiter, body, delimiter);
+        if let Value::String(ref mut f) = *self {
+            f.set_raw_string(raw)
+                .expect("escaped lines keep the string valid");
+        }
+        Ok(true)
+    }
+
+    // Returns the style and the raw text between the delimiters of a multi-line string.
+    fn multiline_body(&self) -> Option<(StringStyle, &str)> {
+        let raw = match *self {
+            Value::String(ref f) => f.repr.raw_value.as_str(),
+            _ => return None,
+        };
+        let style = if raw.starts_with("\"\"\"") {
+            StringStyle::MultilineBasic
+        } else if raw.starts_with("'''") {
+            StringStyle::MultilineLiteral
+        } else {
+            return None;
+        };
+        Some((style, &raw[3..raw.len() - 3]))
     }
 }
 
@@ -373,6 +934,16 @@ impl Value {
         }
     }
 
+    /// Casts `self` to a non-negative integer, returns `None` for negative integers.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_integer().and_then(|i| u64::try_from(i).ok())
+    }
+
+    /// Casts `self` to an integer in the range of `usize`, e.g. a length.
+    pub fn as_usize(&self) -> Option<usize> {
+        self.as_integer().and_then(|i| usize::try_from(i).ok())
+    }
+
     /// Returns true iff `self` is an integer.
     pub fn is_integer(&self) -> bool {
         self.as_integer().is_some()
@@ -391,6 +962,56 @@ impl Value {
         self.as_float().is_some()
     }
 
+    /// Parses a number as written in spreadsheets and reports, with commas as thousands
+    /// separators, e.g. `1,000` or `-1,234.5`, into an integer or a float with the
+    /// canonical TOML literal. Returns `None` if `s` is not such a number.
+    ///
+    /// The separators must delimit groups of 3 decimal digits, so that `1,5` is rejected
+    /// rather than taken for a decimal comma and `0x1,000` is rejected, and the rest of `s`
+    /// must be a valid TOML number, e.g. without leading zeros. Documents are never parsed this way, `a = 1,000` is invalid.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use toml_edit::Value;
+    /// let v = Value::parse_number_lenient(" 1,000 ").unwrap();
+    /// assert_eq!(v.as_integer(), Some(1000));
+    /// assert_eq!(v.to_string(), "1000");
+    /// let v = Value::parse_number_lenient("-1,234.0").unwrap();
+    /// assert_eq!(v.as_float(), Some(-1234.0));
+    /// assert_eq!(v.to_string(), "-1234.0");
+    /// assert!(Value::parse_number_lenient("1,5").is_none());
+    /// assert!(Value::parse_number_lenient("1.000,5").is_none());
+    /// ```
+    #[cfg(feature = "parse")]
+    pub fn parse_number_lenient(s: &str) -> Option<Self> {
+        parser::lenient_number(s)
+    }
+
+    /// Creates the positive infinity float, `inf`.
+    pub fn float_inf() -> Self {
+        Value::from(f64::INFINITY)
+    }
+
+    /// Creates the negative infinity float, `-inf`.
+    pub fn float_neg_inf() -> Self {
+        Value::from(f64::NEG_INFINITY)
+    }
+
+    /// Creates the not-a-number float, `nan`.
+    pub fn float_nan() -> Self {
+        Value::from(f64::NAN)
+    }
+
+    /// Returns true iff `self` is an infinite float, `inf` or `-inf`.
+    pub fn is_inf(&self) -> bool {
+        self.as_float().is_some_and(f64::is_infinite)
+    }
+
+    /// Returns true iff `self` is a not-a-number float, `nan`, `+nan` or `-nan`.
+    pub fn is_nan(&self) -> bool {
+        self.as_float().is_some_and(f64::is_nan)
+    }
+
     /// Casts `self` to boolean.
     pub fn as_bool(&self) -> Option<bool> {
         match *self {
@@ -472,6 +1093,52 @@ impl Value {
         self.as_inline_table().is_some()
     }
 
+    /// Returns the literal `self` was parsed from, or the one it will be written as,
+    /// for all values but arrays and inline tables.
+    ///
+    /// Unlike the typed accessors, this reflects the original text,
+    /// e.g. for numbers parsed with `NumberPolicy::Preserve`.
+    pub fn as_raw(&self) -> Option<&str> {
+        match *self {
+            Value::Integer(ref f) => Some(f.raw()),
+            Value::String(ref f) => Some(f.raw()),
+            Value::Float(ref f) => Some(f.raw()),
+            Value::DateTime(ref f) => Some(f.raw()),
+            Value::Boolean(ref f) => Some(f.raw()),
+            Value::Array(..) | Value::InlineTable(..) => None,
+        }
+    }
+
+    /// Returns the tag of a string captured from an extension literal,
+    /// see `ParseOptions::extension_literals`.
+    pub fn extension_tag(&self) -> Option<&'static str> {
+        match *self {
+            Value::String(ref f) => f.tag,
+            _ => None,
+        }
+    }
+
+    /// Casts `self` to a 128-bit integer, parsing the original literal.
+    ///
+    /// This allows reading integers parsed with `NumberPolicy::Preserve`
+    /// that don't fit into `i64`.
+    #[cfg(feature = "i128")]
+    pub fn as_i128(&self) -> Option<i128> {
+        match *self {
+            Value::Integer(ref f) => {
+                let digits = f.raw().replace("_", "");
+                let (radix, digits) = match digits.get(..2) {
+                    Some("0x") => (16, &digits[2..]),
+                    Some("0o") => (8, &digits[2..]),
+                    Some("0b") => (2, &digits[2..]),
+                    _ => (10, &digits[..]),
+                };
+                i128::from_str_radix(digits, radix).ok()
+            }
+            _ => None,
+        }
+    }
+
     pub(crate) fn get_type(&self) -> ValueType {
         match *self {
             Value::Integer(..) => ValueType::Integer,
@@ -517,13 +1184,14 @@ pub(crate) fn sort_key_value_pairs(items: &mut LinkedHashMap<InternalString, Tab
     }
 }
 
+#[cfg(feature = "parse")]
 impl FromStr for Value {
     type Err = parser::TomlError;
 
     /// Parses a value from a &str
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         use combine::EasyParser;
-        let parsed = parser::value_parser().easy_parse(Stream::new(s));
+        let parsed = parser::value_parser(Default::default()).easy_parse(Stream::new(s));
         match parsed {
             Ok((_, ref rest)) if !rest.input.is_empty() => {
                 Err(Self::Err::from_unparsed(rest.positioner, s))
@@ -533,3 +1201,37 @@ impl FromStr for Value {
         }
     }
 }
+
+// Converts a range of elements to indices like `Vec::drain`, panicking if it is out of bounds.
+pub(crate) fn to_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&i) => i,
+        Bound::Excluded(&i) => i + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&i) => i + 1,
+        Bound::Excluded(&i) => i,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end && end <= len,
+        "range {}..{} out of bounds (len = {})",
+        start,
+        end,
+        len
+    );
+    start..end
+}
+
+// Splits the raw text of a multi-line string before the line of the closing delimiter,
+// if that line has only spaces and tabs before the delimiter.
+fn split_closing_line(body: &str) -> Option<(&str, &str)> {
+    let at = body.rfind('\n')? + 1;
+    let (head, closing) = body.split_at(at);
+    if closing.chars().all(|c| c == ' ' || c == '\t') {
+        Some((head, closing))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,106 @@
+use crate::document::Document;
+use combine::stream::position::SourcePosition;
+use std::ops::Range;
+
+// The source of a parsed document, split into the sections of its tables.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Source {
+    text: String,
+    // by table position, the range of the table in `text`, including its decor,
+    // and the display of the table when it was parsed
+    sections: Vec<(Range<usize>, String)>,
+    // the start of the trailing whitespaces and comments and their value when parsed
+    trailing: (usize, String),
+}
+
+impl Source {
+    // `ends` are the ends of the last key/value pair or header of each table, by position
+    pub(crate) fn new(text: &str, ends: &[Option<SourcePosition>], document: &Document) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        let offset = |pos: SourcePosition| {
+            let start = line_starts[pos.line as usize - 1];
+            text[start..]
+                .char_indices()
+                .nth(pos.column as usize - 1)
+                .map_or(text.len(), |(i, _)| start + i)
+        };
+
+        let mut sections = Vec::with_capacity(ends.len());
+        let mut start = 0;
+        for end in ends {
+            let end = end.map_or(start, offset);
+            sections.push((start..end, String::new()));
+            start = end;
+        }
+        let tables = document
+            .tables_in_original_order()
+            .expect("the parser rejects documents nested too deeply to be displayed");
+        for (position, table) in tables {
+            if let Some(section) = position.and_then(|p| sections.get_mut(p)) {
+                section.1 = table;
+            }
+        }
+        Source {
+            text: text.to_owned(),
+            sections,
+            trailing: (start, document.trailing.to_string()),
+        }
+    }
+}
+
+impl Document {
+    /// Returns a string representation of the document, where the tables which were not
+    /// modified since parsing, including their decor, are copied from the source byte for byte.
+    ///
+    /// The display of parsed documents differs from their source in places, e.g. in
+    /// the whitespaces inside of headers and dotted keys, or in the line endings.
+    /// Tables are displayed in their original order, modified tables are displayed
+    /// like with `to_string_in_original_order`.
+    ///
+    /// Documents which were not parsed are displayed like with `to_string_in_original_order`.
+    ///
+    /// Like `to_string`, panics if the document was edited to be nested too deeply
+    /// to be displayed, parsed documents never are.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use toml_edit::{value, Document};
+    ///
+    /// let input = "[ package ]\r\nname = 'hello'\r\n\r\n[ dependencies ]\r\nserde = '1.0'";
+    /// let mut doc = input.parse::<Document>().unwrap();
+    /// assert_eq!(doc.to_string_verbatim(), input);
+    ///
+    /// doc["dependencies"]["serde"] = value("1.1");
+    /// assert_eq!(
+    ///     doc.to_string_verbatim(),
+    ///     "[ package ]\r\nname = 'hello'\r\n\r\n[dependencies]\nserde = \"1.1\"\n"
+    /// );
+    /// ```
+    pub fn to_string_verbatim(&self) -> String {
+        let source = match self.source {
+            Some(ref source) => source,
+            None => return self.to_string_in_original_order(),
+        };
+        let mut string = String::new();
+        let tables = self
+            .tables_in_original_order()
+            .expect("the document is nested too deeply to be displayed");
+        for (position, table) in tables {
+            match position.and_then(|p| source.sections.get(p)) {
+                Some((range, parsed)) if *parsed == table => {
+                    string.push_str(&source.text[range.clone()])
+                }
+                _ => string.push_str(&table),
+            }
+        }
+        let (start, ref parsed) = source.trailing;
+        if self.trailing == *parsed {
+            string.push_str(&source.text[start..]);
+        } else {
+            string.push_str(&self.trailing);
+        }
+        string
+    }
+}
@@ -0,0 +1,167 @@
+//! String based entry points for wrapping with `wasm-bindgen` (`wasm` feature).
+//!
+//! Paths are dotted keys, e.g. `dependencies."serde.json".version`, values are written
+//! as TOML, e.g. `"1.0"` or `{ version = "1.0" }`, and errors are messages,
+//! so that no types but strings and booleans have to cross the boundary.
+//!
+//! # Example
+//!
+//! ```rust
+//! use toml_edit::wasm::Editor;
+//!
+//! let mut editor = Editor::parse("[package]\nname = 'hello' # the name\n").unwrap();
+//! assert_eq!(editor.get("package.name").unwrap().as_deref(), Some("'hello'"));
+//!
+//! editor.set("package.name", "'world'").unwrap();
+//! editor.set("dependencies.serde", r#"{ version = "1.0" }"#).unwrap();
+//! assert!(editor.set("package.name.first", "1").is_err());
+//! assert_eq!(editor.remove("package.version"), Ok(false));
+//! assert_eq!(editor.to_string(), r#"[package]
+//! name = 'world' # the name
+//!
+//! [dependencies]
+//! serde = { version = "1.0" }
+//! "#);
+//! ```
+
+use crate::document::Document;
+use crate::formatted::decorated;
+use crate::key_path::KeyPath;
+use crate::table::{Item, TableLike};
+use crate::transaction::{self, Edit};
+use crate::value::Value;
+use std::fmt::{Display, Formatter, Result, Write};
+
+/// A document edited by paths, displayed as TOML.
+#[derive(Debug, Clone, Default)]
+pub struct Editor {
+    document: Document,
+}
+
+impl Editor {
+    /// Parses a document.
+    pub fn parse(input: &str) -> std::result::Result<Editor, String> {
+        input
+            .parse::<Document>()
+            .map(Editor::from)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Returns the document.
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// Returns the mutable document.
+    pub fn document_mut(&mut self) -> &mut Document {
+        &mut self.document
+    }
+
+    /// Returns the item at the path written as a value without decor,
+    /// tables are written as inline tables, or `None` if there is no such item.
+    pub fn get(&self, path: &str) -> std::result::Result<Option<String>, String> {
+        let path = parse_path(path)?;
+        let value = match self.document.get_path(&path) {
+            Some(Item::Value(v)) => decorated(v.clone(), "", ""),
+            Some(item) => match item.as_node() {
+                Some(n) => n.to_value(),
+                None => return Ok(Some(String::new())),
+            },
+            None => return Ok(None),
+        };
+        try_to_string(&value).map(Some)
+    }
+
+    /// Sets the value at the path, creating the missing tables.
+    ///
+    /// The decor of a replaced value is preserved.
+    pub fn set(&mut self, path: &str, value: &str) -> std::result::Result<(), String> {
+        let path = parse_path(path)?;
+        let new = value.trim().parse::<Value>().map_err(|e| e.to_string())?;
+        let edit = Edit::Set(path, Item::Value(decorated(new, " ", "")));
+        self.apply(edit)
+    }
+
+    /// Removes the item at the path, returning false if there is no such item.
+    pub fn remove(&mut self, path: &str) -> std::result::Result<bool, String> {
+        let path = parse_path(path)?;
+        let (last, parents) = match path.keys().split_last() {
+            Some(split) => split,
+            None => return Ok(false),
+        };
+        let parent = parents.iter().cloned().collect::<KeyPath>();
+        let table = if parent.is_empty() {
+            Some(self.document.as_table_mut() as &mut dyn TableLike)
+        } else {
+            self.document
+                .get_path_mut(&parent)
+                .and_then(Item::as_table_like_mut)
+        };
+        Ok(table
+            .and_then(|t| t.remove(last.get()))
+            .is_some_and(|item| !item.is_none()))
+    }
+
+    /// Appends the value to the array at the path, creating the array if there is no item.
+    pub fn append(&mut self, path: &str, value: &str) -> std::result::Result<(), String> {
+        let path = parse_path(path)?;
+        let new = value.trim().parse::<Value>().map_err(|e| e.to_string())?;
+        self.apply(Edit::Append(path, new))
+    }
+
+    /// Sorts the values of the table at the path by key, the root table if the path is empty.
+    pub fn sort(&mut self, path: &str) -> std::result::Result<(), String> {
+        if path.trim().is_empty() {
+            self.document.as_table_mut().sort_values();
+            return Ok(());
+        }
+        let path = parse_path(path)?;
+        match self.document.get_path_mut(&path) {
+            Some(Item::Table(table)) => table.sort_values(),
+            Some(Item::Value(Value::InlineTable(table))) => {
+                // the spaces around the first and the last pair move with them
+                table.sort();
+                table.fmt();
+            }
+            _ => return Err(format!("`{}` is not a table", path)),
+        }
+        Ok(())
+    }
+
+    /// Returns the document written as TOML,
+    /// or an error if it's nested too deeply to be displayed.
+    ///
+    /// Displaying such a document with `to_string` panics.
+    pub fn try_to_string(&self) -> std::result::Result<String, String> {
+        try_to_string(&self.document)
+    }
+
+    // applies the edit like a transaction of a single edit, without restyling
+    fn apply(&mut self, edit: Edit) -> std::result::Result<(), String> {
+        transaction::apply(self.document.as_table_mut(), edit)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl From<Document> for Editor {
+    fn from(document: Document) -> Self {
+        Self { document }
+    }
+}
+
+impl Display for Editor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self.document.fmt(f)
+    }
+}
+
+fn try_to_string(value: &dyn Display) -> std::result::Result<String, String> {
+    let mut string = String::new();
+    write!(string, "{}", value).map_err(|_| "nested too deeply to be displayed".to_owned())?;
+    Ok(string)
+}
+
+fn parse_path(path: &str) -> std::result::Result<KeyPath, String> {
+    path.parse::<KeyPath>().map_err(|e| e.to_string())
+}
@@ -0,0 +1,127 @@
+//! Conversion of YAML values into documents and back (`yaml` feature).
+//!
+//! The conversion goes through `serde`, so that it works with `serde_yaml::Value`
+//! (or any other YAML value implementing `Serialize` and `Deserialize`) without
+//! depending on a particular YAML crate. YAML constructs without a TOML counterpart
+//! are mapped as follows:
+//!
+//! * Anchors and aliases are resolved by the YAML parser, an alias becomes a copy
+//!   of the anchored value. Merge keys (`<<`) are kept as ordinary keys unless
+//!   they are applied before the conversion, e.g. with `serde_yaml::Value::apply_merge`.
+//! * Nulls follow `Options::nulls`: an error by default, or `Nulls::Skip` to leave
+//!   them out of tables, or `Nulls::EmptyString` to write them as `""`, or
+//!   `Nulls::Sentinel` to write them as another string.
+//! * Tagged values are serialized by `serde_yaml` as single-entry maps keyed by
+//!   the tag, e.g. `!Point {x: 1}` as `{"!Point": {x: 1}}`. They are an error
+//!   by default, or kept as such tables with `Tags::Table`. Tags are told apart
+//!   from keys starting with `!` by being written with `Serializer::collect_str`,
+//!   as `serde_yaml` does, so that e.g. `{"!important": true}` is an ordinary table.
+//! * Keys must be strings, integers or booleans, the latter two are written as strings.
+//!
+//! Back from TOML, tables become mappings, arrays sequences and date-times strings.
+//!
+//! # Example
+//!
+//! ```rust
+//! use toml_edit::yaml::{self, Nulls, Options};
+//!
+//! let config: serde_yaml::Value = serde_yaml::from_str("name: app\nport: 8080\nproxy: ~\n").unwrap();
+//! assert!(yaml::to_document(&config, &Options::new()).is_err());
+//!
+//! let options = Options::new().nulls(Nulls::Skip);
+//! let doc = yaml::to_document(&config, &options).unwrap();
+//! assert_eq!(doc.to_string(), "name = \"app\"\nport = 8080\n");
+//! let back: serde_yaml::Value = yaml::from_document(&doc).unwrap();
+//! assert_eq!(serde_yaml::to_string(&back).unwrap(), "name: app\nport: 8080\n");
+//! ```
+
+use crate::de;
+use crate::document::Document;
+use crate::ser::{self, Settings};
+use crate::table::Item;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub use crate::ser::Nulls;
+
+/// Handling of tagged YAML values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Tags {
+    /// Converting them is an error.
+    #[default]
+    Error,
+    /// They are kept as single-entry tables keyed by the tag, e.g. `{ "!Point" = { x = 1 } }`.
+    Table,
+}
+
+/// Options of the conversion of YAML values.
+#[derive(Debug, Clone)]
+pub struct Options {
+    settings: Settings,
+    nulls: Nulls,
+    tags: Tags,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            settings: Settings::new(),
+            nulls: Nulls::Error,
+            tags: Tags::Error,
+        }
+    }
+}
+
+impl Options {
+    /// Creates the default options, rejecting nulls and tagged values.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the handling of nulls, `Nulls::Error` by default.
+    pub fn nulls(mut self, policy: Nulls) -> Self {
+        self.nulls = policy;
+        self
+    }
+
+    /// Sets the handling of tagged values, `Tags::Error` by default.
+    pub fn tags(mut self, policy: Tags) -> Self {
+        self.tags = policy;
+        self
+    }
+
+    /// Sets the layout of the output, its handling of nulls is replaced by `nulls`.
+    pub fn settings(mut self, settings: Settings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    fn to_settings(&self) -> Settings {
+        let mut settings = self.settings.clone().nulls(self.nulls);
+        settings.reject_tags = self.tags == Tags::Error;
+        settings
+    }
+}
+
+/// Converts a YAML mapping into a document.
+pub fn to_document<T: Serialize + ?Sized>(
+    yaml: &T,
+    options: &Options,
+) -> Result<Document, ser::Error> {
+    ser::to_document_with(yaml, &options.to_settings())
+}
+
+/// Converts a YAML value into an item, see `ser::to_item_with`.
+pub fn to_item<T: Serialize + ?Sized>(yaml: &T, options: &Options) -> Result<Item, ser::Error> {
+    ser::to_item_with(yaml, &options.to_settings())
+}
+
+/// Converts a document into a YAML value.
+pub fn from_document<T: DeserializeOwned>(doc: &Document) -> Result<T, de::Error> {
+    de::from_document(doc)
+}
+
+/// Converts an item into a YAML value.
+pub fn from_item<T: DeserializeOwned>(item: &Item) -> Result<T, de::Error> {
+    de::from_item(item)
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "capi")]
+
+use pretty_assertions::assert_eq;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+use toml_edit::capi::*;
+
+fn c(s: &str) -> CString {
+    CString::new(s).unwrap()
+}
+
+unsafe fn take(s: *mut c_char) -> Option<String> {
+    if s.is_null() {
+        return None;
+    }
+    let owned = CStr::from_ptr(s).to_str().unwrap().to_owned();
+    toml_edit_string_free(s);
+    Some(owned)
+}
+
+#[test]
+fn edit_through_c() {
+    unsafe {
+        let mut error = ptr::null_mut();
+        let doc = toml_edit_parse(
+            c("[package]\nname = 'hello' # the name\n").as_ptr(),
+            &mut error,
+        );
+        assert!(!doc.is_null());
+
+        let name = toml_edit_get(doc, c("package.name").as_ptr(), &mut error);
+        assert_eq!(take(name).as_deref(), Some("'hello'"));
+        assert_eq!(
+            take(toml_edit_get(
+                doc,
+                c("package.version").as_ptr(),
+                &mut error
+            )),
+            None
+        );
+        assert!(error.is_null());
+
+        assert!(toml_edit_set(
+            doc,
+            c("package.version").as_ptr(),
+            c("\"0.1.0\"").as_ptr(),
+            &mut error
+        ));
+        assert!(!toml_edit_set(
+            doc,
+            c("package.name.first").as_ptr(),
+            c("1").as_ptr(),
+            &mut error
+        ));
+        assert_eq!(
+            take(error).as_deref(),
+            Some("`package.name` is not a table")
+        );
+        assert!(toml_edit_remove(
+            doc,
+            c("package.name").as_ptr(),
+            ptr::null_mut()
+        ));
+        assert_eq!(
+            take(toml_edit_to_string(doc, ptr::null_mut())).as_deref(),
+            Some("[package]\nversion = \"0.1.0\"\n")
+        );
+        toml_edit_free(doc);
+    }
+}
+
+#[test]
+fn parse_errors() {
+    unsafe {
+        let mut error = ptr::null_mut();
+        let doc = toml_edit_parse(c("a = ").as_ptr(), &mut error);
+        assert!(doc.is_null());
+        assert!(take(error).is_some());
+        assert!(toml_edit_parse(ptr::null(), ptr::null_mut()).is_null());
+        toml_edit_free(doc);
+    }
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "cli")]
+
+use pretty_assertions::assert_eq;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// runs the tool with the document on the standard input, returning the output or the error
+fn run(input: &str, args: &[&str]) -> Result<String, String> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_toml-edit"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    if output.status.success() {
+        Ok(String::from_utf8(output.stdout).unwrap())
+    } else {
+        Err(String::from_utf8(output.stderr).unwrap())
+    }
+}
+
+const MANIFEST: &str = r#"[package]
+name = "hello" # the name
+version = "0.1.0"
+keywords = ["a"]
+
+[dependencies]
+serde = "1.0"
+log = "0.4"
+"#;
+
+#[test]
+fn get() {
+    assert_eq!(
+        run(MANIFEST, &["get", "-", "package.name"]).unwrap(),
+        "hello\n"
+    );
+    assert_eq!(
+        run(MANIFEST, &["get", "-", "package.keywords"]).unwrap(),
+        "[\"a\"]\n"
+    );
+    assert_eq!(
+        run(MANIFEST, &["get", "-", "package.edition"]).unwrap_err(),
+        "toml-edit: no item at `package.edition`\n"
+    );
+}
+
+#[test]
+fn edits() {
+    let output = run(MANIFEST, &["set", "-", "package.name", "'world'"]).unwrap();
+    let output = run(&output, &["append", "-", "package.keywords", "\"b\""]).unwrap();
+    let output = run(&output, &["rm", "-", "package.version"]).unwrap();
+    let output = run(&output, &["sort", "-", "dependencies"]).unwrap();
+    assert_eq!(
+        output,
+        r#"[package]
+name = 'world' # the name
+keywords = ["a", "b"]
+
+[dependencies]
+log = "0.4"
+serde = "1.0"
+"#
+    );
+    let output = run(&output, &["fmt", "-", "20"]).unwrap();
+    assert!(output.contains("keywords = [\n    \"a\",\n    \"b\",\n]\n"));
+}
+
+#[test]
+fn in_place() {
+    let path = std::env::temp_dir().join(format!("toml-edit-{}.toml", std::process::id()));
+    std::fs::write(&path, MANIFEST).unwrap();
+    let file = path.to_str().unwrap();
+    assert_eq!(
+        run("", &["set", file, "package.edition", "2018"]).unwrap(),
+        ""
+    );
+    let output = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(output.contains("keywords = [\"a\"]\nedition = 2018\n"));
+}
+
+#[test]
+fn errors() {
+    assert!(run(MANIFEST, &["get", "-"])
+        .unwrap_err()
+        .starts_with("toml-edit: usage:"));
+    assert!(run(MANIFEST, &["frobnicate", "-", "a"]).is_err());
+    assert!(run("a = ", &["get", "-", "a"]).is_err());
+    assert_eq!(
+        run(MANIFEST, &["rm", "-", "package.edition"]).unwrap_err(),
+        "toml-edit: no item at `package.edition`\n"
+    );
+    assert_eq!(
+        run(MANIFEST, &["append", "-", "package.name", "1"]).unwrap_err(),
+        "toml-edit: `package.name` is not an array\n"
+    );
+    assert!(run(MANIFEST, &["set", "-", "package.name", "not a value"]).is_err());
+}
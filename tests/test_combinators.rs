@@ -0,0 +1,73 @@
+#![cfg(feature = "combinators")]
+
+use pretty_assertions::assert_eq;
+use toml_edit::combinators::{dotted_key, inline_table, key, value};
+use toml_edit::{ExtensionLiteral, ParseOptions};
+
+#[test]
+fn test_value_remainder() {
+    let options = ParseOptions::new();
+    let (v, rest) = value("[1, 2] + [3]", &options).unwrap();
+    assert_eq!(v.to_string(), "[1, 2]");
+    assert_eq!(rest, " + [3]");
+
+    let (v, rest) = value("1,000", &options).unwrap();
+    assert_eq!(v.as_integer(), Some(1));
+    assert_eq!(rest, ",000");
+
+    let (v, rest) = value("'''multi\nline''')", &options).unwrap();
+    assert_eq!(v.as_str(), Some("multi\nline"));
+    assert_eq!(rest, ")");
+
+    let (v, rest) = value("1979-05-27T07:32:00Z;", &options).unwrap();
+    assert!(v.is_date_time());
+    assert_eq!(rest, ";");
+
+    // whitespace is not skipped
+    assert!(value(" 1", &options).is_err());
+    let err = value("[1, 'a']", &options).unwrap_err();
+    assert_eq!(err.line_col().0, 1);
+}
+
+#[test]
+fn test_value_options() {
+    const ENV: &[ExtensionLiteral] = &[ExtensionLiteral::new("env", "${", "}")];
+    assert!(value("${HOME}/bin", &ParseOptions::new()).is_err());
+
+    let options = ParseOptions::new().extension_literals(ENV);
+    let (v, rest) = value("${HOME}/bin", &options).unwrap();
+    assert_eq!(v.extension_tag(), Some("env"));
+    assert_eq!(rest, "/bin");
+}
+
+#[test]
+fn test_keys() {
+    let (k, rest) = key("\"a.b\" = 1").unwrap();
+    assert_eq!(k.get(), "a.b");
+    assert_eq!(k.raw(), "\"a.b\"");
+    assert_eq!(rest, " = 1");
+
+    let (k, rest) = key("name.first").unwrap();
+    assert_eq!(k.get(), "name");
+    assert_eq!(rest, ".first");
+    assert!(key("=").is_err());
+
+    let (path, rest) = dotted_key("a . 'b c'.d = 1").unwrap();
+    assert_eq!(path.to_string(), "a.'b c'.d");
+    assert_eq!(rest, " = 1");
+    let (path, rest) = dotted_key("a:").unwrap();
+    assert_eq!(path.len(), 1);
+    assert_eq!(rest, ":");
+}
+
+#[test]
+fn test_inline_table() {
+    let options = ParseOptions::new();
+    let (t, rest) = inline_table("{ x = 1, y = { z = 2 } } end", &options).unwrap();
+    assert_eq!(t.to_string(), "{ x = 1, y = { z = 2 } }");
+    assert_eq!(t.get("x").and_then(|v| v.as_integer()), Some(1));
+    assert_eq!(rest, " end");
+
+    assert!(inline_table("[1]", &options).is_err());
+    assert!(inline_table("{ x = 1, x = 2 }", &options).is_err());
+}
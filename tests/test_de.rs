@@ -0,0 +1,252 @@
+#![cfg(feature = "serde")]
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use toml_edit::{de, Document};
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Manifest<'a> {
+    #[serde(borrow)]
+    package: Package<'a>,
+    #[serde(borrow)]
+    dependencies: BTreeMap<&'a str, Dependency<'a>>,
+    #[serde(default)]
+    bin: Vec<Target>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Package<'a> {
+    name: &'a str,
+    authors: Vec<&'a str>,
+    edition: Option<u16>,
+    #[serde(with = "serde_bytes")]
+    checksum: &'a [u8],
+    #[serde(with = "serde_bytes")]
+    magic: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum Dependency<'a> {
+    Version(&'a str),
+    Detailed {
+        version: &'a str,
+        #[serde(default)]
+        features: Vec<&'a str>,
+    },
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Target {
+    name: String,
+    kind: Kind,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Kind {
+    Bin,
+    Example,
+}
+
+#[test]
+fn test_deserialize_borrowed() {
+    let doc = r#"
+[package]
+name = "toml_edit"
+authors = ["Andronik Ordian <write@reusable.software>"]
+checksum = "abc"
+magic = [0x7f, 69, 76, 70]
+
+[dependencies]
+chrono = "0.4.10"
+combine = { version = "4.5.2", features = ["std"] }
+
+[[bin]]
+name = "main"
+kind = "bin"
+"#
+    .parse::<Document>()
+    .unwrap();
+
+    let manifest: Manifest = de::from_document(&doc).unwrap();
+    assert_eq!(manifest.package.name, "toml_edit");
+    assert_eq!(manifest.package.edition, None);
+    assert_eq!(manifest.package.checksum, b"abc");
+    assert_eq!(manifest.package.magic, b"\x7fELF");
+    assert_eq!(
+        manifest.dependencies["chrono"],
+        Dependency::Version("0.4.10")
+    );
+    assert_eq!(
+        manifest.dependencies["combine"],
+        Dependency::Detailed {
+            version: "4.5.2",
+            features: vec!["std"],
+        }
+    );
+    assert_eq!(
+        manifest.bin,
+        vec![Target {
+            name: "main".into(),
+            kind: Kind::Bin,
+        }]
+    );
+}
+
+#[test]
+fn test_deserialize_errors() {
+    let res = de::from_str::<BTreeMap<String, u8>>("a = 256");
+    assert!(res.is_err());
+    let res = de::from_str::<BTreeMap<String, Kind>>("a = 'library'");
+    assert!(res.unwrap_err().to_string().contains("unknown variant"));
+    let res = de::from_str::<BTreeMap<String, u8>>("a = ");
+    assert!(res.unwrap_err().to_string().contains("TOML parse error"));
+    let example: BTreeMap<String, Kind> = de::from_str("a = 'example'").unwrap();
+    assert_eq!(example["a"], Kind::Example);
+
+    let err = de::from_str::<BTreeMap<String, Vec<Target>>>(
+        "[[bin]]\nname = 'main'\nkind = 'bin'\n[[bin]]\nname = 'example'\nkind = 'library'",
+    )
+    .unwrap_err();
+    assert_eq!(err.path(), Some("bin[1].kind"));
+    assert!(err.to_string().ends_with("for key `bin[1].kind`"));
+    let err =
+        de::from_str::<BTreeMap<String, BTreeMap<String, u8>>>("a = { 'b.c' = -1 }").unwrap_err();
+    assert_eq!(err.path(), Some(r#"a."b.c""#));
+}
+
+#[test]
+fn test_deserialize_coercions() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Legacy {
+        enabled: bool,
+        port: u16,
+        ratio: f64,
+        weights: Vec<f32>,
+    }
+
+    let input = r#"
+enabled = "true"
+port = " 8080 "
+ratio = "0.5"
+weights = ["1", "2.5"]
+"#;
+    let err = de::from_str::<Legacy>(input).unwrap_err();
+    assert_eq!(err.path(), Some("enabled"));
+    let bools = de::Settings::new().bool_from_string(true);
+    let err = de::from_str_with::<Legacy>(input, &bools).unwrap_err();
+    assert_eq!(err.path(), Some("port"));
+    let settings = bools.number_from_string(true);
+    let legacy: Legacy = de::from_str_with(input, &settings).unwrap();
+    assert_eq!(
+        legacy,
+        Legacy {
+            enabled: true,
+            port: 8080,
+            ratio: 0.5,
+            weights: vec![1.0, 2.5],
+        }
+    );
+    let err =
+        de::from_str_with::<Legacy>(&input.replace("\"true\"", "'yes'"), &settings).unwrap_err();
+    assert_eq!(err.path(), Some("enabled"));
+    assert!(err
+        .to_string()
+        .contains(r#"invalid type: string "yes", expected a boolean"#));
+    let err = de::from_str_with::<Legacy>(&input.replace("2.5", "x"), &settings).unwrap_err();
+    assert_eq!(err.path(), Some("weights[1]"));
+}
+
+#[test]
+fn test_document_roundtrip() {
+    let input = r#"
+# comment
+[dependencies]
+chrono = { version = "0.4", features = [ "serde" ] } # trailing
+
+[package]
+name = 'toml_edit'
+date = 1979-05-27T07:32:00-08:00
+
+[dependencies.combine]
+version = "4.5"
+
+[[bin]]
+name = "main"
+"#;
+    let doc = input.parse::<Document>().unwrap();
+    let cached = serde_json::to_vec(&doc).unwrap();
+    let doc: Document = serde_json::from_slice(&cached).unwrap();
+    assert_eq!(doc.to_string_in_original_order(), input);
+    assert_eq!(
+        doc["package"]["date"].as_date_time(),
+        input.parse::<Document>().unwrap()["package"]["date"].as_date_time()
+    );
+}
+
+#[test]
+fn test_deserialize_truncation() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        ports: Vec<u16>,
+        weights: Vec<u8>,
+        limits: BTreeMap<String, i8>,
+    }
+
+    let doc = "ports = [80, 70000]\nweights = [8.9, 300.0]\nlimits = { low = -200, high = 3 }"
+        .parse::<Document>()
+        .unwrap();
+    let err = de::from_document::<Config>(&doc).unwrap_err();
+    assert_eq!(err.path(), Some("ports[1]"));
+    assert_eq!((err.found(), err.target()), (Some("70000"), Some("u16")));
+    let err = de::from_str::<BTreeMap<String, i32>>("a = 1.0").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "expected an integer of type `i32`, found float `1.0` for key `a`"
+    );
+
+    let settings = de::Settings::new().lossy_numbers(true);
+    let (config, conversions) =
+        de::from_document_with_conversions::<Config>(&doc, &settings).unwrap();
+    assert_eq!(config.ports, vec![80, 65535]);
+    assert_eq!(config.weights, vec![8, 255]);
+    assert_eq!(config.limits["low"], -128);
+    let conversions: Vec<_> = conversions
+        .iter()
+        .map(|c| (c.path().unwrap(), c.found(), c.converted()))
+        .collect();
+    assert_eq!(
+        conversions,
+        vec![
+            ("ports[1]", "70000", "65535"),
+            ("weights[0]", "8.9", "8"),
+            ("weights[1]", "300.0", "255"),
+            ("limits.low", "-200", "-128"),
+        ]
+    );
+    let lossy: Config = de::from_document_with(&doc, &settings).unwrap();
+    assert_eq!(lossy, config);
+}
+
+#[test]
+fn test_document_deserialize_checks() {
+    let doc = "a = 1\r\nb = 2\r\n".parse::<Document>().unwrap();
+    let json = serde_json::to_string(&doc).unwrap();
+    let doc: Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(doc.detect_style().newline(), toml_edit::Newline::CrLf);
+
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value["root"] = serde_json::json!({ "Value": { "Boolean": {
+        "value": true,
+        "repr": { "decor": { "prefix": "", "suffix": "" }, "raw_value": "true" }
+    } } });
+    let err = serde_json::from_value::<Document>(value).unwrap_err();
+    assert_eq!(err.to_string(), "the root of a document should be a table");
+
+    let mismatch = json.replace(r#""raw_value":"1""#, r#""raw_value":"2""#);
+    let err = serde_json::from_str::<Document>(&mismatch).unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("value \"2\" denotes another value at `a`"));
+}
@@ -0,0 +1,160 @@
+#![cfg(all(feature = "derive", feature = "parse"))]
+
+use pretty_assertions::assert_eq;
+use toml_edit::binding::TomlEdit;
+use toml_edit::Document;
+
+#[derive(Debug, PartialEq, TomlEdit)]
+struct Config {
+    title: String,
+    debug: bool,
+    #[toml_edit(rename = "max-connections")]
+    max_connections: u16,
+    ratio: Option<f64>,
+    server: Server,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, TomlEdit)]
+struct Server {
+    host: String,
+    ports: Vec<u16>,
+}
+
+#[derive(Debug, PartialEq, TomlEdit)]
+struct Dependency {
+    r#type: String,
+    r#ref: Option<String>,
+}
+
+#[test]
+fn test_derive_roundtrip() {
+    let input = r#"
+# the title
+title = "app"   # shown in the window
+debug = false
+max-connections = 10
+
+tags = [
+    "a", # first
+    "b",
+]
+
+[server] # the server
+host = 'localhost'
+ports = [80, 443]
+"#;
+    let mut doc = input.parse::<Document>().unwrap();
+    let mut config = Config::from_document(&doc).unwrap();
+    assert_eq!(
+        config,
+        Config {
+            title: "app".to_owned(),
+            debug: false,
+            max_connections: 10,
+            ratio: None,
+            server: Server {
+                host: "localhost".to_owned(),
+                ports: vec![80, 443],
+            },
+            tags: vec!["a".to_owned(), "b".to_owned()],
+        }
+    );
+
+    config.update_document(&mut doc);
+    assert_eq!(doc.to_string(), input);
+
+    config.title = "new app".to_owned();
+    config.debug = true;
+    config.ratio = Some(0.5);
+    config.server.ports[1] = 8443;
+    config.tags[1] = "c".to_owned();
+    config.update_document(&mut doc);
+    assert_eq!(
+        doc.to_string(),
+        r#"
+# the title
+title = "new app"   # shown in the window
+debug = true
+max-connections = 10
+
+tags = [
+    "a", # first
+    "c",
+]
+ratio = 0.5
+
+[server] # the server
+host = 'localhost'
+ports = [80, 8443]
+"#
+    );
+
+    config.ratio = None;
+    config.server.ports.push(8080);
+    config.update_document(&mut doc);
+    assert_eq!(
+        doc["server"]["ports"].as_value().unwrap().to_string(),
+        " [80, 8443, 8080]"
+    );
+    assert!(doc.as_table().get("ratio").is_none());
+}
+
+#[test]
+fn test_derive_raw_identifiers() {
+    let mut doc = "type = 'git'  # the source\n".parse::<Document>().unwrap();
+    let mut dep = Dependency::from_document(&doc).unwrap();
+    assert_eq!(
+        dep,
+        Dependency {
+            r#type: "git".to_owned(),
+            r#ref: None,
+        }
+    );
+    dep.r#ref = Some("main".to_owned());
+    dep.update_document(&mut doc);
+    assert_eq!(
+        doc.to_string(),
+        "type = 'git'  # the source\nref = \"main\"\n"
+    );
+}
+
+#[test]
+fn test_derive_errors() {
+    let err = Server::from_document(&"host = 'a'".parse().unwrap()).unwrap_err();
+    assert_eq!(err.path(), Some("ports"));
+    assert_eq!(err.to_string(), "missing value for key `ports`");
+    let err =
+        Server::from_document(&"host = 'a'\nports = [1, 70000]".parse().unwrap()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "integer 70000 out of range for key `ports[1]`"
+    );
+    let err = Config::from_document(&"title = 1".parse().unwrap()).unwrap_err();
+    assert_eq!(err.to_string(), "expected a string for key `title`");
+}
+
+#[derive(Debug, PartialEq, TomlEdit)]
+struct Limits {
+    name: String,
+    load: f32,
+    ratio: f64,
+}
+
+#[test]
+fn test_derive_scalars() {
+    let input = "name = 'a'\nload = 0.1 # not exact in f32\nratio = 0.5\n";
+    let mut doc = input.parse::<Document>().unwrap();
+    let mut limits = Limits::from_document(&doc).unwrap();
+    limits.update_document(&mut doc);
+    assert_eq!(doc.to_string(), input);
+
+    limits.name = "\"quoted\"".to_owned();
+    limits.ratio = 1.0;
+    limits.update_document(&mut doc);
+    assert_eq!(
+        doc.to_string(),
+        "name = \"\\\"quoted\\\"\"\nload = 0.1 # not exact in f32\nratio = 1.0\n"
+    );
+    assert_eq!(Limits::from_document(&doc).unwrap(), limits);
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "parse")]
+
 macro_rules! parse_key {
     ($s:expr) => {{
         let key = $s.parse::<Key>();
@@ -17,7 +19,8 @@ macro_rules! as_table {
 #[cfg(test)]
 #[rustfmt::skip]
 mod tests {
-    use toml_edit::{Document, Key, Value, Table, Item, value, table, array, decorated};
+    use toml_edit::{AutoInline, CommentFilter, ConflictPolicy, Decor, Document, Formatted, Repr, IncludeError, IndexError, InvalidKind, IncludeResolver, Key, KeyPath, Layers, LazyDocument, Metadata, Reflow, TablePlacement, Template, TomlVersion, Value, Table, Item, value, table, array, decorated};
+    use std::collections::HashMap;
     use std::iter::FromIterator;
     use std::fmt;
     use pretty_assertions::assert_eq;
@@ -58,6 +61,13 @@ mod tests {
             self
         }
 
+        fn running_on_doc<F>(&mut self, func: F) -> &mut Self
+            where F: Fn(&mut Document)
+        {
+            func(&mut self.doc);
+            self
+        }
+
         fn produces_display(&self, expected: &str) -> &Self {
             assert_eq!(
                 PrettyString(expected),
@@ -390,235 +400,2505 @@ fn test_remove_value() {
 }
 
 #[test]
-fn test_remove_last_value_from_implicit() {
+fn test_remove_entry() {
     given(r#"
-        [a]
-        b = 1"#
+        name = "hello"
+        # delete this
+        "version" = "1.0.0" # please
+        documentation = "https://docs.rs/hello""#
     ).running(|root| {
-        let a = root.entry("a");
-        assert!(a.is_table());
-        let a = as_table!(a);
-        a.set_implicit(true);
-        let value = a.remove("b");
-        assert!(value.is_some());
-        let value = value.unwrap();
-        assert!(value.is_value());
-        let value = value.as_value().unwrap();
-        assert_eq!(value.as_integer(), Some(1));
-    }).produces(r#""#);
+        let (key, item) = root.remove_entry("version").unwrap();
+        assert_eq!(key.get(), "version");
+        assert_eq!(key.raw(), "\"version\"");
+        assert_eq!(key.decor().prefix(), "        # delete this\n        ");
+        assert_eq!(key.decor().suffix(), " ");
+        assert_eq!(item.as_str(), Some("1.0.0"));
+        assert!(root.remove_entry("version").is_none());
+    }).produces(r#"
+        name = "hello"
+        documentation = "https://docs.rs/hello"
+"#
+    );
 }
 
-// values
-
 #[test]
-fn test_sort_values() {
+fn test_clear() {
     given(r#"
-        [a.z]
-
-        [a]
-        # this comment is attached to b
-        b = 2 # as well as this
-        a = 1
-        c = 3
+        # the package
+        [package] # header
+        name = "hello"
+        keywords = [
+            "a",
+            "b", # the last one
+        ]
+        metadata = { a = 1, b = 2 }
 
-        [a.y]"#
+        [[bin]]
+        name = "main""#
     ).running(|root| {
-        let a = root.entry("a");
-        let a = as_table!(a);
-        a.sort_values();
+        let package = root["package"].as_table_mut().unwrap();
+        package["keywords"].as_array_mut().unwrap().clear();
+        package["metadata"].as_inline_table_mut().unwrap().clear();
+        root["bin"].as_array_of_tables_mut().unwrap().clear();
     }).produces_display(r#"
-        [a]
-        a = 1
-        # this comment is attached to b
-        b = 2 # as well as this
-        c = 3
-
-        [a.z]
-
-        [a.y]
+        # the package
+        [package] # header
+        name = "hello"
+        keywords = [ # the last one
+        ]
+        metadata = {}
 "#
-    ).produces_in_original_order(r#"
-        [a.z]
-
-        [a]
-        a = 1
-        # this comment is attached to b
-        b = 2 # as well as this
-        c = 3
-
-        [a.y]
-"#);
-}
-
-#[test]
-fn test_set_position() {
+    );
     given(r#"
-        [package]
-        [dependencies]
-        [dependencies.opencl]
-        [dev-dependencies]"#
+        # the package
+        [package] # header
+        name = "hello""#
     ).running(|root| {
-        for (header, table) in root.iter_mut() {
-            if header == "dependencies" {
-                let tab = as_table!(table);
-                tab.set_position(0);
-                let (_, segmented) = tab.iter_mut().next().unwrap();
-                as_table!(segmented).set_position(5)
-            }
-        }
-    }).produces_in_original_order(r#"        [dependencies]
-
-        [package]
-        [dev-dependencies]
-        [dependencies.opencl]
-"#);
+        let package = root["package"].as_table_mut().unwrap();
+        package.clear();
+        package["name"] = value("world");
+    }).produces_display(r#"
+        # the package
+        [package] # header
+name = "world"
+"#
+    );
 }
 
 #[test]
-fn test_multiple_zero_positions() {
-    given(r#"
-        [package]
-        [dependencies]
-        [dependencies.opencl]
-        a=""
-        [dev-dependencies]"#
-    ).running(|root| {
-        for (_, table) in root.iter_mut() {
-            as_table!(table).set_position(0)
-        }
-    }).produces_in_original_order(r#"
-        [package]
-        [dependencies]
-        [dev-dependencies]
-        [dependencies.opencl]
-        a=""
-"#);
+fn test_document_clear() {
+    let mut doc = "a = 1\n[b]\nc = 2\n# trailing\n".parse::<Document>().unwrap();
+    doc.clear();
+    assert!(doc.as_table().is_empty());
+    doc["d"] = value(3);
+    assert_eq!(doc.to_string(), "d = 3\n# trailing\n");
 }
 
 #[test]
-fn test_multiple_max_usize_positions() {
+fn test_take_and_replace() {
     given(r#"
         [package]
-        [dependencies]
-        [dependencies.opencl]
-        a=""
-        [dev-dependencies]"#
-    ).running(|root| {
-        for (_, table) in root.iter_mut() {
-            as_table!(table).set_position(usize::MAX)
-        }
-    }).produces_in_original_order(r#"        [dependencies.opencl]
-        a=""
+        name = "hello" # the name
+        metadata = { a = 1, b = 2 }
+
+        # the old one
+        [old]
+        x = 1
 
+        [new]
+        y = 2"#
+    ).running(|root| {
+        let package = root["package"].as_table_mut().unwrap();
+        let name = package.replace("name", value("world")).unwrap();
+        assert_eq!(name.as_str(), Some("hello"));
+        assert!(package.take("missing").is_none());
+        let metadata = package["metadata"].as_inline_table_mut().unwrap();
+        let a = metadata.take("a").unwrap();
+        metadata.insert("a", a.as_integer().unwrap() + 1);
+        let new = root.take("new").unwrap();
+        let old = root.replace("old", new).unwrap();
+        assert_eq!(old["x"].as_integer(), Some(1));
+        assert!(root.take("new").is_none());
+    }).produces_display(r#"
         [package]
-        [dependencies]
-        [dev-dependencies]
-"#);
-}
+        name = "world" # the name
+        metadata = { a = 2, b = 2 }
 
-macro_rules! as_array {
-    ($entry:ident) => (
-        {
-            assert!($entry.is_value());
-            let a = $entry.as_value_mut().unwrap();
-            assert!(a.is_array());
-            a.as_array_mut().unwrap()
-        }
+        # the old one
+        [old]
+        y = 2
+"#
     );
 }
 
 #[test]
-fn test_insert_replace_into_array() {
+fn test_insert_table_next_to() {
+    fn server(ip: &str) -> Table {
+        let mut table = Table::new();
+        table["ip"] = value(ip);
+        table
+    }
+    let mut lib = Table::new();
+    lib["path"] = value("lib.rs");
     given(r#"
-        a = [1,2,3]
-        b = []"#
+[servers]
+
+  # the alpha
+  [servers.alpha]
+  ip = "10.0.0.1"
+
+  [servers.gamma]
+  ip = "10.0.0.3"
+
+[[bin]]
+name = "a"
+"#
     ).running(|root| {
-        {
-            let a = root.entry("a");
-            let a = as_array!(a);
-            assert_eq!(a.len(), 3);
-            assert!(a.get(2).is_some());
-            assert!(a.push(4).is_ok());
-            assert_eq!(a.len(), 4);
-            a.fmt();
-        }
-        let b = root.entry("b");
-        let b = as_array!(b);
-        assert!(b.is_empty());
-        assert!(b.push("hello").is_ok());
-        assert_eq!(b.len(), 1);
+        let servers = root["servers"].as_table_mut().unwrap();
+        assert!(servers.insert_table_after("beta", server("10.0.0.2"), "alpha").is_none());
+        servers.insert_table_before("zero", server("10.0.0.0"), "alpha");
+        root.insert_table_before("lib", lib.clone(), "bin");
+    }).produces_display(r#"
+[servers]
 
-        assert!(b.push_formatted(decorated("world".into(), "\n", "\n")).is_ok());
-        assert!(b.push_formatted(decorated("test".into(), "", "")).is_ok());
+  [servers.zero]
+ip = "10.0.0.0"
 
-        assert!(b.insert(1, "beep").is_ok());
-        assert!(b.insert_formatted(2, decorated("boop".into(), "   ", "   ")).is_ok());
+  # the alpha
+  [servers.alpha]
+  ip = "10.0.0.1"
 
-        // This should preserve formatting.
-        assert_eq!(b.replace(2, "zoink").unwrap().as_str(), Some("boop"));
-        // This should replace formatting.
-        assert_eq!(b.replace_formatted(4, decorated("yikes".into(), "  ", "")).unwrap().as_str(), Some("test"));
+  [servers.beta]
+ip = "10.0.0.2"
 
-        // Check that pushing a different type into an array fails.
-        assert!(b.push(42).is_err());
+  [servers.gamma]
+  ip = "10.0.0.3"
 
-    }).produces(r#"
-        a = [1, 2, 3, 4]
-        b = ["hello", "beep",   "zoink"   ,
-"world"
-,  "yikes"]
+[lib]
+path = "lib.rs"
+
+[[bin]]
+name = "a"
+"#
+    ).produces_in_original_order(r#"
+[servers]
+
+  [servers.zero]
+ip = "10.0.0.0"
+
+  # the alpha
+  [servers.alpha]
+  ip = "10.0.0.1"
+
+  [servers.beta]
+ip = "10.0.0.2"
+
+  [servers.gamma]
+  ip = "10.0.0.3"
+
+[lib]
+path = "lib.rs"
+
+[[bin]]
+name = "a"
 "#
     );
 }
 
 #[test]
-fn test_remove_from_array() {
+fn test_inherit_indentation() {
     given(r#"
-        a = [1, 2, 3, 4]
-        b = ["hello"]"#
-    ).running(|root| {
-        {
-            let a = root.entry("a");
-            let a = as_array!(a);
-            assert_eq!(a.len(), 4);
-            assert!(a.remove(3).is_integer());
-            assert_eq!(a.len(), 3);
+title = "servers"
+
+[servers]
+    [servers.alpha]
+    ip = "10.0.0.1"
+      # the role
+      role = "frontend"
+
+[[bin]]
+  name = "a"
+"#
+    ).running_on_doc(|doc| {
+        doc["servers"]["alpha"]["port"] = value(80);
+        let mut beta = Table::new();
+        beta["ip"] = value("10.0.0.2");
+        doc["servers"]["beta"] = Item::Table(beta);
+        doc["bin"].as_array_of_tables_mut().unwrap().append(Table::new())["name"] = value("b");
+        doc["debug"] = value(true);
+        for path in ["servers.alpha.port", "servers.beta", "bin", "debug"] {
+            doc.inherit_indentation(&path.parse().unwrap());
         }
-        let b = root.entry("b");
-        let b = as_array!(b);
-        assert_eq!(b.len(), 1);
-        assert!(b.remove(0).is_str());
-        assert!(b.is_empty());
-    }).produces(r#"
-        a = [1, 2, 3]
-        b = []
+    }).produces_display(r#"
+title = "servers"
+debug = true
+
+[servers]
+    [servers.alpha]
+    ip = "10.0.0.1"
+      # the role
+      role = "frontend"
+    port = 80
+
+    [servers.beta]
+    ip = "10.0.0.2"
+
+[[bin]]
+  name = "a"
+
+[[bin]]
+  name = "b"
 "#
     );
+    given("[a]\n  x = 1\ny = 2\n").running_on_doc(|doc| {
+        doc["a"]["z"] = value(3);
+        doc.inherit_indentation(&"a.z".parse().unwrap());
+    }).produces_display("[a]\n  x = 1\ny = 2\n  z = 3\n");
 }
 
-macro_rules! as_inline_table {
-    ($entry:ident) => (
-        {
-            assert!($entry.is_value());
-            let a = $entry.as_value_mut().unwrap();
-            assert!(a.is_inline_table());
-            a.as_inline_table_mut().unwrap()
+#[test]
+fn test_array_of_tables_iter_mut() {
+    given(r#"
+[[bin]]
+name = "a"
+
+[[bin]]
+name = "b"
+
+[[bin]]
+name = "c"
+"#
+    ).running(|root| {
+        let bins = root["bin"].as_array_of_tables_mut().unwrap();
+        assert_eq!(bins.first().and_then(|t| t["name"].as_str()), Some("a"));
+        assert_eq!(bins.last().and_then(|t| t["name"].as_str()), Some("c"));
+        for (i, bin) in bins.iter_mut().enumerate() {
+            bin["test"] = value(i % 2 == 0);
+        }
+        bins.first_mut().unwrap().remove("test");
+        bins.last_mut().unwrap()["name"] = value("d");
+        for bin in &mut *bins {
+            let path = format!("src/{}.rs", bin["name"].as_str().unwrap());
+            bin["path"] = value(path);
         }
+        assert_eq!((&*bins).into_iter().count(), 3);
+    }).produces_display(r#"
+[[bin]]
+name = "a"
+path = "src/a.rs"
+
+[[bin]]
+name = "b"
+test = false
+path = "src/b.rs"
+
+[[bin]]
+name = "d"
+test = true
+path = "src/d.rs"
+"#
     );
 }
 
 #[test]
-fn test_insert_into_inline_table() {
+fn test_implicit_tables() {
     given(r#"
-        a = {a=2,  c = 3}
-        b = {}"#
+[package.metadata.docs.rs]
+features = ["full"]
+
+[[package.metadata.bin]]
+name = "a"
+"#
     ).running(|root| {
-        {
-            let a = root.entry("a");
-            let a = as_inline_table!(a);
-            assert_eq!(a.len(), 2);
-            assert!(a.contains_key("a") && a.get("c").is_some() && a.get_mut("c").is_some());
-            a.get_or_insert("b", 42);
+        let paths: Vec<_> = root.implicit_tables().iter().map(ToString::to_string).collect();
+        assert_eq!(paths, ["package", "package.metadata", "package.metadata.docs"]);
+        root.materialize_implicit_tables();
+        assert!(root.implicit_tables().is_empty());
+        let path = "package.metadata".parse().unwrap();
+        root.get_path_mut(&path).unwrap().as_table_mut().unwrap().set_implicit(true);
+    }).produces_display(r#"
+[package]
+
+[package.metadata.docs]
+
+[package.metadata.docs.rs]
+features = ["full"]
+
+[[package.metadata.bin]]
+name = "a"
+"#
+    );
+    given(r#"
+[package]
+[package.metadata]
+[package.metadata.docs]
+[package.metadata.docs.rs]
+features = ["full"]
+
+[[package.metadata.bin]]
+name = "a"
+"#
+    ).running(|root| {
+        assert!(root.implicit_tables().is_empty());
+        root.elide_intermediate_tables();
+        let paths: Vec<_> = root.implicit_tables().iter().map(ToString::to_string).collect();
+        assert_eq!(paths, ["package", "package.metadata", "package.metadata.docs"]);
+    }).produces_display(r#"[package.metadata.docs.rs]
+features = ["full"]
+
+[[package.metadata.bin]]
+name = "a"
+"#
+    );
+    given(r#"# keep me
+[a]
+[a.b]
+x = 1
+[c] # and me
+[c.d]
+y = 2
+"#
+    ).running(|root| {
+        root.elide_intermediate_tables();
+        assert!(root.implicit_tables().is_empty());
+    }).produces_display(r#"# keep me
+[a]
+[a.b]
+x = 1
+[c] # and me
+[c.d]
+y = 2
+"#
+    );
+}
+
+#[test]
+#[cfg(feature = "verbatim")]
+fn test_to_string_verbatim() {
+    let input = "# top\r\na . b = 1\r\n[ x ]\r\n\ty = 2   \r\n[ z . w ]\nv = 3\n[ x . q ]\nr = 4";
+    let mut doc = input.parse::<Document>().unwrap();
+    assert_eq!(doc.to_string_verbatim(), input);
+    assert_ne!(doc.to_string(), input);
+
+    doc["z"]["w"]["v"] = value(5);
+    doc["x"].as_table_mut().unwrap().remove("q");
+    doc["new"] = table();
+    assert_eq!(
+        doc.to_string_verbatim(),
+        "# top\r\na . b = 1\r\n[ x ]\r\n\ty = 2   \r\n[z.w]\nv = 5\n\n[new]\n"
+    );
+    assert_eq!("a = 1".parse::<Document>().unwrap().clone().to_string_verbatim(), "a = 1");
+    let mut doc = Document::new();
+    doc["a"] = value(1);
+    assert_eq!(doc.to_string_verbatim(), "a = 1\n");
+}
+
+#[test]
+#[cfg(feature = "verbatim")]
+fn test_to_string_verbatim_nested() {
+    let nested = |depth| format!("b = 2\na = {}1{}\n", "[".repeat(depth), "]".repeat(depth));
+    assert!(nested(129).parse::<Document>().is_err());
+
+    let input = nested(128);
+    let mut doc = input.parse::<Document>().unwrap();
+    assert_eq!(doc.to_string_verbatim(), input);
+
+    doc.as_table_mut().remove("a");
+    assert_eq!(doc.to_string_verbatim(), "b = 2\n");
+}
+
+#[test]
+fn test_merge3() {
+    let parse = |s: &str| s.parse::<Document>().unwrap();
+    let base = parse(r#"
+[package]
+name = "hello"
+version = "0.1.0"
+
+[dependencies]
+serde = "1.0"
+log = "0.4"
+"#);
+    let ours = parse(r#"
+[package]
+name = "hello"
+version = "0.2.0" # bumped
+
+[dependencies]
+serde = "1.0"
+log = "0.4"
+rand = "0.8"
+"#);
+    let theirs = parse(r#"
+[package]
+name = 'hello'
+version = "0.1.0"
+edition = "2018"
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"] }
+
+[dev-dependencies]
+pretty_assertions = "0.6"
+"#);
+    let merged = Document::merge3(&base, &ours, &theirs).unwrap();
+    assert_eq!(merged.to_string(), r#"
+[package]
+name = "hello"
+version = "0.2.0" # bumped
+edition = "2018"
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"] }
+rand = "0.8"
+
+[dev-dependencies]
+pretty_assertions = "0.6"
+"#);
+
+    let theirs = parse(r#"
+[package]
+name = "hello"
+version = "0.3.0"
+
+[dependencies]
+serde = "1.0"
+log = "0.4"
+rand = "0.7"
+"#);
+    let conflicts = Document::merge3(&base, &ours, &theirs).unwrap_err();
+    let paths: Vec<_> = conflicts.iter().map(|c| c.path().to_string()).collect();
+    assert_eq!(paths, ["package.version", "dependencies.rand"]);
+    assert!(conflicts[1].base().is_none());
+    assert_eq!(conflicts[1].ours().as_str(), Some("0.8"));
+    assert_eq!(conflicts[1].theirs().as_str(), Some("0.7"));
+    assert_eq!(conflicts[0].to_string(), "conflicting changes of `package.version`");
+
+    // keys are not parsed again
+    let base = parse("\"it's \\\"q\\\"\" = 1\n\"'x'\" = 1\nx = 1\n");
+    let theirs = parse("\"it's \\\"q\\\"\" = 2\n\"'x'\" = 2\nx = 1\n");
+    let merged = Document::merge3(&base, &base, &theirs).unwrap();
+    assert_eq!(merged.to_string(), "\"it's \\\"q\\\"\" = 2\n\"'x'\" = 2\nx = 1\n");
+}
+
+#[test]
+fn test_formatted_from_repr() {
+    given(r#"
+        [package]
+        version = "0.1.0"
+        size = 1"#
+    ).running(|root| {
+        let repr = Repr::new(" ", "'0.2.0'", " # bumped");
+        let version = Formatted::<String>::from_repr(repr).unwrap();
+        assert_eq!(version.value(), "0.2.0");
+        root["package"]["version"] = Item::Value(Value::String(version));
+
+        let mut size = Formatted::new(1i64);
+        size.set_raw("0x_ff").unwrap_err();
+        size.set_raw("0xff").unwrap();
+        size.decor_mut().set_prefix(" ");
+        assert_eq!(*size.value(), 255);
+        root["package"]["size"] = Item::Value(Value::Integer(size));
+    }).produces_display(r#"
+        [package]
+        version = '0.2.0' # bumped
+        size = 0xff
+"#);
+
+    let err = Formatted::<bool>::from_repr(Repr::new("", "1", "")).unwrap_err();
+    assert!(err.to_string().contains("Unexpected Integer value, expected Boolean"));
+    assert!(Formatted::<i64>::from_repr(Repr::new("", "1", "\n1")).is_err());
+    assert!(Decor::try_new(" ", " # comment\n").is_ok());
+}
+
+#[test]
+fn test_template_instantiate() {
+    let template = r#"
+[[bin]]
+name = "{{name}}-cli"
+features = "{{features}}" # default features
+
+[lib]
+path = 'src/{{ name }}.rs'
+version = "{{version}}"
+"#.parse::<Template>().unwrap();
+
+    let mut values = HashMap::new();
+    values.insert("name", Value::from("hello"));
+    let err = template.instantiate(&values).unwrap_err();
+    assert_eq!(err.missing(), ["features", "version"]);
+    assert_eq!(err.to_string(), "missing values for placeholders `features`, `version`");
+
+    values.insert("features", Value::from_iter(vec!["std", "serde"]));
+    values.insert("version", Value::from(1.5));
+    let doc = template.instantiate(&values).unwrap();
+    assert_eq!(doc.to_string(), r#"
+[[bin]]
+name = "hello-cli"
+features = ["std", "serde"] # default features
+
+[lib]
+path = "src/hello.rs"
+version = 1.5
+"#);
+}
+
+#[test]
+fn test_assert_valid() {
+    let mut doc = r#"
+# header
+[package] # package
+name = "hello"
+tags = [ "a", # first
+  "b" ]
+"#.parse::<Document>().unwrap();
+    assert_eq!(doc.assert_valid(), Ok(()));
+
+    let package = doc["package"].as_table_mut().unwrap();
+    package.decor_mut("name").unwrap().set_prefix("# no newline");
+    package["version"] = Item::Value(decorated(value("0.1.0").as_value().unwrap().clone(), "\n", ""));
+    let tags = package["tags"].as_array_mut().unwrap();
+    tags.replace_formatted(0, decorated("a".into(), "", "# eats the comma")).unwrap();
+    let errors = doc.assert_valid().unwrap_err();
+    let errors: Vec<_> = errors.iter().map(|e| (e.path().to_string(), e.kind(), e.text())).collect();
+    assert_eq!(errors, [
+        ("package.name".to_owned(), InvalidKind::Decor, "# no newline"),
+        ("package.tags".to_owned(), InvalidKind::Decor, "# eats the comma"),
+        ("package.version".to_owned(), InvalidKind::Decor, "\n"),
+    ]);
+}
+
+#[test]
+fn test_table_lengths() {
+    given(r#"
+        [dependencies]
+        serde = "1.0"
+        log.version = "0.4"
+        [dependencies.rand.features]
+        [[dependencies.bin]]
+        "#
+    ).running(|root| {
+        let deps = root["dependencies"].as_table_mut().unwrap();
+        deps.entry("missing");
+        assert_eq!(deps.len_all(), 5);
+        assert_eq!(deps.len(), 4);
+        assert_eq!(deps.len_values(), 1);
+        assert_eq!(deps.len_subtables(), 3);
+        assert!(!deps.is_logically_empty());
+
+        deps.remove("serde");
+        deps.remove("log");
+        assert!(!deps.is_logically_empty());
+        deps["bin"].as_array_of_tables_mut().unwrap().clear();
+        assert!(deps.is_logically_empty());
+        assert!(!deps.is_empty());
+        assert!(deps["rand"].as_table().unwrap().is_logically_empty());
+    });
+}
+
+#[test]
+fn test_compact() {
+    given(r#"
+        [package]
+        name = "hello"
+        [target.'cfg(unix)'.dependencies]
+        libc = "0.2"
+        [dependencies]
+        serde = { version = "1.0" }
+        "#
+    ).running_on_doc(|doc| {
+        doc["package"].as_table_mut().unwrap().entry("missing");
+        doc.as_table_mut().entry("lib");
+        let deps = doc["target"]["cfg(unix)"]["dependencies"].as_table_mut().unwrap();
+        deps.remove("libc");
+        let removed: Vec<_> = doc.compact().iter().map(|p| p.to_string()).collect();
+        assert_eq!(removed, ["package.missing", "lib"]);
+
+        doc["target"]["cfg(unix)"].as_table_mut().unwrap().remove("dependencies");
+        let removed: Vec<_> = doc.compact().iter().map(|p| p.to_string()).collect();
+        assert_eq!(removed, ["target.'cfg(unix)'", "target"]);
+        assert!(doc.compact().is_empty());
+    }).produces_display(r#"
+        [package]
+        name = "hello"
+        [dependencies]
+        serde = { version = "1.0" }
+        "#);
+}
+
+#[test]
+fn test_try_get_path_mut() {
+    given(r#"
+        [[bin]]
+        name = "a"
+
+        [package]
+        version = "1.0"
+        "#
+    ).running_on_doc(|doc| {
+        let error = doc.try_get_path_mut(&[&"bin", &3, &"name"]).unwrap_err();
+        assert_eq!((error.segment(), error.error()), (1, &IndexError::OutOfBounds { index: 3, len: 1 }));
+        let error = doc.try_get_path_mut(&[&"package", &"version", &"major"]).unwrap_err();
+        assert_eq!(error.error(), &IndexError::NotATable { key: "major".to_owned() });
+        let error = doc["package"].try_index_mut(0).unwrap_err();
+        assert_eq!(error, IndexError::NotAnArray { index: 0 });
+        *doc.try_get_path_mut(&[&"bin", &0, &"path"]).unwrap() = value("src/a.rs");
+    }).produces_display(r#"
+        [[bin]]
+        name = "a"
+path = "src/a.rs"
+
+        [package]
+        version = "1.0"
+        "#
+    );
+}
+
+#[test]
+fn test_nodes() {
+    given(r#"
+        [[bin]]
+        name = 'a'
+        [dependencies]
+        log.version = "0.4"
+        rand = { version = "0.8", features = ["std"] }
+        "#
+    ).running_on_doc(|doc| {
+        doc.as_table_mut().entry("missing");
+        let root = doc.as_node();
+        assert_eq!(root.len(), 2);
+        assert_eq!(root.entries().map(|(k, _)| k).collect::<Vec<_>>(), ["bin", "dependencies"]);
+        let bin = root.get("bin").unwrap();
+        assert!(bin.is_array());
+        assert!(bin.at(0).unwrap().is_table());
+        assert_eq!(bin.at(0).unwrap().get("name").unwrap().as_scalar().unwrap().as_str(), Some("a"));
+        let deps = root.get("dependencies").unwrap();
+        assert!(deps.get("log").unwrap().is_table());
+        let features = deps.get("rand").unwrap().get("features").unwrap();
+        assert_eq!(features.elements().count(), 1);
+        assert!(features.get("std").is_none());
+        assert!(root.get("missing").is_none());
+        assert_eq!(
+            deps.to_value().to_string(),
+            r#"{ log = { version = "0.4" }, rand = { version = "0.8", features = ["std"] } }"#
+        );
+        let other = r#"q = '"quoted"'
+f = [1.0, 2e3, 0.5]"#.parse::<Document>().unwrap();
+        let tree = other.to_value_tree();
+        assert_eq!(tree.to_string(), r#"{ q = "\"quoted\"", f = [1.0, 2000.0, 0.5] }"#);
+        assert_eq!(tree.as_inline_table().unwrap().get("q").unwrap().as_str(), Some("\"quoted\""));
+    }).produces_display(r#"
+        [[bin]]
+        name = 'a'
+        [dependencies]
+        log.version = "0.4"
+        rand = { version = "0.8", features = ["std"] }
+        "#
+    );
+}
+
+#[test]
+fn test_splice() {
+    given(r#"
+        a = [1, 2, 3]
+        b = [
+            "x", # first
+            "y",
+        ]
+        c = []
+
+        [[test]]
+        name = "t1"
+
+        [[test]]
+        name = "t2"
+        "#
+    ).running(|root| {
+        let a = root["a"].as_array_mut().unwrap();
+        a.splice(0..0, vec![0]).unwrap();
+        a.splice(3.., Vec::<i64>::new()).unwrap();
+        let b = root["b"].as_array_mut().unwrap();
+        let removed = b.splice(..1, vec!["v", "w"]).unwrap();
+        assert_eq!(removed[0].as_str(), Some("x"));
+        assert!(b.splice(1..1, vec![1]).is_err());
+        let c = root["c"].as_array_mut().unwrap();
+        c.splice(.., vec![1, 2]).unwrap();
+        let tests = root["test"].as_array_of_tables_mut().unwrap();
+        let mut t = Table::new();
+        t["name"] = value("t3");
+        let removed = tests.splice(..1, vec![t]);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(tests.len(), 2);
+    }).produces_display(r#"
+        a = [0, 1, 2]
+        b = [
+            "v",
+            "w", # first
+            "y",
+        ]
+        c = [1, 2]
+
+        [[test]]
+name = "t3"
+
+        [[test]]
+        name = "t2"
+        "#
+    );
+}
+
+#[test]
+fn test_bareify_keys() {
+    given(r#"
+        "package".'name' = "hello" # the name
+        "a.b" = [{ "c" = 1 }]
+        ''= 2
+
+        [["bin"]]
+        'path' = "src/main.rs"
+        "#
+    ).running_on_doc(|doc| {
+        assert_eq!(doc.bareify_keys(), 5);
+        assert_eq!(doc.bareify_keys(), 0);
+    }).produces_display(r#"
+        package.name = "hello" # the name
+        "a.b" = [{ c = 1 }]
+        ''= 2
+
+        [[bin]]
+        path = "src/main.rs"
+        "#
+    );
+}
+
+#[test]
+fn test_strip_comments() {
+    given(r#"
+        # header
+        a = [
+            1, # one
+            # between
+            2,
+            # last
+        ]
+
+        [[bin]] # first
+        name = "a"
+        # the end
+        "#
+    ).running_on_doc(|doc| {
+        assert_eq!(doc.strip_comments(CommentFilter::All), 6);
+        assert_eq!(doc.strip_comments(CommentFilter::All), 0);
+    }).produces_display(r#"
+        a = [
+            1,
+            2,
+        ]
+
+        [[bin]]
+        name = "a"
+        "#
+    );
+}
+
+#[test]
+fn test_push_line() {
+    given(r#"
+        script = """
+        echo "building" \
+          --release
+        """
+        inline = """one"""
+        name = "single"
+        "#
+    ).running(|root| {
+        let script = root["script"].as_value_mut().unwrap();
+        assert_eq!(
+            script.as_multiline_lines(),
+            Some(vec![r#"        echo "building" \"#, "          --release"])
+        );
+        assert_eq!(script.push_line("cargo test"), Ok(true));
+        let inline = root["inline"].as_value_mut().unwrap();
+        assert_eq!(inline.push_line(r#"two "quoted" \ "#), Ok(true));
+        assert_eq!(inline.push_line("tab\there"), Ok(true));
+        assert_eq!(inline.as_str(), Some("one\ntwo \"quoted\" \\ \ntab\there"));
+        assert!(inline.push_line("a\nb").is_err());
+        assert_eq!(root["name"].as_value_mut().unwrap().push_line("x"), Ok(false));
+    }).produces_display(r#"
+        script = """
+        echo "building" \
+          --release
+cargo test
+        """
+        inline = """one
+two "quoted" \\ 
+tab\there"""
+        name = "single"
+        "#
+    );
+}
+
+#[test]
+fn test_fingerprint() {
+    let parse = |s: &str| s.parse::<Document>().unwrap();
+    let doc = parse(r#"
+        # the package
+        [package]
+        name = "toml_edit"
+        version = 0x10
+        keywords = ['toml', "edit"]
+        released = 1979-05-27T07:32:00Z
+
+        [[bin]]
+        name = 'a'
+        "#);
+    let same = parse(r#"
+        bin = [{ name = "a" }]
+        package.keywords = [ "toml", 'edit' ]
+        package.released = 1979-05-27T07:32:00Z
+        package.name = 'toml_edit'
+        package.version = 16
+        "#);
+    assert_eq!(doc.fingerprint(), same.fingerprint());
+    assert_eq!(
+        parse("a = 1979-05-27T07:32:00Z").fingerprint(),
+        parse("a = 1979-05-27T00:32:00-07:00").fingerprint()
+    );
+    assert_eq!(
+        parse("a = 1979-05-28T00:00:00.5+00:30").fingerprint(),
+        parse("a = 1979-05-27T23:30:00.500Z").fingerprint()
+    );
+    // the same with or without the `datetime` feature
+    let dates = "a = 1979-05-27T07:32:00.999999-08:00\nb = 1979-05-27T07:32:00\nc = 1979-05-27\nd = 07:32:00.5\n";
+    assert_eq!(parse(dates).fingerprint(), 11406754207526880180);
+    assert_eq!(parse("a = nan\nb = -0.0").fingerprint(), parse("b = 0.0\na = -nan").fingerprint());
+    assert_eq!(Document::new().fingerprint(), parse("# empty\n").fingerprint());
+
+    let differs = [
+        "a = 1",
+        "a = '1'",
+        "a = 1.0",
+        "a = [1]",
+        "a = [[1]]",
+        "a = { b = 1 }",
+        "[[a]]\nb = 1",
+        "a = 1979-05-27",
+        "a = 1979-05-27T00:00:00",
+        "a = true",
+        "ab = ''",
+        "a = 'b'",
+    ];
+    let fingerprints: Vec<u64> = differs.iter().map(|s| parse(s).fingerprint()).collect();
+    for (i, a) in fingerprints.iter().enumerate() {
+        for (j, b) in fingerprints.iter().enumerate() {
+            assert_eq!(a == b, i == j, "{} and {}", differs[i], differs[j]);
+        }
+    }
+
+    let mut edited = doc.clone();
+    edited["package"]["keywords"] = array();
+    assert_ne!(edited.fingerprint(), doc.fingerprint());
+    edited["package"]["keywords"] = doc["package"]["keywords"].clone();
+    assert_eq!(edited.fingerprint(), doc.fingerprint());
+}
+
+#[test]
+fn test_item_conversions() {
+    given(r#"
+        [[bin]]
+        name = "a"
+        [[bin]]
+        name = "b"
+        path = { dir = "src" }
+
+        [package]
+        name = "toml_edit"
+        keywords = [{ name = "toml" }]
+        empty = []
+        version = "0.2"
+        "#
+    ).running(|root| {
+        let bin = root.remove("bin").unwrap();
+        let bin = bin.into_value().unwrap();
+        root["package"]["bin"] = value(bin);
+
+        let keywords = root["package"].as_table_mut().unwrap().remove("keywords").unwrap();
+        let keywords = keywords.into_array_of_tables().unwrap();
+        root["keywords"] = Item::ArrayOfTables(keywords);
+
+        let package = root["package"].as_table_mut().unwrap();
+        assert!(package.remove("empty").unwrap().into_array_of_tables().is_err());
+        let version = package.remove("version").unwrap();
+        let version = version.into_table().unwrap_err();
+        assert!(version.into_array_of_tables().unwrap_err().is_value());
+        assert!(Item::None.into_value().is_err());
+        assert!(Item::None.into_table().is_err());
+    }).produces_display(r#"
+        [package]
+        name = "toml_edit"
+bin = [{ name = "a" }, { name = "b", path = { dir = "src" } }]
+
+[[keywords]]
+name = "toml"
+        "#
+    );
+}
+
+#[test]
+fn test_layers() {
+    let parse = |s: &str| s.parse::<Document>().unwrap();
+    let path = |s: &str| s.parse::<KeyPath>().unwrap();
+    let mut layers = Layers::new()
+        .with_layer("defaults", parse("[server]\nhost = 'localhost'\nport = 80\n[log]\nlevel = 'info'\n"))
+        .with_layer("system", parse("server = { port = 8080 }\nlog = 'syslog'\n"));
+    layers.push("user", parse("[server]\n"));
+
+    assert_eq!(layers.len(), 3);
+    assert_eq!(layers.resolve(&path("server.port")).map(|(l, i)| (l, i.as_integer())), Some(("system", Some(8080))));
+    assert_eq!(layers.layer_of(&path("server.host")), Some("defaults"));
+    assert_eq!(layers.layer_of(&path("server")), Some("user"));
+    // `log` is not a table in the system layer
+    assert_eq!(layers.get(&path("log")).and_then(|i| i.as_str()), Some("syslog"));
+    assert!(layers.get(&path("log.level")).is_none());
+    assert!(layers.get(&path("missing")).is_none());
+    assert!(layers.get(&KeyPath::new()).is_none());
+
+    layers.layer_mut("user").unwrap()["server"]["port"] = value(443);
+    assert_eq!(layers.layer_of(&path("server.port")), Some("user"));
+    assert_eq!(layers.pop().map(|l| l.0), Some("user".to_owned()));
+    assert_eq!(layers.layer_of(&path("server.port")), Some("system"));
+    let names: Vec<&str> = layers.iter().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["defaults", "system"]);
+}
+
+#[test]
+fn test_node_metadata() {
+    let mut doc = r#"
+[package]
+name = "a"
+keywords = ["toml", "edit"]
+
+[[bin]]
+path = { dir = "src" }
+"#.parse::<Document>().unwrap();
+    let mut origins = Metadata::new();
+    let name = doc["package"]["name"].node_id().unwrap();
+    let keyword = doc["package"]["keywords"][1].as_value().unwrap().node_id();
+    let bin = doc["bin"].node_id().unwrap();
+    let dir = doc["bin"][0]["path"]["dir"].as_value().unwrap().node_id();
+    origins.insert(name, "name");
+    origins.insert(keyword, "keyword");
+    origins.insert(bin, "bin");
+    origins.insert(dir, "dir");
+    assert_eq!(origins.insert(dir, "path.dir"), Some("dir"));
+    assert_eq!(doc["package"]["name"].node_id(), Some(name));
+    assert_ne!(name, keyword);
+    assert!(Item::None.node_id().is_none());
+
+    // edits keep the ids of the nodes
+    let copy = doc.clone();
+    doc["package"]["keywords"].as_array_mut().unwrap().remove(0);
+    doc["package"].as_table_mut().unwrap().sort_values();
+    if let Some(Value::String(name)) = doc["package"]["name"].as_value_mut() {
+        name.decor_mut().set_suffix(" # the name");
+    }
+    doc["bin"].as_array_of_tables_mut().unwrap().append(Table::new());
+    assert_eq!(doc["package"]["keywords"][0].as_value().unwrap().node_id(), keyword);
+    assert_eq!(origins.get(doc["package"]["name"].node_id().unwrap()), Some(&"name"));
+    assert!(origins.get(copy["bin"].node_id().unwrap()).is_none());
+    let copy = doc.clone_keeping_ids();
+    assert_eq!(origins.get(copy["bin"].node_id().unwrap()), Some(&"bin"));
+    assert_eq!(copy["bin"][0]["path"]["dir"].as_value().unwrap().node_id(), dir);
+    let first = doc["bin"][0].as_table().unwrap().clone();
+    assert_ne!(Some(first.node_id()), doc["bin"][0].node_id());
+    doc.transaction(|tx| {
+        tx.set("package.version".parse().unwrap(), value("0.1.0"));
+    }).unwrap();
+    assert_eq!(origins.get(doc["package"]["name"].node_id().unwrap()), Some(&"name"));
+
+    // replaced nodes are new nodes
+    doc["package"]["name"] = value("b");
+    assert!(origins.get(doc["package"]["name"].node_id().unwrap()).is_none());
+    doc.as_table_mut().remove("bin");
+    origins.retain_nodes(&doc);
+    let mut left: Vec<&str> = origins.iter().map(|(_, &origin)| origin).collect();
+    left.sort();
+    assert_eq!(left, vec!["keyword"]);
+    assert_eq!(origins.remove(keyword), Some("keyword"));
+    assert!(origins.is_empty());
+}
+
+#[test]
+fn test_iter_full() {
+    let doc = r#"
+"quoted" = 1
+# comment
+'literal' = { "a b" = 2, c = 3 }
+empty = 0
+
+[[bin]]
+name = "a"
+
+[table]
+
+[[bin]]
+name = "b"
+"#.parse::<Document>().unwrap();
+    let mut root = doc.as_table().clone();
+    root.remove("empty");
+    let entries: Vec<(String, String, Option<usize>)> = root
+        .iter_full()
+        .map(|(key, _, position)| (key.raw().to_owned(), key.decor().prefix().to_owned(), position))
+        .collect();
+    assert_eq!(entries, vec![
+        (r#""quoted""#.to_owned(), "\n".to_owned(), None),
+        ("'literal'".to_owned(), "# comment\n".to_owned(), None),
+        ("bin".to_owned(), "".to_owned(), Some(1)),
+        ("table".to_owned(), "".to_owned(), Some(2)),
+    ]);
+
+    let inline = doc["literal"].as_inline_table().unwrap();
+    let keys: Vec<String> = inline.iter_full().map(|(key, _)| key.raw().to_owned()).collect();
+    assert_eq!(keys, vec![r#""a b""#, "c"]);
+    let (key, value) = inline.iter_full().last().unwrap();
+    assert_eq!((key.get(), value.as_integer()), ("c", Some(3)));
+}
+
+#[test]
+fn test_extend_from_pairs() {
+    let path = |s: &str| s.parse::<KeyPath>().unwrap();
+    given(r#"
+        [server]
+        dep = { version = "1.0" }
+        port = 80
+        "#
+    ).running_on_doc(|doc| {
+        let conflicts = doc.try_extend(vec![
+            (path("server.host"), value("localhost")),
+            (path("server.port"), value(8080)),
+            (path("server.port.number"), value(8080)),
+            (path("server.dep.features.std"), value(true)),
+            (path("server.dep.sub"), table()),
+            (path("log.'file name'"), value("app.log")),
+        ]).unwrap_err();
+        let conflicts: Vec<String> = conflicts.iter().map(|(path, _)| path.to_string()).collect();
+        assert_eq!(conflicts, vec!["server.port", "server.port.number", "server.dep.sub"]);
+        doc.extend(vec![(path("log.level"), value("info")), (path("log.level"), value("debug"))]);
+    }).produces_display(r#"
+        [server]
+        dep = { version = "1.0", features = { std = true } }
+        port = 80
+host = "localhost"
+
+[log]
+'file name' = "app.log"
+level = "info"
+        "#
+    );
+    assert!(Document::from_pairs(vec![(KeyPath::new(), value(1))]).is_err());
+}
+
+#[test]
+fn test_rename_table() {
+    let path = |s: &str| s.parse::<KeyPath>().unwrap();
+    given(r#"
+        [package] # the package
+        name = "a"
+        meta.docs = { all = true }
+
+        [package.metadata]
+        x = 1
+
+        [[bin]]
+        name = "b"
+
+        [[bin]] # second
+        name = "c"
+        "#
+    ).running_on_doc(|doc| {
+        assert!(doc.rename_table(&path("package"), "'crate'"));
+        assert!(doc.rename_table(&path("bin"), "example"));
+        assert!(doc.rename_table(&path("crate.meta"), "info"));
+        assert!(doc.rename_table(&path("crate.info.docs"), "d"));
+        assert!(!doc.rename_table(&path("crate.name"), "n"));
+        assert!(!doc.rename_table(&path("crate.info"), "metadata"));
+        assert!(!doc.rename_table(&path("missing"), "m"));
+        assert!(!doc.rename_table(&KeyPath::new(), "m"));
+    }).produces_display(r#"
+        ['crate'] # the package
+        name = "a"
+        info.d = { all = true }
+
+        ['crate'.metadata]
+        x = 1
+
+        [[example]]
+        name = "b"
+
+        [[example]] # second
+        name = "c"
+        "#
+    );
+}
+
+#[test]
+fn test_shrink_to_fit() {
+    given(r#"
+        [package] # the package
+        name = "a"
+        keywords = ["x", "y", "z"]
+        meta = { docs = true, tags = [1, 2] }
+
+        [[bin]]
+        name = "b"
+        "#
+    ).running_on_doc(|doc| {
+        let package = doc["package"].as_table_mut().unwrap();
+        let mut suffix = package.decor("name").unwrap().suffix().to_owned();
+        suffix.reserve(1000);
+        package.decor_mut("name").unwrap().set_suffix(suffix);
+        doc["package"]["keywords"].as_array_mut().unwrap().remove(2);
+        assert!(doc.shrink_to_fit() >= 1000);
+        assert_eq!(doc.shrink_to_fit(), 0);
+    }).produces_display(r#"
+        [package] # the package
+        name = "a"
+        keywords = ["x", "y"]
+        meta = { docs = true, tags = [1, 2] }
+
+        [[bin]]
+        name = "b"
+        "#
+    );
+}
+
+#[test]
+fn test_shrink_to_fit_shares_strings() {
+    let value = "x".repeat(100);
+    let input: String = (0..10)
+        .map(|i| format!("key{} = \"{}\" # {}\n", i, value, value))
+        .collect();
+    let mut doc = input.parse::<Document>().unwrap();
+    // the raw values and the comments of all but the first line are released
+    assert!(doc.shrink_to_fit() >= 9 * 2 * value.len());
+    assert_eq!(doc.shrink_to_fit(), 0);
+    assert_eq!(doc.to_string(), input);
+
+    // editing a shared string leaves the others as they were
+    doc.as_table_mut()
+        .decor_mut("key0")
+        .unwrap()
+        .set_suffix("  ");
+    doc.as_table_mut()
+        .decor_mut("key1")
+        .unwrap()
+        .set_prefix("# second\n");
+    let lines: Vec<_> = doc.to_string().lines().map(str::to_owned).collect();
+    assert_eq!(lines[0], format!("key0  = \"{}\" # {}", value, value));
+    assert_eq!(lines[1], "# second");
+    assert_eq!(lines[2], format!("key1 = \"{}\" # {}", value, value));
+    assert_eq!(lines[3], format!("key2 = \"{}\" # {}", value, value));
+}
+
+#[test]
+fn test_comment_spans() {
+    let doc = r#"# header
+[a.b] # b
+c.d = 1 # d
+e = [ # e
+    1, # one
+    # two
+    2,
+]
+f = { g = 1 } # f
+
+[[h]] # first
+[[h]]
+i = 2
+# end
+"#
+    .parse::<Document>()
+    .unwrap();
+    let s = doc.to_string();
+    let spans = doc.comment_spans();
+    let found: Vec<(String, &str)> = spans
+        .iter()
+        .map(|c| {
+            assert_eq!(&s[c.span()], c.text());
+            (c.path().to_string(), c.text())
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("a.b".to_owned(), "# header"),
+            ("a.b".to_owned(), "# b"),
+            ("a.b.c.d".to_owned(), "# d"),
+            ("a.b.e".to_owned(), "# e"),
+            ("a.b.e".to_owned(), "# one"),
+            ("a.b.e".to_owned(), "# two"),
+            ("a.b.f".to_owned(), "# f"),
+            ("h".to_owned(), "# first"),
+            ("".to_owned(), "# end"),
+        ]
+    );
+}
+
+#[test]
+fn test_insert_with_policy() {
+    given(r#"
+        name = "tool"
+
+        [[bin]]
+        name = "a"
+        "#
+    ).running(|root| {
+        let b: Value = "{ name = 'b' }".parse().unwrap();
+        let c: Value = "[{ name = 'c' }, { name = 'd' }]".parse().unwrap();
+        assert!(root.insert_with_policy("bin", value(1), ConflictPolicy::Error).is_err());
+        assert!(root.insert_with_policy("\"bin\"", value(1), ConflictPolicy::Error).is_err());
+        assert!(root.insert_with_policy("bin", value(1), ConflictPolicy::Append).is_err());
+        assert!(root.insert_with_policy("bin", Item::Value(b), ConflictPolicy::Append).unwrap().is_none());
+        assert!(root.insert_with_policy("bin", Item::Value(c), ConflictPolicy::Append).is_ok());
+        assert!(root.insert_with_policy("name", value("x"), ConflictPolicy::Error).unwrap().is_some());
+        assert!(root.insert_with_policy("lib", array(), ConflictPolicy::Replace).unwrap().is_none());
+        let old = root.insert_with_policy("lib", value(true), ConflictPolicy::Replace).unwrap();
+        assert!(old.unwrap().is_array_of_tables());
+    }).produces_display(r#"
+        name = "x"
+lib = true
+
+        [[bin]]
+        name = "a"
+
+[[bin]]
+name = 'b'
+
+[[bin]]
+name = 'c'
+
+[[bin]]
+name = 'd'
+        "#
+    );
+}
+
+#[test]
+fn test_trailing_comments() {
+    given(r#"
+        [a]
+        b = 1
+
+        [c]
+        d = 2
+
+        # vim: set ft=toml:
+        "#
+    ).running_on_doc(|doc| {
+        assert_eq!(doc.trailing(), "\n        # vim: set ft=toml:\n        ");
+        doc.as_table_mut().remove("c");
+        doc.append_table("e", Table::new(), TablePlacement::BeforeTrailing);
+        doc["e"]["f"] = value(3);
+    }).produces_display(r#"
+        [a]
+        b = 1
+
+[e]
+f = 3
+
+        # vim: set ft=toml:
+        "#
+    );
+
+    given(r#"
+        [a]
+        b = 1
+        # the end
+        "#
+    ).running_on_doc(|doc| {
+        doc.as_table_mut().remove("a");
+        doc.append_table("c", Table::new(), TablePlacement::AfterTrailing);
+        doc["c"]["d"] = value(2);
+        doc.set_trailing("# last\n");
+    }).produces_display(r#"        # the end
+        
+[c]
+d = 2
+# last
+"#
+    );
+}
+
+#[test]
+fn test_transaction() {
+    let path = |s: &str| s.parse::<KeyPath>().unwrap();
+    given(r#"
+        [package]
+        name = "hello"
+        keywords = ["a"] # keywords
+        "#
+    ).running_on_doc(|doc| {
+        let errors = doc.transaction(|tx| {
+            tx.append(path("package.keywords"), "b")
+                .append(path("package.keywords"), 1)
+                .append(path("package.name"), "x")
+                .set(KeyPath::new(), value(1))
+                .remove(path("package.name"))
+                .remove(path("package.name"));
+            assert_eq!(tx.len(), 6);
+        }).unwrap_err();
+        let errors: Vec<_> = errors.iter().map(|(i, e)| (*i, e.to_string())).collect();
+        assert_eq!(errors, vec![
+            (1, "`package.keywords` contains values of another type".to_owned()),
+            (2, "`package.name` is not an array".to_owned()),
+            (3, "the path is empty".to_owned()),
+            (5, "there is no item at `package.name`".to_owned()),
+        ]);
+        let _ = &mut doc["package"]["authors"];
+        doc.transaction(|tx| {
+            tx.append(path("package.keywords"), "b")
+                .append(path("package.authors"), "me")
+                .append(path("package.categories"), "tools")
+                .set(path("dependencies.serde.version"), value("1.0"))
+                .remove(path("package.name"));
+        }).unwrap();
+        assert!(doc.transaction(|_| {}).is_ok());
+    }).produces_display(r#"
+        [package]
+        keywords = ["a", "b"] # keywords
+authors = ["me"]
+categories = ["tools"]
+
+[dependencies.serde]
+version = "1.0"
+        "#
+    );
+}
+
+#[test]
+fn test_history() {
+    let mut doc = "a = 1\n".parse::<Document>().unwrap();
+    doc.checkpoint();
+    assert!(!doc.has_history());
+    assert!(!doc.undo());
+
+    doc.enable_history(Some(2));
+    for i in 2..5 {
+        doc.checkpoint();
+        doc["a"] = value(i);
+    }
+    assert!(doc.undo());
+    assert!(doc.undo());
+    assert!(!doc.can_undo());
+    assert_eq!(doc["a"].as_integer(), Some(2));
+
+    assert!(doc.redo());
+    doc.checkpoint();
+    doc["b"] = value(true);
+    assert!(!doc.can_redo());
+    assert!(doc.undo());
+    assert_eq!(doc.to_string(), "a = 3\n");
+
+    doc.disable_history();
+    assert!(!doc.can_undo());
+    assert!(!doc.redo());
+}
+
+#[test]
+fn test_history_keeps_node_ids() {
+    let mut doc = "[t]\nb = 2\n".parse::<Document>().unwrap();
+    doc.enable_history(None);
+    let (t, b) = (doc["t"].node_id(), doc["t"]["b"].node_id());
+    doc.checkpoint();
+    doc["t"]["b"] = value(3);
+    let c = doc["t"]["b"].node_id();
+    doc.checkpoint();
+    doc["t"] = toml_edit::table();
+    assert!(doc.undo());
+    assert!(doc.undo());
+    assert_eq!((doc["t"].node_id(), doc["t"]["b"].node_id()), (t, b));
+    assert!(doc.redo());
+    assert_eq!(doc["t"]["b"].node_id(), c);
+}
+
+#[test]
+fn test_history_matches_snapshots() {
+    // the history behaves like stacks of full snapshots
+    let mut doc = "a = 1\n[t]\nb = 2\n[[bin]]\nname = 'x'\n".parse::<Document>().unwrap();
+    doc.enable_history(Some(6));
+    let (mut undo, mut redo) = (Vec::<String>::new(), Vec::<String>::new());
+    let mut seed = 7u64;
+    for i in 0..400 {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        match (seed >> 33) % 9 {
+            0 | 1 => {
+                doc.checkpoint();
+                undo.push(doc.to_string());
+                redo.clear();
+                if undo.len() > 6 {
+                    undo.remove(0);
+                }
+            }
+            2 => {
+                let current = doc.to_string();
+                assert_eq!(doc.undo(), !undo.is_empty());
+                if let Some(previous) = undo.pop() {
+                    redo.push(current);
+                    assert_eq!(doc.to_string(), previous);
+                }
+            }
+            3 => {
+                let current = doc.to_string();
+                assert_eq!(doc.redo(), !redo.is_empty());
+                if let Some(next) = redo.pop() {
+                    undo.push(current);
+                    assert_eq!(doc.to_string(), next);
+                }
+            }
+            4 => doc["t"]["b"] = value(i),
+            5 => doc["t"]["c"] = value(i),
+            6 => {
+                doc["t"].as_table_mut().unwrap().remove("c");
+            }
+            7 => {
+                let mut t = Table::new();
+                t["name"] = value(i);
+                doc["bin"].as_array_of_tables_mut().unwrap().append(t);
+            }
+            _ => doc["bin"][0]["name"] = value(i),
+        }
+        assert_eq!(doc.can_undo(), !undo.is_empty());
+        assert_eq!(doc.can_redo(), !redo.is_empty());
+    }
+}
+
+#[test]
+fn test_clone_stripped() {
+    given(r#"
+        # template
+        [server]   # original entry
+        host = 'localhost'   # the host
+          ports = [ 80,
+            443, # tls
+          ]
+        limits = {  rate = 10 ,burst=20   }"#
+    ).running(|root| {
+        let stripped = root["server"].clone_stripped();
+        root.insert("backup", stripped);
+        let port = root["server"]["ports"].clone_stripped();
+        assert_eq!(port.as_value().unwrap().to_string(), " [80, 443]");
+    }).produces_display(r#"
+        # template
+        [server]   # original entry
+        host = 'localhost'   # the host
+          ports = [ 80,
+            443, # tls
+          ]
+        limits = {  rate = 10 ,burst=20   }
+
+[backup]
+host = 'localhost'
+ports = [80, 443]
+limits = { rate = 10, burst = 20 }
+"#);
+}
+
+#[test]
+fn test_array_iter_mut() {
+    given(r#"
+        features = [
+          "serde",  # serialization
+          "std",
+        ]
+        weights = [1.5, 2.5]"#
+    ).running(|root| {
+        let features = root["features"].as_array_mut().unwrap();
+        for (i, feature) in features.iter_mut().enumerate() {
+            let renamed = format!("{}-{}", feature.as_str().unwrap(), i);
+            let decor = feature.decor().clone();
+            *feature = decorated(renamed.into(), decor.prefix(), decor.suffix());
+        }
+        let weights = root["weights"].as_array_mut().unwrap();
+        assert!(weights.get_mut(2).is_none());
+        let weight = weights.get_mut(1).unwrap();
+        *weight = decorated(Value::from(weight.as_float().unwrap() * 3.0), " ", "");
+    }).produces_display(r#"
+        features = [
+          "serde-0",  # serialization
+          "std-1",
+        ]
+        weights = [1.5, 7.5]
+"#);
+}
+
+#[test]
+fn test_preview() {
+    let doc = r#"
+title = """
+multi
+line"""
+ports = [8080, 8081, 8082]
+
+[[servers]]
+name = "alpha"   # the first
+[[servers]]
+name = "beta"
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"] }
+"#
+    .parse::<Document>()
+    .unwrap();
+    assert_eq!(doc["title"].preview(20), r#""multi\nline""#);
+    assert_eq!(doc["title"].preview(8), r#""multi\…"#);
+    assert_eq!(doc["ports"].preview(100), "[8080, 8081, 8082]");
+    assert_eq!(doc["ports"].preview(12), "[8080, 8081, … 1 more]");
+    assert_eq!(doc["ports"].preview(3), "[…, … 2 more]");
+    assert_eq!(
+        doc["servers"].preview(100),
+        r#"[{name = "alpha"}, {name = "beta"}]"#
+    );
+    assert_eq!(
+        doc["dependencies"].preview(40),
+        r#"{serde = {version = "1.0", … 1 more}}"#
+    );
+    let root = Item::Table(doc.as_table().clone());
+    assert_eq!(root.preview(16), r#"{title = "mult…, … 3 more}"#);
+    assert_eq!(Item::None.preview(10), "");
+}
+
+#[test]
+fn test_append_with_comment() {
+    given(r#"
+        [[bin]]
+        name = "a"
+        [dependencies]
+        serde = "1.0""#
+    ).running(|root| {
+        let bins = root["bin"].as_array_of_tables_mut().unwrap();
+        let mut bin = Table::new();
+        bin["name"] = value("b");
+        bins.append_with_comment(bin.clone(), "added by cargo-release\n\n  #  keep\n");
+        bin["name"] = value("c");
+        bins.append_with_comment(bin, "");
+    }).produces_in_original_order(r#"
+        [[bin]]
+        name = "a"
+
+# added by cargo-release
+#
+#  keep
+[[bin]]
+name = "b"
+
+[[bin]]
+name = "c"
+        [dependencies]
+        serde = "1.0"
+"#);
+}
+
+#[test]
+fn test_find_duplicate_keys_case_variant() {
+    let doc = r#"Name = "a"
+name.first = "b"
+'NAME' = "c"
+bin = 1
+
+[[BIN]]
+x = 1
+
+[Bin.sub]
+"#
+    .parse::<Document>()
+    .unwrap();
+    let text = doc.to_string();
+    let groups: Vec<Vec<_>> = doc
+        .as_table()
+        .find_duplicate_keys_case_variant()
+        .iter()
+        .map(|keys| keys.iter().map(|k| &text[k.span()]).collect())
+        .collect();
+    assert_eq!(
+        groups,
+        vec![vec!["Name", "name", "'NAME'"], vec!["bin", "BIN", "Bin"]]
+    );
+    let dotted = doc["name"].as_table().unwrap();
+    assert!(dotted.find_duplicate_keys_case_variant().is_empty());
+}
+
+#[test]
+fn test_array_set_operations() {
+    given(
+        r#"
+        features = [
+            "std",
+            # proc macros
+            "derive",
+            "alloc",
+            'std',
+        ]
+        numbers = [ 1, 2, 3 ]
+        empty = []
+        "#,
+    )
+    .running(|root| {
+        let other = |s: &str| s.parse::<Value>().unwrap();
+        let features = root["features"].as_array_mut().unwrap();
+        assert!(features.contains(&Value::from("std")));
+        assert!(!features.contains(&Value::from("serde")));
+        features.dedup();
+        features.sort();
+
+        let numbers = root["numbers"].as_array_mut().unwrap();
+        assert!(numbers.union(other(r#"["a"]"#).as_array().unwrap()).is_err());
+        numbers.difference(other("[1, 3]").as_array().unwrap());
+
+        let empty = root["empty"].as_array_mut().unwrap();
+        empty
+            .union(other(r#"["a", "b", "a"]"#).as_array().unwrap())
+            .unwrap();
+    })
+    .produces_display(
+        r#"
+        features = [
+            "alloc",
+            # proc macros
+            "derive",
+            "std",
+        ]
+        numbers = [ 2 ]
+        empty = ["a", "b"]
+        "#,
+    );
+}
+
+#[test]
+fn test_extend_with_capacity() {
+    given(
+        r#"
+        [package]
+        name = "a"
+        "#,
+    )
+    .running(|root| {
+        let package = root["package"].as_table_mut().unwrap();
+        package.reserve(2);
+        package.extend(vec![("name", value("b")), ("version", value("0.1.0"))]);
+
+        let mut keywords = toml_edit::Array::with_capacity(2);
+        keywords.extend(vec!["toml", "edit"]);
+        let rejected = keywords.try_extend(vec![Value::from(1), Value::from("cli")]);
+        assert_eq!(rejected.unwrap_err()[0].as_integer(), Some(1));
+        package["keywords"] = value(keywords);
+    })
+    .produces_display(
+        r#"
+        [package]
+        name = "b"
+version = "0.1.0"
+keywords = ["toml", "edit", "cli"]
+        "#,
+    );
+}
+
+#[test]
+#[should_panic(expected = "can't extend an array of String with Integer")]
+fn test_extend_with_another_type() {
+    let mut array = toml_edit::Array::default();
+    array.extend(vec![Value::from("a"), Value::from(1)]);
+}
+
+#[test]
+fn test_default_style() {
+    let path = |s: &str| s.parse::<toml_edit::KeyPath>().unwrap();
+    let mut doc = "[a]\r\n\tx=1\r\n\tys=[\r\n\t\t1,\r\n\t\t2\r\n\t]\r\n"
+        .parse::<Document>()
+        .unwrap();
+    let style = doc.detect_style();
+    assert_eq!(style.indent(), "\t");
+    assert_eq!(style.newline(), toml_edit::Newline::CrLf);
+    assert_eq!(style.eq_spacing(), ("", ""));
+    assert!(!style.array_trailing_comma());
+    assert_eq!(toml_edit::StyleReport::new().eq_spacing(), (" ", " "));
+
+    doc.set_default_style(style);
+    doc.transaction(|tx| {
+        tx.set(path("a.z"), value(2));
+    })
+    .unwrap();
+    assert_eq!(
+        doc.to_string(),
+        "[a]\r\n\tx=1\r\n\tys=[\r\n\t\t1,\r\n\t\t2\r\n\t]\r\n\tz=2\r\n"
+    );
+}
+
+#[test]
+fn test_transaction_restyles_only_created_items() {
+    let path = |s: &str| s.parse::<toml_edit::KeyPath>().unwrap();
+    let mut doc = "a=1\nl=[\n  1,\n  2,\n]\n[p]\nc = 3\nm = [\n  1,\n  2\n]\n"
+        .parse::<Document>()
+        .unwrap();
+    doc.set_default_style(doc.detect_style());
+    doc.transaction(|tx| {
+        tx.set(path("p.d"), value(4));
+        tx.set(path("d"), value(4));
+        tx.set(path("p.c"), value(5));
+    })
+    .unwrap();
+    assert_eq!(
+        doc.to_string(),
+        "a=1\nl=[\n  1,\n  2,\n]\nd=4\n[p]\nc = 5\nm = [\n  1,\n  2\n]\nd=4\n"
+    );
+
+    let mut doc = "a=1\nb=2\nc = 3\n".parse::<Document>().unwrap();
+    doc.set_default_style(doc.detect_style());
+    doc["d"] = value(4);
+    doc.apply_default_style(&path("d"));
+    assert_eq!(doc.to_string(), "a=1\nb=2\nc = 3\nd=4\n");
+}
+
+#[test]
+fn test_checked_unsigned_integers() {
+    use std::convert::TryFrom;
+    use toml_edit::Value;
+
+    let mut doc = Document::new();
+    doc["requests"] = value(Value::try_from(i64::MAX as u64).unwrap());
+    doc["bytes"] = value(Value::try_from(7usize).unwrap());
+    assert_eq!(doc["requests"].as_u64(), Some(i64::MAX as u64));
+    assert_eq!(doc["bytes"].as_usize(), Some(7));
+
+    let err = Value::try_from(u128::MAX).unwrap_err();
+    assert_eq!(err.value(), u128::MAX);
+    assert_eq!(err.source_type(), "u128");
+    assert!(Value::try_from(i64::MAX as u64 + 1).is_err());
+
+    let negative = Value::from(-1);
+    assert_eq!(negative.as_u64(), None);
+    assert_eq!(negative.as_usize(), None);
+}
+
+#[test]
+fn test_contains_path() {
+    let path = |s: &str| s.parse::<toml_edit::KeyPath>().unwrap();
+    let doc = "a.b = 1\n[c]\nd = { e = [1] }\n"
+        .parse::<Document>()
+        .unwrap();
+    assert!(doc.contains_value(&path("a.b")));
+    assert!(doc.contains_table(&path("a")));
+    assert!(doc.contains_value(&path("c.d.e")));
+    assert!(!doc.contains_path(&path("c.d.e.f")));
+    assert!(!doc.contains_array_of_tables(&path("c")));
+    assert!(doc["c"].as_table().unwrap().contains_path(&path("d.e")));
+
+    let (key, item) = doc.as_table().get_key_value("c").unwrap();
+    assert_eq!(key.get(), "c");
+    assert!(item.is_table());
+}
+
+#[test]
+fn test_find_text() {
+    let doc = "a.b = \"x\\u0078\"\n[t]\nc = { d = 'x' }\ne = ['x', \"\\tx\"]\n# x\n"
+        .parse::<Document>()
+        .unwrap();
+    let rendered = doc.to_string();
+    let found: Vec<_> = doc
+        .find_text("x")
+        .into_iter()
+        .map(|(path, span)| (path.to_string(), &rendered[span]))
+        .collect();
+    assert_eq!(found.len(), 5);
+    assert_eq!(found[0], ("a.b".to_owned(), "x"));
+    assert_eq!(found[1].0, "t.c.d");
+    assert_eq!(found[2].0, "t.e");
+    assert_eq!(found[3].0, "t.e");
+    assert_eq!(found[4].0, "");
+
+    let found: Vec<_> = doc
+        .find_text_outside_escapes("0")
+        .into_iter()
+        .map(|(path, _)| path.to_string())
+        .collect();
+    assert!(found.is_empty());
+    assert_eq!(doc.find_text_outside_escapes("\\t").len(), 1);
+    assert!(doc.find_text("").is_empty());
+}
+
+#[test]
+fn test_comment_out() {
+    let path = |s: &str| s.parse::<toml_edit::KeyPath>().unwrap();
+    let s = "a = 1\n[t]\nx = 1 # one\n# the list\nys = [\n    1,\n]\n\n[[bin]]\nname = 'a'\n\n[[bin]]\nname = 'b'\n# end\n";
+    let mut doc = s.parse::<Document>().unwrap();
+
+    // the last pair of a table is kept in the previous line
+    let t = doc["t"].as_table_mut().unwrap();
+    assert!(t.comment_out("ys"));
+    assert_eq!(
+        t.to_string(),
+        "x = 1 # one\n# the list\n# ys = [\n#     1,\n# ]\n"
+    );
+    assert!(t.uncomment("ys"));
+    assert!(!t.comment_out("missing"));
+    assert_eq!(doc.to_string(), s);
+
+    // the next line is the header of a subtable
+    assert!(doc.as_table_mut().comment_out("a"));
+    assert!(doc.to_string().starts_with("# a = 1\n[t]\nx = 1 # one\n"));
+    assert!(doc.uncomment(&path("a")));
+    assert_eq!(doc.to_string(), s);
+
+    // the only pair of the root table is kept at the end of the document
+    let mut lone = "a = 1\n# end\n".parse::<Document>().unwrap();
+    assert!(!lone.as_table_mut().comment_out("a"));
+    assert!(lone.comment_out(&path("a")));
+    assert_eq!(lone.to_string(), "# a = 1\n# end\n");
+    assert!(lone.uncomment(&path("a")));
+    assert_eq!(lone.to_string(), "a = 1\n# end\n");
+
+    // arrays of tables at the end of the document
+    assert!(doc.comment_out(&path("bin")));
+    assert!(doc.to_string().ends_with(
+        "    1,\n]\n\n# [[bin]]\n# name = 'a'\n#\n# [[bin]]\n# name = 'b'\n# end\n"
+    ));
+    assert!(!doc.contains_path(&path("bin")));
+    assert!(doc.uncomment(&path("bin")));
+    assert_eq!(doc.to_string(), s);
+
+    // edited comments which are not valid TOML anymore are kept
+    assert!(doc.comment_out(&path("t.x")));
+    let edited = doc.to_string().replace("# x = 1", "# x = ");
+    let mut doc = edited.parse::<Document>().unwrap();
+    assert!(!doc.uncomment(&path("t.x")));
+    assert_eq!(doc.to_string(), edited);
+    assert!(!doc.uncomment(&path("missing.x")));
+
+    // a subtable is restored before its sibling
+    let s = "[a]\n[a.b]\nx = 1\n[a.c]\ny = 2\n";
+    let mut doc = s.parse::<Document>().unwrap();
+    assert!(doc.comment_out(&path("a.b")));
+    assert_eq!(doc.to_string(), "[a]\n# [a.b]\n# x = 1\n[a.c]\ny = 2\n");
+    assert!(doc.uncomment(&path("a.b")));
+    assert_eq!(doc.to_string(), s);
+
+    // the lines follow the original order of the tables
+    let s = "[a]\nx = 1\n[b]\ny = 2\n[a.c]\nz = 3\n[d]\nw = 4\n";
+    let mut doc = s.parse::<Document>().unwrap();
+    assert!(doc.comment_out(&path("a.c")));
+    assert_eq!(
+        doc.to_string_in_original_order(),
+        "[a]\nx = 1\n[b]\ny = 2\n# [a.c]\n# z = 3\n[d]\nw = 4\n"
+    );
+    assert!(doc.uncomment(&path("a.c")));
+    assert_eq!(doc.to_string_in_original_order(), s);
+}
+
+#[test]
+fn test_format_matching() {
+    use toml_edit::FmtOptions;
+
+    let mut doc = r#"[target.'cfg(unix)'.dependencies]
+b="1"
+a={version="1",features=["x"]}
+[target."cfg(windows)".dependencies]
+d="1"
+c="1"
+[[bin]]
+z="1"
+y="1"
+"#
+    .parse::<Document>()
+    .unwrap();
+    let options = FmtOptions::new().sort(true);
+    assert_eq!(
+        doc.format_matching(&["target.*.dependencies", "bin"], &options)
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        doc.to_string(),
+        r#"[target.'cfg(unix)'.dependencies]
+a = { features = ["x"], version = "1" }
+b = "1"
+[target."cfg(windows)".dependencies]
+c = "1"
+d = "1"
+[[bin]]
+y = "1"
+z = "1"
+"#
+    );
+    assert_eq!(doc.format_matching(&["missing.*"], &options).unwrap(), 0);
+    assert!(doc.format_matching(&["target..a"], &options).is_err());
+}
+
+#[test]
+fn test_stable_ids_sidecar() {
+    let mut doc = r#"
+[[bin]]
+name = "a"
+[[bin]]
+name = "b"
+deps = { x = [1, 2] }
+"#
+    .parse::<Document>()
+    .unwrap();
+    let mut ids = toml_edit::StableIds::new();
+    ids.assign_all(&doc);
+    assert_eq!(ids.len(), 10);
+    let id = ids
+        .get(doc["bin"][1]["deps"]["x"][1].node_id().unwrap())
+        .unwrap()
+        .to_owned();
+
+    let saved = doc.to_string();
+    let sidecar = ids.to_sidecar(&doc);
+    assert!(sidecar.contains(&format!("{}\tbin[1].deps.x[1]\n", id)));
+    let reparsed = saved.parse::<Document>().unwrap();
+    let mut ids = toml_edit::StableIds::from_sidecar(&sidecar, &reparsed).unwrap();
+    assert_eq!(ids.len(), 10);
+    assert_eq!(ids.node(&id), reparsed["bin"][1]["deps"]["x"][1].node_id());
+    let new = ids.assign(reparsed["bin"][0].node_id().unwrap()).to_owned();
+    assert!(new != id);
+
+    doc["bin"][0]["name"] = value("c");
+    assert_eq!(
+        toml_edit::StableIds::from_sidecar(&sidecar, &doc).unwrap_err(),
+        toml_edit::SidecarError::Modified
+    );
+    let broken = format!("{}n1\tbin[7]\n", sidecar);
+    assert_eq!(
+        toml_edit::StableIds::from_sidecar(&broken, &reparsed).unwrap_err(),
+        toml_edit::SidecarError::Malformed(12)
+    );
+
+    // duplicated entries, with new ids or sharing the ids of the original
+    let mut doc = "[[bin]]\nname = 'a'\n".parse::<Document>().unwrap();
+    let mut ids = toml_edit::StableIds::new();
+    ids.assign_all(&doc);
+    let clone = doc["bin"][0].clone();
+    let copy = doc["bin"][0].clone_keeping_ids();
+    let bins = doc["bin"].as_array_of_tables_mut().unwrap();
+    bins.append(clone.into_table().unwrap());
+    bins.append(copy.into_table().unwrap());
+    ids.assign_all(&doc);
+    let sidecar = ids.to_sidecar(&doc);
+    let reparsed = doc.to_string().parse::<Document>().unwrap();
+    let ids = toml_edit::StableIds::from_sidecar(&sidecar, &reparsed).unwrap();
+    assert_eq!(ids.len(), 6);
+}
+
+#[test]
+fn test_matches_shape() {
+    use toml_edit::{MismatchKind, Shape, TableShape};
+
+    let doc = r#"
+[package]
+name = "a"
+edition = 2018
+
+[[bin]]
+name = "a"
+[[bin]]
+name = 1
+path = ["src/b.rs"]
+"#
+    .parse::<Document>()
+    .unwrap();
+    let version = Shape::one_of(vec![Shape::String, Shape::Integer]);
+    let package = TableShape::new()
+        .required("name", Shape::String)
+        .optional("edition", version.clone());
+    assert_eq!(doc["package"].matches_shape(&package.into()), Ok(()));
+
+    let bin = TableShape::new()
+        .required("name", Shape::String)
+        .optional("path", Shape::String)
+        .deny_unknown_keys(true);
+    let mismatches = doc["bin"]
+        .matches_shape(&Shape::array(bin.clone().into()))
+        .unwrap_err();
+    let found: Vec<_> = mismatches
+        .iter()
+        .map(|m| (m.path(), m.kind(), m.found()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("[1].name", MismatchKind::Type, "an integer"),
+            ("[1].path", MismatchKind::Type, "an array"),
+        ]
+    );
+
+    let manifest = TableShape::new()
+        .required("package", TableShape::new().deny_unknown_keys(true))
+        .required("lib", TableShape::new());
+    let mismatches = doc.root.matches_shape(&manifest.into()).unwrap_err();
+    let messages: Vec<_> = mismatches.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "unknown key at `package.name`",
+            "unknown key at `package.edition`",
+            "missing a table at `lib`",
+        ]
+    );
+
+    let err = doc["package"]["edition"]
+        .matches_shape(&Shape::Float)
+        .unwrap_err();
+    assert_eq!(err[0].to_string(), "expected a float, found an integer");
+    assert_eq!(Item::None.matches_shape(&Shape::Any), Ok(()));
+    assert_eq!(
+        Item::None.matches_shape(&version).unwrap_err()[0].expected(),
+        "a string or an integer"
+    );
+}
+
+#[test]
+fn test_reconcile() {
+    let mut source = r#"
+[dependencies]
+# serialization
+serde = { version = "1.0", features = ["derive"] } # pinned
+log = "0.4"
+
+# the binaries
+[[bin]]
+name = "a" # first
+[[bin]]
+name = "b"
+[[bin]]
+name = "c"
+"#
+    .parse::<Document>()
+    .unwrap();
+    let target = r#"
+[dependencies]
+serde = { version = "1.0.100", features = ["derive"] }
+log = { version = "0.4" }
+rand = "0.8"
+
+[[bin]]
+name = "a"
+path = "src/a.rs"
+[[bin]]
+name = "d"
+"#
+    .parse::<Document>()
+    .unwrap();
+
+    let changes = toml_edit::reconcile(&source, &target).unwrap();
+    let paths: Vec<_> = changes
+        .iter()
+        .map(|c| match *c {
+            toml_edit::Change::Insert(ref p, _) => format!("+{}", p),
+            toml_edit::Change::Remove(ref p) => format!("-{}", p),
+            toml_edit::Change::Update(ref p, _) => format!("~{}", p),
+        })
+        .collect();
+    assert_eq!(paths, vec![
+        "~dependencies.serde.version",
+        "~dependencies.log",
+        "+dependencies.rand",
+        "~bin",
+    ]);
+    source.apply_changes(&changes).unwrap();
+    assert_eq!(source.to_string(), r#"
+[dependencies]
+# serialization
+serde = { version = "1.0.100", features = ["derive"] } # pinned
+log = { version = "0.4" }
+rand = "0.8"
+
+# the binaries
+[[bin]]
+name = "a" # first
+path = "src/a.rs"
+[[bin]]
+name = "d"
+"#);
+    assert!(toml_edit::reconcile(&source, &target).unwrap().is_empty());
+
+    // the changes are applied all at once or not at all
+    let mut other = "dependencies = 1\n".parse::<Document>().unwrap();
+    let errors = other.apply_changes(&changes).unwrap_err();
+    assert_eq!(errors[0].0, 0);
+    assert_eq!(other.to_string(), "dependencies = 1\n");
+}
+
+#[test]
+fn test_insert_formatted() {
+    given(r#"
+        [a]
+        # move this
+        "b" = 1 # please
+        [c]
+        d = 2"#
+    ).running(|root| {
+        let (key, item) = {
+            let a = root.entry("a");
+            let a = as_table!(a);
+            a.remove_entry("b").unwrap()
+        };
+        let c = root.entry("c");
+        let c = as_table!(c);
+        assert!(c.insert_formatted(&key, item).is_none());
+        let mut key = parse_key!("'d'").clone();
+        key.decor_mut().set_prefix("        # replaced\n        ");
+        assert_eq!(c.insert_formatted(&key, value(3)).and_then(|i| i.as_integer()), Some(2));
+    }).produces(r#"
+        [a]
+        [c]
+        # replaced
+        'd' = 3
+        # move this
+        "b" = 1 # please
+"#
+    );
+}
+
+#[test]
+fn test_remove_last_value_from_implicit() {
+    given(r#"
+        [a]
+        b = 1"#
+    ).running(|root| {
+        let a = root.entry("a");
+        assert!(a.is_table());
+        let a = as_table!(a);
+        a.set_implicit(true);
+        let value = a.remove("b");
+        assert!(value.is_some());
+        let value = value.unwrap();
+        assert!(value.is_value());
+        let value = value.as_value().unwrap();
+        assert_eq!(value.as_integer(), Some(1));
+    }).produces(r#""#);
+}
+
+#[test]
+fn test_cursor_mut() {
+    given(r#"
+        [dependencies]
+        serde = "1.0"
+        # replace this
+        chrono = "0.4" # please
+        # delete this
+        local = { path = "../local" }
+        rand = { path = "../rand" }"#
+    ).running(|root| {
+        let deps = root.entry("dependencies");
+        let deps = as_table!(deps);
+        for mut entry in deps.cursor_mut() {
+            if entry.key() == "chrono" {
+                assert_eq!(entry.replace(value("0.5")).as_str(), Some("0.4"));
+            } else if entry.key() == "local" {
+                assert!(entry.remove().is_inline_table());
+            }
+        }
+        deps.retain(|key, item| key != "rand" && !item.is_inline_table());
+    }).produces(r#"
+        [dependencies]
+        serde = "1.0"
+        # replace this
+        chrono = "0.5"
+"#
+    );
+}
+
+// values
+
+#[test]
+fn test_sort_values_by_group() {
+    given(r#"
+        [dependencies]
+        toml = { git = "https://github.com/alexcrichton/toml-rs" }
+        serde = "1.0"
+        # shared
+        log = { workspace = true } # by all crates
+        anyhow = { git = "https://github.com/dtolnay/anyhow" }
+        chrono = "0.4"
+
+        [dependencies.rand]
+        version = "0.8""#
+    ).running(|root| {
+        let deps = root.entry("dependencies");
+        let deps = as_table!(deps);
+        deps.sort_values_by_group(|key, v| {
+            let git = v.as_inline_table().is_some_and(|t| t.contains_key("git"));
+            (git, key == "serde")
+        });
+    }).produces_display(r#"
+        [dependencies]
+        chrono = "0.4"
+        # shared
+        log = { workspace = true } # by all crates
+        serde = "1.0"
+        anyhow = { git = "https://github.com/dtolnay/anyhow" }
+        toml = { git = "https://github.com/alexcrichton/toml-rs" }
+
+        [dependencies.rand]
+        version = "0.8"
+"#
+    );
+}
+
+#[test]
+fn test_sort_values() {
+    given(r#"
+        [a.z]
+
+        [a]
+        # this comment is attached to b
+        b = 2 # as well as this
+        a = 1
+        c = 3
+
+        [a.y]"#
+    ).running(|root| {
+        let a = root.entry("a");
+        let a = as_table!(a);
+        a.sort_values();
+    }).produces_display(r#"
+        [a]
+        a = 1
+        # this comment is attached to b
+        b = 2 # as well as this
+        c = 3
+
+        [a.z]
+
+        [a.y]
+"#
+    ).produces_in_original_order(r#"
+        [a.z]
+
+        [a]
+        a = 1
+        # this comment is attached to b
+        b = 2 # as well as this
+        c = 3
+
+        [a.y]
+"#);
+}
+
+#[test]
+fn test_set_position() {
+    given(r#"
+        [package]
+        [dependencies]
+        [dependencies.opencl]
+        [dev-dependencies]"#
+    ).running(|root| {
+        for (header, table) in root.iter_mut() {
+            if header == "dependencies" {
+                let tab = as_table!(table);
+                tab.set_position(0);
+                let (_, segmented) = tab.iter_mut().next().unwrap();
+                as_table!(segmented).set_position(5)
+            }
+        }
+    }).produces_in_original_order(r#"        [dependencies]
+
+        [package]
+        [dev-dependencies]
+        [dependencies.opencl]
+"#);
+}
+
+#[test]
+fn test_multiple_zero_positions() {
+    given(r#"
+        [package]
+        [dependencies]
+        [dependencies.opencl]
+        a=""
+        [dev-dependencies]"#
+    ).running(|root| {
+        for (_, table) in root.iter_mut() {
+            as_table!(table).set_position(0)
+        }
+    }).produces_in_original_order(r#"
+        [package]
+        [dependencies]
+        [dev-dependencies]
+        [dependencies.opencl]
+        a=""
+"#);
+}
+
+#[test]
+fn test_multiple_max_usize_positions() {
+    given(r#"
+        [package]
+        [dependencies]
+        [dependencies.opencl]
+        a=""
+        [dev-dependencies]"#
+    ).running(|root| {
+        for (_, table) in root.iter_mut() {
+            as_table!(table).set_position(usize::MAX)
+        }
+    }).produces_in_original_order(r#"        [dependencies.opencl]
+        a=""
+
+        [package]
+        [dependencies]
+        [dev-dependencies]
+"#);
+}
+
+macro_rules! as_array {
+    ($entry:ident) => (
+        {
+            assert!($entry.is_value());
+            let a = $entry.as_value_mut().unwrap();
+            assert!(a.is_array());
+            a.as_array_mut().unwrap()
+        }
+    );
+}
+
+#[test]
+fn test_insert_replace_into_array() {
+    given(r#"
+        a = [1,2,3]
+        b = []"#
+    ).running(|root| {
+        {
+            let a = root.entry("a");
+            let a = as_array!(a);
+            assert_eq!(a.len(), 3);
+            assert!(a.get(2).is_some());
+            assert!(a.push(4).is_ok());
+            assert_eq!(a.len(), 4);
+            a.fmt();
+        }
+        let b = root.entry("b");
+        let b = as_array!(b);
+        assert!(b.is_empty());
+        assert!(b.push("hello").is_ok());
+        assert_eq!(b.len(), 1);
+
+        assert!(b.push_formatted(decorated("world".into(), "\n", "\n")).is_ok());
+        assert!(b.push_formatted(decorated("test".into(), "", "")).is_ok());
+
+        assert!(b.insert(1, "beep").is_ok());
+        assert!(b.insert_formatted(2, decorated("boop".into(), "   ", "   ")).is_ok());
+
+        // This should preserve formatting.
+        assert_eq!(b.replace(2, "zoink").unwrap().as_str(), Some("boop"));
+        // This should replace formatting.
+        assert_eq!(b.replace_formatted(4, decorated("yikes".into(), "  ", "")).unwrap().as_str(), Some("test"));
+
+        // Check that pushing a different type into an array fails.
+        assert!(b.push(42).is_err());
+
+    }).produces(r#"
+        a = [1, 2, 3, 4]
+        b = ["hello", "beep",   "zoink"   ,
+"world"
+,  "yikes"]
+"#
+    );
+}
+
+#[test]
+fn test_remove_from_array() {
+    given(r#"
+        a = [1, 2, 3, 4]
+        b = ["hello"]"#
+    ).running(|root| {
+        {
+            let a = root.entry("a");
+            let a = as_array!(a);
+            assert_eq!(a.len(), 4);
+            assert!(a.remove(3).is_integer());
+            assert_eq!(a.len(), 3);
+        }
+        let b = root.entry("b");
+        let b = as_array!(b);
+        assert_eq!(b.len(), 1);
+        assert!(b.remove(0).is_str());
+        assert!(b.is_empty());
+    }).produces(r#"
+        a = [1, 2, 3]
+        b = []
+"#
+    );
+}
+
+#[test]
+fn test_array_trailing_comma() {
+    given(r#"
+        a = [
+          1,
+          2,
+        ]
+        b = [1, 2]
+        c = []"#
+    ).running(|root| {
+        {
+            let a = root.entry("a");
+            let a = as_array!(a);
+            assert!(a.trailing_comma());
+            assert_eq!(a.trailing(), "\n        ");
+            assert!(a.push_formatted(decorated(3.into(), "\n          ", "")).is_ok());
+        }
+        {
+            let b = root.entry("b");
+            let b = as_array!(b);
+            assert!(!b.trailing_comma());
+            b.set_trailing_comma(true);
+            b.set_trailing(" ");
+        }
+        let c = root.entry("c");
+        let c = as_array!(c);
+        c.set_trailing_comma(true);
+    }).produces(r#"
+        a = [
+          1,
+          2,
+          3,
+        ]
+        b = [1, 2, ]
+        c = []
+"#
+    );
+}
+
+#[test]
+fn test_array_typed_views() {
+    given(r#"
+        a = ["derive", "std"]
+        b = [1, 2]"#
+    ).running(|root| {
+        {
+            let a = root.entry("a");
+            let a = as_array!(a);
+            assert_eq!(a.as_slice_of_str(), Some(vec!["derive", "std"]));
+            assert_eq!(a.iter_str().collect::<Vec<_>>(), vec!["derive", "std"]);
+            assert_eq!(a.iter_int().count(), 0);
+        }
+        let b = root.entry("b");
+        let b = as_array!(b);
+        assert_eq!(b.as_slice_of_str(), None);
+        assert_eq!(b.iter_int().collect::<Vec<_>>(), vec![1, 2]);
+        assert!(b.push("three").is_err());
+        assert!(b.push_any("three", TomlVersion::V0_5).is_err());
+        b.push_any("three", TomlVersion::V1_0).unwrap();
+    }).produces(r#"
+        a = ["derive", "std"]
+        b = [1, 2, "three"]
+"#
+    );
+}
+
+macro_rules! as_inline_table {
+    ($entry:ident) => (
+        {
+            assert!($entry.is_value());
+            let a = $entry.as_value_mut().unwrap();
+            assert!(a.is_inline_table());
+            a.as_inline_table_mut().unwrap()
+        }
+    );
+}
+
+#[test]
+fn test_insert_into_inline_table() {
+    given(r#"
+        a = {a=2,  c = 3}
+        b = {}"#
+    ).running(|root| {
+        {
+            let a = root.entry("a");
+            let a = as_inline_table!(a);
+            assert_eq!(a.len(), 2);
+            assert!(a.contains_key("a") && a.get("c").is_some() && a.get_mut("c").is_some());
+            a.get_or_insert("b", 42);
             assert_eq!(a.len(), 3);
             a.fmt();
         }
@@ -691,6 +2971,343 @@ fn test_as_table_like() {
     });
 }
 
+#[test]
+fn test_as_table_like_mut() {
+    given(r#"
+        [dependencies]
+        a = { version = "0.1", features = ["x"] }
+        [dependencies.b]
+        version = "0.2"
+        features = ["y"] # b features"#
+    ).running(|root| {
+        for (_, dep) in root["dependencies"].as_table_like_mut().unwrap().iter_mut() {
+            let dep = dep.as_table_like_mut().unwrap();
+            assert!(dep.contains_key("features"));
+            dep.remove("features");
+            dep.insert("version", value("1.0"));
+            dep.insert("optional", value(true));
+            dep.decor_mut("version").unwrap().set_prefix("\n");
+        }
+        let deps = root["dependencies"].as_table_like_mut().unwrap();
+        let mut c = Table::new();
+        c["version"] = value("0.3");
+        assert!(deps.insert("c", Item::Table(c)).is_none());
+        let a = deps.get_mut("a").unwrap().as_table_like_mut().unwrap();
+        let mut path = Table::new();
+        path["path"] = value("a");
+        a.insert("source", Item::Table(path));
+    }).produces(r#"
+        [dependencies]
+        a = {
+version = "1.0",optional = true,source = { path = "a" }}
+        [dependencies.b]
+
+version = "1.0"
+optional = true
+
+[dependencies.c]
+version = "0.3"
+"#
+    );
+}
+
+#[test]
+fn test_auto_inline() {
+    given(r#"
+        [dependencies]
+        a = { version = "0.1", path = "../a", features = ["x"] }
+
+        # b is special
+        [dependencies.b]
+        version = "0.2"
+
+        [dependencies.c.target]
+        version = "0.3"
+
+        [dependencies.d] # pinned
+        version = "0.4"
+
+        [dependencies.e]
+        # pinned
+        version = "0.5"
+
+        [dependencies.f]
+        version = "0.6" # pinned
+
+        [dependencies.g]
+        features = ["x", # pinned
+        ]"#
+    ).running(|root| {
+        let deps = root["dependencies"].as_table_mut().unwrap();
+        AutoInline::new().max_keys(2).apply(deps);
+    }).produces_display(r#"
+        [dependencies]
+        # b is special
+        b = { version = "0.2" }
+
+[dependencies.a]
+version = "0.1"
+path = "../a"
+features = ["x"]
+
+        [dependencies.c.target]
+        version = "0.3"
+
+        [dependencies.d] # pinned
+        version = "0.4"
+
+        [dependencies.e]
+        # pinned
+        version = "0.5"
+
+        [dependencies.f]
+        version = "0.6" # pinned
+
+        [dependencies.g]
+        features = ["x", # pinned
+        ]
+"#
+    );
+}
+
+#[test]
+fn test_interpolate() {
+    given(r#"
+        [server]
+        url = "http://${HOST}:${PORT}/" # where
+        mirrors = ["${HOST}", 'escaped $${HOST}', "$HOST ${}"]
+        env = { "${HOST}" = "${MISSING}", dir = '${HOME}/${HOST}' }
+        [[targets]]
+        name = "${HOST"
+        port = 8080"#
+    ).running_on_doc(|doc| {
+        doc.interpolate(|name| match name {
+            "HOST" => Some("example.com".to_owned()),
+            "PORT" => Some("80".to_owned()),
+            "HOME" => Some("/home/${HOST}".to_owned()),
+            _ => None,
+        });
+    }).produces(r#"
+        [server]
+        url = "http://example.com:80/" # where
+        mirrors = ["example.com", "escaped ${HOST}", "$HOST ${}"]
+        env = { "${HOST}" = "${MISSING}", dir = "/home/${HOST}/example.com" }
+        [[targets]]
+        name = "${HOST"
+        port = 8080
+"#
+    );
+}
+
+#[test]
+fn test_expand_dotted_keys() {
+    given(r#"
+        name = "toml_edit"
+        # the edition
+        package.edition = "2018"
+        package.metadata.docs = true
+        [dependencies]
+        serde.version = "1.0""#
+    ).running_on_doc(|doc| {
+        doc.expand_dotted_keys();
+    }).produces_display(r#"
+        name = "toml_edit"
+
+[package]
+        # the edition
+        edition = "2018"
+
+[package.metadata]
+        docs = true
+        [dependencies]
+
+[dependencies.serde]
+        version = "1.0"
+"#
+    );
+}
+
+#[test]
+fn test_collapse_to_dotted() {
+    given(r#"
+        name = "toml_edit"
+
+        # the package
+        [package]
+        edition = "2018"
+        [package.metadata.docs]
+        all-features = true
+        [[bin]]
+        name = "main"
+        [bin.target]
+        os = "linux""#
+    ).running_on_doc(|doc| {
+        doc.collapse_to_dotted(3);
+    }).produces_display(r#"
+        name = "toml_edit"
+
+        # the package
+        [package]
+        edition = "2018"
+        metadata.docs.all-features = true
+        [[bin]]
+        name = "main"
+        target.os = "linux"
+"#
+    );
+}
+
+#[test]
+fn test_try_to_string() {
+    fn nested(depth: usize) -> Document {
+        let mut v = Value::from(1);
+        for _ in 0..depth {
+            v = Value::from(vec![v]);
+        }
+        let mut doc = Document::new();
+        doc["a"] = value(v);
+        doc
+    }
+
+    let doc = nested(100);
+    assert_eq!(doc.try_to_string().unwrap(), doc.to_string());
+    assert!(nested(1000).try_to_string().is_err());
+
+    let mut doc = Document::new();
+    let mut t = doc.as_table_mut();
+    for _ in 0..1000 {
+        t = t.entry("a").or_insert(table()).as_table_mut().unwrap();
+    }
+    assert!(doc.try_to_string().is_err());
+}
+
+#[test]
+fn test_write_to() {
+    let mut doc = r#"
+[a.b]
+dotted.key = 1979-05-27T07:32:00Z # comment
+"quoted key" = [ 1.5, 2e3 ]
+
+[[c]]
+x = { y = 'z' }
+"#
+    .parse::<Document>()
+    .unwrap();
+    for (i, n) in [0, -7, 42, i64::MIN, i64::MAX].iter().enumerate() {
+        doc["n"][i.to_string().as_str()] = value(*n);
+    }
+    assert_eq!(doc["n"]["3"].as_value().unwrap().to_string(), " -9223372036854775808");
+
+    let mut out = Vec::new();
+    doc.write_to(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), doc.to_string());
+
+    let mut full = [0u8; 8];
+    let err = doc.write_to(&mut &mut full[..]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+
+    let mut deep = Document::new();
+    let mut v = Value::from(1);
+    for _ in 0..1000 {
+        v = Value::from(vec![v]);
+    }
+    deep["a"] = value(v);
+    let err = deep.write_to(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_lazy_document() {
+    let input = r#"title = "lazy"
+
+[package]
+description = """
+[not.a.header]
+"""
+keywords = [
+  ["nested"], # [comment]
+  ['a]b'],
+]
+
+# the dependencies
+[dependencies.serde]
+version = "1.0"
+
+[[bin]]
+name = "main"
+
+['quoted . key']
+a = 1
+"#;
+    let mut doc = LazyDocument::parse(input).unwrap();
+    assert_eq!(doc.to_string(), input);
+    assert!(doc.table_mut(&["not", "a", "header"]).unwrap().is_none());
+    assert!(doc.table_mut(&["bin"]).unwrap().is_none());
+
+    let package = doc.table_mut(&["package"]).unwrap().unwrap();
+    assert_eq!(package["keywords"].as_array().map(|a| a.len()), Some(2));
+    package["version"] = value("0.1.0");
+    let serde = doc.table_mut(&["dependencies", "serde"]).unwrap().unwrap();
+    serde["features"] = value(Value::from(vec!["derive"]));
+    assert!(doc.table_mut(&["quoted . key"]).unwrap().is_some());
+    assert!(doc.is_parsed(&["package"]));
+    assert!(!doc.is_parsed(&[]));
+
+    let expected = input
+        .replace("]\n\n# the", "]\nversion = \"0.1.0\"\n\n# the")
+        .replace("\"1.0\"\n", "\"1.0\"\nfeatures = [\"derive\"]\n");
+    assert_eq!(doc.to_string(), expected);
+    assert_eq!(doc.into_document().unwrap().to_string(), expected);
+}
+
+fn load(path: &str) -> std::io::Result<String> {
+    match path {
+        "base.toml" => Ok("include = 'common.toml'\n[server]\nport = 80\nhost = 'base'\n".to_owned()),
+        "common.toml" => Ok("# common\nname = 'common'\n[server]\nhost = 'common'\ntimeout = 30\n".to_owned()),
+        "db.toml" => Ok("url = 'postgres://'\n".to_owned()),
+        "cycle.toml" => Ok("include = ['db.toml', 'cycle.toml']\n".to_owned()),
+        _ => Err(std::io::ErrorKind::NotFound.into()),
+    }
+}
+
+#[test]
+fn test_include() {
+    given(r#"
+        include = ["common.toml", "base.toml"]
+        name = "app"
+
+        [server]
+        port = 8080
+
+        [database]
+        include = "db.toml""#
+    ).running_on_doc(|doc| {
+        IncludeResolver::new(load).resolve(doc).unwrap();
+    }).produces_display(r#"        name = "app"
+
+        [server]
+        port = 8080
+host = 'base'
+timeout = 30
+
+        [database]
+url = 'postgres://'
+"#
+    );
+
+    let mut doc = "include = 'cycle.toml'".parse::<Document>().unwrap();
+    let err = IncludeResolver::new(load).resolve(&mut doc).unwrap_err();
+    assert_eq!(err.to_string(), "Include cycle: cycle.toml -> cycle.toml");
+
+    let mut doc = "[a]\nuse = ['missing.toml']".parse::<Document>().unwrap();
+    let err = IncludeResolver::new(load).key("use").resolve(&mut doc).unwrap_err();
+    assert!(matches!(err, IncludeError::Load { ref path, .. } if path == "missing.toml"));
+
+    let mut doc = "include = 1".parse::<Document>().unwrap();
+    let err = IncludeResolver::new(load).resolve(&mut doc).unwrap_err();
+    assert!(matches!(err, IncludeError::InvalidDirective { .. }));
+}
+
 #[test]
 fn test_inline_table_append() {
     let mut a = Value::from_iter(vec![
@@ -713,4 +3330,80 @@ fn test_inline_table_append() {
     assert!(b.is_empty());
 }
 
+#[test]
+fn test_value_from_collections() {
+    use std::collections::{BTreeMap, HashMap};
+
+    let nested = Value::from(vec![vec!["a", "b"], vec![], vec!["c"]]);
+    assert_eq!(nested.to_string(), r#"[["a", "b"], [], ["c"]]"#);
+
+    let optional = Value::from(vec![Some(1), None, Some(3)]);
+    assert_eq!(optional.to_string(), "[1, 3]");
+
+    let mut features = HashMap::new();
+    features.insert("std", vec!["alloc"]);
+    features.insert("default", vec!["std"]);
+    let mut package = BTreeMap::new();
+    package.insert("name".to_owned(), Some(Value::from("toml_edit")));
+    package.insert("edition".to_owned(), None);
+    package.insert("features".to_owned(), Some(Value::from(features)));
+    assert_eq!(
+        Value::from(package).to_string(),
+        r#"{ features = { default = ["std"], std = ["alloc"] }, name = "toml_edit" }"#
+    );
+
+    let keys = BTreeMap::from([("a\nb", 1), ("'\"", 2), ("", 3), ("a.b", 4)]);
+    let table = Value::from(keys);
+    assert_eq!(
+        table.to_string(),
+        r#"{ "" = 3, "'\"" = 2, "a\nb" = 1, "a.b" = 4 }"#
+    );
+    let table = table.as_inline_table().unwrap();
+    assert_eq!(table.get("a\nb").and_then(Value::as_integer), Some(1));
+    assert_eq!(table.get("'\"").and_then(Value::as_integer), Some(2));
+}
+
+#[test]
+fn test_reflow() {
+    given(r#"
+        [package]
+        keywords = ["toml", "edit", "format", "preserving"]
+        authors = [
+            "Andronik",
+        ]
+        commented = [
+            "a", # first
+        ]
+        metadata.docs = { all-features = true, targets = ["x86_64-unknown-linux-gnu"] }
+        # the serde dependency
+        serde = { version = "1.0", features = ["derive", "std", "alloc", "unstable"] }
+        small = {version="1.0"}"#
+    ).running(|root| {
+        Reflow::new().max_width(50).apply(root);
+    }).produces_display(r#"
+        [package]
+        keywords = [
+            "toml",
+            "edit",
+            "format",
+            "preserving",
+        ]
+        authors = ["Andronik"]
+        commented = [
+            "a", # first
+        ]
+        small = { version = "1.0" }
+
+[package.metadata.docs]
+all-features = true
+targets = ["x86_64-unknown-linux-gnu"]
+
+        # the serde dependency
+        [package.serde]
+version = "1.0"
+features = ["derive", "std", "alloc", "unstable"]
+"#
+    );
+}
+
 } // mod tests
@@ -0,0 +1,89 @@
+#![cfg(feature = "test-util")]
+
+use pretty_assertions::assert_eq;
+use toml_edit::test_util::{Generator, Mutation};
+use toml_edit::{Document, KeyPath};
+
+// bytes of a linear congruential generator
+fn bytes(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 56) as u8
+        })
+        .collect()
+}
+
+fn assert_roundtrip(doc: &Document) {
+    assert_eq!(doc.assert_valid(), Ok(()));
+    let s = doc.to_string();
+    let parsed = s
+        .parse::<Document>()
+        .unwrap_or_else(|e| panic!("{}\n{}", e, s));
+    assert_eq!(parsed.to_string(), s);
+}
+
+#[test]
+fn generated_documents_are_valid() {
+    for seed in 0..500 {
+        let data = bytes(seed, 300);
+        let mut gen = Generator::new(&data);
+        let mut doc = gen.document();
+        assert_roundtrip(&doc);
+        for mutation in gen.mutations(&doc, 4) {
+            mutation.apply(&mut doc);
+        }
+        assert_roundtrip(&doc);
+    }
+}
+
+#[test]
+fn generation_is_deterministic() {
+    let data = bytes(42, 100);
+    let a = Generator::new(&data).document().to_string();
+    let b = Generator::new(&data).document().to_string();
+    assert_eq!(a, b);
+
+    let mut gen = Generator::new(&[]);
+    assert!(gen.is_exhausted());
+    assert!(gen.document().as_table().is_empty());
+}
+
+#[test]
+fn arbitrary_documents() {
+    use arbitrary::{Arbitrary, Unstructured};
+    use toml_edit::{Table, Value};
+
+    for seed in 0..100 {
+        let data = bytes(seed, 300);
+        let doc = Document::arbitrary(&mut Unstructured::new(&data)).unwrap();
+        assert_eq!(
+            doc.to_string(),
+            Generator::new(&data).document().to_string()
+        );
+        assert_roundtrip(&doc);
+    }
+
+    // only the bytes used by the generator are consumed
+    let data = bytes(7, 20);
+    let mut u = Unstructured::new(&data);
+    let value = Value::arbitrary(&mut u).unwrap();
+    let mut gen = Generator::new(&data);
+    assert_eq!(value.to_string(), gen.value().to_string());
+    let table = Table::arbitrary(&mut u).unwrap();
+    assert_eq!(table.to_string(), gen.table().to_string());
+}
+
+#[test]
+fn mutations() {
+    let mut doc = "a = 1 # one\n[b]\nc = 'd'\n".parse::<Document>().unwrap();
+    let path = |s: &str| s.parse::<KeyPath>().unwrap();
+    assert!(Mutation::Set(path("a"), 2.into()).apply(&mut doc));
+    assert!(Mutation::Set(path("b.e"), "f".into()).apply(&mut doc));
+    assert!(Mutation::Remove(path("b.c")).apply(&mut doc));
+    assert!(!Mutation::Remove(path("x.y")).apply(&mut doc));
+    assert_eq!(doc.to_string(), "a = 2 # one\n[b]\ne = \"f\"\n");
+}
@@ -0,0 +1,118 @@
+#![cfg(feature = "ini")]
+
+use pretty_assertions::assert_eq;
+use toml_edit::ini::{from_ini, to_ini, Inference, IniError};
+use toml_edit::{value, Document};
+
+const INI: &str = r#"; global settings
+name = my app
+debug: true
+
+[server]
+; where to listen
+host = 0.0.0.0
+port = 8080
+timeout = 1.5
+greeting = "hello, world"
+ratio = 1/2
+list = [1, 2]
+
+[paths.data]
+dir = C:\data
+"#;
+
+#[test]
+fn import_strings() {
+    let doc = from_ini(INI, Inference::Strings).unwrap();
+    assert_eq!(doc["server"]["port"].as_str(), Some("8080"));
+    assert_eq!(doc["server"]["greeting"].as_str(), Some("\"hello, world\""));
+    assert_eq!(doc["paths.data"]["dir"].as_str(), Some("C:\\data"));
+    assert!(doc.assert_valid().is_ok());
+}
+
+#[test]
+fn import_values() {
+    let doc = from_ini(INI, Inference::Values).unwrap();
+    assert_eq!(
+        doc.to_string(),
+        r#"# global settings
+name = "my app"
+debug = true
+
+[server]
+# where to listen
+host = "0.0.0.0"
+port = 8080
+timeout = 1.5
+greeting = "hello, world"
+ratio = "1/2"
+list = "[1, 2]"
+
+["paths.data"]
+dir = "C:\\data"
+"#
+    );
+    let reparsed = doc.to_string().parse::<Document>().unwrap();
+    assert_eq!(reparsed.to_string(), doc.to_string());
+}
+
+#[test]
+fn import_errors() {
+    let err = |s| from_ini(s, Inference::Strings).unwrap_err();
+    assert_eq!(err("a = 1\nb"), IniError::Syntax { line: 2 });
+    assert_eq!(err("[]"), IniError::Syntax { line: 1 });
+    assert_eq!(err("[a"), IniError::Syntax { line: 1 });
+    assert_eq!(err(" = 1"), IniError::Syntax { line: 1 });
+    assert_eq!(err("[a]\nb = 1\n\nb = 2"), IniError::Duplicate { line: 4 });
+    assert_eq!(err("[a]\n[a]"), IniError::Duplicate { line: 2 });
+    assert_eq!(err("[a]\n[a]").to_string(), "duplicate INI key at line 2");
+}
+
+#[test]
+fn export() {
+    let doc = from_ini(INI, Inference::Values).unwrap();
+    let ini = to_ini(&doc).unwrap();
+    assert_eq!(
+        ini,
+        r#"name = my app
+debug = true
+
+[server]
+host = 0.0.0.0
+port = 8080
+timeout = 1.5
+greeting = hello, world
+ratio = 1/2
+list = [1, 2]
+
+[paths.data]
+dir = C:\data
+"#
+    );
+    let again = from_ini(&ini, Inference::Values).unwrap();
+    assert_eq!(to_ini(&again).unwrap(), ini);
+}
+
+#[test]
+fn export_errors() {
+    let err = |s: &str| {
+        to_ini(&s.parse::<Document>().unwrap())
+            .unwrap_err()
+            .to_string()
+    };
+    assert_eq!(err("a = [1]"), "`a` can't be written to an INI file");
+    assert_eq!(err("[a.b]\nc = 1"), "`a.b` can't be written to an INI file");
+    assert_eq!(err("[[a]]"), "`a` can't be written to an INI file");
+    assert_eq!(
+        err("a = '''\nb\nc'''"),
+        "`a` can't be written to an INI file"
+    );
+    assert_eq!(
+        err("[a]\n'b=c' = 1"),
+        r#"`a."b=c"` can't be written to an INI file"#
+    );
+
+    let mut doc = Document::new();
+    doc["a"] = value("b");
+    assert_eq!(to_ini(&doc).unwrap(), "a = b\n");
+}
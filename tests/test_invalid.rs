@@ -1,3 +1,5 @@
+#![cfg(feature = "parse")]
+
 use toml_edit::Document;
 
 fn run(toml: &str, msg: &str) {
@@ -0,0 +1,36 @@
+#![cfg(all(feature = "parse", not(feature = "datetime")))]
+
+use toml_edit::{DatetimeKind, Document, Value};
+
+#[test]
+fn test_opaque_datetime_round_trip() {
+    let toml = r#"odt = 1979-05-27T07:32:00.999999-07:00 # offset
+ldt = 1979-05-27T07:32:00
+ld = 1979-05-27
+lt = 00:32:00.999999
+array = [1979-05-27, 1980-01-01]
+"#;
+    let doc = toml.parse::<Document>().unwrap();
+    assert_eq!(doc.to_string(), toml);
+
+    let kinds: Vec<_> = ["odt", "ldt", "ld", "lt"]
+        .iter()
+        .map(|key| doc[key].as_date_time().unwrap().kind())
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            DatetimeKind::OffsetDateTime,
+            DatetimeKind::LocalDateTime,
+            DatetimeKind::LocalDate,
+            DatetimeKind::LocalTime,
+        ]
+    );
+    assert_eq!(
+        doc["odt"].as_date_time().unwrap().to_string(),
+        "1979-05-27T07:32:00.999999-07:00"
+    );
+
+    let v = Value::from(doc["ld"].as_date_time().unwrap().clone());
+    assert_eq!(v.to_string(), "1979-05-27");
+}
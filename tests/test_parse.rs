@@ -1,4 +1,6 @@
-use toml_edit::{Key, Value};
+#![cfg(feature = "parse")]
+
+use toml_edit::{Document, Item, Key, StringStyle, Value};
 
 macro_rules! parse {
     ($s:expr, $ty:ty) => {{
@@ -51,6 +53,15 @@ fn test_key_from_str() {
     test_key!("'hello key'bla", "'hello key'bla");
     let wp = "C:\\Users\\appveyor\\AppData\\Local\\Temp\\1\\cargo-edit-test.YizxPxxElXn9";
     test_key!(wp, wp);
+
+    // keys are equal regardless of their representation and decor
+    let quoted = "\"a\"".parse::<Key>().unwrap();
+    let mut bare = "a".parse::<Key>().unwrap();
+    bare.decor_mut().set_prefix("# the key\n");
+    assert_eq!(quoted, bare);
+    assert_eq!(quoted.cmp(&bare), std::cmp::Ordering::Equal);
+    let keys: std::collections::HashSet<_> = vec![quoted, bare].into_iter().collect();
+    assert_eq!(keys.len(), 1);
 }
 
 #[test]
@@ -88,3 +99,574 @@ string'''"#
     let basic = r#""\\\"\b\/\f\n\r\t\u00E9\U000A0000""#;
     assert_eq!(Value::from(basic).as_str(), parse_value!(basic).as_str());
 }
+
+#[test]
+fn test_number_policy() {
+    use toml_edit::{Document, NumberPolicy, ParseOptions};
+
+    assert!("a = 18446744073709551616".parse::<Document>().is_err());
+    assert!("c = 1e400".parse::<Document>().is_err());
+
+    let saturate = ParseOptions::new().number_policy(NumberPolicy::Saturate);
+    // the policy applies to out of range literals, not to invalid ones
+    assert!(Document::parse_with("b = -0x_ff", &saturate).is_err());
+    let doc = Document::parse_with("a = 18446744073709551616\nc = -1e400\n", &saturate).unwrap();
+    assert_eq!(doc["a"].as_integer(), Some(i64::MAX));
+    assert_eq!(doc["c"].as_float(), Some(f64::MIN));
+    assert_eq!(
+        doc.to_string(),
+        "a = 9223372036854775807\nc = -1.7976931348623157e308\n"
+    );
+
+    let preserve = ParseOptions::new().number_policy(NumberPolicy::Preserve);
+    let doc = Document::parse_with("a = 18446744073709551616\nc = 1e400\n", &preserve).unwrap();
+    assert_eq!(doc["a"].as_integer(), Some(i64::MAX));
+    assert_eq!(doc["c"].as_float(), Some(f64::INFINITY));
+    assert_eq!(
+        doc["a"].as_value().and_then(Value::as_raw),
+        Some("18446744073709551616")
+    );
+    assert_eq!(doc.to_string(), "a = 18446744073709551616\nc = 1e400\n");
+}
+
+#[test]
+fn test_toml_version() {
+    use toml_edit::{Document, ParseOptions, TomlVersion};
+
+    let input = "a = [1, 'two', { three = 3 }]\n";
+    assert!(input.parse::<Document>().is_err());
+
+    let v1 = ParseOptions::new().version(TomlVersion::V1_0);
+    let doc = Document::parse_with(input, &v1).unwrap();
+    assert_eq!(doc["a"].as_array().map(|a| a.len()), Some(3));
+    assert_eq!(doc.to_string(), input);
+}
+
+#[test]
+fn test_extension_literals() {
+    use toml_edit::{Document, ExtensionLiteral, InvalidKind, ParseOptions};
+
+    const EXTENSIONS: &[ExtensionLiteral] = &[
+        ExtensionLiteral::new("env", "${", "}"),
+        ExtensionLiteral::new("jinja", "{{", "}}"),
+    ];
+    let options = ParseOptions::new().extension_literals(EXTENSIONS);
+
+    let input = r#"home = ${HOME}
+ports = [ {{ port }}, {{ port + 1 }} ]
+server = { host = {{ host | default("localhost") }}, user = "root" }
+literal = '${HOME}'
+"#;
+    assert!(input.parse::<Document>().is_err());
+    let doc = Document::parse_with(input, &options).unwrap();
+    assert_eq!(doc.to_string(), input);
+    assert_eq!(doc["home"].as_str(), Some("${HOME}"));
+    assert_eq!(doc["home"].as_value().unwrap().extension_tag(), Some("env"));
+    assert_eq!(doc["ports"][1].as_str(), Some("{{ port + 1 }}"));
+    assert_eq!(
+        doc["server"]["host"].as_value().unwrap().extension_tag(),
+        Some("jinja")
+    );
+    assert_eq!(
+        doc["server"]["user"].as_value().unwrap().extension_tag(),
+        None
+    );
+    assert_eq!(doc["literal"].as_value().unwrap().extension_tag(), None);
+
+    let errors = doc.assert_valid().unwrap_err();
+    assert_eq!(errors.len(), 4);
+    assert!(errors.iter().all(|e| e.kind() == InvalidKind::Value));
+
+    // extension literals are closed on the same line
+    assert!(Document::parse_with("a = {{ x\n}}\n", &options).is_err());
+    assert!(Document::parse_with("a = ${HOME\n", &options).is_err());
+    // values may still start like an extension literal
+    let doc = Document::parse_with("a = { b = 1 }\n", &options).unwrap();
+    assert_eq!(doc["a"]["b"].as_integer(), Some(1));
+}
+
+#[test]
+fn test_table_conflicts() {
+    use toml_edit::{Document, ParseOptions, TableConflicts};
+
+    let input = r#"# generated
+name = "app"
+# the server
+server = { host = "localhost" } # defaults
+bins = [{ name = "a" }]
+
+[server]
+port = 8080
+
+[server.tls]  # added later
+cert = "cert.pem"
+
+[[deps]]
+dev = { x = 1 }
+[deps.dev.y]
+"#;
+    let err = input.parse::<Document>().unwrap_err();
+    assert_eq!(err.line_col(), (7, 1));
+    assert!(Document::parse_with_conflicts(input, &ParseOptions::new()).is_err());
+
+    let merge = ParseOptions::new().table_conflicts(TableConflicts::Merge);
+    let (doc, conflicts) = Document::parse_with_conflicts(input, &merge).unwrap();
+    assert_eq!(doc["server"]["host"].as_str(), Some("localhost"));
+    assert_eq!(doc["server"]["tls"]["cert"].as_str(), Some("cert.pem"));
+    assert!(doc["deps"][0]["dev"]["y"].is_table());
+    assert_eq!(
+        doc.to_string(),
+        r#"# generated
+name = "app"
+bins = [{ name = "a" }]
+# the server
+# defaults
+
+[server]
+host = "localhost"
+port = 8080
+
+[server.tls]  # added later
+cert = "cert.pem"
+
+[[deps]]
+[deps.dev]
+x = 1
+[deps.dev.y]
+"#
+    );
+    let conflicts: Vec<_> = conflicts
+        .iter()
+        .map(|c| (c.path().to_string(), &input[c.span()], c.line_col()))
+        .collect();
+    assert_eq!(
+        conflicts,
+        vec![
+            ("server".to_owned(), "[server]", (7, 1)),
+            ("deps.dev".to_owned(), "[deps.dev.y]", (15, 1)),
+        ]
+    );
+
+    let (doc, _) =
+        Document::parse_with_conflicts("a = { x = 1 } # inline\n[a] # hdr\ny = 2\n", &merge)
+            .unwrap();
+    assert_eq!(doc.to_string(), "# inline\n[a] # hdr\nx = 1\ny = 2\n");
+
+    // arrays and explicit tables are not merged
+    assert!(Document::parse_with(
+        "bins = [{ name = 'a' }]
+[[bins]]
+",
+        &merge
+    )
+    .is_err());
+    assert!(Document::parse_with(
+        "a = 1
+[a.b]
+",
+        &merge
+    )
+    .is_err());
+    assert!(Document::parse_with(
+        "[a]
+[b]
+[a]
+",
+        &merge
+    )
+    .is_err());
+}
+
+#[test]
+fn test_parse_number_lenient() {
+    let lenient = |s: &str| Value::parse_number_lenient(s).map(|v| v.to_string());
+    assert_eq!(lenient("1,000").as_deref(), Some("1000"));
+    assert_eq!(lenient("+12,345,678").as_deref(), Some("12345678"));
+    assert_eq!(lenient("-999").as_deref(), Some("-999"));
+    assert_eq!(lenient("1,234.50").as_deref(), Some("1234.5"));
+    assert_eq!(lenient("2,000.").as_deref(), None);
+    assert_eq!(lenient("1,000e3").as_deref(), Some("1000000.0"));
+    assert_eq!(lenient("0.1").as_deref(), Some("0.1"));
+    assert_eq!(lenient("-inf").as_deref(), Some("-inf"));
+    for invalid in &[
+        "",
+        ",100",
+        "1,00",
+        "1,0000",
+        "12345,678",
+        "1,000,00",
+        "1.000,5",
+        "1_000",
+        "0,100",
+        "--1",
+        "007",
+        "1,000 apples",
+        "0x1,000",
+        "0o7,777",
+        "0b1,010",
+        "true",
+        "'1'",
+    ] {
+        assert_eq!(lenient(invalid), None, "{}", invalid);
+    }
+    assert!("a = 1,000".parse::<Document>().is_err());
+}
+
+#[test]
+fn test_into_flattened_iter() {
+    use toml_edit::Document;
+
+    let doc = r#"
+title = "example"
+[owner]
+name = { first = "Tom" }
+[empty]
+[servers.alpha]
+ip = "10.0.0.1"
+[[products]]
+name = "Hammer"
+"#
+    .parse::<Document>()
+    .unwrap();
+    let flattened: Vec<_> = doc
+        .into_iter()
+        .map(|(path, item)| {
+            let path: Vec<_> = path.iter().map(Key::get).collect();
+            (path.join("."), item.is_value())
+        })
+        .collect();
+    assert_eq!(
+        flattened,
+        [
+            ("title".to_owned(), true),
+            ("owner.name".to_owned(), true),
+            ("empty".to_owned(), false),
+            ("servers.alpha.ip".to_owned(), true),
+            ("products".to_owned(), false),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_multi() {
+    use toml_edit::Document;
+
+    let input = "a = 1\r\n---  \r\nb = 2\n---\nc = \n---";
+    let docs: Vec<_> = Document::parse_multi(input, "---").collect();
+    assert_eq!(docs.len(), 4);
+    let (ref first, ref range) = *docs[0].as_ref().unwrap();
+    assert_eq!(first["a"].as_integer(), Some(1));
+    assert_eq!(&input[range.clone()], "a = 1\r\n");
+    let (ref second, ref range) = *docs[1].as_ref().unwrap();
+    assert_eq!(second.to_string(), "b = 2\n");
+    assert_eq!(range.clone(), 14..20);
+    assert!(docs[2].is_err());
+    let (ref last, ref range) = *docs[3].as_ref().unwrap();
+    assert!(last.as_table().is_empty());
+    assert_eq!(range.clone(), input.len()..input.len());
+}
+
+#[test]
+fn test_dotted_keys() {
+    use toml_edit::Document;
+
+    let input = r#"name = "toml_edit"
+package . edition = '2018'
+# comment
+package."authors" = []
+[dependencies]
+serde.version = "1.0"
+serde.features = ["derive"]
+[package.metadata]
+docs = true
+"#;
+    let doc = input.parse::<Document>().unwrap();
+    assert_eq!(doc["package"]["edition"].as_str(), Some("2018"));
+    assert!(doc["package"].as_table().unwrap().is_dotted());
+    assert_eq!(
+        doc["dependencies"]["serde"]["version"].as_str(),
+        Some("1.0")
+    );
+    assert_eq!(doc["package"]["metadata"]["docs"].as_bool(), Some(true));
+    assert_eq!(
+        doc.to_string_in_original_order(),
+        input.replace("package . edition", "package.edition")
+    );
+
+    parse_error!("a = 1\na.b = 2", Document, "Duplicate key `a`");
+    parse_error!("a.b = 1\na = 2", Document, "Duplicate key `a`");
+    parse_error!("a.b = 1\na.b = 2", Document, "Duplicate key `b`");
+    parse_error!("a.b = 1\n[a]", Document, "Duplicate key `a`");
+    parse_error!("[a.b]\nc = 1\n[a]\nb.d = 2", Document, "Duplicate key `b`");
+    parse_error!(
+        "[a]\nb.c = 1\nb.c = 2",
+        Document,
+        "Duplicate key `c` in `[a.b]` table"
+    );
+    parse_error!(
+        "[a.'x']\nb = 1\nb.c = 2",
+        Document,
+        "Duplicate key `b` in `[a.'x']` table"
+    );
+    parse_error!("a = 1\na = 2", Document, "Duplicate key `a` in `[]` table");
+}
+
+#[test]
+fn test_key_path() {
+    use toml_edit::KeyPath;
+
+    let path = parse!(r#" a . "b.c" .'d' "#, KeyPath);
+    let keys: Vec<_> = path.iter().map(Key::get).collect();
+    assert_eq!(keys, ["a", "b.c", "d"]);
+    assert_eq!(path.to_string(), r#"a."b.c".'d'"#);
+    assert_eq!(path, KeyPath::from(&["a", "b.c", "d"][..]));
+    assert_eq!(
+        KeyPath::from(&["a", "b.c", "d"][..]).to_string(),
+        r#"a."b.c".d"#
+    );
+    assert_eq!(KeyPath::from(&["", "\"\n"][..]).to_string(), r#"""."\"\n""#);
+    assert_eq!(
+        path.parent().map(|p| p.to_string()),
+        Some(r#"a."b.c""#.to_owned())
+    );
+    assert!(path.starts_with(&parse!("a", KeyPath)));
+    assert!(!path.starts_with(&parse!("b", KeyPath)));
+    parse_error!("a.", KeyPath, "Expected");
+    parse_error!("a b", KeyPath, "Could not parse the line");
+
+    let mut doc = r#"
+a = { "b.c" = { d = 1 } }
+[e.f]
+g = [1]
+"#
+    .parse::<Document>()
+    .unwrap();
+    assert_eq!(doc.get_path(&path).and_then(Item::as_integer), Some(1));
+    assert!(doc.get_path(&parse!("e.f.g", KeyPath)).unwrap().is_array());
+    assert!(doc.get_path(&parse!("e.f.g.h", KeyPath)).is_none());
+    assert!(doc.get_path(&parse!("e.x", KeyPath)).is_none());
+    assert!(doc.get_path(&KeyPath::new()).is_none());
+    *doc.get_path_mut(&parse!("e.f", KeyPath)).unwrap() = toml_edit::table();
+    assert!(doc.get_path(&parse!("e.f.g", KeyPath)).is_none());
+}
+
+#[test]
+fn test_error_render() {
+    let source = "a = 1\n\tb = [1, 2 3]\n";
+    let err = source.parse::<Document>().unwrap_err();
+    assert_eq!(err.line_col(), (2, 12));
+    let rendered = err.render(source, None);
+    let mut lines = rendered.lines();
+    assert_eq!(lines.next(), Some("error: TOML parse error"));
+    assert_eq!(lines.next(), Some(" --> 2:12"));
+    assert_eq!(lines.next(), Some("  |"));
+    assert_eq!(lines.next(), Some("2 | \tb = [1, 2 3]"));
+    assert_eq!(lines.next(), Some("  | \t          ^"));
+    assert_eq!(lines.next(), Some("  = Unexpected `3`"));
+    assert!(lines.next().unwrap().starts_with("  = Expected "));
+
+    // the excerpt is skipped if the source doesn't contain the line
+    let rendered = err.render("", Some("a.toml"));
+    assert!(rendered.starts_with("error: TOML parse error\n --> a.toml:2:12\n  = Unexpected `3`\n"));
+}
+
+#[test]
+fn test_special_floats() {
+    assert!(parse_value!("inf").is_inf());
+    assert!(parse_value!("-inf").is_inf());
+    assert!(parse_value!("+nan").is_nan());
+    assert!(!parse_value!("1.0").is_inf());
+    assert!(!parse_value!("1.0").is_nan());
+    assert!(!parse_value!("'nan'").is_nan());
+    assert_eq!(Value::float_inf().as_float(), Some(f64::INFINITY));
+    assert_eq!(Value::float_neg_inf().to_string(), "-inf");
+    assert!(Value::float_nan().is_nan());
+    assert_eq!(Value::from(f64::NAN).to_string(), "nan");
+    assert_eq!(Value::from(-f64::INFINITY).to_string(), "-inf");
+    parse_error!("infinity", Value, "Could not parse the line");
+}
+
+#[test]
+#[cfg(feature = "datetime")]
+fn test_datetime_kind() {
+    use chrono::{FixedOffset, NaiveDate, NaiveTime};
+    use toml_edit::{DatetimeConversion, DatetimeKind};
+
+    let kind = |s: &str| parse_value!(s).as_date_time().map(|d| d.kind());
+    assert_eq!(
+        kind("1979-05-27T07:32:00-07:00"),
+        Some(DatetimeKind::OffsetDateTime)
+    );
+    assert_eq!(
+        kind("1979-05-27T07:32:00"),
+        Some(DatetimeKind::LocalDateTime)
+    );
+    assert_eq!(kind("1979-05-27"), Some(DatetimeKind::LocalDate));
+    assert_eq!(kind("07:32:00"), Some(DatetimeKind::LocalTime));
+    assert_eq!(kind("1979"), None);
+
+    let convert = |s: &str, kind: DatetimeKind, conversion: DatetimeConversion| {
+        parse_value!(s)
+            .as_date_time()
+            .and_then(|d| d.convert(kind, &conversion))
+            .map(|d| d.to_string())
+    };
+    let strict = DatetimeConversion::new();
+    let lossy = DatetimeConversion::new().lossy(true);
+    let offset = "1979-05-27T07:32:00-07:00";
+    assert_eq!(
+        convert(offset, DatetimeKind::OffsetDateTime, strict),
+        Some("1979-05-27 07:32:00 -07:00".to_owned())
+    );
+    assert_eq!(convert(offset, DatetimeKind::LocalDateTime, strict), None);
+    assert_eq!(
+        convert(offset, DatetimeKind::LocalDateTime, lossy),
+        Some("1979-05-27 07:32:00".to_owned())
+    );
+    assert_eq!(
+        convert(offset, DatetimeKind::LocalTime, lossy.utc(true)),
+        Some("14:32:00".to_owned())
+    );
+    assert_eq!(
+        convert("1979-05-27T07:32:00", DatetimeKind::LocalDate, strict),
+        None
+    );
+    assert_eq!(
+        convert("1979-05-27T07:32:00", DatetimeKind::LocalDate, lossy),
+        Some("1979-05-27".to_owned())
+    );
+
+    let midnight = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
+    let utc = FixedOffset::east_opt(0).unwrap();
+    assert_eq!(
+        convert("1979-05-27", DatetimeKind::OffsetDateTime, lossy),
+        None
+    );
+    assert_eq!(
+        convert(
+            "1979-05-27",
+            DatetimeKind::OffsetDateTime,
+            strict.time(midnight).offset(utc)
+        ),
+        Some("1979-05-27 00:00:00 +00:00".to_owned())
+    );
+    let date = NaiveDate::from_ymd_opt(1979, 5, 27).unwrap();
+    assert_eq!(
+        convert("07:32:00", DatetimeKind::LocalDateTime, strict.date(date)),
+        Some("1979-05-27 07:32:00".to_owned())
+    );
+    assert_eq!(
+        convert("07:32:00", DatetimeKind::LocalDate, strict.date(date)),
+        None
+    );
+}
+
+#[test]
+fn test_string_styles() {
+    let strings = [
+        "",
+        "plain",
+        "quote\" and 'apostrophe'",
+        "back\\slash \\n",
+        "\nleading newline",
+        "ends with quote\"",
+        "ends with apostrophe'",
+        "three '''apostrophes",
+        "three \"\"\"quotes\"\"\"\"",
+        "tab\tand\nnewline\r\n",
+        "bell\u{7}",
+    ];
+    let styles = [
+        StringStyle::Basic,
+        StringStyle::Literal,
+        StringStyle::MultilineBasic,
+        StringStyle::MultilineLiteral,
+    ];
+    for s in strings.iter() {
+        let v = Value::from(*s);
+        assert_eq!(parse_value!(v.to_string()).as_str(), Some(*s));
+        for &style in styles.iter() {
+            let v = Value::string_in_style(s, style);
+            assert_eq!(
+                parse_value!(v.to_string()).as_str(),
+                Some(*s),
+                "{:?}",
+                style
+            );
+            if let Ok(v) = Value::try_string_in_style(s, style) {
+                assert_eq!(v.to_string(), Value::string_in_style(s, style).to_string());
+            }
+        }
+    }
+
+    let err = Value::try_string_in_style("a\nb", StringStyle::Literal).unwrap_err();
+    assert_eq!((err.style(), err.character()), (StringStyle::Literal, '\n'));
+    let err = Value::try_string_in_style("a'''b", StringStyle::MultilineLiteral).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"a multi-line literal string can't contain '\''"#
+    );
+    let v = Value::try_string_in_style("a\nb", StringStyle::MultilineLiteral).unwrap();
+    assert_eq!(v.to_string(), "'''a\nb'''");
+}
+
+#[cfg(feature = "i128")]
+#[test]
+fn test_as_i128() {
+    use toml_edit::{Document, NumberPolicy, ParseOptions};
+
+    let preserve = ParseOptions::new().number_policy(NumberPolicy::Preserve);
+    let doc = Document::parse_with("a = -18_446_744_073_709_551_616\nb = 0xff", &preserve).unwrap();
+    assert_eq!(
+        doc["a"].as_value().and_then(Value::as_i128),
+        Some(-18_446_744_073_709_551_616)
+    );
+    assert_eq!(doc["b"].as_value().and_then(Value::as_i128), Some(0xff));
+}
+
+#[test]
+fn test_error_excerpt() {
+    use toml_edit::ParseOptions;
+
+    let short = "a = 'x\nb = 1";
+    let err = short.parse::<Document>().unwrap_err();
+    assert_eq!(err.excerpt(), 1..7);
+    assert!(err.to_string().contains("\n1 | a = 'x\n"));
+
+    // a minified document with the error near the end of a long line
+    let long = format!("a = {{ {} z = }}", "k = 1, ".repeat(1000));
+    let err = long.parse::<Document>().unwrap_err();
+    let (line, column) = err.line_col();
+    assert_eq!((line, column), (1, long.len()));
+    assert_eq!(err.excerpt(), long.len() - 120..long.len() + 1);
+    let excerpt = format!("...{}", &long[long.len() - 121..]);
+    assert!(err.to_string().contains(&format!("\n1 | {}\n", excerpt)));
+    let caret = format!("\n  | {}^\n", " ".repeat(123));
+    assert!(err.to_string().contains(&caret));
+    let rendered = err.render(&long, Some("minified.toml"));
+    assert!(rendered.contains(&format!("\n1 | {}\n", excerpt)));
+    assert!(rendered.contains(&caret));
+
+    let options = ParseOptions::new().error_context(0);
+    let err = Document::parse_with(&long, &options).unwrap_err();
+    assert_eq!(err.excerpt(), column..column + 1);
+    assert!(err.to_string().contains("\n1 | ...}\n  |    ^\n"));
+}
+
+#[test]
+fn test_nested_too_deeply() {
+    let header = |depth: usize| format!("[{}]\nx = 1\n", vec!["a"; depth].join("."));
+    let array = |depth: usize| format!("x = {}1{}\n", "[".repeat(depth), "]".repeat(depth));
+    let inline = |depth: usize| format!("x = {}1{}\n", "{ a = ".repeat(depth), " }".repeat(depth));
+    let dotted = |depth: usize| format!("[a]\n{} = 1\n", vec!["b"; depth].join("."));
+
+    for nested in [header, array, inline, dotted].iter() {
+        let doc = nested(128).parse::<Document>().unwrap();
+        assert_eq!(doc.try_to_string().unwrap(), nested(128));
+
+        let err = nested(129).parse::<Document>().unwrap_err();
+        assert!(err.to_string().contains("Nested more than 128 levels deep"));
+    }
+    let err = array(129).parse::<Document>().unwrap_err();
+    assert_eq!(err.line_col(), (1, 133));
+}
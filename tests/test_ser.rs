@@ -0,0 +1,316 @@
+#![cfg(feature = "serde")]
+
+use pretty_assertions::assert_eq;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use toml_edit::ser::{self, NonFiniteFloats, Nulls, Settings, StringStyle};
+
+#[derive(Serialize)]
+struct Manifest {
+    package: Package,
+    dependencies: BTreeMap<&'static str, Dependency>,
+    bin: Vec<Target>,
+}
+
+#[derive(Serialize)]
+struct Package {
+    name: &'static str,
+    description: Option<&'static str>,
+    path: &'static str,
+    version: f64,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Dependency {
+    Version(&'static str),
+    Detailed {
+        version: &'static str,
+        features: Vec<&'static str>,
+    },
+}
+
+#[derive(Serialize)]
+struct Target {
+    name: &'static str,
+}
+
+fn manifest() -> Manifest {
+    let mut dependencies = BTreeMap::new();
+    dependencies.insert("chrono", Dependency::Version("0.4.10"));
+    dependencies.insert(
+        "combine",
+        Dependency::Detailed {
+            version: "4.5.2",
+            features: vec!["std", "alloc"],
+        },
+    );
+    Manifest {
+        package: Package {
+            name: "toml_edit",
+            description: None,
+            path: "C:\\toml",
+            version: 1.0,
+        },
+        dependencies,
+        bin: vec![Target { name: "a" }, Target { name: "b" }],
+    }
+}
+
+#[test]
+fn test_to_string() {
+    let expected = r#"[package]
+name = "toml_edit"
+path = 'C:\toml'
+version = 1.0
+
+[dependencies]
+chrono = "0.4.10"
+
+[dependencies.combine]
+version = "4.5.2"
+features = ["std", "alloc"]
+
+[[bin]]
+name = "a"
+
+[[bin]]
+name = "b"
+"#;
+    assert_eq!(ser::to_string(&manifest()).unwrap(), expected);
+}
+
+#[test]
+fn test_to_string_with_settings() {
+    let settings = Settings::new()
+        .array_of_tables(false)
+        .string_style(StringStyle::Basic)
+        .multiline_array_threshold(Some(1))
+        .indent("  ");
+    let expected = r#"bin = [
+  { name = "a" },
+  { name = "b" },
+]
+
+[package]
+name = "toml_edit"
+path = "C:\\toml"
+version = 1.0
+
+[dependencies]
+chrono = "0.4.10"
+
+[dependencies.combine]
+version = "4.5.2"
+features = [
+  "std",
+  "alloc",
+]
+"#;
+    assert_eq!(
+        ser::to_string_with(&manifest(), &settings).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn test_serialize_roundtrip() {
+    let s = ser::to_string_pretty(&manifest()).unwrap();
+    let doc = s.parse::<toml_edit::Document>().unwrap();
+    assert_eq!(doc.to_string(), s);
+    assert_eq!(
+        doc["dependencies"]["combine"]["features"][1].as_str(),
+        Some("alloc")
+    );
+}
+
+#[test]
+fn test_serialize_keys_as_written() {
+    let mut map = BTreeMap::new();
+    map.insert("\"q\"", 1);
+    map.insert("a\\nb", 2);
+    map.insert("a.b", 3);
+    map.insert("it's \"x\"", 4);
+    let s = ser::to_string(&map).unwrap();
+    let back: BTreeMap<String, i32> = toml_edit::de::from_str(&s).unwrap();
+    let back: Vec<_> = back.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+    assert_eq!(
+        back,
+        vec![("\"q\"", 1), ("a.b", 3), ("a\\nb", 2), ("it's \"x\"", 4)]
+    );
+}
+
+#[test]
+fn test_serialize_errors() {
+    assert!(ser::to_string(&42).is_err());
+    let mut map = BTreeMap::new();
+    map.insert("a", vec![Some(1), None]);
+    assert!(ser::to_string(&map).is_err());
+    let mut map = BTreeMap::new();
+    map.insert("a", u64::MAX);
+    assert!(ser::to_string(&map).is_err());
+    let mut map = BTreeMap::new();
+    map.insert("a", f64::NAN);
+    assert!(ser::to_string(&map).is_err());
+}
+
+#[test]
+fn test_serialize_non_finite_floats() {
+    let mut map = BTreeMap::new();
+    map.insert("a", f64::INFINITY);
+    map.insert("b", f64::NEG_INFINITY);
+    map.insert("c", f64::NAN);
+    assert!(ser::to_string(&map).is_err());
+    let settings = Settings::new().non_finite_floats(NonFiniteFloats::Emit);
+    let s = ser::to_string_with(&map, &settings).unwrap();
+    assert_eq!(s, "a = inf\nb = -inf\nc = nan\n");
+    let doc = s.parse::<toml_edit::Document>().unwrap();
+    assert!(doc["a"].as_value().is_some_and(toml_edit::Value::is_inf));
+    assert!(doc["c"].as_value().is_some_and(toml_edit::Value::is_nan));
+}
+
+#[test]
+fn test_serialize_nulls() {
+    let mut map = BTreeMap::new();
+    map.insert("a", None);
+    map.insert("b", Some(1));
+    assert_eq!(ser::to_string(&map).unwrap(), "b = 1\n");
+    assert!(ser::to_string_with(&map, &Settings::new().nulls(Nulls::Error)).is_err());
+    let settings = Settings::new().nulls(Nulls::EmptyString);
+    assert_eq!(
+        ser::to_string_with(&map, &settings).unwrap(),
+        "a = \"\"\nb = 1\n"
+    );
+}
+
+#[test]
+fn test_nulls_sentinel_round_trip() {
+    #[derive(Debug, PartialEq, serde::Deserialize, Serialize)]
+    struct Config {
+        name: Option<String>,
+        port: Option<u16>,
+        unit: (),
+        tags: Vec<Option<String>>,
+    }
+
+    let config = Config {
+        name: None,
+        port: Some(80),
+        unit: (),
+        tags: vec![None, Some("none!".into())],
+    };
+    let nulls = Nulls::Sentinel("none");
+    let s = ser::to_string_with(&config, &Settings::new().nulls(nulls)).unwrap();
+    assert_eq!(
+        s,
+        "name = \"none\"\nport = 80\nunit = \"none\"\ntags = [\"none\", \"none!\"]\n"
+    );
+    let settings = toml_edit::de::Settings::new().nulls(nulls);
+    let back: Config = toml_edit::de::from_str_with(&s, &settings).unwrap();
+    assert_eq!(back, config);
+    // without the policy, the sentinel is a string
+    let err = toml_edit::de::from_str::<Config>(&s).unwrap_err();
+    assert_eq!(err.path(), Some("unit"));
+
+    // missing keys and empty strings
+    let settings = toml_edit::de::Settings::new().nulls(Nulls::EmptyString);
+    let back: Config =
+        toml_edit::de::from_str_with("port = \"\"\nunit = \"\"\ntags = [\"\"]", &settings).unwrap();
+    assert_eq!((back.name, back.port, back.tags), (None, None, vec![None]));
+}
+
+#[test]
+fn test_restore_comments() {
+    #[derive(serde::Deserialize, Serialize)]
+    struct Config {
+        name: String,
+        servers: Vec<Server>,
+    }
+
+    #[derive(serde::Deserialize, Serialize)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    let input = r#"# the config
+name = "prod" # the environment
+
+# the first server
+[[servers]]
+host = "a" # primary
+port = 80
+
+[[servers]] # backup
+host = "b"
+# keep in sync with the firewall
+port = 81
+# the end
+"#;
+    let doc = input.parse::<toml_edit::Document>().unwrap();
+    let comments = doc.comments();
+    assert_eq!(comments.len(), 5);
+    assert_eq!(comments.get("servers[1]").unwrap().suffix(), " # backup");
+    assert_eq!(comments.trailing(), "# the end\n");
+
+    let mut config: Config = toml_edit::de::from_document(&doc).unwrap();
+    config.servers[1].port = 8081;
+    config.servers.push(Server {
+        host: "c".to_owned(),
+        port: 82,
+    });
+    let mut doc = ser::to_document(&config).unwrap();
+    doc.apply_comments(&comments);
+    assert_eq!(
+        doc.to_string(),
+        r#"# the config
+name = "prod" # the environment
+
+# the first server
+[[servers]]
+host = "a" # primary
+port = 80
+
+[[servers]] # backup
+host = "b"
+# keep in sync with the firewall
+port = 8081
+
+[[servers]]
+host = "c"
+port = 82
+# the end
+"#
+    );
+}
+
+#[test]
+fn test_serialize_items() {
+    let mut doc = "# targets\n[package]\nname = 'hello' # the name\n"
+        .parse::<toml_edit::Document>()
+        .unwrap();
+    let bin = vec![Target { name: "a" }, Target { name: "b" }];
+    doc["bin"] = ser::to_item(&bin).unwrap();
+    doc["package"]["version"] = ser::to_item(&"0.1.0").unwrap();
+    doc["package"]["description"] = ser::to_item(&None::<&str>).unwrap();
+    assert_eq!(
+        doc.to_string(),
+        r#"# targets
+[package]
+name = 'hello' # the name
+version = "0.1.0"
+
+[[bin]]
+name = "a"
+
+[[bin]]
+name = "b"
+"#
+    );
+    let names: Vec<BTreeMap<String, String>> = toml_edit::de::from_item(&doc["bin"]).unwrap();
+    assert_eq!(names[1]["name"], "b");
+
+    let value = ser::to_value(&bin).unwrap();
+    assert_eq!(value.to_string(), r#"[{ name = "a" }, { name = "b" }]"#);
+    assert!(ser::to_value(&None::<i64>).is_err());
+}
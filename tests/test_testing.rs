@@ -0,0 +1,75 @@
+#![cfg(feature = "test-util")]
+
+use pretty_assertions::assert_eq;
+use toml_edit::testing::{compare, compare_text};
+use toml_edit::{assert_toml_eq, assert_toml_text_eq, Document};
+
+#[test]
+fn test_compare_ignores_formatting() {
+    let doc = r#"
+# the package
+[package]
+name = 'app'
+version = "0.1.0"
+authors = [ "a",
+  "b" ]
+
+[[bin]]
+name = "app"
+"#
+    .parse::<Document>()
+    .unwrap();
+    assert_toml_eq!(
+        doc,
+        r#"
+bin = [{ name = "app" }]
+package = { authors = ["a", "b"], version = "0.1.0", name = "app" }
+"#
+    );
+    assert_toml_eq!("a.b = 0x10", "[a]\nb = 16");
+    assert!(compare(&doc, "").is_err());
+}
+
+#[test]
+fn test_compare_report() {
+    let actual = "[package]\nname = 'app'\nedition = '2018'\n\n[dependencies]\nserde = '1.0'\n";
+    let expected = "[package]\nname = 'app'\nversion = '0.1.0'\n\n[dependencies]\nserde = '1.1'\n";
+    assert_eq!(
+        compare(actual, expected).unwrap_err(),
+        r#"TOML documents differ:
+missing `package.version`, expected '0.1.0'
+unexpected `package.edition` = '2018'
+`dependencies.serde`: expected '1.1', got '1.0'
+
+--- expected
++++ actual
+ [package]
+ name = 'app'
+-version = '0.1.0'
++edition = '2018'
+ 
+ [dependencies]
+-serde = '1.1'
++serde = '1.0'
+"#
+    );
+    assert_eq!(
+        compare("a = 1", "a = ").unwrap_err().lines().next(),
+        Some("the expected document is invalid: TOML parse error at line 1, column 5")
+    );
+}
+
+#[test]
+fn test_compare_text() {
+    assert_toml_text_eq!("a = 1 # one\n", "a = 1 # one\n");
+    assert_eq!(
+        compare_text("a = 1\n", "a = 1 # one\n").unwrap_err(),
+        "TOML documents are written differently:\n\n--- expected\n+++ actual\n-a = 1 # one\n+a = 1\n"
+    );
+}
+
+#[test]
+#[should_panic(expected = "`a`: expected 2, got 1")]
+fn test_assert_toml_eq_panics() {
+    assert_toml_eq!("a = 1", "a = 2");
+}
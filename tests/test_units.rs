@@ -0,0 +1,109 @@
+#![cfg(feature = "units")]
+
+use pretty_assertions::assert_eq;
+use std::time::Duration;
+use toml_edit::units::{ByteSize, Quantity, Unit};
+
+#[test]
+fn parse_durations() {
+    let parse = |s| Duration::parse(s);
+    assert_eq!(parse("10s"), Some(Duration::from_secs(10)));
+    assert_eq!(parse("1.5 h"), Some(Duration::from_secs(5400)));
+    assert_eq!(parse("1_000ms"), Some(Duration::from_secs(1)));
+    assert_eq!(parse("2min"), Some(Duration::from_secs(120)));
+    assert_eq!(parse("3µs"), Some(Duration::from_micros(3)));
+    assert_eq!(parse("0.5ns"), Some(Duration::from_nanos(1)));
+    assert_eq!(parse("10"), None);
+    assert_eq!(parse("s"), None);
+    assert_eq!(parse("-1s"), None);
+    assert_eq!(parse("1.2.3s"), None);
+    assert_eq!(parse("10 years"), None);
+}
+
+#[test]
+fn format_durations() {
+    let format = |d: Duration| d.format();
+    assert_eq!(format(Duration::from_secs(0)), "0s");
+    assert_eq!(format(Duration::from_secs(90)), "90s");
+    assert_eq!(format(Duration::from_secs(7200)), "2h");
+    assert_eq!(format(Duration::from_millis(1500)), "1500ms");
+    assert_eq!(format(Duration::from_secs(86400 * 7)), "7d");
+}
+
+#[test]
+fn byte_sizes() {
+    assert_eq!(ByteSize::parse("5MB"), Some(ByteSize(5_000_000)));
+    assert_eq!(ByteSize::parse("5mib"), Some(ByteSize(5 << 20)));
+    assert_eq!(ByteSize::parse("1.5 KiB"), Some(ByteSize(1536)));
+    assert_eq!(ByteSize::parse("42"), Some(ByteSize(42)));
+    assert_eq!(ByteSize::parse("20000PB"), None);
+    assert_eq!(ByteSize::parse("5 bits"), None);
+
+    assert_eq!(ByteSize(0).format(), "0B");
+    assert_eq!(ByteSize(1000).format(), "1KB");
+    assert_eq!(ByteSize(3 << 30).format(), "3GiB");
+    assert_eq!(ByteSize(1001).format(), "1001B");
+}
+
+#[test]
+fn quantities_keep_their_strings() {
+    let mut q = Quantity::<Duration>::parse("60s").unwrap();
+    q.set(Duration::from_secs(60));
+    assert_eq!(q.raw(), "60s");
+    q.set(Duration::from_secs(120));
+    assert_eq!(q.raw(), "2m");
+    assert_eq!(q.to_string(), "2m");
+    assert_eq!(Quantity::from(ByteSize(2048)).raw(), "2KiB");
+}
+
+#[cfg(feature = "parse")]
+mod binding {
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+    use toml_edit::binding::{TomlEdit, TomlField};
+    use toml_edit::units::{ByteSize, Quantity};
+    use toml_edit::{Document, Item, TableLike};
+
+    struct Server {
+        timeout: Quantity<Duration>,
+        max_body: Option<Quantity<ByteSize>>,
+    }
+
+    impl TomlEdit for Server {
+        fn from_table(table: &dyn TableLike) -> Result<Self, toml_edit::binding::Error> {
+            Ok(Server {
+                timeout: TomlField::read_key(table, "timeout")?,
+                max_body: TomlField::read_key(table, "max-body")?,
+            })
+        }
+
+        fn update_table(&self, table: &mut dyn TableLike) {
+            self.timeout.update_key(table, "timeout");
+            self.max_body.update_key(table, "max-body");
+        }
+    }
+
+    #[test]
+    fn bound_quantities() {
+        let mut doc = "timeout = '30 s'  # idle\nmax-body = '1MB'\n"
+            .parse::<Document>()
+            .unwrap();
+        let mut server = Server::from_document(&doc).unwrap();
+        assert_eq!(*server.timeout.value(), Duration::from_secs(30));
+
+        server.update_document(&mut doc);
+        assert_eq!(
+            doc.to_string(),
+            "timeout = '30 s'  # idle\nmax-body = '1MB'\n"
+        );
+
+        server.timeout.set(Duration::from_secs(45));
+        server.max_body = None;
+        server.update_document(&mut doc);
+        assert_eq!(doc.to_string(), "timeout = \"45s\"  # idle\n");
+
+        doc["timeout"] = Item::Value("soon".into());
+        let err = Server::from_document(&doc).err().unwrap();
+        assert_eq!(err.to_string(), "invalid quantity `soon` for key `timeout`");
+    }
+}
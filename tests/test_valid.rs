@@ -1,3 +1,5 @@
+#![cfg(feature = "parse")]
+
 use pretty_assertions::assert_eq;
 use serde_json::Map as JsonMap;
 use serde_json::Value as Json;
@@ -60,6 +62,10 @@ fn run(json: &str, toml: &str) {
     // compare structure with jsons
     assert_eq!(json, toml_json);
 
+    #[cfg(feature = "verbatim")]
+    assert_eq!(doc.to_string_verbatim(), toml);
+    assert_eq!(doc.assert_valid(), Ok(()));
+
     // check round-trip equality
     let toml = doc.to_string();
     let doc = toml.parse::<Document>();
@@ -0,0 +1,81 @@
+#![cfg(feature = "wasm")]
+
+use pretty_assertions::assert_eq;
+use toml_edit::wasm::Editor;
+
+#[test]
+fn edit_by_paths() {
+    let mut editor = Editor::parse(
+        r#"[package]
+name = "hello"
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"] } # serialization
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        editor.get("dependencies").unwrap().as_deref(),
+        Some(r#"{ serde = { version = "1.0", features = ["derive"] } }"#)
+    );
+    assert_eq!(editor.get("package.version"), Ok(None));
+
+    editor
+        .set("dependencies.serde.version", r#""1.0.100""#)
+        .unwrap();
+    editor
+        .set(r#"target."cfg(unix)".dependencies.libc"#, r#""0.2""#)
+        .unwrap();
+    assert_eq!(editor.remove("package.name"), Ok(true));
+    assert_eq!(editor.remove("dependencies.log"), Ok(false));
+    assert_eq!(
+        editor.to_string(),
+        r#"[package]
+
+[dependencies]
+serde = { version = "1.0.100", features = ["derive"] } # serialization
+
+[target."cfg(unix)".dependencies]
+libc = "0.2"
+"#
+    );
+}
+
+#[test]
+fn append_and_sort() {
+    let mut editor = Editor::parse("b = [1]\na = 2\n\n[t]\ny = 1\nx = { d = 1, c = 2 }\n").unwrap();
+    editor.append("b", "2").unwrap();
+    editor.append("c", "'x'").unwrap();
+    assert_eq!(
+        editor.append("b", "'x'"),
+        Err("`b` contains values of another type".to_owned())
+    );
+    assert_eq!(
+        editor.append("a", "1"),
+        Err("`a` is not an array".to_owned())
+    );
+    editor.sort("").unwrap();
+    editor.sort("t").unwrap();
+    editor.sort("t.x").unwrap();
+    assert_eq!(editor.sort("a"), Err("`a` is not a table".to_owned()));
+    assert_eq!(
+        editor.to_string(),
+        "a = 2\nb = [1, 2]\nc = ['x']\n\n[t]\nx = { c = 2, d = 1 }\ny = 1\n"
+    );
+}
+
+#[test]
+fn errors() {
+    assert!(Editor::parse("a = ").is_err());
+    let mut editor = Editor::parse("a = 1\n").unwrap();
+    assert_eq!(editor.set("a.b", "1"), Err("`a` is not a table".to_owned()));
+    assert!(editor.set("b", "not a value").is_err());
+    assert!(editor.set("c..d", "1").is_err());
+    assert!(editor.get("").is_err());
+    assert_eq!(editor.to_string(), "a = 1\n");
+
+    let deep = vec!["t"; 200].join(".");
+    editor.set(&deep, "1").unwrap();
+    assert!(editor.try_to_string().is_err());
+    assert!(editor.get("t").is_err());
+}
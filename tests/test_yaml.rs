@@ -0,0 +1,106 @@
+#![cfg(feature = "yaml")]
+
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use serde_yaml::Value as Yaml;
+use toml_edit::ser::Settings;
+use toml_edit::yaml::{self, Nulls, Options, Tags};
+
+fn yaml(s: &str) -> Yaml {
+    serde_yaml::from_str(s).unwrap()
+}
+
+#[test]
+fn test_yaml_nulls() {
+    let config = yaml("a: null\nb: [x, ~]\nc:\n  d:\n  e: 1\n");
+    let err = yaml::to_document(&config, &Options::new()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "missing values can't be represented in TOML"
+    );
+
+    let options = Options::new().nulls(Nulls::Skip);
+    assert!(yaml::to_document(&config, &options).is_err());
+
+    let options = Options::new().nulls(Nulls::EmptyString);
+    let doc = yaml::to_document(&config, &options).unwrap();
+    assert_eq!(
+        doc.to_string(),
+        "a = \"\"\nb = [\"x\", \"\"]\n\n[c]\nd = \"\"\ne = 1\n"
+    );
+
+    let config = yaml("c:\n  d: null\n  e: [1, 2]\n");
+    let options = Options::new()
+        .nulls(Nulls::Skip)
+        .settings(Settings::pretty().nulls(Nulls::Error));
+    let doc = yaml::to_document(&config, &options).unwrap();
+    assert_eq!(doc.to_string(), "[c]\ne = [\n    1,\n    2,\n]\n");
+}
+
+#[test]
+fn test_yaml_anchors() {
+    let config = yaml(
+        "defaults: &defaults\n  adapter: postgres\n  host: localhost\n\
+         development:\n  database: dev\n  <<: *defaults\n\
+         test: *defaults\n",
+    );
+    let doc = yaml::to_document(&config, &Options::new()).unwrap();
+    assert_eq!(
+        doc.to_string(),
+        "[defaults]\nadapter = \"postgres\"\nhost = \"localhost\"\n\n\
+         [development]\ndatabase = \"dev\"\n\n\
+         [development.\"<<\"]\nadapter = \"postgres\"\nhost = \"localhost\"\n\n\
+         [test]\nadapter = \"postgres\"\nhost = \"localhost\"\n"
+    );
+
+    let mut merged = config;
+    merged.apply_merge().unwrap();
+    let doc = yaml::to_document(&merged, &Options::new()).unwrap();
+    assert_eq!(
+        doc["development"].as_table().unwrap().to_string(),
+        "database = \"dev\"\nadapter = \"postgres\"\nhost = \"localhost\"\n"
+    );
+}
+
+#[test]
+fn test_yaml_tags() {
+    let config = yaml("origin: !Point {x: 1, y: 2}\n");
+    let err = yaml::to_document(&config, &Options::new()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "the tagged value `!Point` can't be represented in TOML"
+    );
+    assert!(yaml::to_item(&yaml("[!A 1]"), &Options::new()).is_err());
+    assert!(yaml::to_document(&yaml("!Root {a: 1}"), &Options::new()).is_err());
+
+    let options = Options::new().tags(Tags::Table);
+    let doc = yaml::to_document(&config, &options).unwrap();
+    assert_eq!(
+        doc.to_string(),
+        "[origin]\n\n[origin.\"!Point\"]\nx = 1\ny = 2\n"
+    );
+
+    // keys starting with `!` are not tags
+    let config = yaml("'!important': true\na: {'!b': 1}\n");
+    let doc = yaml::to_document(&config, &Options::new()).unwrap();
+    assert_eq!(
+        doc.to_string(),
+        "\"!important\" = true\n\n[a]\n\"!b\" = 1\n"
+    );
+    let config = json!({"!b": 1});
+    assert!(yaml::to_document(&config, &Options::new()).is_ok());
+}
+
+#[test]
+fn test_yaml_roundtrip() {
+    let config = yaml("name: app\nports: [80, 443]\nservers:\n- host: a\n- host: b\nratio: 0.5\n");
+    let doc = yaml::to_document(&config, &Options::new()).unwrap();
+    let back: Yaml = yaml::from_document(&doc).unwrap();
+    assert_eq!(back, config);
+
+    let doc = "when = 1979-05-27T07:32:00Z\n"
+        .parse::<toml_edit::Document>()
+        .unwrap();
+    let back: Yaml = yaml::from_item(&doc["when"]).unwrap();
+    assert!(back.is_string());
+}
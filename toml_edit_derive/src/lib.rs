@@ -0,0 +1,86 @@
+//! `#[derive(TomlEdit)]` for binding structs to `toml_edit` documents,
+//! see the `toml_edit::binding` module.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Derives `toml_edit::binding::TomlEdit` for structs with named fields.
+///
+/// Fields are bound to the keys of the same names, without the `r#` of raw identifiers,
+/// `#[toml_edit(rename = "key")]` binds a field to another key.
+#[proc_macro_derive(TomlEdit, attributes(toml_edit))]
+pub fn derive_toml_edit(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "TomlEdit can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "TomlEdit can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut idents = Vec::new();
+    let mut keys = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let mut key = LitStr::new(&ident.unraw().to_string(), ident.span());
+        for attr in field.attrs.iter() {
+            if !attr.path().is_ident("toml_edit") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    key = meta.value()?.parse()?;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported toml_edit attribute"))
+                }
+            })?;
+        }
+        idents.push(ident);
+        keys.push(key);
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::toml_edit::binding::TomlEdit for #name #ty_generics #where_clause {
+            fn from_table(
+                table: &dyn ::toml_edit::TableLike,
+            ) -> ::std::result::Result<Self, ::toml_edit::binding::Error> {
+                ::std::result::Result::Ok(Self {
+                    #(
+                        #idents: ::toml_edit::binding::TomlField::read_key(table, #keys)?,
+                    )*
+                })
+            }
+
+            fn update_table(&self, table: &mut dyn ::toml_edit::TableLike) {
+                #(
+                    ::toml_edit::binding::TomlField::update_key(&self.#idents, table, #keys);
+                )*
+            }
+        }
+    })
+}